@@ -1,22 +1,305 @@
 precision highp float;
+precision highp sampler2D;
 uniform vec2 u_fractalPosition;
 uniform vec2 u_cJulia;
+uniform vec2 u_viewCenter;
+uniform mat2 u_skew;
+// Rotation of the view around its center, applied before u_skew, in radians
+uniform float u_rotation;
 uniform float u_fractalZoom;
 uniform float u_brightness;
 uniform float u_contrast;
 uniform int u_highQuality;
+// Iteration budget, already lowered when not in high quality
+uniform int u_maxIterations;
+// Samples per pixel along each axis
+uniform int u_supersampling;
+// The samples are at random positions inside their part of the pixel instead of its center,
+// different for each seed
+uniform int u_jitter;
+uniform int u_jitterSeed;
+// Center and radius in pixels of the disk drawn, for the magnifier lens. Everything is
+// drawn when the radius is 0.
+uniform vec3 u_circle;
 uniform float u_r;
 uniform float u_g;
 uniform float u_b;
 uniform float u_gamma;
 uniform int u_fractal_type;
+// Roots of the polynomial of the Newton fractal
+uniform vec2 u_newtonRoots[3];
+// Growth rates of the Lyapunov fractal in turn, bit i set when the rate i is B, the y
+// coordinate, and clear when it is A, the x coordinate
+uniform int u_lyapunovSequence;
+uniform int u_lyapunovLength;
+// Multiplier of the Newton step of the Nova fractal
+uniform float u_novaRelaxation;
+uniform float u_time;
+// Shift of the colors along the palette, for the color cycling
+uniform float u_colorPhase;
+uniform int u_customColoring;
+uniform int u_valueFormula;
+uniform int u_coloringAlgorithm;
+uniform float u_stripeDensity;
+uniform float u_stripeMix;
+uniform float u_tiaBlend;
+uniform float u_curvatureMix;
+uniform float u_decompositionMix;
+uniform int u_fieldLines;
+uniform float u_coloringParameters[16];
+// Parameters of the iterate function of the plugins
+uniform float u_fractalParameters[8];
+uniform int u_usePalette;
+uniform sampler2D u_palette;
+// Color balance of the final image: lift of the shadows, gamma of the midtones and gain of
+// the highlights, per channel
+uniform vec3 u_lift;
+uniform vec3 u_balanceGamma;
+uniform vec3 u_gain;
+// Image of the image trap, u_trapScale units wide and turned by u_trapRotation radians
+uniform sampler2D u_trap;
+uniform float u_trapScale;
+uniform float u_trapRotation;
+// Orbit trap: a point at u_trapCenter, a line through it at the angle u_trapRotation, or a
+// ring of radius u_trapSize around it
+uniform int u_trapShape;
+uniform vec2 u_trapCenter;
+uniform float u_trapSize;
+// Texture mapped on the exterior, mirrored every other repetition if u_textureMirror is set
+uniform sampler2D u_exterior;
+uniform float u_textureBlend;
+uniform int u_textureMirror;
+// Deep zoom by perturbation: the orbit of the center of the view u_viewCenter, computed in
+// double precision, in rows of 4096 texels, and the center of the viewport in pixels
+uniform int u_perturbation;
+// Smooth, banded or equalized iteration count. The equalization maps the values up to
+// u_histogramRange through the lookup table u_histogram, of HISTOGRAM_SIZE fractions
+uniform int u_iterationColoring;
+uniform sampler2D u_histogram;
+uniform float u_histogramRange;
+// Distance estimate to the boundary of the set: off, darkened edges or line art, drawn
+// u_boundaryWidth pixels wide
+uniform int u_boundaryStyle;
+uniform float u_boundaryWidth;
+// Power n of z^n + c for the Julia, Mandelbrot, Burning Ship and Tricorn fractals
+uniform float u_exponent;
+uniform sampler2D u_referenceOrbit;
+uniform int u_referenceLength;
+uniform vec2 u_viewportCenter;
+
+// BEGIN COLORING FUNCTION
+// Replaced at runtime by the coloring graph editor
+vec3 customColor(float value) {
+    return vec3(value);
+}
+// END COLORING FUNCTION
+
+// Last value of z, for the coloring
+vec2 finalZ;
+// Offset of the point from u_viewCenter, with the perturbation
+vec2 perturbationDelta;
+// The orbit didn't escape
+bool interior = false;
+// Derivative of z with respect to the pixel location, for the distance estimate
+vec2 zDerivative;
 
 const int JULIA = 0;
 const int MANDELBROT = 1;
+const int CUSTOM = 2;
+const int BURNING_SHIP = 3;
+const int TRICORN = 4;
+const int NEWTON = 5;
+const int LYAPUNOV = 6;
+const int NOVA = 7;
+
+const int ITERATIONS = 0;
+const int STRIPE_AVERAGE = 1;
+const int TRIANGLE_INEQUALITY = 2;
+const int CURVATURE_AVERAGE = 3;
+const int BINARY_DECOMPOSITION = 4;
+const int PERIOD = 5;
+const int ATOM_DOMAIN = 6;
+const int IMAGE_TRAP = 7;
+const int TEXTURE_MAP = 8;
+const int ORBIT_TRAP = 9;
+
+const int SMOOTH = 0;
+const int BANDS = 1;
+const int HISTOGRAM = 2;
+// Kept in sync with histogram.rs
+const int HISTOGRAM_SIZE = 256;
+
+const int TRAP_POINT = 0;
+const int TRAP_LINE = 1;
+const int TRAP_RING = 2;
+
+const int BOUNDARY_OFF = 0;
+const int BOUNDARY_EDGES = 1;
+const int BOUNDARY_LINE_ART = 2;
+
+// Sum of the orbit values for the average colorings, and the last one added
+float orbitSum = 0.0;
+float orbitLast = 0.0;
+float orbitCount = 0.0;
+// The two previous points of the orbit, for the curvature
+vec2 orbitPrevious[2];
+int orbitHistory = 0;
+// Iteration where |z| was the smallest, for the atom domains
+float atomIteration = 0.0;
+float atomMinimum = 1e30;
+float atomDomain = 0.0;
+// Fractional part of the smooth iteration count, 1 when not smoothed
+float smoothFraction = 1.0;
+// Color of the trap image where the orbit first landed on it
+bool trapHit = false;
+vec3 trapColor;
+// Smallest distance of the orbit to the orbit trap
+float trapDistance = 1e30;
+
+// Forget the orbit of the previous sample
+void resetOrbit() {
+    interior = false;
+    orbitSum = 0.0;
+    orbitLast = 0.0;
+    orbitCount = 0.0;
+    orbitHistory = 0;
+    atomIteration = 0.0;
+    atomMinimum = 1e30;
+    atomDomain = 0.0;
+    smoothFraction = 1.0;
+    trapHit = false;
+    trapDistance = 1e30;
+    zDerivative = u_fractal_type == JULIA ? vec2(1.0, 0.0) : vec2(0.0);
+}
+
+// z is the new value of the orbit, computed from a previous value of squared modulus
+// previousModulus2
+void accumulateOrbit(in vec2 z, in float previousModulus2, in vec2 c) {
+    switch (u_coloringAlgorithm) {
+        case STRIPE_AVERAGE:
+            orbitLast = 0.5 + 0.5 * sin(u_stripeDensity * atan(z.y, z.x));
+            break;
+        case TRIANGLE_INEQUALITY: {
+            // Position of |z| between the bounds given by the triangle inequality
+            float lowBound = abs(previousModulus2 - length(c));
+            float highBound = previousModulus2 + length(c);
+            if(highBound - lowBound <= 0.0)
+                return;
+            orbitLast = (length(z) - lowBound) / (highBound - lowBound);
+            break;
+        }
+        case CURVATURE_AVERAGE: {
+            vec2 delta = z - orbitPrevious[0];
+            vec2 previousDelta = orbitPrevious[0] - orbitPrevious[1];
+            orbitPrevious[1] = orbitPrevious[0];
+            orbitPrevious[0] = z;
+            if(orbitHistory < 2) {
+                orbitHistory++;
+                return;
+            }
+            // Angle between the two moves, normalized to [0, 1]
+            float sine = delta.y * previousDelta.x - delta.x * previousDelta.y;
+            float cosine = delta.x * previousDelta.x + delta.y * previousDelta.y;
+            orbitLast = abs(atan(sine, cosine)) / 3.14159265;
+            break;
+        }
+        case IMAGE_TRAP: {
+            if(trapHit)
+                return;
+            float cosine = cos(u_trapRotation), sine = sin(u_trapRotation);
+            // Inverse rotation of z into the frame of the image, centered on the origin
+            vec2 uv = mat2(cosine, -sine, sine, cosine) * z / u_trapScale + 0.5;
+            if(any(lessThan(uv, vec2(0.0))) || any(greaterThan(uv, vec2(1.0))))
+                return;
+            // No implicit derivatives in the non-uniform control flow of the loop
+            vec4 texel = textureLod(u_trap, vec2(uv.x, 1.0 - uv.y), 0.0);
+            if(texel.a > 0.5) {
+                trapHit = true;
+                trapColor = texel.rgb;
+            }
+            return;
+        }
+        case ORBIT_TRAP: {
+            vec2 offset = z - u_trapCenter;
+            float distance;
+            if(u_trapShape == TRAP_LINE)
+                distance = abs(dot(offset, vec2(-sin(u_trapRotation), cos(u_trapRotation))));
+            else if(u_trapShape == TRAP_RING)
+                distance = abs(length(offset) - u_trapSize);
+            else
+                distance = length(offset);
+            trapDistance = min(trapDistance, distance);
+            return;
+        }
+        case ATOM_DOMAIN:
+            atomIteration += 1.0;
+            if(length(z) < atomMinimum) {
+                atomMinimum = length(z);
+                atomDomain = atomIteration;
+            }
+            return;
+        default:
+            return;
+    }
+    orbitSum += orbitLast;
+    orbitCount += 1.0;
+}
+
+// Value equalized by the histogram of the view, the texels are interpolated by hand as
+// float textures cannot be filtered everywhere
+float equalized(float value) {
+    float position = clamp(value / u_histogramRange, 0.0, 1.0) * float(HISTOGRAM_SIZE - 1);
+    int index = min(int(position), HISTOGRAM_SIZE - 2);
+    float low = texelFetch(u_histogram, ivec2(index, 0), 0).r;
+    float high = texelFetch(u_histogram, ivec2(index + 1, 0), 0).r;
+    return mix(low, high, position - float(index)) * u_histogramRange;
+}
+
+// Average of the orbit values, interpolated between the last two averages for smoothness
+float orbitAverage() {
+    if(orbitCount < 2.0)
+        return 0.0;
+    float average = orbitSum / orbitCount;
+    float previous = (orbitSum - orbitLast) / (orbitCount - 1.0);
+    return mix(previous, average, smoothFraction);
+}
+
+vec2 complexMultiply(in vec2 a, in vec2 b) {
+    return vec2(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x);
+}
+
+vec2 complexDivide(in vec2 a, in vec2 b) {
+    return vec2(a.x * b.x + a.y * b.y, a.y * b.x - a.x * b.y) / dot(b, b);
+}
+
+// z^u_exponent, by the polar form unless squaring
+vec2 complexPower(in vec2 z) {
+    if(u_exponent == 2.0)
+        return vec2(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y);
+    float modulus2 = dot(z, z);
+    if(modulus2 == 0.0)
+        return vec2(0.0);
+    float angle = u_exponent * atan(z.y, z.x);
+    return pow(modulus2, 0.5 * u_exponent) * vec2(cos(angle), sin(angle));
+}
+
+// dz' = n z^(n-1) dz + dc/dlocation, only when the distance estimate is drawn
+void accumulateDerivative(in vec2 z, in float dc) {
+    if(u_boundaryStyle == BOUNDARY_OFF)
+        return;
+    vec2 slope;
+    if(u_exponent == 2.0)
+        slope = 2.0 * z;
+    else if(dot(z, z) == 0.0)
+        slope = vec2(0.0);
+    else
+        slope = u_exponent * complexDivide(complexPower(z), z);
+    zDerivative = complexMultiply(slope, zDerivative) + vec2(dc, 0.0);
+}
 
 float julia_inner(in int max_iter, in float cutoff, inout vec2 z) {
     vec2 z2;
-    float tmp, value;
+    float value;
     int iterNumber;
 
     for(iterNumber = 0; iterNumber < max_iter; iterNumber++)
@@ -27,12 +310,13 @@ float julia_inner(in int max_iter, in float cutoff, inout vec2 z) {
             break;
         }
 
-        tmp = z2.x - z2.y + u_cJulia.x;
-        z.y = 2.0 * z.x * z.y + u_cJulia.y;
-        z.x = tmp;
+        accumulateDerivative(z, 0.0);
+        z = complexPower(z) + u_cJulia;
+        accumulateOrbit(z, z2.x + z2.y, u_cJulia);
     }
 
     if(iterNumber == max_iter) {
+        interior = true;
         iterNumber = 1000000;
     }
     return float(iterNumber + 1);
@@ -40,24 +324,223 @@ float julia_inner(in int max_iter, in float cutoff, inout vec2 z) {
 
 float mandelbrot_inner(in int max_iter, in float cutoff, inout vec2 c) {
     vec2 z = vec2(0.0, 0.0);
-    float tmp;
+    float modulus2;
     int iterNumber;
 
     for(iterNumber=0; iterNumber < max_iter; iterNumber++)
     {
-        if( (z.x * z.x + z.y * z.y) > cutoff) {
+        modulus2 = z.x * z.x + z.y * z.y;
+        if(modulus2 > cutoff) {
             break;
         }
 
-        tmp = z.x * z.x - z.y * z.y + c.x;
-        z.y = 2.0 * z.x * z.y + c.y;
-        z.x = tmp;
+        accumulateDerivative(z, 1.0);
+        z = complexPower(z) + c;
+        accumulateOrbit(z, modulus2, c);
     }
 
     // Used by the smoothing step
     c = z;
 
     if(iterNumber == max_iter) {
+        interior = true;
+        iterNumber = 0;
+    }
+    return float(iterNumber + 1);
+}
+
+// Mandelbrot-like variations of z^2 + c: the Burning Ship takes the absolute values of the
+// parts of z before squaring it, the Tricorn its conjugate
+float variation_inner(in int max_iter, in float cutoff, inout vec2 c) {
+    vec2 z = vec2(0.0, 0.0);
+    float modulus2;
+    int iterNumber;
+
+    for(iterNumber=0; iterNumber < max_iter; iterNumber++)
+    {
+        modulus2 = dot(z, z);
+        if(modulus2 > cutoff) {
+            break;
+        }
+        // The folds of the Burning Ship are not holomorphic, its derivative is approximated
+        // by the one of the folded z
+        if(u_fractal_type == BURNING_SHIP)
+            z = abs(z);
+        else {
+            z.y = -z.y;
+            zDerivative.y = -zDerivative.y;
+        }
+        accumulateDerivative(z, 1.0);
+        z = complexPower(z) + c;
+        accumulateOrbit(z, modulus2, c);
+    }
+
+    c = z;
+
+    if(iterNumber == max_iter) {
+        interior = true;
+        iterNumber = 0;
+    }
+    return float(iterNumber + 1);
+}
+
+// Newton's method on the polynomial of roots u_newtonRoots, from z. The logarithm of the
+// iterations to reach a root, plus the index of the root so that each basin has its colors
+float newton_inner(in int max_iter, inout vec2 z) {
+    const float TOLERANCE = 1e-6;
+    int root = -1;
+    int iterNumber;
+
+    for(iterNumber=0; iterNumber < max_iter && root < 0; iterNumber++)
+    {
+        vec2 a = z - u_newtonRoots[0];
+        vec2 b = z - u_newtonRoots[1];
+        vec2 d = z - u_newtonRoots[2];
+        vec2 derivative = complexMultiply(a, b) + complexMultiply(b, d) + complexMultiply(a, d);
+        if(dot(derivative, derivative) < 1e-20)
+            break;
+        float modulus2 = dot(z, z);
+        z -= complexDivide(complexMultiply(complexMultiply(a, b), d), derivative);
+        accumulateOrbit(z, modulus2, vec2(0.0));
+        for(int i = 0; i < 3; i++) {
+            if(dot(z - u_newtonRoots[i], z - u_newtonRoots[i]) < TOLERANCE)
+                root = i;
+        }
+    }
+
+    if(root < 0) {
+        interior = true;
+        return 0.0;
+    }
+    return log(float(iterNumber)) + float(root);
+}
+
+// Lyapunov exponent of the logistic map x -> r x (1 - x) from x = 0.5, with the rates r of
+// the sequence taken from the point. Negative where the map is stable, returned as a
+// positive value, the chaotic points are interior
+float lyapunov_inner(in int max_iter, in vec2 rates) {
+    // Iterations skipped before averaging, for x to settle
+    int warmup = min(50, max_iter / 4);
+    float x = 0.5;
+    float sum = 0.0;
+    for(int n = 0; n < max_iter; n++) {
+        float r = ((u_lyapunovSequence >> (n % u_lyapunovLength)) & 1) != 0 ? rates.y : rates.x;
+        if(n >= warmup)
+            sum += log(max(abs(r * (1.0 - 2.0 * x)), 1e-30));
+        x = r * x * (1.0 - x);
+        // Past 4 the map leaves [0, 1] and diverges
+        if(abs(x) > 1e10) {
+            interior = true;
+            return 0.0;
+        }
+    }
+    finalZ = vec2(x, 0.0);
+    float exponent = sum / float(max(max_iter - warmup, 1));
+    if(exponent >= 0.0) {
+        interior = true;
+        return 0.0;
+    }
+    return -exponent;
+}
+
+// Nova fractal: Newton's method on z^3 - 1 relaxed by u_novaRelaxation, plus c, from z = 1.
+// The logarithm of the iterations to converge
+float nova_inner(in int max_iter, inout vec2 c) {
+    const float TOLERANCE = 1e-6;
+    vec2 z = vec2(1.0, 0.0);
+    bool converged = false;
+    int iterNumber;
+
+    for(iterNumber=0; iterNumber < max_iter && !converged; iterNumber++)
+    {
+        vec2 z2 = complexMultiply(z, z);
+        if(dot(z2, z2) < 1e-20)
+            break;
+        vec2 step = u_novaRelaxation * complexDivide(complexMultiply(z2, z) - vec2(1.0, 0.0), 3.0 * z2) - c;
+        float modulus2 = dot(z, z);
+        z -= step;
+        accumulateOrbit(z, modulus2, c);
+        converged = dot(step, step) < TOLERANCE;
+    }
+
+    c = z;
+    if(!converged) {
+        interior = true;
+        return 0.0;
+    }
+    return log(float(iterNumber));
+}
+
+vec2 referencePoint(in int n) {
+    return texelFetch(u_referenceOrbit, ivec2(n % 4096, n / 4096), 0).xy;
+}
+
+// Mandelbrot or Julia iteration of the point perturbationDelta away from the reference
+// orbit: only the difference dz to the reference is iterated, small enough to keep the
+// details in single precision. It is moved back to the start of the reference when the
+// point gets closer to 0 than to the reference, or when the reference ends
+float perturbation_inner(in int max_iter, in float cutoff, inout vec2 location) {
+    bool julia = u_fractal_type == JULIA;
+    vec2 dc = julia ? vec2(0.0) : perturbationDelta;
+    vec2 dz = julia ? perturbationDelta : vec2(0.0);
+    vec2 c = julia ? u_cJulia : location;
+    vec2 z = referencePoint(0) + dz;
+    float modulus2;
+    int n = 0;
+    int iterNumber;
+
+    for(iterNumber = 0; iterNumber < max_iter; iterNumber++)
+    {
+        modulus2 = dot(z, z);
+        if(modulus2 > cutoff) {
+            break;
+        }
+        accumulateDerivative(z, julia ? 0.0 : 1.0);
+        dz = 2.0 * complexMultiply(referencePoint(n), dz) + complexMultiply(dz, dz) + dc;
+        n++;
+        z = referencePoint(n) + dz;
+        accumulateOrbit(z, modulus2, c);
+        if(dot(z, z) < dot(dz, dz) || n >= u_referenceLength - 1) {
+            dz = z - referencePoint(0);
+            n = 0;
+        }
+    }
+
+    location = z;
+
+    if(iterNumber == max_iter) {
+        interior = true;
+        iterNumber = julia ? 1000000 : 0;
+    }
+    return float(iterNumber + 1);
+}
+
+// BEGIN CUSTOM FRACTAL
+// Replaced at runtime by the custom fractal file
+vec2 iterate(vec2 z, vec2 c) {
+    return vec2(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + c;
+}
+// END CUSTOM FRACTAL
+
+float custom_inner(in int max_iter, in float cutoff, inout vec2 c) {
+    vec2 z = vec2(0.0, 0.0);
+    float modulus2;
+    int iterNumber;
+
+    for(iterNumber=0; iterNumber < max_iter; iterNumber++)
+    {
+        modulus2 = dot(z, z);
+        if(modulus2 > cutoff) {
+            break;
+        }
+        z = iterate(z, c);
+        accumulateOrbit(z, modulus2, c);
+    }
+
+    c = z;
+
+    if(iterNumber == max_iter) {
+        interior = true;
         iterNumber = 0;
     }
     return float(iterNumber + 1);
@@ -65,42 +548,139 @@ float mandelbrot_inner(in int max_iter, in float cutoff, inout vec2 c) {
 
 float computeHighQuality(in vec2 location)
 {
-    const int MAX_ITER = 4096;
+    int MAX_ITER = u_maxIterations;
     const float N = 16.0;
     float value;
 
     switch (u_fractal_type) {
         case JULIA:
-            value = julia_inner(MAX_ITER, N, location); break;
+            if(u_perturbation != 0)
+                value = perturbation_inner(MAX_ITER, N, location);
+            else
+                value = julia_inner(MAX_ITER, N, location);
+            break;
         case MANDELBROT:
-            value = mandelbrot_inner(MAX_ITER, N, location);
-            // Smoothing the fractal: result = value - log2( log( abs(location) / log(N) ) )
-            value = value - log2(log(sqrt(location.x * location.x + location.y * location.y)/log(4.0)) );
+            if(u_perturbation != 0)
+                value = perturbation_inner(MAX_ITER, N, location);
+            else
+                value = mandelbrot_inner(MAX_ITER, N, location);
+            // Smoothing the fractal: result = value - logn( log( abs(location) / log(N) ) )
+            value = value - log(log(length(location)/log(4.0))) / log(u_exponent);
+            smoothFraction = fract(value);
+            break;
+        case CUSTOM:
+            value = custom_inner(MAX_ITER, N, location); break;
+        case BURNING_SHIP:
+        case TRICORN:
+            value = variation_inner(MAX_ITER, N, location);
+            value = value - log(log(length(location)/log(4.0))) / log(u_exponent);
+            smoothFraction = fract(value);
             break;
+        case NEWTON:
+            value = newton_inner(MAX_ITER, location);
+            finalZ = location;
+            return value;
+        case LYAPUNOV:
+            finalZ = vec2(0.0);
+            return lyapunov_inner(MAX_ITER, location);
+        case NOVA:
+            value = nova_inner(MAX_ITER, location);
+            finalZ = location;
+            return value;
     }
+    finalZ = location;
     return log(value);
 }
 
 float computeLowQuality(in vec2 location)
 {
-    const int MAX_ITER = 1024;
+    int MAX_ITER = u_maxIterations;
     const float N = 4.0;
     float value;
 
     switch (u_fractal_type) {
         case JULIA:
-            value = julia_inner(MAX_ITER, N, location); break;
+            if(u_perturbation != 0)
+                value = perturbation_inner(MAX_ITER, N, location);
+            else
+                value = julia_inner(MAX_ITER, N, location);
+            break;
         case MANDELBROT:
-            value = mandelbrot_inner(MAX_ITER, N, location); break;
+            if(u_perturbation != 0)
+                value = perturbation_inner(MAX_ITER, N, location);
+            else
+                value = mandelbrot_inner(MAX_ITER, N, location);
+            break;
+        case CUSTOM:
+            value = custom_inner(MAX_ITER, N, location); break;
+        case BURNING_SHIP:
+        case TRICORN:
+            value = variation_inner(MAX_ITER, N, location); break;
+        case NEWTON:
+            value = newton_inner(MAX_ITER, location);
+            finalZ = location;
+            return value;
+        case LYAPUNOV:
+            finalZ = vec2(0.0);
+            return lyapunov_inner(MAX_ITER, location);
+        case NOVA:
+            value = nova_inner(MAX_ITER, location);
+            finalZ = location;
+            return value;
     }
+    finalZ = location;
     return log(value);
 }
 
+// Distance estimate of the escaped point to the boundary of the set, in pixels, from the last
+// z and its derivative. Negative when unknown
+float boundaryDistance() {
+    bool supported = u_fractal_type == JULIA || u_fractal_type == MANDELBROT
+        || u_fractal_type == BURNING_SHIP || u_fractal_type == TRICORN;
+    float modulus = length(finalZ);
+    float derivativeModulus = length(zDerivative);
+    if(!supported || interior || derivativeModulus == 0.0)
+        return -1.0;
+    return 0.5 * modulus * log(modulus) / derivativeModulus * u_fractalZoom;
+}
+
+// Period of the attracting cycle reached by z, 0 when not found.
+// MAX_PERIOD is kept in sync with period.rs
+float interiorPeriod(in vec2 z, in vec2 c) {
+    const int MAX_PERIOD = 64;
+    vec2 start = z;
+    for(int period = 1; period <= MAX_PERIOD; period++) {
+        z = complexPower(z) + c;
+        if(distance(z, start) < 1e-4)
+            return float(period);
+    }
+    return 0.0;
+}
+
+// BEGIN VALUE FORMULA
+// Replaced at runtime by the coloring formula
+float valueFormula(float value, vec2 location, vec2 z) {
+    return value;
+}
+// END VALUE FORMULA
+
 out vec4 out_color;
 
-void main(void)
+// Color of the point of the framebuffer fragCoord, in pixels
+vec3 shade(in vec2 fragCoord)
 {
-    vec2 location = vec2(gl_FragCoord.x, gl_FragCoord.y)/u_fractalZoom - u_fractalPosition;
+    resetOrbit();
+    // Like State::transform
+    mat2 transform = u_skew * mat2(cos(u_rotation), sin(u_rotation), -sin(u_rotation), cos(u_rotation));
+    vec2 location;
+    if(u_perturbation != 0) {
+        // Relative to the center, the offset keeps its precision however deep the zoom
+        perturbationDelta = transform * (fragCoord - u_viewportCenter) / u_fractalZoom;
+        location = u_viewCenter + perturbationDelta;
+    } else {
+        location = fragCoord/u_fractalZoom - u_fractalPosition;
+        location = u_viewCenter + transform * (location - u_viewCenter);
+    }
     float value;
 
     if(u_highQuality != 0)
@@ -108,9 +688,99 @@ void main(void)
     else
         value = computeLowQuality(location);
 
-    out_color =  vec4(
-        pow(
-            u_brightness + u_contrast * vec3(value, value, value) * vec3(u_r, u_g, u_b),
-            vec3(1.0/u_gamma)
-        ), 1.0);
+    bool escapeTime = u_fractal_type != NEWTON && u_fractal_type != LYAPUNOV && u_fractal_type != NOVA;
+    if(u_coloringAlgorithm == ITERATIONS && !interior && escapeTime) {
+        if(u_iterationColoring == BANDS)
+            value = log(floor(exp(value)));
+        else if(u_iterationColoring == HISTOGRAM)
+            value = equalized(value);
+    }
+
+    if(u_coloringAlgorithm == STRIPE_AVERAGE)
+        value = mix(value, orbitAverage(), u_stripeMix);
+    else if(u_coloringAlgorithm == TRIANGLE_INEQUALITY)
+        value = mix(value, orbitAverage(), u_tiaBlend);
+    else if(u_coloringAlgorithm == CURVATURE_AVERAGE)
+        value = mix(value, orbitAverage(), u_curvatureMix);
+    else if(u_coloringAlgorithm == ATOM_DOMAIN)
+        value = atomDomain;
+    else if(u_coloringAlgorithm == ORBIT_TRAP)
+        // Grows as the orbit comes closer to the trap
+        value = -log(max(trapDistance, 1e-6));
+    else if(u_coloringAlgorithm == PERIOD && interior)
+        value = interiorPeriod(finalZ, u_fractal_type == JULIA ? u_cJulia : location);
+    else if(u_coloringAlgorithm == BINARY_DECOMPOSITION) {
+        value = mix(value, finalZ.y > 0.0 ? 1.0 : 0.0, u_decompositionMix);
+        if(u_fieldLines != 0) {
+            // The external field lines follow the argument of the last z
+            const float LINES = 16.0;
+            float turns = atan(finalZ.y, finalZ.x) / 6.28318531 * LINES;
+            value *= smoothstep(0.0, 0.15, abs(fract(turns + 0.5) - 0.5));
+        }
+    }
+
+    if(u_valueFormula != 0)
+        value = valueFormula(value, location, finalZ);
+
+    vec3 color;
+    if(u_coloringAlgorithm == IMAGE_TRAP && trapHit)
+        color = trapColor;
+    else if(u_customColoring != 0)
+        color = customColor(value);
+    else if(u_usePalette != 0)
+        color = texture(u_palette, vec2(fract(u_brightness + u_colorPhase + u_contrast * value), 0.5)).rgb;
+    else if(u_colorPhase != 0.0)
+        // The tint wraps around like a palette while it cycles
+        color = fract(u_brightness + u_colorPhase + u_contrast * value) * vec3(u_r, u_g, u_b);
+    else
+        color = u_brightness + u_contrast * vec3(value, value, value) * vec3(u_r, u_g, u_b);
+
+    if(u_coloringAlgorithm == TEXTURE_MAP && !interior) {
+        // The iteration count goes along the texture, the angle of the last z across it
+        vec2 uv = vec2(u_brightness + u_colorPhase + u_contrast * value, atan(finalZ.y, finalZ.x) / 6.28318531 + 0.5);
+        uv = u_textureMirror != 0 ? 1.0 - abs(1.0 - mod(uv, 2.0)) : fract(uv);
+        color = mix(color, textureLod(u_exterior, uv, 0.0).rgb, u_textureBlend);
+    }
+
+    if(u_boundaryStyle != BOUNDARY_OFF) {
+        float pixels = boundaryDistance();
+        // 0 on the boundary, 1 from u_boundaryWidth pixels away from it
+        float edge = interior || pixels < 0.0 ? 1.0 : smoothstep(0.0, u_boundaryWidth, pixels);
+        if(u_boundaryStyle == BOUNDARY_LINE_ART)
+            color = vec3(edge);
+        else
+            color *= edge;
+    }
+
+    return pow(color, vec3(1.0/u_gamma));
+}
+
+vec3 colorBalance(in vec3 color)
+{
+    color = clamp(color, 0.0, 1.0);
+    color = color * (1.0 + u_gain) + u_lift * (1.0 - color);
+    return pow(clamp(color, 0.0, 1.0), 1.0 / (1.0 + u_balanceGamma));
+}
+
+// Pseudo-random position inside the part `index` of the pixel, the same in every frame
+vec2 jitter(vec2 pixel, int index)
+{
+    vec2 seed = pixel + float(index + 97 * u_jitterSeed) * vec2(17.0, 31.0);
+    return fract(sin(vec2(dot(seed, vec2(12.9898, 78.233)), dot(seed, vec2(39.3468, 11.135)))) * 43758.5453);
+}
+
+void main(void)
+{
+    if(u_circle.z > 0.0 && distance(gl_FragCoord.xy, u_circle.xy) > u_circle.z)
+        discard;
+    int samples = max(u_supersampling, 1);
+    vec3 color = vec3(0.0);
+    // Regular grid of samples inside the pixel, jittered or at the center of their cell
+    for(int i = 0; i < samples; i++)
+        for(int j = 0; j < samples; j++)
+        {
+            vec2 offset = u_jitter != 0 ? jitter(gl_FragCoord.xy, i * samples + j) : vec2(0.5);
+            color += shade(gl_FragCoord.xy - 0.5 + (vec2(i, j) + offset) / float(samples));
+        }
+    out_color = vec4(colorBalance(color / float(samples * samples)), 1.0);
 }
@@ -0,0 +1,515 @@
+# English messages of the UI, the fallback of the other languages.
+
+about = About
+about-button = About…
+accessibility = Accessibility
+accumulate-samples = Accumulate samples
+accumulate-samples-hint = Once the view stopped, average more frames of random samples into it for smoother details.
+add-annotation-hint = Press N over the view to add a note at the cursor
+add-bookmark = Bookmark this view
+add-keyframe = Add the current view as a keyframe
+add-lfo = Add LFO
+add-monitor = Add a monitor
+add-node = Add node
+algorithm = Algorithm
+algorithm-atom-domains = Atom domains
+algorithm-binary-decomposition = Binary decomposition
+algorithm-curvature-average = Curvature average
+algorithm-image-trap = Image trap
+algorithm-interior-period = Interior period
+algorithm-iteration-count = Iteration count
+algorithm-orbit-trap = Orbit trap
+algorithm-stripe-average = Stripe average
+algorithm-texture-map = Exterior texture
+algorithm-triangle-inequality-average = Triangle inequality average
+amount = Amount
+analysis = Analysis
+animate = Animate…
+animation = Animation
+animation-help = { $variable } is the time in seconds. Available: + - * / ^, pi, sin, cos, tan, abs, sqrt, exp, log, floor, fract
+annotations = Annotations
+argument = Argument
+audio = Audio
+auto-iterations = Automatic
+auto-iterations-hint = Double the iterations with every tenfold zoom, so that the deep zooms keep their details.
+auto-skew = Auto skew
+auto-skew-hint = Correct the stretch of deep zooms
+back = Back
+band-bass = Bass
+band-mids = Mids
+band-treble = Treble
+battery-saver = Battery saver
+battery-saver-hint = Animations and moves of the view are drawn at 30 FPS at most. Nothing is drawn while the view does not change
+benchmark = Benchmark
+benchmark-button = Benchmark…
+benchmark-failed = Failed
+benchmark-high = High
+benchmark-hint = Renders fixed views at fixed sizes and reports the median GPU time per frame, to compare GPUs and versions
+benchmark-low = Low
+benchmark-milliseconds = { $value } ms
+benchmark-quality = Quality
+benchmark-resolution = Resolution
+benchmark-time = Time per frame
+benchmark-view = View
+blue = Blue
+bookmark-name = Bookmark name
+bookmarks = Bookmarks
+boundary = Boundary
+boundary-edges = Dark edges
+boundary-hint = Drawn from the distance estimate to the set, sharp at any zoom. The supersampling smooths it further
+boundary-line-art = Line art
+boundary-off = Not drawn
+boundary-unsupported = Only for the Julia, Mandelbrot, Burning Ship and Tricorn fractals
+boundary-width = Boundary width
+brightness = Brightness
+cancel = Cancel
+cannot-load-plugin = Cannot load the plugin
+center-and-zoom = Center and zoom
+center-coordinates = Center
+clear = Clear
+click-to-recenter = Click to recenter
+click-to-recenter-hint = A click moves the point clicked to the center. Turn it off to avoid accidental clicks while dragging
+click-zoom-step = Double click zoom step
+close-message = Close the message
+close-tab = Close the tab
+color-parameters = Color parameters
+coloring-formula = Coloring formula
+coloring-graph = Coloring graph
+composite-export = Export several views
+composite-export-hint = Several views in one PNG image: the Mandelbrot set next to a Julia set, or a wallpaper spanning monitors.
+composite-width = Each view is half of the export width of { $width } px
+contrast = Contrast
+copy-coordinates = Copy coordinates
+copy-coordinates-hint = Copy the center and the magnification of the view at full precision, to write them down or share them
+copy-diagnostics = Copy diagnostics
+copy-shader-error = Copy the log
+copy-view-link = Copy view as link
+copy-view-link-hint = Copy a link to the exact view and colors, to share it. Paste it in the field below, or over the fractal, to open it.
+cpu-renderer = Draw on the CPU
+cpu-renderer-hint = Slower, for the GPUs that draw incorrectly. Only the built-in fractals with the iteration coloring
+cpu-unsupported-boundary = The boundary style { $style } is only drawn on the GPU
+cpu-unsupported-color-balance = The color balance is only drawn on the GPU
+cpu-unsupported-color-cycling = The color cycling is only drawn on the GPU
+cpu-unsupported-coloring = The { $coloring } coloring is only drawn on the GPU
+cpu-unsupported-fractal = The { $fractal } fractal is only drawn on the GPU
+cpu-unsupported-graph = The coloring graph is only drawn on the GPU
+cpu-unsupported-value-formula = The value formula is only drawn on the GPU
+crosshair = Crosshair
+crosshair-hint = Mark the center of the view, the point of the coordinates above
+current-iterations = { $iterations } now
+cursor = Cursor
+curvature-mix = Curvature mix
+custom-fractal-hint = GLSL function iterate(z, c) giving the next value of the orbit. It is reloaded when the file is saved, errors are in the log.
+cycle-colors = Cycle colors
+cycle-colors-hint = Shift the colors along the palette with the time, the classic color cycling
+cycling-speed = Cycling speed
+decomposition-mix = Decomposition mix
+deepest-zoom = Deepest zoom
+deepest-zoom-hint = The center of the view is a double: the pixels cannot be closer than its precision, the zoom stops about 1e15 pixels per unit.
+depth = Depth
+duration = Duration
+easing = Easing
+easing-linear = Linear
+easing-smooth = Smooth
+edit-custom-fractal = Edit the function
+exit = Exit
+explain-regions = Explain regions
+explain-regions-hint = Outline and name the main parts of the Mandelbrot set
+exponent = Exponent
+export-big-tiff = Export a BigTIFF image
+export-big-tiff-hint = Tiled TIFF of the view at the width given, for the image viewers and GIS tools handling gigapixel images.
+export-deep-zoom = Export a Deep Zoom image
+export-deep-zoom-hint = Tile pyramid of the view at the width given, to pan and zoom in a web page with OpenSeadragon.
+export-iterations = Export the iterations
+export-iterations-hint = Smooth iteration count of each pixel of the view, as a NumPy array (.npy) or CSV. NaN inside the set.
+export-kfr = Export .kfr location…
+export-mandel-machine = Export Mandel Machine location…
+export-overlays = Annotations and scale bar
+export-overlays-hint = Draw the visible annotations and scale bar over the exported image.
+export-png = Export a PNG image
+export-png-hint = The view rendered offscreen at the width given, independently of the size of the window.
+export-render-statistics = Export as CSV
+export-render-statistics-hint = Frame time, GPU time, iteration budget, magnification and quality of every frame of the session.
+export-video = Export the video
+export-video-hint = Encoded with ffmpeg to MP4 or WebM, or written as numbered PNG images.
+export-width = Export width
+exported = Exported { $file }
+external-rays = External rays
+field-lines = Field lines
+files = Files
+filter-bigtiff = BigTIFF image
+filter-csv = CSV table
+filter-deep-zoom = Deep Zoom image
+filter-gradients = UltraFractal gradients
+filter-images = Images
+filter-kfr = Kalles Fraktaler location
+filter-mmf = Mandel Machine location
+filter-mp4 = MP4 video
+filter-npy = NumPy array
+filter-png = PNG image
+filter-png-sequence = Numbered PNG images
+filter-preset = Preset
+filter-replay = Input replay
+filter-webm = WebM video
+find-nearby-minibrot = Find nearby minibrot
+find-nearby-minibrot-hint = Fly to the nucleus of the most prominent minibrot around the center of the view. Computed with 64-bit floats, it is out of reach past a magnification of about 10¹².
+fine-tuning = Fine tuning
+finish = Finish
+formula-functions = Available: + - * / ^, pi, sin, cos, tan, abs, sqrt, exp, log, floor, fract
+fractal-burning-ship = Burning Ship
+fractal-custom = Custom
+fractal-julia = Julia
+fractal-lyapunov = Lyapunov
+fractal-mandelbrot = Mandelbrot
+fractal-newton = Newton
+fractal-nova = Nova
+fractal-tricorn = Tricorn
+fractal-view = Fractal view, centered on { $re } { $im }i, magnification { $magnification }. Arrows to move, + and - to zoom
+frame-rate = Frame rate
+frame-rate-cap = Frame rate cap
+frame-time = { $time } ms per frame ({ $fps } FPS)
+frame-time-plot = Frame time on the CPU, up to { $max } ms
+fullscreen = Fullscreen
+fullscreen-hint = Borderless fullscreen, for screenshots. F11 toggles it too
+gallery = Gallery
+gallery-empty = No bookmarks yet, add some in the bookmarks section
+gallery-hint = Click a view to open it, right click to rename or remove it
+gamma = Gamma
+global-parameters = Global parameters
+go = Go
+gpu = GPU
+gpu-in-use = In use: { $adapter }
+gpu-time = GPU { $time } ms
+gpu-time-plot = GPU time of the view, up to { $max } ms
+gradient-editor-hint = Double-click to add a color stop. Drag the handles below to move the stops, click one to change its color.
+green = Green
+help = Help
+help-brightness = Offset added to the coloring value after the contrast
+help-channel = Weight of the channel in the RGB tint, the color is value × (red, green, blue)
+help-contrast = Factor applied to the coloring value before the palette or the RGB tint
+help-curvature-mix = Blend between the iteration count and the average turning angle of the orbit
+help-cycling-speed = Palettes gone through per second by the color cycling, backwards when negative
+help-decomposition-mix = Blend between the iteration count and the sign of the imaginary part of the last z
+help-exponent = Power n of z^n + c, 2 for the usual sets. Other values, fractional ones included, give the multibrot and multijulia sets, without the deep zoom
+help-gamma = Each color channel is raised to the power 1 / gamma
+help-julia-angle = Argument of the constant c in z → z² + c, in degrees
+help-julia-radius = Modulus of the constant c in z → z² + c
+help-julia-x = Real part of the constant c in z → z² + c
+help-julia-y = Imaginary part of the constant c in z → z² + c
+help-max-iterations = Iterations of the orbit before a point is taken as inside the set, more iterations show more details near the boundary
+help-nova-relaxation = Multiplier of the Newton step on z³ - 1 before adding c, 1 for Newton's method itself
+help-overlay = What the controls do (F1)
+help-stripe-density = Frequency k of sin(k·arg z) averaged along the orbit
+help-stripe-mix = Blend between the iteration count and the stripe average
+help-texture-blend = Blend between the usual colors and the texture, mapped on the exterior with the iteration count (scaled by the contrast) along it and the angle of the last z across it
+help-tia-blend = Blend between the iteration count and the average position of |z| between the bounds of the triangle inequality
+help-trap-scale = Width of the trap image in the complex plane, the image is centered on the origin and the orbit takes the color of the first opaque pixel it lands on
+help-zoom = Pixels per unit of the complex plane, the view is height / zoom units high
+high-contrast = High contrast
+high-quality = High Quality
+highlights = Highlights
+hours-ago = { $count } h ago
+hud = Coordinates overlay
+hud-center = Center { $re } { $im }i
+hud-hint = Show the point under the cursor, the center, the magnification and the iterations over the fractal.
+hud-iterations = { $count } iterations
+hud-pointer = Cursor { $re } { $im }i
+hue = Hue
+hue-range = Hue range
+hyperbolic-component = Hyperbolic component of period { $period }
+import-kfr = Import .kfr location…
+import-ugr = Import .ugr gradients…
+invalid-link = Invalid link: { $error }
+invert-scroll = Invert the scroll zoom
+iteration-coloring = Iteration coloring
+iteration-coloring-bands = Escape-time bands
+iteration-coloring-hint = Smooth count, one band per iteration, or histogram equalization: each color covers as much of the view
+iteration-coloring-histogram = Histogram
+iteration-coloring-smooth = Smooth
+iteration-data-unsupported = The iterations of the { $fractal } fractal are only computed on the GPU
+iteration-formula = Formula
+iteration-formula-hint = Iteration of z from 0, like z = z^3 + c, with the complex numbers z, c and i, the operators + - * / ^ and the functions sin, cos, tan, exp, log, sqrt and abs.
+iteration-value = Iteration value
+jittered-samples = Jittered samples
+jittered-samples-hint = Several samples per pixel at random positions inside it, against the moiré of the fine details.
+julia-1 = Julia 1
+julia-2 = Julia 2
+julia-constant = Julia constant
+julia-parameters = Julia parameters
+keyframe = Keyframe { $index }
+landmark-antenna = Antenna (filament)
+landmark-elephant-valley = Elephant valley
+landmark-main-cardioid = Main cardioid (period 1)
+landmark-period-2-bulb = Period 2 bulb
+landmark-period-3-bulb = Period 3 bulb
+landmark-period-3-minibrot = Period 3 minibrot
+landmark-seahorse-valley = Seahorse valley
+language = Language
+layout-editing-colors = Editing colors
+layout-exploring = Exploring
+layout-name = Layout name
+layout-presenting = Presenting
+layouts = Layouts
+lfo = LFO { $number }
+listen-to-the-audio-input = Listen to the audio input
+load-exterior-texture = Load exterior texture…
+load-preset = Load preset…
+load-trap-image = Load trap image…
+lock-hue-range = Lock the hue range
+loop-timeline = Loop
+lyapunov-parameters = Lyapunov parameters
+lyapunov-sequence = Sequence
+lyapunov-sequence-empty = The sequence is empty
+lyapunov-sequence-hint = Growth rates of the logistic map in turn: A is the x coordinate of the point, B its y coordinate. Up to 32 letters, like AB or BBBBBBAAAAAA
+lyapunov-sequence-invalid = Only A and B are allowed, not { $rate }
+lyapunov-sequence-too-long = The sequence has more than { $max } rates
+lyapunov-view = Show the rates from 2 to 4
+lyapunov-view-hint = Move to the square of growth rates between 2 and 4, where the stable and chaotic regions meet
+magnification = Magnification { $value }
+magnification-value = Magnification
+magnifier = Magnifier
+max-iterations = Iterations
+measure = Measure
+measure-hint = Click two points of the view to measure their distance, Escape clears the measurement
+measurement = Δ = { $re } { $im }i   |Δ| = { $distance }   angle { $angle }°
+middle-button = Middle button
+midtones = Midtones
+minibrot-found = Flying to a minibrot of period { $period }
+minibrot-not-found = No minibrot found around the center of the view
+minibrot-too-deep = The view is too deep to locate its minibrots
+minibrot-too-small = The minibrot found is too small to fly to
+minimap = Minimap
+minimap-hint = The whole fractal in a corner, with the outline of the view. Click or drag in it to move the view there
+minutes-ago = { $count } min ago
+mirror-texture = Mirror the repetitions
+mirror-texture-hint = Mirror every other repetition of the texture so that it has no seams
+misiurewicz-found = Found the Misiurewicz point M({ $preperiod }, { $period })
+misiurewicz-hint = Press M over the fractal to find the closest Misiurewicz point
+misiurewicz-not-found = No Misiurewicz point found under the pointer
+misiurewicz-point = M({ $preperiod }, { $period }) at { $re } { $im }i
+modulation = Modulation
+modulus = Modulus
+monitor-height = Height
+monitor-width = Width
+mouse-controls = Mouse
+move-up = Move up
+new-tab = New tab
+new-tab-hint = Open the view in a new tab, to compare regions or keep a reference while exploring
+newton-root = Root { $index }
+newton-roots = Polynomial roots
+next = Next
+next-seed = Next seed
+no-configuration-directory = No configuration directory
+node-clamp = Clamp to [0, 1]
+node-fractional-part = Fractional part
+node-log = Log
+node-offset = Offset
+node-power = Power
+node-scale = Scale
+node-sine = Sine
+node-square-root = Square root
+not-in-a-hyperbolic-component = Not in a hyperbolic component
+note-number = Note { $number }
+nova-parameters = Nova parameters
+nova-relaxation = Relaxation
+nudge = Nudge with the keyboard
+nudge-hint = ] increases and [ decreases the parameter last used, Shift for finer steps and Ctrl for coarser ones.
+nudge-parameter = Parameter
+nudge-step = Step, of the range of the slider
+oklab-interpolation = Interpolate in OKLab
+oklab-interpolation-hint = Blend the colors between the stops in a perceptual color space, instead of straight lines in RGB that give muddy midpoints
+open = Open
+open-gallery = Gallery
+output = Output
+output-grayscale = Grayscale
+output-palette = Palette
+output-rgb-tint = RGB tint
+overlay-bookmarks = Views saved under a name. The gallery shows them as thumbnails, and a link shares the view
+overlay-canvas = Drag to pan, click to center a point, double-click to zoom in and right double-click to zoom out, or use the mouse wheel. Pinch to zoom on touch screens. WASD pans, and once the canvas is focused the arrow keys pan and + and - zoom.
+overlay-close = Click anywhere or press Escape to close
+overlay-coloring = Over a slider the mouse wheel changes it, Shift for finer and Ctrl for coarser steps. 📌 pins it above the canvas
+overlay-files = Ctrl+S exports a PNG image and F12 saves a screenshot. Drop a screenshot or a preset on the window to open it
+overlay-help = The tour of the interface, and these notes
+overlay-julia = Drag to move the constant c of the Julia set, Shift for finer moves
+overlay-mouse-controls = Which buttons pan and center the view, and how much the wheel and double-clicks zoom
+overlay-nudge = [ and ] change the last slider used by a step
+overlay-type = The number keys 1 to 7 switch between the fractal types
+palette = Palette
+palette-from-image = Palette from image…
+pan-button = Drag with
+paste-link = Paste a link
+path-center = Center
+path-circle = Circle
+path-lemniscate = Lemniscate
+path-length = { $points } points, { $duration } s
+path-pause = ⏸ Pause
+path-play = ▶ Play
+path-radius = Radius
+path-record = ⏺ Record
+path-record-hint = Record the moves of the Julia pad
+path-recorded = Recorded
+path-shape = Path of c
+path-speed = Path speed
+path-stop = ⏹ Stop
+performance-overlay = Performance overlay
+perturbation = Perturbation
+perturbation-hint = The view is deep enough to be computed around the orbit of its center, in double precision.
+phase = Phase
+pick-julia-seed = Pick Julia seed
+pick-julia-seed-hint = Click a point of the view to switch to its Julia set. Ctrl+click does it without this mode.
+pin-to-the-toolbar = Pin to the toolbar
+pixel-size = Pixel size { $value }
+play-replay = Play a recording
+play-replay-hint = Plays the recorded input from the recorded starting state. Escape stops it.
+play-timeline = Play
+plugin-missing = No plugin { $plugin }.wasm in the plugins directory
+preferred-gpu = Preferred GPU
+preview = Preview
+previous-seed = Previous seed
+primary-button = Left button
+probe = Probe
+probe-bounded = Bounded for { $count } iterations
+probe-cycle = Cycle of period { $period }
+probe-escaped = Escapes at iteration { $count }
+probe-gpu-only = Only computed on the GPU
+probe-hint = Show the orbit of the point under the pointer and whether it escapes, computed on the CPU with the formula of the shader
+probe-no-cycle = No cycle found
+probe-orbit = Orbit of { $length } points
+probe-smooth = Smooth count { $value }
+progressive-refinement = Progressive refinement
+progressive-refinement-hint = Draw the view at a lower resolution while it changes, and at the full resolution once it stops.
+random-palette = Random palette
+rate = Rate
+ray = Ray { $angle }
+recent = Recent
+recenter-button = Recenter with
+record-input = Record the input
+red = Red
+redo = Redo
+reduced-motion = Reduced motion
+reduced-motion-hint = Jump to the new views instead of flying to them, and zoom in steps
+refine-region = Refine a region
+refine-region-hint = Drag over the view to render a rectangle at the highest quality, with 3×3 samples per pixel
+remove = Remove
+remove-keyframe = Remove the keyframe
+rename = Rename
+render-farm = Render farm
+render-farm-hint = Render the tiles of the exports on the machines running `fractal_gl worker <this machine>:<port>` instead of this GPU
+render-farm-port = Port
+render-farm-unsupported = The workers of the render farm cannot draw the custom formulas, the coloring graph nor the images
+render-farm-workers = { $count } workers connected on port { $port }
+render-statistics = Render statistics
+renderer-unavailable = The GPU cannot draw the fractal, it is drawn on the CPU. Help → About lists what the GPU supports. { $error }
+repaint-on-input = Idle, drawn on input
+reset = Reset
+reset-section = Reset the parameters of this section
+reset-view = Reset view
+reset-view-hint = Back to the whole fractal, unturned, with the same parameters and colors
+restart-to-apply = Restart to apply
+reveal-in-folder = Reveal in folder
+rotation = Rotation
+rotation-hint = Turn the view around its center. Shift and drag over the fractal to turn it with the mouse
+run-benchmark = Run
+run-script = Run
+save-layout = Save the current layout
+save-preset = Save preset…
+save-preset-hint = Save the view and its colors to a TOML file that can be edited by hand and shared
+scale-bar = Scale bar
+script-console = Script console
+script-error = Error
+script-help = Rhai script over the view: zoom(z), zoom_by(factor), get_zoom(), center(re, im), get_center(), julia(re, im), rotation(degrees), iterations(n), fractal("mandelbrot"), step() shows the view as a frame, snapshot("file.png") exports it.
+script-too-long = The script was stopped after { $seconds } s, the app is frozen while it runs
+scroll-zoom-step = Scroll zoom step
+secondary-button = Right button
+seconds-ago = { $count } s ago
+seed = Seed
+set-end-keyframe = End at this view
+set-start-keyframe = Start from this view
+settings-panel = Settings
+shader-error = Shader error
+shader-error-hint = The shaders do not build, the view keeps the last working ones.
+shadows = Shadows
+show-annotations = Show the annotations
+show-keyframe-hint = Shows the view of the keyframe
+side-by-side = Mandelbrot and Julia
+side-by-side-hint = The Mandelbrot set next to the Julia set of the split view
+side-by-side-unavailable = Only for the Mandelbrot set
+sixteen-bit-channels = 16 bits per channel
+sixteen-bit-unsupported = This GPU cannot render 16 bits per channel
+skip-tour = Skip the tour
+snap-to-grid = Snap to the grid
+split-julia-hint = Julia set of the point under the pointer in the Mandelbrot set. Click to explore it.
+split-view = Split view with the Julia set
+split-view-hint = Show beside the Mandelbrot set the Julia set of the point under the pointer.
+status-cannot-export = Cannot export { $file }
+status-cannot-open = Cannot open { $file }
+status-cannot-save = Cannot save { $file }
+status-export-failed = The export failed
+status-export-resumed = Resuming the interrupted export of { $file }
+status-history = Last messages
+status-opened = Opened { $file }
+status-opened-rounded = Opened { $file }, its center is rounded to the precision of the renderer
+status-render-farm-failed = Cannot start the render farm
+status-saved = Saved { $file }
+stop-animating = Stop animating
+stop-recording = Stop recording
+stop-timeline = Stop
+stripe-density = Stripe density
+stripe-mix = Stripe mix
+supersampling = Supersampling
+supersampling-hint = Render the view larger and scale it down, for smoother edges. Slower.
+tab = View { $index }
+target-hue-shift = Hue shift
+target-julia-angle = Julia angle
+target-nothing = Nothing
+target-zoom-pulse = Zoom pulse
+texture-blend = Texture blend
+tia-blend = TIA blend
+timeline = Timeline
+trace-ray = Trace ray
+trap-angle = Line angle
+trap-center = Trap center
+trap-coloring = Coloring
+trap-line = Line
+trap-point = Point
+trap-ring = Ring
+trap-rotation = Trap rotation
+trap-scale = Trap scale
+trap-size = Ring radius
+tutorial = Tour
+tutorial-button = Tour of the interface…
+tutorial-canvas = This is the fractal. Click to center the view on a point, double-click to zoom in, right double-click to zoom out, and use the mouse wheel to zoom. Once focused, the arrow keys pan and + and - zoom.
+tutorial-export = The Files section imports and exports locations for other fractal programs. The tour can be shown again from the Help menu, and the ? button next to it, or F1, explains every control.
+tutorial-julia = With the Julia type, drag in this pad to move the constant c of the Julia set. Hold Shift for finer moves.
+tutorial-progress = Step { $step } of { $total }
+tutorial-type = Switch between the Mandelbrot set and the Julia sets here.
+type = Type
+undo = Undo
+unknown = unknown
+unlimited = Unlimited
+unpin = Unpin
+unpin-from-the-toolbar = Unpin from the toolbar
+use-palette = Use palette
+use-the-coloring-graph = Use the coloring graph
+use-the-formula = Use the formula
+variable-angle = Argument of the last z, in [-pi, pi]
+variable-iter = Iteration count
+variable-t = Time in seconds
+variable-value = Logarithm of the iteration count, smoothed in high quality
+variable-x = Real part of the point
+variable-y = Imaginary part of the point
+variable-zx = Real part of the last z
+variable-zy = Imaginary part of the last z
+variables = Variables
+video-frames = { $count } frames
+view-center = View center
+vsync = VSync
+wallpaper = Wallpaper
+wallpaper-hint = The view continued across the monitors, in pixels of the desktop
+waveform-random = Random
+waveform-sine = Sine
+waveform-triangle = Triangle
+zoom = Zoom
+zoom-box = Zoom on a box
+zoom-box-hint = Draw a box over the fractal to zoom in on it. A drag with the secondary button draws one too, unless it pans the view
+zoom-video = Zoom video
@@ -0,0 +1,515 @@
+# Messages français de l’interface.
+
+about = À propos
+about-button = À propos…
+accessibility = Accessibilité
+accumulate-samples = Accumuler les échantillons
+accumulate-samples-hint = Une fois la vue arrêtée, y moyenner d’autres images d’échantillons aléatoires pour des détails plus lisses.
+add-annotation-hint = Appuyez sur N au-dessus de la vue pour ajouter une note sous le curseur
+add-bookmark = Ajouter cette vue aux signets
+add-keyframe = Ajouter la vue actuelle comme image clé
+add-lfo = Ajouter un LFO
+add-monitor = Ajouter un écran
+add-node = Ajouter un nœud
+algorithm = Algorithme
+algorithm-atom-domains = Domaines atomiques
+algorithm-binary-decomposition = Décomposition binaire
+algorithm-curvature-average = Moyenne de courbure
+algorithm-image-trap = Piège image
+algorithm-interior-period = Période intérieure
+algorithm-iteration-count = Nombre d’itérations
+algorithm-orbit-trap = Piège orbital
+algorithm-stripe-average = Moyenne des rayures
+algorithm-texture-map = Texture extérieure
+algorithm-triangle-inequality-average = Moyenne de l’inégalité triangulaire
+amount = Intensité
+analysis = Analyse
+animate = Animer…
+animation = Animation
+animation-help = { $variable } est le temps en secondes. Disponible : + - * / ^, pi, sin, cos, tan, abs, sqrt, exp, log, floor, fract
+annotations = Annotations
+argument = Argument
+audio = Audio
+auto-iterations = Automatique
+auto-iterations-hint = Doubler les itérations à chaque zoom par dix, pour que les zooms profonds gardent leurs détails.
+auto-skew = Redressement automatique
+auto-skew-hint = Corrige l’étirement des zooms profonds
+back = Précédent
+band-bass = Graves
+band-mids = Médiums
+band-treble = Aigus
+battery-saver = Économie de batterie
+battery-saver-hint = Les animations et les déplacements de la vue sont dessinés à 30 IPS au plus. Rien n’est dessiné tant que la vue ne change pas
+benchmark = Banc d’essai
+benchmark-button = Banc d’essai…
+benchmark-failed = Échec
+benchmark-high = Haute
+benchmark-hint = Rend des vues fixes à des tailles fixes et donne le temps GPU médian par image, pour comparer les GPU et les versions
+benchmark-low = Basse
+benchmark-milliseconds = { $value } ms
+benchmark-quality = Qualité
+benchmark-resolution = Résolution
+benchmark-time = Temps par image
+benchmark-view = Vue
+blue = Bleu
+bookmark-name = Nom du signet
+bookmarks = Signets
+boundary = Frontière
+boundary-edges = Bords sombres
+boundary-hint = Tracée à partir de l’estimation de la distance à l’ensemble, nette à tout zoom. Le suréchantillonnage la lisse davantage
+boundary-line-art = Dessin au trait
+boundary-off = Non tracée
+boundary-unsupported = Seulement pour les fractales de Julia, de Mandelbrot, Burning Ship et Tricorn
+boundary-width = Largeur de la frontière
+brightness = Luminosité
+cancel = Annuler
+cannot-load-plugin = Impossible de charger le plugin
+center-and-zoom = Centrer et zoomer
+center-coordinates = Centre
+clear = Effacer
+click-to-recenter = Cliquer pour recentrer
+click-to-recenter-hint = Un clic déplace le point cliqué au centre. Désactivez-le pour éviter les clics accidentels en faisant glisser
+click-zoom-step = Pas du zoom du double clic
+close-message = Fermer le message
+close-tab = Fermer l’onglet
+color-parameters = Paramètres de couleur
+coloring-formula = Formule de coloration
+coloring-graph = Graphe de coloration
+composite-export = Exporter plusieurs vues
+composite-export-hint = Plusieurs vues dans une image PNG : l’ensemble de Mandelbrot à côté d’un ensemble de Julia, ou un fond d’écran couvrant plusieurs écrans.
+composite-width = Chaque vue fait la moitié de la largeur d’export de { $width } px
+contrast = Contraste
+copy-coordinates = Copier les coordonnées
+copy-coordinates-hint = Copier le centre et le grossissement de la vue en pleine précision, pour les noter ou les partager
+copy-diagnostics = Copier le diagnostic
+copy-shader-error = Copier le journal
+copy-view-link = Copier la vue comme lien
+copy-view-link-hint = Copier un lien vers la vue et les couleurs exactes, pour les partager. Le coller dans le champ ci-dessous, ou sur la fractale, pour l’ouvrir.
+cpu-renderer = Dessiner avec le CPU
+cpu-renderer-hint = Plus lent, pour les GPU qui dessinent mal. Seulement les fractales intégrées avec la coloration par itérations
+cpu-unsupported-boundary = Le style de frontière { $style } n’est dessiné que par le GPU
+cpu-unsupported-color-balance = La balance des couleurs n’est dessinée que par le GPU
+cpu-unsupported-color-cycling = Le cycle des couleurs n’est dessiné que par le GPU
+cpu-unsupported-coloring = La coloration { $coloring } n’est dessinée que par le GPU
+cpu-unsupported-fractal = La fractale { $fractal } n’est dessinée que par le GPU
+cpu-unsupported-graph = Le graphe de coloration n’est dessiné que par le GPU
+cpu-unsupported-value-formula = La formule de valeur n’est dessinée que par le GPU
+crosshair = Réticule
+crosshair-hint = Marquer le centre de la vue, le point des coordonnées ci-dessus
+current-iterations = { $iterations } actuellement
+cursor = Curseur
+curvature-mix = Mélange de courbure
+custom-fractal-hint = Fonction GLSL iterate(z, c) donnant la valeur suivante de l'orbite. Elle est rechargée à l'enregistrement du fichier, les erreurs sont dans le journal.
+cycle-colors = Faire défiler les couleurs
+cycle-colors-hint = Décaler les couleurs le long de la palette avec le temps, le défilement classique des couleurs
+cycling-speed = Vitesse de défilement
+decomposition-mix = Mélange de décomposition
+deepest-zoom = Zoom maximal
+deepest-zoom-hint = Le centre de la vue est un double : les pixels ne peuvent pas être plus proches que sa précision, le zoom s’arrête vers 1e15 pixels par unité.
+depth = Amplitude
+duration = Durée
+easing = Accélération
+easing-linear = Linéaire
+easing-smooth = Douce
+edit-custom-fractal = Modifier la fonction
+exit = Quitter
+explain-regions = Expliquer les régions
+explain-regions-hint = Dessine et nomme les principales parties de l’ensemble de Mandelbrot
+exponent = Exposant
+export-big-tiff = Exporter une image BigTIFF
+export-big-tiff-hint = TIFF en tuiles de la vue à la largeur donnée, pour les visionneuses et outils SIG gérant les images de plusieurs gigapixels.
+export-deep-zoom = Exporter une image Deep Zoom
+export-deep-zoom-hint = Pyramide de tuiles de la vue à la largeur donnée, à parcourir et zoomer dans une page web avec OpenSeadragon.
+export-iterations = Exporter les itérations
+export-iterations-hint = Nombre d'itérations lissé de chaque pixel de la vue, en tableau NumPy (.npy) ou CSV. NaN à l'intérieur de l'ensemble.
+export-kfr = Exporter la position .kfr…
+export-mandel-machine = Exporter la position Mandel Machine…
+export-overlays = Annotations et barre d’échelle
+export-overlays-hint = Dessine les annotations et la barre d’échelle visibles sur l’image exportée.
+export-png = Exporter une image PNG
+export-png-hint = La vue rendue hors écran à la largeur donnée, indépendamment de la taille de la fenêtre.
+export-render-statistics = Exporter en CSV
+export-render-statistics-hint = Durée d'image, durée GPU, budget d'itérations, grossissement et qualité de chaque image de la session.
+export-video = Exporter la vidéo
+export-video-hint = Encodée avec ffmpeg en MP4 ou WebM, ou écrite en images PNG numérotées.
+export-width = Largeur d'export
+exported = { $file } exporté
+external-rays = Rayons externes
+field-lines = Lignes de champ
+files = Fichiers
+filter-bigtiff = Image BigTIFF
+filter-csv = Tableau CSV
+filter-deep-zoom = Image Deep Zoom
+filter-gradients = Dégradés UltraFractal
+filter-images = Images
+filter-kfr = Position Kalles Fraktaler
+filter-mmf = Position Mandel Machine
+filter-mp4 = Vidéo MP4
+filter-npy = Tableau NumPy
+filter-png = Image PNG
+filter-png-sequence = Images PNG numérotées
+filter-preset = Préréglage
+filter-replay = Enregistrement des actions
+filter-webm = Vidéo WebM
+find-nearby-minibrot = Trouver un minibrot proche
+find-nearby-minibrot-hint = Vole jusqu’au noyau du minibrot le plus visible autour du centre de la vue. Calculé en flottants 64 bits, il est hors de portée au-delà d’un grossissement d’environ 10¹².
+fine-tuning = Réglage fin
+finish = Terminer
+formula-functions = Disponible : + - * / ^, pi, sin, cos, tan, abs, sqrt, exp, log, floor, fract
+fractal-burning-ship = Burning Ship
+fractal-custom = Personnalisé
+fractal-julia = Julia
+fractal-lyapunov = Lyapunov
+fractal-mandelbrot = Mandelbrot
+fractal-newton = Newton
+fractal-nova = Nova
+fractal-tricorn = Tricorne
+fractal-view = Vue de la fractale, centrée sur { $re } { $im }i, grossissement { $magnification }. Flèches pour se déplacer, + et - pour zoomer
+frame-rate = Fréquence d’images
+frame-rate-cap = Limite d’images par seconde
+frame-time = { $time } ms par image ({ $fps } IPS)
+frame-time-plot = Durée d'image sur le CPU, jusqu'à { $max } ms
+fullscreen = Plein écran
+fullscreen-hint = Plein écran sans bordure, pour les captures d’écran. F11 le bascule aussi
+gallery = Galerie
+gallery-empty = Aucun signet pour l'instant, ajoutez-en dans la section des signets
+gallery-hint = Cliquez sur une vue pour l'ouvrir, clic droit pour la renommer ou la supprimer
+gamma = Gamma
+global-parameters = Paramètres globaux
+go = Aller
+gpu = GPU
+gpu-in-use = Utilisé : { $adapter }
+gpu-time = GPU { $time } ms
+gpu-time-plot = Durée GPU de la vue, jusqu'à { $max } ms
+gradient-editor-hint = Double-cliquer pour ajouter un arrêt de couleur. Glisser les poignées en dessous pour déplacer les arrêts, en cliquer une pour changer sa couleur.
+green = Vert
+help = Aide
+help-brightness = Décalage ajouté à la valeur de coloration après le contraste
+help-channel = Poids du canal dans la teinte RVB, la couleur vaut valeur × (rouge, vert, bleu)
+help-contrast = Facteur appliqué à la valeur de coloration avant la palette ou la teinte RVB
+help-curvature-mix = Mélange entre le nombre d’itérations et l’angle moyen de virage de l’orbite
+help-cycling-speed = Palettes parcourues par seconde par le défilement des couleurs, à l’envers si négatif
+help-decomposition-mix = Mélange entre le nombre d’itérations et le signe de la partie imaginaire du dernier z
+help-exponent = Puissance n de z^n + c, 2 pour les ensembles habituels. Les autres valeurs, fractionnaires comprises, donnent les ensembles multibrot et multijulia, sans le zoom profond
+help-gamma = Chaque canal de couleur est élevé à la puissance 1 / gamma
+help-julia-angle = Argument de la constante c dans z → z² + c, en degrés
+help-julia-radius = Module de la constante c dans z → z² + c
+help-julia-x = Partie réelle de la constante c dans z → z² + c
+help-julia-y = Partie imaginaire de la constante c dans z → z² + c
+help-max-iterations = Itérations de l’orbite avant qu’un point soit considéré dans l’ensemble, plus d’itérations montrent plus de détails près du bord
+help-nova-relaxation = Multiplicateur du pas de Newton sur z³ - 1 avant d’ajouter c, 1 pour la méthode de Newton elle-même
+help-overlay = Ce que font les contrôles (F1)
+help-stripe-density = Fréquence k de sin(k·arg z) moyenné le long de l’orbite
+help-stripe-mix = Mélange entre le nombre d’itérations et la moyenne des rayures
+help-texture-blend = Mélange entre les couleurs habituelles et la texture, plaquée sur l’extérieur avec le nombre d’itérations (multiplié par le contraste) dans sa longueur et l’angle du dernier z dans sa largeur
+help-tia-blend = Mélange entre le nombre d’itérations et la position moyenne de |z| entre les bornes de l’inégalité triangulaire
+help-trap-scale = Largeur de l’image piège dans le plan complexe, l’image est centrée sur l’origine et l’orbite prend la couleur du premier pixel opaque où elle tombe
+help-zoom = Pixels par unité du plan complexe, la vue mesure hauteur / zoom unités
+high-contrast = Contraste élevé
+high-quality = Haute qualité
+highlights = Hautes lumières
+hours-ago = il y a { $count } h
+hud = Coordonnées sur la vue
+hud-center = Centre { $re } { $im }i
+hud-hint = Afficher le point sous le curseur, le centre, le grossissement et les itérations sur la fractale.
+hud-iterations = { $count } itérations
+hud-pointer = Curseur { $re } { $im }i
+hue = Teinte
+hue-range = Plage de teintes
+hyperbolic-component = Composante hyperbolique de période { $period }
+import-kfr = Importer une position .kfr…
+import-ugr = Importer des dégradés .ugr…
+invalid-link = Lien invalide : { $error }
+invert-scroll = Inverser le zoom de la molette
+iteration-coloring = Coloration des itérations
+iteration-coloring-bands = Bandes de temps d’échappement
+iteration-coloring-hint = Nombre lissé, une bande par itération, ou égalisation d’histogramme : chaque couleur couvre autant de la vue
+iteration-coloring-histogram = Histogramme
+iteration-coloring-smooth = Lissée
+iteration-data-unsupported = Les itérations de la fractale { $fractal } ne sont calculées que par le GPU
+iteration-formula = Formule
+iteration-formula-hint = Itération de z depuis 0, comme z = z^3 + c, avec les nombres complexes z, c et i, les opérateurs + - * / ^ et les fonctions sin, cos, tan, exp, log, sqrt et abs.
+iteration-value = Valeur d’itération
+jittered-samples = Échantillons aléatoires
+jittered-samples-hint = Plusieurs échantillons par pixel à des positions aléatoires, contre le moiré des détails fins.
+julia-1 = Julia 1
+julia-2 = Julia 2
+julia-constant = Constante de Julia
+julia-parameters = Paramètres de Julia
+keyframe = Image clé { $index }
+landmark-antenna = Antenne (filament)
+landmark-elephant-valley = Vallée des éléphants
+landmark-main-cardioid = Cardioïde principale (période 1)
+landmark-period-2-bulb = Bulbe de période 2
+landmark-period-3-bulb = Bulbe de période 3
+landmark-period-3-minibrot = Minibrot de période 3
+landmark-seahorse-valley = Vallée des hippocampes
+language = Langue
+layout-editing-colors = Édition des couleurs
+layout-exploring = Exploration
+layout-name = Nom de la disposition
+layout-presenting = Présentation
+layouts = Dispositions
+lfo = LFO { $number }
+listen-to-the-audio-input = Écouter l’entrée audio
+load-exterior-texture = Charger la texture extérieure…
+load-preset = Charger un préréglage…
+load-trap-image = Charger l’image piège…
+lock-hue-range = Bloquer la plage de teintes
+loop-timeline = En boucle
+lyapunov-parameters = Paramètres de Lyapunov
+lyapunov-sequence = Séquence
+lyapunov-sequence-empty = La séquence est vide
+lyapunov-sequence-hint = Taux de croissance de la suite logistique à tour de rôle : A est l’abscisse du point, B son ordonnée. Jusqu’à 32 lettres, comme AB ou BBBBBBAAAAAA
+lyapunov-sequence-invalid = Seuls A et B sont permis, pas { $rate }
+lyapunov-sequence-too-long = La séquence a plus de { $max } taux
+lyapunov-view = Afficher les taux de 2 à 4
+lyapunov-view-hint = Aller au carré des taux de croissance entre 2 et 4, où se rencontrent les régions stables et chaotiques
+magnification = Grossissement { $value }
+magnification-value = Grossissement
+magnifier = Loupe
+max-iterations = Itérations
+measure = Mesurer
+measure-hint = Cliquez sur deux points de la vue pour mesurer leur distance, Échap efface la mesure
+measurement = Δ = { $re } { $im }i   |Δ| = { $distance }   angle { $angle }°
+middle-button = Bouton du milieu
+midtones = Tons moyens
+minibrot-found = Vol vers un minibrot de période { $period }
+minibrot-not-found = Aucun minibrot trouvé autour du centre de la vue
+minibrot-too-deep = La vue est trop profonde pour localiser ses minibrots
+minibrot-too-small = Le minibrot trouvé est trop petit pour y voler
+minimap = Mini-carte
+minimap-hint = Toute la fractale dans un coin, avec le contour de la vue. Cliquez ou faites glisser dedans pour y déplacer la vue
+minutes-ago = il y a { $count } min
+mirror-texture = Répétitions en miroir
+mirror-texture-hint = Retourne une répétition sur deux de la texture pour qu’elle n’ait pas de raccords
+misiurewicz-found = Point de Misiurewicz M({ $preperiod }, { $period }) trouvé
+misiurewicz-hint = Appuyez sur M au-dessus de la fractale pour trouver le point de Misiurewicz le plus proche
+misiurewicz-not-found = Aucun point de Misiurewicz trouvé sous le pointeur
+misiurewicz-point = M({ $preperiod }, { $period }) en { $re } { $im }i
+modulation = Modulation
+modulus = Module
+monitor-height = Hauteur
+monitor-width = Largeur
+mouse-controls = Souris
+move-up = Monter
+new-tab = Nouvel onglet
+new-tab-hint = Ouvrir la vue dans un nouvel onglet, pour comparer des régions ou garder une référence en explorant
+newton-root = Racine { $index }
+newton-roots = Racines du polynôme
+next = Suivant
+next-seed = Graine suivante
+no-configuration-directory = Pas de dossier de configuration
+node-clamp = Limiter à [0, 1]
+node-fractional-part = Partie fractionnaire
+node-log = Logarithme
+node-offset = Décalage
+node-power = Puissance
+node-scale = Échelle
+node-sine = Sinus
+node-square-root = Racine carrée
+not-in-a-hyperbolic-component = Hors d’une composante hyperbolique
+note-number = Note { $number }
+nova-parameters = Paramètres de Nova
+nova-relaxation = Relaxation
+nudge = Ajustement au clavier
+nudge-hint = ] augmente et [ diminue le dernier paramètre utilisé, Maj pour des pas plus fins et Ctrl pour des pas plus grands.
+nudge-parameter = Paramètre
+nudge-step = Pas, de la plage du curseur
+oklab-interpolation = Interpoler en OKLab
+oklab-interpolation-hint = Mélange les couleurs entre les arrêts dans un espace perceptuel, plutôt qu’en ligne droite en RVB qui donne des milieux ternes
+open = Ouvrir
+open-gallery = Galerie
+output = Sortie
+output-grayscale = Niveaux de gris
+output-palette = Palette
+output-rgb-tint = Teinte RVB
+overlay-bookmarks = Les vues enregistrées sous un nom. La galerie les montre en vignettes, et un lien partage la vue
+overlay-canvas = Glissez pour déplacer, cliquez pour centrer un point, double-cliquez pour zoomer et double-cliquez avec le bouton droit pour dézoomer, ou utilisez la molette. Pincez pour zoomer sur les écrans tactiles. WASD déplace la vue, et une fois le canevas sélectionné, les flèches la déplacent et + et - zooment.
+overlay-close = Cliquez n’importe où ou appuyez sur Échap pour fermer
+overlay-coloring = Sur un curseur, la molette le modifie, Maj pour des pas plus fins et Ctrl pour des pas plus grands. 📌 l’épingle au-dessus du canevas
+overlay-files = Ctrl+S exporte une image PNG et F12 enregistre une capture. Déposez une capture ou un préréglage sur la fenêtre pour l’ouvrir
+overlay-help = La visite de l’interface, et ces notes
+overlay-julia = Glissez pour déplacer la constante c de l’ensemble de Julia, Maj pour des déplacements plus fins
+overlay-mouse-controls = Les boutons qui déplacent et centrent la vue, et l’ampleur du zoom de la molette et des doubles clics
+overlay-nudge = [ et ] modifient d’un pas le dernier curseur utilisé
+overlay-type = Les touches 1 à 7 passent d’un type de fractale à l’autre
+palette = Palette
+palette-from-image = Palette depuis une image…
+pan-button = Glisser avec
+paste-link = Coller un lien
+path-center = Centre
+path-circle = Cercle
+path-lemniscate = Lemniscate
+path-length = { $points } points, { $duration } s
+path-pause = ⏸ Pause
+path-play = ▶ Lecture
+path-radius = Rayon
+path-record = ⏺ Enregistrer
+path-record-hint = Enregistrer les mouvements du pavé de Julia
+path-recorded = Enregistré
+path-shape = Trajet de c
+path-speed = Vitesse du chemin
+path-stop = ⏹ Arrêter
+performance-overlay = Statistiques de performance
+perturbation = Perturbation
+perturbation-hint = La vue est assez profonde pour être calculée autour de l’orbite de son centre, en double précision.
+phase = Phase
+pick-julia-seed = Choisir la graine de Julia
+pick-julia-seed-hint = Cliquer un point de la vue pour passer à son ensemble de Julia. Ctrl+clic le fait sans ce mode.
+pin-to-the-toolbar = Épingler dans la barre d’outils
+pixel-size = Taille du pixel { $value }
+play-replay = Rejouer un enregistrement
+play-replay-hint = Rejoue les actions enregistrées depuis l'état de départ enregistré. Échap l'arrête.
+play-timeline = Lire
+plugin-missing = Pas de plugin { $plugin }.wasm dans le dossier des plugins
+preferred-gpu = GPU préféré
+preview = Aperçu
+previous-seed = Graine précédente
+primary-button = Bouton gauche
+probe = Sonde
+probe-bounded = Bornée pendant { $count } itérations
+probe-cycle = Cycle de période { $period }
+probe-escaped = S'échappe à l'itération { $count }
+probe-gpu-only = Calculé uniquement sur le GPU
+probe-hint = Afficher l'orbite du point sous le pointeur et s'il s'échappe, calculés sur le CPU avec la formule du shader
+probe-no-cycle = Aucun cycle trouvé
+probe-orbit = Orbite de { $length } points
+probe-smooth = Compte lissé { $value }
+progressive-refinement = Affinage progressif
+progressive-refinement-hint = Dessiner la vue en basse résolution pendant qu’elle change, et en pleine résolution une fois arrêtée.
+random-palette = Palette aléatoire
+rate = Fréquence
+ray = Rayon { $angle }
+recent = Récents
+recenter-button = Recentrer avec
+record-input = Enregistrer les actions
+red = Rouge
+redo = Rétablir
+reduced-motion = Mouvements réduits
+reduced-motion-hint = Saute aux nouvelles vues au lieu d’y voler, et zoome par paliers
+refine-region = Affiner une région
+refine-region-hint = Faites glisser sur la vue pour rendre un rectangle en qualité maximale, avec 3×3 échantillons par pixel
+remove = Supprimer
+remove-keyframe = Supprimer l’image clé
+rename = Renommer
+render-farm = Ferme de rendu
+render-farm-hint = Rendre les tuiles des exports sur les machines exécutant `fractal_gl worker <cette machine>:<port>` au lieu de ce GPU
+render-farm-port = Port
+render-farm-unsupported = Les machines de la ferme de rendu ne peuvent pas dessiner les formules personnalisées, le graphe de coloration ni les images
+render-farm-workers = { $count } machines connectées sur le port { $port }
+render-statistics = Statistiques de rendu
+renderer-unavailable = Le GPU ne peut pas dessiner la fractale, elle est dessinée par le CPU. Aide → À propos liste ce que le GPU prend en charge. { $error }
+repaint-on-input = Au repos, dessiné sur action
+reset = Réinitialiser
+reset-section = Réinitialiser les paramètres de cette section
+reset-view = Réinitialiser la vue
+reset-view-hint = Revenir à la fractale entière, sans rotation, avec les mêmes paramètres et couleurs
+restart-to-apply = Redémarrer pour appliquer
+reveal-in-folder = Afficher dans le dossier
+rotation = Rotation
+rotation-hint = Tourner la vue autour de son centre. Maj et glisser sur la fractale pour la tourner à la souris
+run-benchmark = Lancer
+run-script = Exécuter
+save-layout = Enregistrer la disposition actuelle
+save-preset = Enregistrer un préréglage…
+save-preset-hint = Enregistrer la vue et ses couleurs dans un fichier TOML modifiable à la main et partageable
+scale-bar = Barre d’échelle
+script-console = Console de script
+script-error = Erreur
+script-help = Script Rhai sur la vue : zoom(z), zoom_by(facteur), get_zoom(), center(re, im), get_center(), julia(re, im), rotation(degrés), iterations(n), fractal("mandelbrot"), step() affiche la vue comme une image, snapshot("fichier.png") l'exporte.
+script-too-long = Le script a été arrêté après { $seconds } s, l’application est figée pendant qu’il tourne
+scroll-zoom-step = Pas du zoom de la molette
+secondary-button = Bouton droit
+seconds-ago = il y a { $count } s
+seed = Graine
+set-end-keyframe = Finir sur cette vue
+set-start-keyframe = Partir de cette vue
+settings-panel = Réglages
+shader-error = Erreur de shader
+shader-error-hint = Les shaders ne compilent pas, la vue garde les derniers fonctionnels.
+shadows = Ombres
+show-annotations = Afficher les annotations
+show-keyframe-hint = Affiche la vue de l’image clé
+side-by-side = Mandelbrot et Julia
+side-by-side-hint = L’ensemble de Mandelbrot à côté de l’ensemble de Julia de la vue partagée
+side-by-side-unavailable = Seulement pour l’ensemble de Mandelbrot
+sixteen-bit-channels = 16 bits par canal
+sixteen-bit-unsupported = Ce GPU ne peut pas rendre 16 bits par canal
+skip-tour = Passer la visite
+snap-to-grid = Aimanter à la grille
+split-julia-hint = Ensemble de Julia du point sous le pointeur dans l’ensemble de Mandelbrot. Cliquer pour l’explorer.
+split-view = Vue partagée avec l’ensemble de Julia
+split-view-hint = Afficher à côté de l’ensemble de Mandelbrot l’ensemble de Julia du point sous le pointeur.
+status-cannot-export = Impossible d'exporter { $file }
+status-cannot-open = Impossible d'ouvrir { $file }
+status-cannot-save = Impossible d'enregistrer { $file }
+status-export-failed = L'export a échoué
+status-export-resumed = Reprise de l'export interrompu de { $file }
+status-history = Derniers messages
+status-opened = { $file } ouvert
+status-opened-rounded = { $file } ouvert, son centre est arrondi à la précision du rendu
+status-render-farm-failed = Impossible de démarrer la ferme de rendu
+status-saved = { $file } enregistré
+stop-animating = Arrêter l’animation
+stop-recording = Arrêter l'enregistrement
+stop-timeline = Arrêter
+stripe-density = Densité des rayures
+stripe-mix = Mélange des rayures
+supersampling = Suréchantillonnage
+supersampling-hint = Rendre la vue plus grande puis la réduire, pour des bords plus lisses. Plus lent.
+tab = Vue { $index }
+target-hue-shift = Décalage de teinte
+target-julia-angle = Angle de Julia
+target-nothing = Rien
+target-zoom-pulse = Pulsation du zoom
+texture-blend = Mélange de la texture
+tia-blend = Mélange TIA
+timeline = Chronologie
+trace-ray = Tracer le rayon
+trap-angle = Angle de la droite
+trap-center = Centre du piège
+trap-coloring = Coloration
+trap-line = Droite
+trap-point = Point
+trap-ring = Anneau
+trap-rotation = Rotation du piège
+trap-scale = Taille du piège
+trap-size = Rayon de l’anneau
+tutorial = Visite
+tutorial-button = Visite de l’interface…
+tutorial-canvas = Voici la fractale. Cliquez pour centrer la vue sur un point, double-cliquez pour zoomer, double-cliquez avec le bouton droit pour dézoomer, et utilisez la molette pour zoomer. Une fois la vue sélectionnée, les flèches la déplacent et + et - zooment.
+tutorial-export = La section Fichiers importe et exporte les positions pour d’autres programmes de fractales. La visite peut être relancée depuis le menu Aide, et le bouton ? à côté, ou F1, explique chaque contrôle.
+tutorial-julia = Avec le type Julia, faites glisser dans ce pavé pour déplacer la constante c de l’ensemble de Julia. Maintenez Maj pour des mouvements plus fins.
+tutorial-progress = Étape { $step } sur { $total }
+tutorial-type = Passez ici de l’ensemble de Mandelbrot aux ensembles de Julia.
+type = Type
+undo = Annuler
+unknown = inconnu
+unlimited = Illimitée
+unpin = Retirer
+unpin-from-the-toolbar = Retirer de la barre d’outils
+use-palette = Utiliser la palette
+use-the-coloring-graph = Utiliser le graphe de coloration
+use-the-formula = Utiliser la formule
+variable-angle = Argument du dernier z, dans [-pi, pi]
+variable-iter = Nombre d’itérations
+variable-t = Temps en secondes
+variable-value = Logarithme du nombre d’itérations, lissé en haute qualité
+variable-x = Partie réelle du point
+variable-y = Partie imaginaire du point
+variable-zx = Partie réelle du dernier z
+variable-zy = Partie imaginaire du dernier z
+variables = Variables
+video-frames = { $count } images
+view-center = Centre de la vue
+vsync = Synchronisation verticale
+wallpaper = Fond d’écran
+wallpaper-hint = La vue prolongée sur les écrans, en pixels du bureau
+waveform-random = Aléatoire
+waveform-sine = Sinus
+waveform-triangle = Triangle
+zoom = Zoom
+zoom-box = Zoomer sur un cadre
+zoom-box-hint = Tracer un cadre sur la fractale pour zoomer dessus. Un glisser avec le bouton secondaire en trace un aussi, sauf s’il déplace la vue
+zoom-video = Vidéo de zoom
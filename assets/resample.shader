@@ -0,0 +1,19 @@
+precision highp float;
+// View rendered offscreen, scaled to the viewport starting at u_origin: each pixel averages
+// u_factor texels along each axis, or each texel covers u_divisor pixels along each axis
+uniform sampler2D u_image;
+uniform int u_factor;
+uniform int u_divisor;
+uniform vec2 u_origin;
+
+out vec4 out_color;
+
+void main(void)
+{
+    ivec2 texel = ivec2(gl_FragCoord.xy - u_origin) * u_factor / u_divisor;
+    vec4 color = vec4(0.0);
+    for(int i = 0; i < u_factor; i++)
+        for(int j = 0; j < u_factor; j++)
+            color += texelFetch(u_image, texel + ivec2(i, j), 0);
+    out_color = color / float(u_factor * u_factor);
+}
@@ -0,0 +1,19 @@
+fn main() {
+    // The GPU drivers of Windows look for these variables among the exports of the
+    // executable, and Rust does not export anything from executables, see src/app/gpu.rs
+    let target_os = std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let target_env = std::env::var("CARGO_CFG_TARGET_ENV").unwrap_or_default();
+    match (target_os.as_str(), target_env.as_str()) {
+        ("windows", "msvc") => {
+            for symbol in [
+                "NvOptimusEnablement",
+                "AmdPowerXpressRequestHighPerformance",
+            ] {
+                println!("cargo:rustc-link-arg-bins=/EXPORT:{symbol}");
+            }
+        }
+        // GNU ld cannot export single symbols
+        ("windows", _) => println!("cargo:rustc-link-arg-bins=-Wl,--export-all-symbols"),
+        _ => {}
+    }
+}
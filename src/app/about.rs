@@ -0,0 +1,141 @@
+use std::fmt::Write as _;
+
+use eframe::egui::{self, CollapsingHeader, Grid};
+
+use super::{capabilities::Capabilities, i18n::tr, render_stats::RenderStats};
+
+/// Extensions that would allow more precision than the 32 bits floats used by the shader.
+const PRECISION_EXTENSIONS: [&str; 4] = [
+    "GL_ARB_gpu_shader_fp64",
+    "GL_ARB_gpu_shader_int64",
+    "GL_ARB_gpu_shader5",
+    "GL_NV_gpu_shader5",
+];
+
+/// What the GL context reports about itself.
+pub struct Diagnostics {
+    vendor: String,
+    renderer: String,
+    version: String,
+    shading_language: String,
+    /// The extensions of [PRECISION_EXTENSIONS] that are available.
+    extensions: Vec<&'static str>,
+    capabilities: Capabilities,
+}
+
+impl Diagnostics {
+    pub fn query(gl: &eframe::glow::Context) -> Self {
+        use eframe::glow::HasContext as _;
+        unsafe {
+            Self {
+                vendor: gl.get_parameter_string(glow::VENDOR),
+                renderer: gl.get_parameter_string(glow::RENDERER),
+                version: gl.get_parameter_string(glow::VERSION),
+                shading_language: gl.get_parameter_string(glow::SHADING_LANGUAGE_VERSION),
+                extensions: PRECISION_EXTENSIONS
+                    .into_iter()
+                    .filter(|extension| gl.supported_extensions().contains(*extension))
+                    .collect(),
+                capabilities: Capabilities::query(gl),
+            }
+        }
+    }
+
+    /// Renderer and vendor of the GPU.
+    pub fn adapter(&self) -> String {
+        format!("{} ({})", self.renderer, self.vendor)
+    }
+}
+
+/// About dialog, with the diagnostics to copy into bug reports.
+pub struct About {
+    pub open: bool,
+    /// Unknown until a GL context is available.
+    diagnostics: Option<Diagnostics>,
+}
+
+impl About {
+    pub fn new(diagnostics: Option<Diagnostics>) -> Self {
+        Self {
+            open: false,
+            diagnostics,
+        }
+    }
+
+    fn rows(&self) -> Vec<(&'static str, String)> {
+        let mut rows = vec![
+            ("Version", env!("CARGO_PKG_VERSION").to_string()),
+            (
+                "System",
+                format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            ),
+        ];
+        match &self.diagnostics {
+            Some(diagnostics) => rows.extend([
+                ("GL vendor", diagnostics.vendor.clone()),
+                ("GL renderer", diagnostics.renderer.clone()),
+                ("GL version", diagnostics.version.clone()),
+                ("GLSL version", diagnostics.shading_language.clone()),
+                (
+                    "Precision extensions",
+                    if diagnostics.extensions.is_empty() {
+                        "none".to_string()
+                    } else {
+                        diagnostics.extensions.join(", ")
+                    },
+                ),
+                (
+                    "Shaders",
+                    diagnostics
+                        .capabilities
+                        .glsl_version()
+                        .unwrap_or("unsupported")
+                        .to_string(),
+                ),
+                ("Disabled features", {
+                    let disabled = diagnostics.capabilities.disabled_features();
+                    if disabled.is_empty() {
+                        "none".to_string()
+                    } else {
+                        disabled.join(", ")
+                    }
+                }),
+            ]),
+            None => rows.push(("GL", "unavailable".to_string())),
+        }
+        rows
+    }
+
+    fn report(&self) -> String {
+        let mut report = String::new();
+        for (name, value) in self.rows() {
+            let _ = writeln!(report, "{name}: {value}");
+        }
+        report
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, stats: &RenderStats) {
+        let mut open = self.open;
+        egui::Window::new(tr("about"))
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.heading("FractalGl");
+                Grid::new("Diagnostics").show(ui, |ui| {
+                    for (name, value) in self.rows() {
+                        ui.label(name);
+                        ui.label(value);
+                        ui.end_row();
+                    }
+                });
+                if ui.button(tr("copy-diagnostics")).clicked() {
+                    ui.ctx().copy_text(self.report());
+                }
+                CollapsingHeader::new(tr("render-statistics"))
+                    .default_open(false)
+                    .show(ui, |ui| stats.ui(ui));
+            });
+        self.open = open;
+    }
+}
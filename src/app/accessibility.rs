@@ -0,0 +1,41 @@
+use eframe::egui::{
+    text_edit::TextEditState, Context, EventFilter, Key, Response, Ui, Vec2, WidgetInfo, WidgetType,
+};
+
+/// Name an icon-only button: shown on hover, and read by screen readers instead of the icon.
+pub fn icon_button(response: Response, name: String) -> Response {
+    response.widget_info(|| WidgetInfo::labeled(WidgetType::Button, response.enabled(), &name));
+    response.on_hover_text(name)
+}
+
+/// Presses of the arrow keys while `response` has the focus, x to the right and y up. The
+/// arrows are kept from moving the focus to the next widget.
+pub fn arrow_keys(ui: &Ui, response: &Response) -> Vec2 {
+    if !response.has_focus() {
+        return Vec2::ZERO;
+    }
+    ui.memory_mut(|memory| {
+        memory.set_focus_lock_filter(
+            response.id,
+            EventFilter {
+                horizontal_arrows: true,
+                vertical_arrows: true,
+                ..Default::default()
+            },
+        )
+    });
+    ui.input(|i| {
+        let count = |key| i.num_presses(key) as f32;
+        Vec2::new(
+            count(Key::ArrowRight) - count(Key::ArrowLeft),
+            count(Key::ArrowUp) - count(Key::ArrowDown),
+        )
+    })
+}
+
+/// Whether a text field has the focus, the keys are then typed in it rather than shortcuts.
+/// Unlike [Context::wants_keyboard_input], the focus on other widgets does not count.
+pub fn is_typing(ctx: &Context) -> bool {
+    ctx.memory(|memory| memory.focused())
+        .is_some_and(|id| TextEditState::load(ctx, id).is_some())
+}
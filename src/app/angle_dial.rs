@@ -0,0 +1,69 @@
+use eframe::{
+    egui::{Response, Sense, Stroke, Ui, Widget, WidgetInfo},
+    epaint::Vec2,
+};
+
+use super::{accessibility::arrow_keys, i18n::tr, parameter::step_factor};
+
+/// Change of the angle for one press of an arrow key, in radians.
+const KEY_STEP: f32 = std::f32::consts::PI / 180.0;
+
+/// Round knob to pick an angle, in radians, counter-clockwise from the positive x axis.
+pub struct AngleDial<'a> {
+    angle: &'a mut f32,
+    diameter: f32,
+}
+
+impl<'a> AngleDial<'a> {
+    pub fn new(angle: &'a mut f32) -> AngleDial<'a> {
+        AngleDial {
+            angle,
+            diameter: 48.0,
+        }
+    }
+}
+
+impl Widget for AngleDial<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let (rect, mut response) =
+            ui.allocate_exact_size(Vec2::splat(self.diameter), Sense::click_and_drag());
+
+        if let Some(pointer) = response.interact_pointer_pos() {
+            let direction = pointer - rect.center();
+            if direction.length_sq() > 0.0 {
+                // The screen y axis points down
+                *self.angle = (-direction.y).atan2(direction.x);
+                response.mark_changed();
+            }
+        }
+
+        let arrows = arrow_keys(ui, &response);
+        let turns = arrows.x + arrows.y;
+        if turns != 0.0 {
+            *self.angle += turns * KEY_STEP * step_factor(ui.input(|i| i.modifiers));
+            response.mark_changed();
+        }
+        response.widget_info(|| {
+            WidgetInfo::slider(
+                ui.is_enabled(),
+                self.angle.to_degrees() as f64,
+                tr("argument"),
+            )
+        });
+
+        let visuals = ui.style().interact(&response);
+        let radius = self.diameter * 0.5 - visuals.fg_stroke.width;
+        let painter = ui.painter();
+        painter.circle(
+            rect.center(),
+            radius,
+            visuals.bg_fill,
+            Stroke::new(1.0, visuals.fg_stroke.color),
+        );
+        let tip = rect.center() + Vec2::new(self.angle.cos(), -self.angle.sin()) * radius;
+        painter.line_segment([rect.center(), tip], visuals.fg_stroke);
+        painter.circle_filled(tip, 3.0, visuals.fg_stroke.color);
+
+        response
+    }
+}
@@ -0,0 +1,110 @@
+use eframe::egui::{ComboBox, Ui};
+
+use super::{
+    accessibility::icon_button,
+    expression::Expr,
+    i18n::{tr, tr_args},
+    parameter::Parameter,
+    State,
+};
+
+/// Name of the time variable in the expressions, in seconds.
+const TIME_VARIABLE: &str = "t";
+
+struct Binding {
+    parameter: Parameter,
+    source: String,
+    /// The parsed source, or why it couldn't be parsed.
+    expr: Result<Expr, String>,
+}
+
+impl Binding {
+    fn new(parameter: Parameter, source: String) -> Self {
+        let expr = compile(&source);
+        Self {
+            parameter,
+            source,
+            expr,
+        }
+    }
+}
+
+fn compile(source: &str) -> Result<Expr, String> {
+    let expr = Expr::parse(source).map_err(|e| e.to_string())?;
+    if let Some(unknown) = expr.variables().into_iter().find(|v| *v != TIME_VARIABLE) {
+        return Err(format!(
+            "Unknown variable {unknown:?}, only {TIME_VARIABLE} is available"
+        ));
+    }
+    Ok(expr)
+}
+
+/// Parameters driven by an expression of the time, like `0.3*sin(t)`.
+pub struct Animations {
+    bindings: Vec<Binding>,
+}
+
+impl Animations {
+    pub fn new() -> Self {
+        Self {
+            bindings: Vec::new(),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.bindings.iter().any(|binding| binding.expr.is_ok())
+    }
+
+    /// Set the animated parameters of `state` to their value at `time`, in seconds.
+    pub fn apply(&self, state: &mut State, time: f64) {
+        let variable = |name: &str| (name == TIME_VARIABLE).then_some(time);
+        for binding in &self.bindings {
+            let Ok(expr) = &binding.expr else {
+                continue;
+            };
+            match expr.eval(&variable) {
+                Ok(value) if value.is_finite() => binding.parameter.set(state, value as f32),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        let mut removed = None;
+        for (i, binding) in self.bindings.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(binding.parameter.label());
+                if ui.text_edit_singleline(&mut binding.source).changed() {
+                    binding.expr = compile(&binding.source);
+                }
+                if icon_button(ui.small_button("✖"), tr("stop-animating")).clicked() {
+                    removed = Some(i);
+                }
+            });
+            if let Err(e) = &binding.expr {
+                ui.colored_label(ui.visuals().error_fg_color, e.as_str());
+            }
+        }
+        if let Some(i) = removed {
+            self.bindings.remove(i);
+        }
+
+        let mut added = None;
+        ComboBox::from_id_salt("Animate parameter")
+            .selected_text(tr("animate"))
+            .show_ui(ui, |ui| {
+                for parameter in Parameter::ALL {
+                    if self.bindings.iter().all(|b| b.parameter != parameter)
+                        && ui.selectable_label(false, parameter.label()).clicked()
+                    {
+                        added = Some(parameter);
+                    }
+                }
+            });
+        if let Some(parameter) = added {
+            self.bindings
+                .push(Binding::new(parameter, format!("0.3*sin({TIME_VARIABLE})")));
+        }
+        ui.label(tr_args("animation-help", &[("variable", &TIME_VARIABLE)]));
+    }
+}
@@ -0,0 +1,166 @@
+use std::fmt::Write as _;
+
+use eframe::egui::{vec2, Align2, Color32, FontId, Grid, Rect, Stroke, TextEdit, Ui};
+use log::error;
+
+use super::{
+    complex::Complex,
+    i18n::{tr, tr_args},
+    overlay::Canvas,
+    settings, State,
+};
+
+/// Name of the setting holding the annotations.
+const ANNOTATIONS_SETTING: &str = "annotations";
+
+/// Text note attached to a point of the complex plane.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Annotation {
+    pub point: Complex,
+    pub note: String,
+}
+
+/// Markers with notes, drawn over the fractal at their complex coordinates.
+pub struct Annotations {
+    pub visible: bool,
+    annotations: Vec<Annotation>,
+}
+
+/// One annotation per line: real part, imaginary part and note, separated by tabs.
+fn parse(content: &str) -> Vec<Annotation> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let re = fields.next()?.parse().ok()?;
+            let im = fields.next()?.parse().ok()?;
+            Some(Annotation {
+                point: Complex::new(re, im),
+                note: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+fn serialize(annotations: &[Annotation]) -> String {
+    let mut content = String::new();
+    for annotation in annotations {
+        // The notes are single lines, but tabs could still be pasted in them
+        let note = annotation.note.replace(['\t', '\n'], " ");
+        let _ = writeln!(
+            content,
+            "{}\t{}\t{}",
+            annotation.point.re, annotation.point.im, note
+        );
+    }
+    content
+}
+
+impl Annotations {
+    pub fn new() -> Self {
+        Self {
+            visible: true,
+            annotations: Vec::new(),
+        }
+    }
+
+    /// Annotations saved from the previous runs.
+    pub fn load() -> Self {
+        Self {
+            visible: true,
+            annotations: settings::load(ANNOTATIONS_SETTING)
+                .map(|content| parse(&content))
+                .unwrap_or_default(),
+        }
+    }
+
+    fn save(&self) {
+        if let Err(e) = settings::save(ANNOTATIONS_SETTING, &serialize(&self.annotations)) {
+            error!("Cannot save the annotations: {:?}", e);
+        }
+    }
+
+    pub fn add(&mut self, point: Complex) {
+        self.annotations.push(Annotation {
+            point,
+            note: tr_args("note-number", &[("number", &(self.annotations.len() + 1))]),
+        });
+        self.visible = true;
+        self.save();
+    }
+
+    /// List of the annotations to edit their notes, returns the point to go to if asked.
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<Complex> {
+        ui.checkbox(&mut self.visible, tr("show-annotations"));
+        ui.label(tr("add-annotation-hint"));
+
+        let mut go_to = None;
+        let mut removed = None;
+        let mut changed = false;
+        Grid::new("Annotations").show(ui, |ui| {
+            for (i, annotation) in self.annotations.iter_mut().enumerate() {
+                changed |= ui
+                    .add(TextEdit::singleline(&mut annotation.note).desired_width(140.0))
+                    .changed();
+                if ui.button(tr("go")).clicked() {
+                    go_to = Some(annotation.point);
+                }
+                if ui.button(tr("remove")).clicked() {
+                    removed = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(i) = removed {
+            self.annotations.remove(i);
+            changed = true;
+        }
+        if changed {
+            self.save();
+        }
+        go_to
+    }
+
+    /// Draw the markers of the annotations inside the view.
+    pub fn paint(
+        &self,
+        canvas: &mut impl Canvas,
+        rect: Rect,
+        pixels_per_point: f32,
+        state: &State,
+    ) {
+        if !self.visible {
+            return;
+        }
+        let font = FontId::proportional(13.0);
+        for annotation in &self.annotations {
+            let [x, y] = state.offset_of([annotation.point.re, annotation.point.im]);
+            let position = rect.center() + vec2(x, y) / pixels_per_point;
+            if !rect.contains(position) {
+                continue;
+            }
+            canvas.circle(
+                position,
+                5.0,
+                Color32::from_rgb(255, 200, 0),
+                Stroke::new(1.5, Color32::BLACK),
+            );
+            // Shadow to keep the text readable over any color
+            let anchor = position + vec2(8.0, 0.0);
+            canvas.text(
+                anchor + vec2(1.0, 1.0),
+                Align2::LEFT_CENTER,
+                &annotation.note,
+                font.clone(),
+                Color32::BLACK,
+            );
+            canvas.text(
+                anchor,
+                Align2::LEFT_CENTER,
+                &annotation.note,
+                font.clone(),
+                Color32::WHITE,
+            );
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use eframe::egui::{Color32, Context, Stroke, Theme, ThemePreference, Ui, Visuals};
+use log::error;
+
+use super::{i18n::tr, settings};
+
+/// Names of the settings.
+const HIGH_CONTRAST_SETTING: &str = "high-contrast";
+const REDUCED_MOTION_SETTING: &str = "reduced-motion";
+
+fn load(name: &str) -> bool {
+    settings::load(name).is_some_and(|value| value == "on")
+}
+
+fn save(name: &str, value: bool) {
+    if let Err(e) = settings::save(name, if value { "on" } else { "off" }) {
+        error!("Cannot save the {} setting: {:?}", name, e);
+    }
+}
+
+/// White on black, with yellow for what is hovered and active.
+fn high_contrast() -> Visuals {
+    let mut visuals = Visuals::dark();
+    visuals.override_text_color = Some(Color32::WHITE);
+    visuals.panel_fill = Color32::BLACK;
+    visuals.window_fill = Color32::BLACK;
+    visuals.extreme_bg_color = Color32::BLACK;
+    visuals.faint_bg_color = Color32::from_gray(24);
+    visuals.window_stroke = Stroke::new(1.0, Color32::WHITE);
+    visuals.hyperlink_color = Color32::YELLOW;
+    visuals.selection.bg_fill = Color32::from_rgb(0, 70, 160);
+    visuals.selection.stroke = Stroke::new(2.0, Color32::YELLOW);
+
+    let widgets = &mut visuals.widgets;
+    widgets.noninteractive.bg_stroke = Stroke::new(1.0, Color32::WHITE);
+    widgets.noninteractive.fg_stroke = Stroke::new(1.0, Color32::WHITE);
+    for (state, color) in [
+        (&mut widgets.inactive, Color32::WHITE),
+        (&mut widgets.hovered, Color32::YELLOW),
+        (&mut widgets.active, Color32::YELLOW),
+        (&mut widgets.open, Color32::YELLOW),
+    ] {
+        state.bg_fill = Color32::BLACK;
+        state.weak_bg_fill = Color32::BLACK;
+        state.bg_stroke = Stroke::new(2.0, color);
+        state.fg_stroke = Stroke::new(2.0, color);
+    }
+    visuals
+}
+
+/// Accessibility settings of the UI.
+pub struct Appearance {
+    high_contrast: bool,
+    /// No animated moves of the view, and no smoothing of the mouse wheel zoom.
+    reduced_motion: bool,
+}
+
+impl Appearance {
+    /// Settings saved from the previous runs.
+    pub fn load() -> Self {
+        Self {
+            high_contrast: load(HIGH_CONTRAST_SETTING),
+            reduced_motion: load(REDUCED_MOTION_SETTING),
+        }
+    }
+
+    pub fn reduced_motion(&self) -> bool {
+        self.reduced_motion
+    }
+
+    pub fn apply(&self, ctx: &Context) {
+        if self.high_contrast {
+            ctx.set_visuals_of(Theme::Dark, high_contrast());
+            ctx.set_theme(ThemePreference::Dark);
+        } else {
+            ctx.set_visuals_of(Theme::Dark, Visuals::dark());
+            ctx.set_theme(ThemePreference::System);
+        }
+        let reduced_motion = self.reduced_motion;
+        ctx.all_styles_mut(|style| {
+            // Collapsing headers, scroll areas and windows open and close at once
+            style.animation_time = if reduced_motion { 0.0 } else { 1.0 / 12.0 };
+        });
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        let mut changed = false;
+        if ui
+            .checkbox(&mut self.high_contrast, tr("high-contrast"))
+            .changed()
+        {
+            save(HIGH_CONTRAST_SETTING, self.high_contrast);
+            changed = true;
+        }
+        if ui
+            .checkbox(&mut self.reduced_motion, tr("reduced-motion"))
+            .on_hover_text(tr("reduced-motion-hint"))
+            .changed()
+        {
+            save(REDUCED_MOTION_SETTING, self.reduced_motion);
+            changed = true;
+        }
+        if changed {
+            self.apply(ui.ctx());
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use std::sync::{Arc, Mutex};
+
+use eframe::egui::{ComboBox, ProgressBar, Slider, Ui};
+use log::error;
+
+use super::{i18n::tr, State};
+
+/// Message ids of the frequency bands of the audio input, their energy drives the
+/// modulations.
+const BANDS: [&str; 3] = ["band-bass", "band-mids", "band-treble"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Target {
+    None,
+    ZoomPulse,
+    HueShift,
+    JuliaAngle,
+}
+
+impl Target {
+    const ALL: [Target; 4] = [
+        Target::None,
+        Target::ZoomPulse,
+        Target::HueShift,
+        Target::JuliaAngle,
+    ];
+
+    fn label(self) -> String {
+        tr(match self {
+            Target::None => "target-nothing",
+            Target::ZoomPulse => "target-zoom-pulse",
+            Target::HueShift => "target-hue-shift",
+            Target::JuliaAngle => "target-julia-angle",
+        })
+    }
+}
+
+/// Modulate the rendering with the energy of the bass, mids and treble of the default
+/// audio input.
+pub struct AudioReactive {
+    /// Energy of each band, normalized to `[0, 1]`, written by the audio thread.
+    energies: Arc<Mutex<[f32; 3]>>,
+    input: Option<input::AudioInput>,
+    targets: [Target; 3],
+    amounts: [f32; 3],
+}
+
+impl AudioReactive {
+    pub fn new() -> Self {
+        Self {
+            energies: Arc::new(Mutex::new([0.0; 3])),
+            input: None,
+            targets: [Target::ZoomPulse, Target::HueShift, Target::None],
+            amounts: [0.5; 3],
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.input.is_some()
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        let mut enabled = self.input.is_some();
+        if ui
+            .checkbox(&mut enabled, tr("listen-to-the-audio-input"))
+            .changed()
+        {
+            self.input = None;
+            if enabled {
+                match input::AudioInput::start(self.energies.clone()) {
+                    Ok(input) => self.input = Some(input),
+                    Err(e) => error!("Cannot listen to the audio input: {:?}", e),
+                }
+            }
+        }
+
+        let energies = *self.energies.lock().unwrap();
+        for (band, name) in BANDS.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add(ProgressBar::new(energies[band]).desired_width(40.0));
+                ComboBox::from_label(tr(name))
+                    .selected_text(self.targets[band].label())
+                    .show_ui(ui, |ui| {
+                        for target in Target::ALL {
+                            ui.selectable_value(&mut self.targets[band], target, target.label());
+                        }
+                    });
+            });
+            ui.add(Slider::new(&mut self.amounts[band], 0.0..=2.0).text(tr("amount")));
+        }
+    }
+
+    /// Apply the modulations to the state about to be rendered.
+    pub fn modulate(&self, state: &mut State) {
+        if self.input.is_none() {
+            return;
+        }
+        let energies = *self.energies.lock().unwrap();
+        for ((energy, target), amount) in energies.iter().zip(self.targets).zip(self.amounts) {
+            let amount = energy * amount;
+            match target {
+                Target::None => {}
+                Target::ZoomPulse => state.zoom *= 1.0 + 0.2 * amount,
+                Target::HueShift => {
+                    [state.r, state.g, state.b] =
+                        rotate_hue([state.r, state.g, state.b], amount * std::f32::consts::PI);
+                }
+                Target::JuliaAngle => {
+                    let (sin, cos) = (amount * 0.5).sin_cos();
+                    let c = state.c_julia;
+                    state.c_julia.x = c.x * cos - c.y * sin;
+                    state.c_julia.y = c.x * sin + c.y * cos;
+                }
+            }
+        }
+    }
+}
+
+/// Rotate `color` around the gray axis of the RGB cube.
+fn rotate_hue(color: [f32; 3], angle: f32) -> [f32; 3] {
+    let (sin, cos) = angle.sin_cos();
+    let third: f32 = 1.0 / 3.0;
+    let sqrt_third = third.sqrt();
+    let a = cos + (1.0 - cos) * third;
+    let b = third * (1.0 - cos) - sqrt_third * sin;
+    let c = third * (1.0 - cos) + sqrt_third * sin;
+    let [r, g, bl] = color;
+    [
+        r * a + g * b + bl * c,
+        r * c + g * a + bl * b,
+        r * b + g * c + bl * a,
+    ]
+}
+
+#[cfg(feature = "audio")]
+mod input {
+    use std::sync::{Arc, Mutex};
+
+    use anyhow::{anyhow, bail, Context, Result};
+    use cpal::{
+        traits::{DeviceTrait, HostTrait, StreamTrait},
+        FromSample, Sample, SampleFormat, SizedSample, Stream, StreamConfig,
+    };
+    use log::{error, info};
+
+    /// Low-pass cutoffs separating the bands, in Hz.
+    const BASS_CUTOFF: f32 = 200.0;
+    const TREBLE_CUTOFF: f32 = 2000.0;
+
+    /// Capture stream of the default input device, stops when dropped.
+    pub struct AudioInput {
+        _stream: Stream,
+    }
+
+    impl AudioInput {
+        pub fn start(energies: Arc<Mutex<[f32; 3]>>) -> Result<Self> {
+            let device = cpal::default_host()
+                .default_input_device()
+                .ok_or(anyhow!("No audio input device"))?;
+            let config = device
+                .default_input_config()
+                .context("Cannot get the configuration of the audio input")?;
+            info!(
+                "Listening to {} ({:?})",
+                device.name().unwrap_or_default(),
+                config
+            );
+
+            let stream = match config.sample_format() {
+                SampleFormat::F32 => build::<f32>(&device, &config.into(), energies),
+                SampleFormat::I16 => build::<i16>(&device, &config.into(), energies),
+                SampleFormat::U16 => build::<u16>(&device, &config.into(), energies),
+                format => bail!("Unsupported sample format {:?}", format),
+            }?;
+            stream.play().context("Cannot start the audio stream")?;
+            Ok(Self { _stream: stream })
+        }
+    }
+
+    fn build<T: SizedSample>(
+        device: &cpal::Device,
+        config: &StreamConfig,
+        energies: Arc<Mutex<[f32; 3]>>,
+    ) -> Result<Stream>
+    where
+        f32: FromSample<T>,
+    {
+        let channels = config.channels as usize;
+        let mut analyzer = BandAnalyzer::new(config.sample_rate.0 as f32);
+        device
+            .build_input_stream(
+                config,
+                move |data: &[T], _: &cpal::InputCallbackInfo| {
+                    for frame in data.chunks(channels) {
+                        // Mix down to mono
+                        let sample = frame.iter().map(|s| s.to_sample::<f32>()).sum::<f32>()
+                            / channels as f32;
+                        analyzer.push(sample);
+                    }
+                    *energies.lock().unwrap() = analyzer.energies();
+                },
+                |e| error!("Audio input error: {}", e),
+                None,
+            )
+            .context("Cannot open the audio stream")
+    }
+
+    /// Split the signal in 3 bands with one-pole filters and follow their loudness.
+    struct BandAnalyzer {
+        bass_alpha: f32,
+        treble_alpha: f32,
+        /// Attack and release of the envelope followers, per sample.
+        attack: f32,
+        release: f32,
+        bass_filter: f32,
+        treble_filter: f32,
+        envelopes: [f32; 3],
+        /// Slowly decaying maximum of each envelope, for the normalization.
+        peaks: [f32; 3],
+    }
+
+    impl BandAnalyzer {
+        fn new(sample_rate: f32) -> Self {
+            let alpha =
+                |cutoff: f32| 1.0 - (-2.0 * std::f32::consts::PI * cutoff / sample_rate).exp();
+            let per_sample = |seconds: f32| 1.0 - (-1.0 / (seconds * sample_rate)).exp();
+            Self {
+                bass_alpha: alpha(BASS_CUTOFF),
+                treble_alpha: alpha(TREBLE_CUTOFF),
+                attack: per_sample(0.01),
+                release: per_sample(0.2),
+                bass_filter: 0.0,
+                treble_filter: 0.0,
+                envelopes: [0.0; 3],
+                peaks: [1e-4; 3],
+            }
+        }
+
+        fn push(&mut self, sample: f32) {
+            self.bass_filter += self.bass_alpha * (sample - self.bass_filter);
+            self.treble_filter += self.treble_alpha * (sample - self.treble_filter);
+            let bands = [
+                self.bass_filter,
+                self.treble_filter - self.bass_filter,
+                sample - self.treble_filter,
+            ];
+
+            for (band, value) in bands.into_iter().enumerate() {
+                let level = value.abs();
+                let envelope = &mut self.envelopes[band];
+                let rate = if level > *envelope {
+                    self.attack
+                } else {
+                    self.release
+                };
+                *envelope += rate * (level - *envelope);
+                // Forget about past loud passages in a few seconds
+                self.peaks[band] = (self.peaks[band] * 0.99999).max(*envelope).max(1e-4);
+            }
+        }
+
+        fn energies(&self) -> [f32; 3] {
+            std::array::from_fn(|band| (self.envelopes[band] / self.peaks[band]).min(1.0))
+        }
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+mod input {
+    use std::sync::{Arc, Mutex};
+
+    use anyhow::{bail, Result};
+
+    pub struct AudioInput;
+
+    impl AudioInput {
+        pub fn start(_energies: Arc<Mutex<[f32; 3]>>) -> Result<Self> {
+            bail!("fractal_gl was built without the \"audio\" feature")
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use std::{fmt::Write as _, fs};
+
+use eframe::egui::{self, Grid, ProgressBar};
+use log::{error, info};
+
+use super::{
+    file_dialog,
+    i18n::{tr, tr_args},
+    position::Position,
+    FractalGl, FractalType, State,
+};
+
+/// Draws timed per case, the median is reported.
+const FRAMES: u32 = 5;
+/// Sizes of the offscreen renders, in pixels.
+const RESOLUTIONS: [[i32; 2]; 2] = [[1280, 720], [1920, 1080]];
+
+/// Views of the benchmark, from cheap to expensive, always the same to compare the runs.
+fn views() -> Vec<(&'static str, State)> {
+    let view = |fractal_type, center: [f64; 2], magnification: f32, iterations| {
+        let mut state = State::new();
+        state.fractal_type = fractal_type;
+        state.center_position = Position {
+            x: -center[0],
+            y: -center[1],
+        };
+        // Like the magnification of the render command, on the 1080 pixels high render
+        state.zoom = magnification * 1080.0 / 4.0;
+        state.max_iterations = iterations;
+        state.auto_iterations = false;
+        state
+    };
+    vec![
+        (
+            "Mandelbrot",
+            view(FractalType::Mandelbrot, [-0.5, 0.0], 1.0, 1024),
+        ),
+        ("Julia", view(FractalType::Julia, [0.0, 0.0], 1.0, 1024)),
+        (
+            "Seahorse valley",
+            view(FractalType::Mandelbrot, [-0.7436, 0.1318], 2000.0, 8192),
+        ),
+        (
+            "Burning Ship",
+            view(FractalType::BurningShip, [-1.755, -0.03], 50.0, 4096),
+        ),
+        ("Newton", view(FractalType::Newton, [0.0, 0.0], 1.0, 256)),
+    ]
+}
+
+/// Quality settings compared: high quality and samples per pixel.
+const QUALITIES: [(bool, i32); 3] = [(false, 1), (true, 1), (true, 2)];
+
+fn quality_name(high_quality: bool, samples: i32) -> String {
+    let quality = tr(if high_quality {
+        "benchmark-high"
+    } else {
+        "benchmark-low"
+    });
+    if samples > 1 {
+        format!("{quality} ×{}", samples * samples)
+    } else {
+        quality
+    }
+}
+
+/// One render of the benchmark.
+struct Case {
+    view: &'static str,
+    size: [i32; 2],
+    high_quality: bool,
+    samples: i32,
+    state: State,
+}
+
+fn cases() -> Vec<Case> {
+    let mut cases = Vec::new();
+    for (view, state) in views() {
+        for size in RESOLUTIONS {
+            for (high_quality, samples) in QUALITIES {
+                cases.push(Case {
+                    view,
+                    size,
+                    high_quality,
+                    samples,
+                    state: State {
+                        high_quality,
+                        supersampling: samples,
+                        ..state
+                    },
+                });
+            }
+        }
+    }
+    cases
+}
+
+/// Fixed views rendered offscreen at fixed sizes and timed on the GPU, to compare GPUs and
+/// the changes of the renderer. One case is rendered per frame so that the UI stays alive.
+pub struct Benchmark {
+    pub open: bool,
+    cases: Vec<Case>,
+    /// Median milliseconds per frame of the cases done, in their order.
+    results: Vec<Result<f64, String>>,
+    /// A case is being rendered.
+    running: bool,
+    /// Id of the case requested from the renderer, if any.
+    pending: Option<u64>,
+    /// Ids of the requests, a cancelled run cannot mix its results with the next one.
+    next_id: u64,
+}
+
+impl Benchmark {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            cases: cases(),
+            results: Vec::new(),
+            running: false,
+            pending: None,
+            next_id: 0,
+        }
+    }
+
+    /// Record the case rendered since the last frame and request the next one.
+    pub fn update(&mut self, ctx: &egui::Context, fractal: &mut FractalGl) {
+        if !self.running {
+            return;
+        }
+        for (id, time) in fractal.take_benchmarks() {
+            if Some(id) == self.pending {
+                self.pending = None;
+                self.results.push(
+                    time.map(|seconds| seconds * 1000.0)
+                        .map_err(|e| e.to_string()),
+                );
+            }
+        }
+        if self.pending.is_none() {
+            let index = self.results.len();
+            match self.cases.get(index) {
+                Some(case) => {
+                    fractal.request_benchmark(self.next_id, case.state, case.size, FRAMES);
+                    self.pending = Some(self.next_id);
+                    self.next_id += 1;
+                }
+                None => {
+                    info!("Benchmark done");
+                    self.running = false;
+                }
+            }
+        }
+        ctx.request_repaint();
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = "view,width,height,high_quality,samples,ms_per_frame\n".to_string();
+        for (case, result) in self.cases.iter().zip(&self.results) {
+            let time = result
+                .as_ref()
+                .map_or(String::new(), |ms| format!("{ms:.3}"));
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{}",
+                case.view,
+                case.size[0],
+                case.size[1],
+                case.high_quality as u8,
+                case.samples * case.samples,
+                time
+            );
+        }
+        csv
+    }
+
+    fn export(&self) {
+        let Some(path) = file_dialog::save_file(&[(&tr("filter-csv"), &["csv"])], "benchmark.csv")
+        else {
+            return;
+        };
+        match fs::write(&path, self.to_csv()) {
+            Ok(()) => info!("Exported the benchmark to {}", path.display()),
+            Err(e) => error!("Cannot write {}: {:?}", path.display(), e),
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+        egui::Window::new(tr("benchmark"))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(tr("benchmark-hint"));
+                ui.horizontal(|ui| {
+                    if self.running {
+                        if ui.button(tr("cancel")).clicked() {
+                            self.running = false;
+                            self.pending = None;
+                        }
+                        let progress = self.results.len() as f32 / self.cases.len() as f32;
+                        ui.add(ProgressBar::new(progress).show_percentage());
+                    } else {
+                        if ui.button(tr("run-benchmark")).clicked() {
+                            self.results.clear();
+                            self.running = true;
+                        }
+                        if ui
+                            .add_enabled(
+                                !self.results.is_empty(),
+                                egui::Button::new(tr("export-render-statistics")),
+                            )
+                            .clicked()
+                        {
+                            self.export();
+                        }
+                    }
+                });
+                if self.results.is_empty() {
+                    return;
+                }
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        Grid::new("Benchmark").striped(true).show(ui, |ui| {
+                            ui.strong(tr("benchmark-view"));
+                            ui.strong(tr("benchmark-resolution"));
+                            ui.strong(tr("benchmark-quality"));
+                            ui.strong(tr("benchmark-time"));
+                            ui.end_row();
+                            for (case, result) in self.cases.iter().zip(&self.results) {
+                                ui.label(case.view);
+                                ui.label(format!("{}×{}", case.size[0], case.size[1]));
+                                ui.label(quality_name(case.high_quality, case.samples));
+                                match result {
+                                    Ok(ms) => ui.label(tr_args(
+                                        "benchmark-milliseconds",
+                                        &[("value", &format!("{ms:.2}"))],
+                                    )),
+                                    Err(e) => ui.label(tr("benchmark-failed")).on_hover_text(e),
+                                };
+                                ui.end_row();
+                            }
+                        });
+                    });
+            });
+        self.open = open;
+    }
+}
@@ -0,0 +1,393 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{File, OpenOptions},
+    io::{BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result};
+use eframe::egui::Vec2;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    checkpoint::Checkpoint,
+    overlay::{self, Overlay},
+    tiled_export::{export_height, tile_state, TileRenderer},
+    State,
+};
+
+/// Side of the tiles, in pixels, a multiple of 16 as TIFF requires.
+const TILE_SIZE: usize = 256;
+
+/// Field types of the directory entries.
+const SHORT: u16 = 3;
+const LONG: u16 = 4;
+const LONG8: u16 = 16;
+
+/// Entry of the image file directory: tag, type, count and value or offset of the values.
+struct Entry(u16, u16, u64, u64);
+
+/// Tiles in the file, saved in the [Checkpoint].
+#[derive(Serialize, Deserialize)]
+struct Progress {
+    end: u64,
+    written: Vec<(u64, u64)>,
+}
+
+/// Export of the view as a tiled BigTIFF: uncompressed RGB, 8 or 16 bits per channel.
+///
+/// The tiles are written as they are rendered, a few per frame, and the directory listing
+/// them is appended at the end, so the whole image is never held in memory.
+///
+/// The tiles flushed to the file are listed in a [Checkpoint]: an export to the same file of
+/// the same view, cancelled or interrupted, resumes where it stopped.
+pub struct BigTiffExport {
+    pub path: PathBuf,
+    file: BufWriter<File>,
+    /// Write offset, the end of the file.
+    end: u64,
+    width: usize,
+    height: usize,
+    /// 16 bits per channel instead of 8.
+    deep: bool,
+    /// Tiles still to request, by index in the file.
+    tiles: VecDeque<(usize, State)>,
+    /// Tiles requested from the renderer: their index by request id.
+    pending: HashMap<u64, usize>,
+    next_id: u64,
+    /// Offset and size in bytes of each tile, in row-major order, 0 bytes until written.
+    written: Vec<(u64, u64)>,
+    checkpoint: Checkpoint,
+    /// Annotations and scale bar drawn over the image.
+    overlay: Option<Overlay>,
+}
+
+impl BigTiffExport {
+    /// Export the view `state` gives on a canvas of `viewport_px` pixels, to an image
+    /// `width` pixels wide, with `overlay` drawn over it.
+    pub fn start(
+        path: PathBuf,
+        state: &State,
+        viewport_px: Vec2,
+        width: usize,
+        deep: bool,
+        overlay: Option<Overlay>,
+    ) -> Result<Self> {
+        let width = width.max(1);
+        let height = export_height(viewport_px, width);
+        let parameters = (
+            state,
+            [viewport_px.x, viewport_px.y],
+            width,
+            deep,
+            overlay.is_some(),
+        );
+        let (checkpoint, progress) = Checkpoint::load::<Progress>(&path, &parameters)?;
+        let (file, progress) = match progress {
+            Some(progress) => (Self::reopen(&path, progress.end), Some(progress)),
+            None => (Self::create(&path), None),
+        };
+        let file = file.with_context(|| format!("Cannot write {}", path.display()))?;
+
+        let (columns, rows) = (width.div_ceil(TILE_SIZE), height.div_ceil(TILE_SIZE));
+        // Edge tiles are rendered whole, TIFF tiles all have the same size
+        let mut tiles = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (row, column)))
+            .enumerate()
+            .map(|(index, (row, column))| {
+                let rect = [column, row, column + 1, row + 1];
+                let rect = rect.map(|side| (side * TILE_SIZE) as f32);
+                let size = [width as f32, height as f32];
+                (index, tile_state(state, viewport_px, size, rect))
+            })
+            .collect::<VecDeque<_>>();
+        let Progress { end, written } = progress.unwrap_or(Progress {
+            end: 16,
+            written: vec![(0, 0); tiles.len()],
+        });
+        tiles.retain(|(index, _)| written.get(*index).is_some_and(|(_, size)| *size == 0));
+
+        Ok(Self {
+            path,
+            file,
+            end,
+            width,
+            height,
+            deep,
+            written,
+            tiles,
+            pending: HashMap::new(),
+            next_id: 0,
+            checkpoint,
+            overlay,
+        })
+    }
+
+    fn create(path: &Path) -> Result<BufWriter<File>> {
+        let mut file = BufWriter::new(File::create(path)?);
+        // Little endian BigTIFF, 8 byte offsets, and the offset of the directory once known
+        file.write_all(b"II")?;
+        file.write_all(&43u16.to_le_bytes())?;
+        file.write_all(&8u16.to_le_bytes())?;
+        file.write_all(&0u16.to_le_bytes())?;
+        file.write_all(&0u64.to_le_bytes())?;
+        Ok(file)
+    }
+
+    /// The file of an interrupted export, without what was written after the checkpoint.
+    fn reopen(path: &Path, end: u64) -> Result<BufWriter<File>> {
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        file.set_len(end)?;
+        file.seek(SeekFrom::Start(end))?;
+        Ok(BufWriter::new(file))
+    }
+
+    /// Write the tiles rendered since the last frame and request the next ones. Returns
+    /// whether the export is complete.
+    pub fn update(&mut self, renderer: &mut dyn TileRenderer) -> Result<bool> {
+        for (id, pixels) in renderer.take_tiles() {
+            let Some(index) = self.pending.remove(&id) else {
+                continue;
+            };
+            let mut pixels = pixels?;
+            if let Some(overlay) = &self.overlay {
+                let columns = self.width.div_ceil(TILE_SIZE);
+                let (row, column) = (index / columns, index % columns);
+                overlay.draw(&mut overlay::Tile {
+                    pixels: &mut pixels,
+                    origin: [column * TILE_SIZE, row * TILE_SIZE],
+                    size: [TILE_SIZE; 2],
+                    deep: self.deep,
+                });
+            }
+            let rgb = self.rgb(&pixels);
+            self.file.write_all(&rgb)?;
+            self.written[index] = (self.end, rgb.len() as u64);
+            self.end += rgb.len() as u64;
+        }
+
+        while self.pending.len() < renderer.capacity() {
+            let Some((index, state)) = self.tiles.pop_front() else {
+                break;
+            };
+            let size = [TILE_SIZE as i32; 2];
+            renderer.request_tile(self.next_id, state, size, self.deep);
+            self.pending.insert(self.next_id, index);
+            self.next_id += 1;
+        }
+
+        if self.is_complete() {
+            self.finish()
+                .with_context(|| format!("Cannot write {}", self.path.display()))?;
+            self.checkpoint.remove()?;
+            return Ok(true);
+        }
+        if self.checkpoint.is_due() {
+            self.save_checkpoint()?;
+        }
+        Ok(false)
+    }
+
+    fn is_complete(&self) -> bool {
+        self.tiles.is_empty() && self.pending.is_empty()
+    }
+
+    /// Save the tiles written, once they are on the disk.
+    fn save_checkpoint(&mut self) -> Result<()> {
+        self.file.flush()?;
+        self.file.get_ref().sync_data()?;
+        let progress = Progress {
+            end: self.end,
+            written: self.written.clone(),
+        };
+        self.checkpoint.save(&progress)
+    }
+
+    /// Fraction of the tiles written.
+    pub fn progress(&self) -> f32 {
+        let left = self.tiles.len() + self.pending.len();
+        1.0 - left as f32 / self.written.len().max(1) as f32
+    }
+
+    /// RGB channels of RGBA `pixels`, in little endian when 16 bits.
+    fn rgb(&self, pixels: &[u8]) -> Vec<u8> {
+        if self.deep {
+            pixels
+                .chunks_exact(8)
+                .flat_map(|pixel| {
+                    (0..3).flat_map(move |c| {
+                        u16::from_ne_bytes([pixel[2 * c], pixel[2 * c + 1]]).to_le_bytes()
+                    })
+                })
+                .collect()
+        } else {
+            pixels
+                .chunks_exact(4)
+                .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+                .collect()
+        }
+    }
+
+    /// Append the tile offsets, the tile sizes and the directory, then point the header to
+    /// the directory.
+    fn finish(&mut self) -> Result<()> {
+        let mut write_array = |values: &mut dyn Iterator<Item = u64>| -> Result<u64> {
+            let offset = self.end;
+            for value in values {
+                self.file.write_all(&value.to_le_bytes())?;
+                self.end += 8;
+            }
+            Ok(offset)
+        };
+        let count = self.written.len() as u64;
+        let offsets = write_array(&mut self.written.iter().map(|(offset, _)| *offset))?;
+        let sizes = write_array(&mut self.written.iter().map(|(_, size)| *size))?;
+        // A single value is stored in the entry itself
+        let (offsets, sizes) = if count == 1 {
+            (self.written[0].0, self.written[0].1)
+        } else {
+            (offsets, sizes)
+        };
+
+        let bits = if self.deep { 16u64 } else { 8 };
+        let entries = [
+            Entry(256, LONG, 1, self.width as u64),
+            Entry(257, LONG, 1, self.height as u64),
+            // Three shorts, packed in the value
+            Entry(258, SHORT, 3, bits | (bits << 16) | (bits << 32)),
+            // No compression
+            Entry(259, SHORT, 1, 1),
+            // RGB
+            Entry(262, SHORT, 1, 2),
+            Entry(277, SHORT, 1, 3),
+            // Channels interleaved
+            Entry(284, SHORT, 1, 1),
+            Entry(322, SHORT, 1, TILE_SIZE as u64),
+            Entry(323, SHORT, 1, TILE_SIZE as u64),
+            Entry(324, LONG8, count, offsets),
+            Entry(325, LONG8, count, sizes),
+        ];
+        let directory = self.end;
+        self.file.write_all(&(entries.len() as u64).to_le_bytes())?;
+        for Entry(tag, field_type, count, value) in entries {
+            self.file.write_all(&tag.to_le_bytes())?;
+            self.file.write_all(&field_type.to_le_bytes())?;
+            self.file.write_all(&count.to_le_bytes())?;
+            self.file.write_all(&value.to_le_bytes())?;
+        }
+        // No next directory
+        self.file.write_all(&0u64.to_le_bytes())?;
+
+        self.file.seek(SeekFrom::Start(8))?;
+        self.file.write_all(&directory.to_le_bytes())?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+impl Drop for BigTiffExport {
+    /// Save the progress of a cancelled export, to resume it.
+    fn drop(&mut self) {
+        if !self.is_complete() {
+            if let Err(e) = self.save_checkpoint() {
+                error!("{:#}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::app::tiled_export::FlatRenderer;
+
+    const COLOR: [u16; 4] = [0x1234, 0x5678, 0x9abc, 0xffff];
+
+    fn start(path: &Path, viewport_px: Vec2, deep: bool) -> BigTiffExport {
+        let width = viewport_px.x as usize;
+        BigTiffExport::start(path.into(), &State::new(), viewport_px, width, deep, None).unwrap()
+    }
+
+    fn run(mut export: BigTiffExport) {
+        let mut renderer = FlatRenderer::new(COLOR);
+        while !export.update(&mut renderer).unwrap() {}
+    }
+
+    fn read_u64(bytes: &[u8], offset: u64) -> u64 {
+        u64::from_le_bytes(bytes[offset as usize..][..8].try_into().unwrap())
+    }
+
+    /// Type, count and value of the entries of the directory of the file, by tag.
+    fn directory(bytes: &[u8]) -> HashMap<u16, (u16, u64, u64)> {
+        let start = read_u64(bytes, 8) as usize;
+        let count = read_u64(bytes, start as u64) as usize;
+        bytes[start + 8..][..count * 20]
+            .chunks_exact(20)
+            .map(|entry| {
+                let tag = u16::from_le_bytes([entry[0], entry[1]]);
+                let field_type = u16::from_le_bytes([entry[2], entry[3]]);
+                let count = u64::from_le_bytes(entry[4..12].try_into().unwrap());
+                let value = u64::from_le_bytes(entry[12..20].try_into().unwrap());
+                (tag, (field_type, count, value))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn header_and_tiles() {
+        let path = std::env::temp_dir().join("fractal_gl_bigtiff_test.tif");
+        run(start(&path, Vec2::new(300.0, 200.0), true));
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // Little endian BigTIFF
+        assert_eq!(&bytes[..8], b"II\x2b\x00\x08\x00\x00\x00");
+        let entries = directory(&bytes);
+        assert_eq!(entries[&256], (LONG, 1, 300));
+        assert_eq!(entries[&257], (LONG, 1, 200));
+        assert_eq!(entries[&258], (SHORT, 3, 16 | (16 << 16) | (16 << 32)));
+        // Two tiles side by side, the one on the edge rendered whole, then their offsets and
+        // sizes, then the directory
+        let tile_bytes = (TILE_SIZE * TILE_SIZE * 6) as u64;
+        let (_, count, offsets) = entries[&324];
+        let (_, _, sizes) = entries[&325];
+        assert_eq!(count, 2);
+        assert_eq!(offsets, 16 + 2 * tile_bytes);
+        assert_eq!(sizes, offsets + 16);
+        assert_eq!(read_u64(&bytes, 8), sizes + 16);
+        assert_eq!(read_u64(&bytes, offsets), 16);
+        assert_eq!(read_u64(&bytes, offsets + 8), 16 + tile_bytes);
+        assert_eq!(read_u64(&bytes, sizes), tile_bytes);
+        assert_eq!(read_u64(&bytes, sizes + 8), tile_bytes);
+        // RGB without the alpha, in little endian
+        assert_eq!(&bytes[16..22], [0x34, 0x12, 0x78, 0x56, 0xbc, 0x9a]);
+    }
+
+    #[test]
+    fn resume() {
+        let temp = std::env::temp_dir();
+        let (whole, resumed) = (
+            temp.join("fractal_gl_bigtiff_whole.tif"),
+            temp.join("fractal_gl_bigtiff_resumed.tif"),
+        );
+        // 10 tiles, more than a frame requests
+        let viewport_px = Vec2::new(1280.0, 512.0);
+        run(start(&whole, viewport_px, false));
+
+        let mut export = start(&resumed, viewport_px, false);
+        let mut renderer = FlatRenderer::new(COLOR);
+        export.update(&mut renderer).unwrap();
+        export.update(&mut renderer).unwrap();
+        drop(export);
+        let export = start(&resumed, viewport_px, false);
+        assert_eq!(export.progress(), 0.8);
+        run(export);
+
+        assert_eq!(fs::read(&whole).unwrap(), fs::read(&resumed).unwrap());
+        fs::remove_file(&whole).unwrap();
+        fs::remove_file(&resumed).unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+use eframe::egui::{Button, TextEdit, Ui};
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use super::{i18n::tr, settings, State};
+
+/// Name of the setting holding the bookmarks.
+const BOOKMARKS_SETTING: &str = "bookmarks.json";
+
+#[derive(Serialize, Deserialize)]
+struct Bookmark {
+    name: String,
+    state: State,
+}
+
+/// Views saved by the user under a name, kept between the runs.
+pub struct Bookmarks {
+    saved: Vec<Bookmark>,
+    /// Name typed for the next bookmark.
+    new_name: String,
+}
+
+impl Bookmarks {
+    pub fn new() -> Self {
+        Self {
+            saved: Vec::new(),
+            new_name: String::new(),
+        }
+    }
+
+    /// Bookmarks saved from the previous runs.
+    pub fn load() -> Self {
+        let saved = settings::load(BOOKMARKS_SETTING).map_or(Vec::new(), |content| {
+            serde_json::from_str(&content).unwrap_or_else(|e| {
+                error!("Cannot load the bookmarks: {:?}", e);
+                Vec::new()
+            })
+        });
+        Self {
+            saved,
+            new_name: String::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.saved.is_empty()
+    }
+
+    /// Names and views of the bookmarks, in the order they were added.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &State)> {
+        self.saved
+            .iter()
+            .map(|bookmark| (bookmark.name.as_str(), &bookmark.state))
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.saved.remove(index);
+        self.save();
+    }
+
+    /// Give the bookmark `index` the name `name`, replacing the bookmark already named so.
+    pub fn rename(&mut self, index: usize, name: String) {
+        if name.is_empty() || self.saved[index].name == name {
+            return;
+        }
+        self.saved[index].name = name;
+        let name = &self.saved[index].name;
+        let duplicate = (0..self.saved.len()).find(|i| *i != index && self.saved[*i].name == *name);
+        if let Some(duplicate) = duplicate {
+            self.saved.remove(duplicate);
+        }
+        self.save();
+    }
+
+    fn save(&self) {
+        let saved = serde_json::to_string_pretty(&self.saved)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| settings::save(BOOKMARKS_SETTING, &content));
+        if let Err(e) = saved {
+            error!("Cannot save the bookmarks: {:?}", e);
+        }
+    }
+
+    /// List of the bookmarks, and the field to bookmark `current`. Returns the view to go
+    /// to, if one was picked.
+    pub fn ui(&mut self, ui: &mut Ui, current: &State) -> Option<State> {
+        let mut picked = None;
+        let mut removed = None;
+        for (i, bookmark) in self.saved.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.button(tr("go")).clicked() {
+                    picked = Some(bookmark.state);
+                }
+                if ui.button(tr("remove")).clicked() {
+                    removed = Some(i);
+                }
+                ui.label(&bookmark.name).on_hover_text(format!(
+                    "{:?} ×{:.0}",
+                    bookmark.state.fractal_type, bookmark.state.zoom
+                ));
+            });
+        }
+        if let Some(i) = removed {
+            self.remove(i);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.new_name)
+                    .hint_text(tr("bookmark-name"))
+                    .desired_width(120.0),
+            );
+            let name = self.new_name.trim().to_string();
+            if ui
+                .add_enabled(!name.is_empty(), Button::new(tr("add-bookmark")))
+                .clicked()
+            {
+                // Saving under an existing name replaces it
+                self.saved.retain(|bookmark| bookmark.name != name);
+                self.saved.push(Bookmark {
+                    name,
+                    state: *current,
+                });
+                self.new_name.clear();
+                self.save();
+            }
+        });
+        picked
+    }
+}
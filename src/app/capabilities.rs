@@ -0,0 +1,67 @@
+/// What the GL context supports, to adapt the renderer instead of failing on older GPUs.
+#[derive(Clone, Debug)]
+pub struct Capabilities {
+    /// OpenGL ES or WebGL, rather than desktop OpenGL.
+    pub embedded: bool,
+    pub major: u32,
+    pub minor: u32,
+    /// GPU time of the draws, for the render statistics.
+    pub timer_query: bool,
+    /// Render targets of 16 bits per channel, for the deep exports.
+    pub norm16: bool,
+}
+
+impl Capabilities {
+    pub fn query(gl: &eframe::glow::Context) -> Self {
+        use eframe::glow::HasContext as _;
+        let version = gl.version();
+        let extensions = gl.supported_extensions();
+        let has = |names: &[&str]| names.iter().any(|name| extensions.contains(*name));
+        let embedded = version.is_embedded;
+        let at_least = |major, minor| (version.major, version.minor) >= (major, minor);
+        Self {
+            embedded,
+            major: version.major,
+            minor: version.minor,
+            // WebGL 2 only has them behind an extension that glow does not use
+            timer_query: !cfg!(target_arch = "wasm32")
+                && if embedded {
+                    has(&["GL_EXT_disjoint_timer_query"])
+                } else {
+                    at_least(3, 3) || has(&["GL_ARB_timer_query"])
+                },
+            norm16: !embedded || has(&["GL_EXT_texture_norm16", "EXT_texture_norm16"]),
+        }
+    }
+
+    /// First line of the shaders for this context, `None` when it is too old for them.
+    pub fn glsl_version(&self) -> Option<&'static str> {
+        let version = (self.major, self.minor);
+        if self.embedded {
+            (version >= (3, 0)).then_some("#version 300 es")
+        } else {
+            (version >= (3, 3)).then_some("#version 330")
+        }
+    }
+
+    /// Name of the context, like `OpenGL ES 3.0`.
+    pub fn api(&self) -> String {
+        let name = if self.embedded { "OpenGL ES" } else { "OpenGL" };
+        format!("{name} {}.{}", self.major, self.minor)
+    }
+
+    /// Features turned off for lack of support, for the diagnostics.
+    pub fn disabled_features(&self) -> Vec<&'static str> {
+        let mut disabled = Vec::new();
+        if self.glsl_version().is_none() {
+            disabled.push("rendering, OpenGL 3.3 or OpenGL ES 3.0 is needed");
+        }
+        if !self.timer_query {
+            disabled.push("GPU timings");
+        }
+        if !self.norm16 {
+            disabled.push("16 bits per channel exports");
+        }
+        disabled
+    }
+}
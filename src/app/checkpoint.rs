@@ -0,0 +1,116 @@
+//! Progress of the long tiled exports saved next to them, to resume them after a cancel or a
+//! crash instead of starting over.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result};
+use log::warn;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use web_time::Instant;
+
+/// Delay between two checkpoints of an export in progress.
+const INTERVAL: Duration = Duration::from_secs(10);
+
+/// Content of the checkpoint file.
+#[derive(Serialize, Deserialize)]
+struct Saved<P, T> {
+    /// What is exported: the view, the size and the format. The checkpoint of another export
+    /// to the same file is not resumed.
+    parameters: P,
+    progress: T,
+}
+
+/// Checkpoint of an export, in `<file of the export>.resume`, saved every [INTERVAL] and
+/// removed once the export is complete.
+pub struct Checkpoint {
+    path: PathBuf,
+    parameters: serde_json::Value,
+    saved: Instant,
+}
+
+impl Checkpoint {
+    /// Checkpoint of the export of `parameters` to `export_path`, with the progress the last
+    /// run of the same export saved. The checkpoint of another export is removed.
+    pub fn load<T: DeserializeOwned>(
+        export_path: &Path,
+        parameters: &impl Serialize,
+    ) -> Result<(Self, Option<T>)> {
+        let mut path = export_path.as_os_str().to_owned();
+        path.push(".resume");
+        let checkpoint = Self {
+            path: path.into(),
+            parameters: serde_json::to_value(parameters)?,
+            saved: Instant::now(),
+        };
+        let saved = match fs::read(&checkpoint.path) {
+            Ok(json) => serde_json::from_slice::<Saved<serde_json::Value, T>>(&json)
+                .inspect_err(|e| warn!("Ignoring {}: {}", checkpoint.path.display(), e))
+                .ok(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => {
+                return Err(e).with_context(|| format!("Cannot read {}", checkpoint.path.display()))
+            }
+        };
+        let progress = saved
+            .filter(|saved| saved.parameters == checkpoint.parameters)
+            .map(|saved| saved.progress);
+        if progress.is_none() {
+            checkpoint.remove()?;
+        }
+        Ok((checkpoint, progress))
+    }
+
+    /// Whether the progress was saved long enough ago.
+    pub fn is_due(&self) -> bool {
+        self.saved.elapsed() >= INTERVAL
+    }
+
+    /// Save `progress`, replacing the last checkpoint only once written whole.
+    pub fn save(&mut self, progress: &impl Serialize) -> Result<()> {
+        let saved = Saved {
+            parameters: &self.parameters,
+            progress,
+        };
+        let mut partial = self.path.as_os_str().to_owned();
+        partial.push(".partial");
+        fs::write(&partial, serde_json::to_vec(&saved)?)
+            .and_then(|()| fs::rename(&partial, &self.path))
+            .with_context(|| format!("Cannot write {}", self.path.display()))?;
+        self.saved = Instant::now();
+        Ok(())
+    }
+
+    /// Remove the checkpoint, once the export is complete.
+    pub fn remove(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Cannot remove {}", self.path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resume_the_same_export() {
+        let export = std::env::temp_dir().join("fractal_gl_checkpoint_test.dzi");
+        let (mut checkpoint, progress) = Checkpoint::load::<Vec<usize>>(&export, &1).unwrap();
+        assert_eq!(progress, None);
+        checkpoint.save(&vec![3, 5]).unwrap();
+
+        let (_, progress) = Checkpoint::load::<Vec<usize>>(&export, &1).unwrap();
+        assert_eq!(progress, Some(vec![3, 5]));
+        // Another export to the same file starts over
+        let (checkpoint, progress) = Checkpoint::load::<Vec<usize>>(&export, &2).unwrap();
+        assert_eq!(progress, None);
+        assert!(!checkpoint.path.exists());
+    }
+}
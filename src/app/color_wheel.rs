@@ -0,0 +1,97 @@
+use eframe::{
+    egui::{Color32, Response, Sense, Stroke, Ui, Widget, WidgetInfo, WidgetType},
+    epaint::Vec2,
+};
+
+use super::{accessibility::arrow_keys, parameter::step_factor};
+
+/// Number of segments of the hue ring.
+const SEGMENTS: usize = 36;
+/// Change of the offset for one press of an arrow key, in wheel radii.
+const KEY_STEP: f32 = 0.02;
+
+/// RGB shift of a color wheel offset: x towards red, y towards green-blue, without changing
+/// the average of the channels.
+pub fn rgb_shift(offset: [f32; 2]) -> [f32; 3] {
+    let [x, y] = offset;
+    let sqrt3_2 = 3f32.sqrt() / 2.0;
+    [x, -0.5 * x + sqrt3_2 * y, -0.5 * x - sqrt3_2 * y]
+}
+
+/// Color wheel of a color balance: the puck is dragged away from the center towards the
+/// hue to push the colors to. The offset stays inside the unit disk.
+pub struct ColorWheel<'a> {
+    offset: &'a mut [f32; 2],
+    name: String,
+    diameter: f32,
+}
+
+impl<'a> ColorWheel<'a> {
+    pub fn new(offset: &'a mut [f32; 2], name: String) -> ColorWheel<'a> {
+        ColorWheel {
+            offset,
+            name,
+            diameter: 64.0,
+        }
+    }
+}
+
+impl Widget for ColorWheel<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let (rect, mut response) =
+            ui.allocate_exact_size(Vec2::splat(self.diameter), Sense::click_and_drag());
+        let radius = self.diameter * 0.5 - 4.0;
+
+        let mut offset = Vec2::new(self.offset[0], self.offset[1]);
+        if response.double_clicked() {
+            offset = Vec2::ZERO;
+        } else if let Some(pointer) = response.interact_pointer_pos() {
+            // The screen y axis points down
+            let direction = (pointer - rect.center()) / radius;
+            offset = Vec2::new(direction.x, -direction.y);
+        }
+        let arrows = arrow_keys(ui, &response);
+        if arrows != Vec2::ZERO {
+            offset += arrows * KEY_STEP * step_factor(ui.input(|i| i.modifiers));
+        }
+        if offset.length() > 1.0 {
+            offset = offset.normalized();
+        }
+        if [offset.x, offset.y] != *self.offset {
+            *self.offset = [offset.x, offset.y];
+            response.mark_changed();
+        }
+        response.widget_info(|| {
+            WidgetInfo::labeled(
+                WidgetType::Other,
+                ui.is_enabled(),
+                format!("{}: {:.2} {:.2}", self.name, offset.x, offset.y),
+            )
+        });
+
+        let painter = ui.painter();
+        let visuals = ui.style().interact(&response);
+        // Ring of the hues the puck pushes towards
+        for i in 0..SEGMENTS {
+            let angle = |i: usize| i as f32 / SEGMENTS as f32 * std::f32::consts::TAU;
+            let point = |angle: f32| rect.center() + Vec2::new(angle.cos(), -angle.sin()) * radius;
+            let middle = (angle(i) + angle(i + 1)) / 2.0;
+            let [r, g, b] = rgb_shift([middle.cos(), middle.sin()])
+                .map(|channel| ((0.5 + 0.5 * channel).clamp(0.0, 1.0) * 255.0) as u8);
+            painter.line_segment(
+                [point(angle(i)), point(angle(i + 1))],
+                Stroke::new(4.0, Color32::from_rgb(r, g, b)),
+            );
+        }
+        painter.circle_stroke(rect.center(), 2.0, visuals.fg_stroke);
+        let puck = rect.center() + Vec2::new(offset.x, -offset.y) * radius;
+        painter.circle(
+            puck,
+            4.0,
+            visuals.bg_fill,
+            Stroke::new(1.5, visuals.fg_stroke.color),
+        );
+
+        response.on_hover_text(self.name)
+    }
+}
@@ -0,0 +1,201 @@
+use eframe::egui::{Button, ComboBox, DragValue, Frame, Ui};
+
+use super::{accessibility::icon_button, fractal_gl::COLORING_PARAMETERS, i18n::tr};
+
+/// Transfer function applied to the value flowing through the graph.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Node {
+    Scale(f32),
+    Offset(f32),
+    Power(f32),
+    /// `0.5 + 0.5 * sin(frequency * value)`
+    Sine(f32),
+    Log,
+    Sqrt,
+    Fract,
+    Clamp,
+}
+
+impl Node {
+    const ALL: [Node; 8] = [
+        Node::Scale(2.0),
+        Node::Offset(0.5),
+        Node::Power(2.0),
+        Node::Sine(6.0),
+        Node::Log,
+        Node::Sqrt,
+        Node::Fract,
+        Node::Clamp,
+    ];
+
+    fn name(self) -> String {
+        tr(match self {
+            Node::Scale(_) => "node-scale",
+            Node::Offset(_) => "node-offset",
+            Node::Power(_) => "node-power",
+            Node::Sine(_) => "node-sine",
+            Node::Log => "node-log",
+            Node::Sqrt => "node-square-root",
+            Node::Fract => "node-fractional-part",
+            Node::Clamp => "node-clamp",
+        })
+    }
+
+    fn parameter(self) -> Option<f32> {
+        match self {
+            Node::Scale(x) | Node::Offset(x) | Node::Power(x) | Node::Sine(x) => Some(x),
+            Node::Log | Node::Sqrt | Node::Fract | Node::Clamp => None,
+        }
+    }
+
+    fn parameter_mut(&mut self) -> Option<&mut f32> {
+        match self {
+            Node::Scale(x) | Node::Offset(x) | Node::Power(x) | Node::Sine(x) => Some(x),
+            Node::Log | Node::Sqrt | Node::Fract | Node::Clamp => None,
+        }
+    }
+
+    /// GLSL expression of the node applied to `v`, its parameter is `parameter`.
+    fn glsl(self, parameter: &str) -> String {
+        match self {
+            Node::Scale(_) => format!("v * {parameter}"),
+            Node::Offset(_) => format!("v + {parameter}"),
+            Node::Power(_) => format!("pow(max(v, 0.0), {parameter})"),
+            Node::Sine(_) => format!("0.5 + 0.5 * sin(v * {parameter})"),
+            Node::Log => "log(max(v, 1e-6))".to_string(),
+            Node::Sqrt => "sqrt(max(v, 0.0))".to_string(),
+            Node::Fract => "fract(v)".to_string(),
+            Node::Clamp => "clamp(v, 0.0, 1.0)".to_string(),
+        }
+    }
+}
+
+/// What turns the final value into a color.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Output {
+    Palette,
+    /// The value times the red, green and blue parameters.
+    Tint,
+    Grayscale,
+}
+
+impl Output {
+    const ALL: [Output; 3] = [Output::Palette, Output::Tint, Output::Grayscale];
+
+    fn name(self) -> String {
+        tr(match self {
+            Output::Palette => "output-palette",
+            Output::Tint => "output-rgb-tint",
+            Output::Grayscale => "output-grayscale",
+        })
+    }
+
+    fn glsl(self) -> &'static str {
+        match self {
+            Output::Palette => "texture(u_palette, vec2(fract(v), 0.5)).rgb",
+            Output::Tint => "v * vec3(u_r, u_g, u_b)",
+            Output::Grayscale => "vec3(v)",
+        }
+    }
+}
+
+/// Chain of nodes from the iteration value to the color, compiled into the `customColor`
+/// function of the fragment shader.
+///
+/// Each node owns the parameter slot matching its position, so that changing a parameter
+/// only updates a uniform instead of rebuilding the shader.
+pub struct ColoringGraph {
+    nodes: Vec<Node>,
+    output: Output,
+}
+
+impl ColoringGraph {
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node::Scale(0.3), Node::Fract],
+            output: Output::Palette,
+        }
+    }
+
+    pub fn parameters(&self) -> [f32; COLORING_PARAMETERS] {
+        let mut parameters = [0.0; COLORING_PARAMETERS];
+        for (parameter, node) in parameters.iter_mut().zip(&self.nodes) {
+            *parameter = node.parameter().unwrap_or_default();
+        }
+        parameters
+    }
+
+    pub fn to_glsl(&self) -> String {
+        let mut glsl = String::from("vec3 customColor(float value) {\n    float v = value;\n");
+        for (slot, node) in self.nodes.iter().enumerate() {
+            let parameter = format!("u_coloringParameters[{slot}]");
+            glsl += &format!("    v = {};\n", node.glsl(&parameter));
+        }
+        glsl += &format!("    return {};\n}}", self.output.glsl());
+        glsl
+    }
+
+    /// Returns true when the structure of the graph changed and the shader must be rebuilt.
+    pub fn ui(&mut self, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        let mut move_up = None;
+        let mut remove = None;
+
+        ui.label(tr("iteration-value"));
+        for (index, node) in self.nodes.iter_mut().enumerate() {
+            ui.label("↓");
+            Frame::group(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(node.name());
+                    if let Some(value) = node.parameter_mut() {
+                        ui.add(DragValue::new(value).speed(0.01).max_decimals(3));
+                    }
+                    if icon_button(ui.add_enabled(index > 0, Button::new("⬆")), tr("move-up"))
+                        .clicked()
+                    {
+                        move_up = Some(index);
+                    }
+                    if icon_button(ui.button("🗑"), tr("remove")).clicked() {
+                        remove = Some(index);
+                    }
+                });
+            });
+        }
+        ui.label("↓");
+
+        if let Some(index) = move_up {
+            self.nodes.swap(index - 1, index);
+            changed = true;
+        }
+        if let Some(index) = remove {
+            self.nodes.remove(index);
+            changed = true;
+        }
+
+        Frame::group(ui.style()).show(ui, |ui| {
+            ComboBox::from_label(tr("output"))
+                .selected_text(self.output.name())
+                .show_ui(ui, |ui| {
+                    for output in Output::ALL {
+                        changed |= ui
+                            .selectable_value(&mut self.output, output, output.name())
+                            .changed();
+                    }
+                });
+        });
+
+        ui.add_enabled_ui(self.nodes.len() < COLORING_PARAMETERS, |ui| {
+            ui.menu_button(tr("add-node"), |ui| {
+                for node in Node::ALL {
+                    if ui.button(node.name()).clicked() {
+                        self.nodes.push(node);
+                        changed = true;
+                        ui.close_menu();
+                    }
+                }
+            });
+        });
+
+        changed
+    }
+}
@@ -0,0 +1,282 @@
+use eframe::egui::{self, vec2, Button, DragValue, Grid, Rect, Sense, Stroke, Ui, Vec2};
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    i18n::{tr, tr_args},
+    png::Panel,
+    position::Position,
+    settings, skew,
+    tiled_export::{export_height, tile_state},
+    FractalType, State,
+};
+
+/// Name of the setting holding the monitors of the wallpaper.
+const MONITORS_SETTING: &str = "wallpaper-monitors.json";
+/// Height of the preview of the monitors, in points.
+const PREVIEW_HEIGHT: f32 = 80.0;
+
+/// Screen the wallpaper spans, in pixels of the desktop.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Monitor {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Views gathered in one image.
+#[derive(Clone, Copy, PartialEq)]
+pub enum CompositeLayout {
+    /// The Mandelbrot set next to the Julia set of the split view.
+    SideBySide,
+    /// The view continued over the monitors, for a wallpaper spanning them.
+    Wallpaper,
+}
+
+/// `[left, top, right, bottom]` of the box around `monitors`.
+fn bounds(monitors: &[Monitor]) -> [i32; 4] {
+    let left = monitors.iter().map(|m| m.x).min().unwrap_or(0);
+    let top = monitors.iter().map(|m| m.y).min().unwrap_or(0);
+    let right = monitors.iter().map(|m| m.x + m.width as i32).max();
+    let bottom = monitors.iter().map(|m| m.y + m.height as i32).max();
+    [left, top, right.unwrap_or(1), bottom.unwrap_or(1)]
+}
+
+/// Julia set of `c` next to the Mandelbrot set `state`, as the split view shows it.
+pub fn julia_view(state: &State, c: [f64; 2]) -> State {
+    let mut julia = *state;
+    julia.fractal_type = FractalType::Julia;
+    julia.c_julia.x = c[0] as f32;
+    julia.c_julia.y = c[1] as f32;
+    julia.center_position = Position { x: 0.0, y: 0.0 };
+    julia.zoom = State::new().zoom;
+    julia.skew = skew::IDENTITY;
+    julia
+}
+
+/// The Mandelbrot set `state` and the Julia set of `c`, side by side in an image `width`
+/// pixels wide, as they are in the split view of a canvas of `viewport_px` pixels. Returns
+/// the size of the image and its panels.
+pub fn side_by_side(
+    state: &State,
+    c: [f64; 2],
+    viewport_px: Vec2,
+    width: usize,
+) -> ([usize; 2], Vec<Panel>) {
+    let half = (width / 2).max(1);
+    let half_viewport = vec2(viewport_px.x / 2.0, viewport_px.y);
+    let height = export_height(half_viewport, half);
+    let size = [half as f32, height as f32];
+    let whole = [0.0, 0.0, size[0], size[1]];
+    let panels = [*state, julia_view(state, c)]
+        .iter()
+        .enumerate()
+        .map(|(i, state)| Panel {
+            origin: [i * half, 0],
+            size: [half, height],
+            state: tile_state(state, half_viewport, size, whole),
+        })
+        .collect();
+    ([2 * half, height], panels)
+}
+
+/// The view `state` of a canvas of `viewport_px` pixels continued over `monitors`, the
+/// width of the canvas spanning all of them. The space between the monitors is left
+/// transparent. Returns the size of the image and its panels.
+pub fn wallpaper(
+    state: &State,
+    viewport_px: Vec2,
+    monitors: &[Monitor],
+) -> ([usize; 2], Vec<Panel>) {
+    let [left, top, right, bottom] = bounds(monitors);
+    let size = [(right - left) as f32, (bottom - top) as f32];
+    let panels = monitors
+        .iter()
+        .map(|monitor| {
+            let origin = [(monitor.x - left) as usize, (monitor.y - top) as usize];
+            let rect = [
+                origin[0] as f32,
+                origin[1] as f32,
+                (origin[0] + monitor.width as usize) as f32,
+                (origin[1] + monitor.height as usize) as f32,
+            ];
+            Panel {
+                origin,
+                size: [monitor.width as usize, monitor.height as usize],
+                state: tile_state(state, viewport_px, size, rect),
+            }
+        })
+        .collect();
+    ([size[0] as usize, size[1] as usize], panels)
+}
+
+/// Settings of the exports gathering several views in one image.
+pub struct CompositeExport {
+    pub open: bool,
+    pub layout: CompositeLayout,
+    pub monitors: Vec<Monitor>,
+}
+
+impl CompositeExport {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            layout: CompositeLayout::SideBySide,
+            monitors: vec![Monitor {
+                x: 0,
+                y: 0,
+                width: 1920,
+                height: 1080,
+            }],
+        }
+    }
+
+    /// Monitors configured in the previous runs.
+    pub fn load() -> Self {
+        let mut export = Self::new();
+        if let Some(monitors) = settings::load(MONITORS_SETTING)
+            .and_then(|content| serde_json::from_str::<Vec<Monitor>>(&content).ok())
+            .filter(|monitors| !monitors.is_empty())
+        {
+            export.monitors = monitors;
+        }
+        export
+    }
+
+    fn save(&self) {
+        let saved = serde_json::to_string(&self.monitors)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| settings::save(MONITORS_SETTING, &content));
+        if let Err(e) = saved {
+            error!("Cannot save the monitors: {:?}", e);
+        }
+    }
+
+    /// Outline of the monitors, to check their arrangement.
+    fn preview(&self, ui: &mut Ui) {
+        let [left, top, right, bottom] = bounds(&self.monitors).map(|side| side as f32);
+        let scale = PREVIEW_HEIGHT / (bottom - top).max(1.0);
+        let size = vec2((right - left) * scale, PREVIEW_HEIGHT).min(ui.available_size());
+        let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+        let stroke = Stroke::new(1.0, ui.visuals().text_color());
+        for (i, monitor) in self.monitors.iter().enumerate() {
+            let min = rect.min + vec2(monitor.x as f32 - left, monitor.y as f32 - top) * scale;
+            let screen = Rect::from_min_size(
+                min,
+                vec2(monitor.width as f32, monitor.height as f32) * scale,
+            );
+            ui.painter()
+                .rect(screen, 2.0, ui.visuals().faint_bg_color, stroke);
+            ui.painter().text(
+                screen.center(),
+                egui::Align2::CENTER_CENTER,
+                i + 1,
+                egui::FontId::proportional(12.0),
+                ui.visuals().text_color(),
+            );
+        }
+    }
+
+    fn monitors_ui(&mut self, ui: &mut Ui) {
+        let mut changed = false;
+        let mut removed = None;
+        Grid::new("Monitors").striped(true).show(ui, |ui| {
+            ui.label("");
+            ui.strong("x");
+            ui.strong("y");
+            ui.strong(tr("monitor-width"));
+            ui.strong(tr("monitor-height"));
+            ui.end_row();
+            let count = self.monitors.len();
+            for (i, monitor) in self.monitors.iter_mut().enumerate() {
+                ui.label(format!("{}", i + 1));
+                changed |= ui.add(DragValue::new(&mut monitor.x)).changed();
+                changed |= ui.add(DragValue::new(&mut monitor.y)).changed();
+                for side in [&mut monitor.width, &mut monitor.height] {
+                    changed |= ui.add(DragValue::new(side).range(1..=16384)).changed();
+                }
+                if ui
+                    .add_enabled(count > 1, Button::new(tr("remove")))
+                    .clicked()
+                {
+                    removed = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(i) = removed {
+            self.monitors.remove(i);
+            changed = true;
+        }
+        if ui.button(tr("add-monitor")).clicked() {
+            // On the right of the last one, like a usual arrangement
+            let last = self.monitors.last().copied().unwrap_or(Monitor {
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 1080,
+            });
+            self.monitors.push(Monitor {
+                x: last.x + last.width as i32,
+                y: last.y,
+                width: 1920,
+                height: last.height,
+            });
+            changed = true;
+        }
+        if changed {
+            self.save();
+        }
+        self.preview(ui);
+    }
+
+    /// Returns true when the export is asked for. `split_view` tells whether the current
+    /// view can be shown next to its Julia set, `width` is the width of the exports.
+    pub fn show(&mut self, ctx: &egui::Context, split_view: bool, width: usize) -> bool {
+        let mut open = self.open;
+        let mut export = false;
+        egui::Window::new(tr("composite-export"))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add_enabled_ui(split_view, |ui| {
+                        ui.radio_value(
+                            &mut self.layout,
+                            CompositeLayout::SideBySide,
+                            tr("side-by-side"),
+                        )
+                        .on_hover_text(tr("side-by-side-hint"))
+                        .on_disabled_hover_text(tr("side-by-side-unavailable"));
+                    });
+                    ui.radio_value(
+                        &mut self.layout,
+                        CompositeLayout::Wallpaper,
+                        tr("wallpaper"),
+                    )
+                    .on_hover_text(tr("wallpaper-hint"));
+                });
+                ui.separator();
+                let ready = match self.layout {
+                    CompositeLayout::SideBySide => {
+                        ui.label(tr_args("composite-width", &[("width", &width)]));
+                        split_view
+                    }
+                    CompositeLayout::Wallpaper => {
+                        self.monitors_ui(ui);
+                        !self.monitors.is_empty()
+                    }
+                };
+                ui.separator();
+                if ui
+                    .add_enabled(ready, Button::new(tr("export-png")))
+                    .clicked()
+                {
+                    export = true;
+                }
+            });
+        self.open = open && !export;
+        export
+    }
+}
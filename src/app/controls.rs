@@ -0,0 +1,172 @@
+use eframe::egui::{ComboBox, PointerButton, Slider, Ui};
+use log::error;
+
+use super::{i18n::tr, settings};
+
+/// Names of the settings.
+const INVERT_SCROLL_SETTING: &str = "invert-scroll";
+const PAN_BUTTON_SETTING: &str = "pan-button";
+const RECENTER_BUTTON_SETTING: &str = "recenter-button";
+const CLICK_TO_RECENTER_SETTING: &str = "click-to-recenter";
+const SCROLL_STEP_SETTING: &str = "scroll-zoom-step";
+const CLICK_STEP_SETTING: &str = "click-zoom-step";
+
+/// Buttons that can drag the view, and the ones that can recenter it.
+const PAN_BUTTONS: [PointerButton; 3] = [
+    PointerButton::Primary,
+    PointerButton::Secondary,
+    PointerButton::Middle,
+];
+const RECENTER_BUTTONS: [PointerButton; 2] = [PointerButton::Primary, PointerButton::Secondary];
+
+fn button_name(button: PointerButton) -> &'static str {
+    match button {
+        PointerButton::Primary => "primary",
+        PointerButton::Secondary => "secondary",
+        PointerButton::Middle => "middle",
+        PointerButton::Extra1 => "extra1",
+        PointerButton::Extra2 => "extra2",
+    }
+}
+
+fn button_label(button: PointerButton) -> String {
+    tr(match button {
+        PointerButton::Primary => "primary-button",
+        PointerButton::Secondary => "secondary-button",
+        _ => "middle-button",
+    })
+}
+
+fn load_button(name: &str, buttons: &[PointerButton]) -> Option<PointerButton> {
+    let value = settings::load(name)?;
+    buttons
+        .iter()
+        .copied()
+        .find(|button| button_name(*button) == value)
+}
+
+fn load_step(name: &str, default: f32) -> f32 {
+    settings::load(name)
+        .and_then(|value| value.parse().ok())
+        .filter(|step: &f32| (1.01..=2.0).contains(step))
+        .unwrap_or(default)
+}
+
+fn save(name: &str, value: &str) {
+    if let Err(e) = settings::save(name, value) {
+        error!("Cannot save the {} setting: {:?}", name, e);
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// How the mouse moves the view.
+pub struct Controls {
+    /// Scrolling up zooms out rather than in.
+    invert_scroll: bool,
+    /// Button dragging the view.
+    pub pan_button: PointerButton,
+    /// Button whose click moves the clicked point to the center.
+    pub recenter_button: PointerButton,
+    /// Without it, clicks do not recenter, they are easy to do by accident while dragging.
+    pub click_to_recenter: bool,
+    /// Zoom factor of a step of the mouse wheel.
+    scroll_step: f32,
+    /// Zoom factor of a double click.
+    pub click_step: f32,
+}
+
+impl Controls {
+    /// Settings saved from the previous runs.
+    pub fn load() -> Self {
+        Self {
+            invert_scroll: settings::load(INVERT_SCROLL_SETTING).is_some_and(|v| v == "on"),
+            pan_button: load_button(PAN_BUTTON_SETTING, &PAN_BUTTONS)
+                .unwrap_or(PointerButton::Primary),
+            recenter_button: load_button(RECENTER_BUTTON_SETTING, &RECENTER_BUTTONS)
+                .unwrap_or(PointerButton::Primary),
+            click_to_recenter: settings::load(CLICK_TO_RECENTER_SETTING).is_none_or(|v| v == "on"),
+            scroll_step: load_step(SCROLL_STEP_SETTING, 1.1),
+            click_step: load_step(CLICK_STEP_SETTING, 1.2),
+        }
+    }
+
+    /// Zoom factor of a scroll of `delta_y` points, 1 without scroll.
+    pub fn scroll_zoom(&self, delta_y: f32) -> f32 {
+        let delta_y = if self.invert_scroll {
+            -delta_y
+        } else {
+            delta_y
+        };
+        if delta_y > 0.0 {
+            self.scroll_step
+        } else if delta_y < 0.0 {
+            1.0 / self.scroll_step
+        } else {
+            1.0
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        if ui
+            .checkbox(&mut self.invert_scroll, tr("invert-scroll"))
+            .changed()
+        {
+            save(INVERT_SCROLL_SETTING, on_off(self.invert_scroll));
+        }
+        for (label, setting, button, buttons) in [
+            (
+                "pan-button",
+                PAN_BUTTON_SETTING,
+                &mut self.pan_button,
+                &PAN_BUTTONS[..],
+            ),
+            (
+                "recenter-button",
+                RECENTER_BUTTON_SETTING,
+                &mut self.recenter_button,
+                &RECENTER_BUTTONS[..],
+            ),
+        ] {
+            ComboBox::from_label(tr(label))
+                .selected_text(button_label(*button))
+                .show_ui(ui, |ui| {
+                    for choice in buttons {
+                        if ui
+                            .selectable_value(button, *choice, button_label(*choice))
+                            .changed()
+                        {
+                            save(setting, button_name(*choice));
+                        }
+                    }
+                });
+        }
+        if ui
+            .checkbox(&mut self.click_to_recenter, tr("click-to-recenter"))
+            .on_hover_text(tr("click-to-recenter-hint"))
+            .changed()
+        {
+            save(CLICK_TO_RECENTER_SETTING, on_off(self.click_to_recenter));
+        }
+        for (label, setting, step) in [
+            (
+                "scroll-zoom-step",
+                SCROLL_STEP_SETTING,
+                &mut self.scroll_step,
+            ),
+            ("click-zoom-step", CLICK_STEP_SETTING, &mut self.click_step),
+        ] {
+            let response = ui.add(Slider::new(step, 1.01..=2.0).text(tr(label)).suffix("×"));
+            // Saved once the slider is released, not on every frame of the drag
+            if response.drag_stopped() || (response.changed() && !response.dragged()) {
+                save(setting, &step.to_string());
+            }
+        }
+    }
+}
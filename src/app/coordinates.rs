@@ -0,0 +1,84 @@
+use eframe::egui::{vec2, Color32, DragValue, Painter, Rect, Stroke, Ui};
+
+use super::{hud, i18n::tr, Position, State};
+
+/// Half length of the arms of the crosshair, in points.
+const CROSSHAIR_SIZE: f32 = 10.0;
+
+/// Center and magnification of the view, to type the locations published elsewhere and to
+/// copy them. `viewport_height_px` is the height of the view, for the magnification.
+pub fn ui(ui: &mut Ui, state: &mut State, viewport_height_px: f32) {
+    let decimals = hud::decimals(state.zoom);
+    let [mut re, mut im] = state.point_at([0.0, 0.0]);
+    ui.horizontal(|ui| {
+        let mut changed = false;
+        for (value, prefix) in [(&mut re, "re "), (&mut im, "im ")] {
+            changed |= ui
+                .add(
+                    DragValue::new(value)
+                        .speed(state.pixel_size())
+                        .min_decimals(decimals)
+                        .max_decimals(17)
+                        .prefix(prefix),
+                )
+                .changed();
+        }
+        ui.label(tr("center-coordinates"));
+        if changed {
+            state.center_position = Position { x: -re, y: -im };
+        }
+    });
+
+    let mut magnification = state.magnification(viewport_height_px);
+    let speed = magnification * 0.01;
+    ui.horizontal(|ui| {
+        let response = ui.add(
+            DragValue::new(&mut magnification)
+                .speed(speed)
+                .range(1e-3..=f64::MAX)
+                .custom_formatter(|value, _| format!("{value:.4e}"))
+                .custom_parser(|text| text.trim().trim_end_matches('×').parse().ok())
+                .suffix("×"),
+        );
+        ui.label(tr("magnification-value"));
+        if response.changed() {
+            state.zoom = (magnification * viewport_height_px as f64 / 4.0) as f32;
+        }
+    });
+
+    if ui
+        .button(tr("copy-coordinates"))
+        .on_hover_text(tr("copy-coordinates-hint"))
+        .clicked()
+    {
+        // Full precision, the decimals shown are only the ones of the pixels
+        ui.ctx().copy_text(format!(
+            "re = {re}\nim = {im}\nmagnification = {magnification:e}"
+        ));
+    }
+}
+
+/// Mark the center of `rect`, the point of the coordinates.
+pub fn paint_crosshair(painter: &Painter, rect: Rect) {
+    let center = rect.center();
+    // A light line over a dark one, to show over any color
+    for stroke in [
+        Stroke::new(3.0, Color32::from_black_alpha(160)),
+        Stroke::new(1.0, Color32::WHITE),
+    ] {
+        painter.line_segment(
+            [
+                center - vec2(CROSSHAIR_SIZE, 0.0),
+                center + vec2(CROSSHAIR_SIZE, 0.0),
+            ],
+            stroke,
+        );
+        painter.line_segment(
+            [
+                center - vec2(0.0, CROSSHAIR_SIZE),
+                center + vec2(0.0, CROSSHAIR_SIZE),
+            ],
+            stroke,
+        );
+    }
+}
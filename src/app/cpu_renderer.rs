@@ -0,0 +1,475 @@
+//! Renderer of the views on the CPU, in double precision.
+//!
+//! It draws the built-in fractals and the plugins with a CPU kernel, with the iteration
+//! count coloring only. The other colorings, the coloring graph and the other custom
+//! fractals are drawn by the fragment shader alone, [unsupported] tells why a view is not
+//! drawn.
+
+use std::{sync::mpsc, thread, time::Duration};
+
+use anyhow::{anyhow, Result};
+use eframe::egui::{
+    pos2, Align2, Checkbox, Color32, ColorImage, FontId, Rect, TextureHandle, TextureOptions, Ui,
+};
+use log::error;
+use rayon::prelude::*;
+
+use super::{
+    i18n::{tr, tr_args},
+    orbit::orbit,
+    plugins::{Kernel, KernelInstance},
+    settings,
+    state::{BoundaryStyle, ColoringAlgorithm, FractalType, IterationColoring, State},
+    Palette,
+};
+
+/// Name of the setting choosing the CPU renderer over the GPU.
+const SETTING: &str = "cpu-renderer";
+/// Delay between two checks of the render in progress.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Tolerance of the root finding of the Newton and Nova fractals, as in the shader.
+const TOLERANCE: f64 = 1e-6;
+
+/// Why `state` cannot be drawn on the CPU, which only knows the built-in fractals and the
+/// `kernel` of its plugin, with the smooth iteration count coloring and no other effect
+/// of the shader. `None` when it can.
+pub fn unsupported(state: &State, kernel: Option<&Kernel>) -> Option<String> {
+    if state.fractal_type == FractalType::Custom && (state.plugin.is_none() || kernel.is_none()) {
+        let fractal = state
+            .plugin
+            .map_or_else(|| state.fractal_type.label(), |id| id.to_string());
+        Some(tr_args("cpu-unsupported-fractal", &[("fractal", &fractal)]))
+    } else if state.custom_coloring {
+        Some(tr("cpu-unsupported-graph"))
+    } else if state.coloring_algorithm != ColoringAlgorithm::Iterations {
+        Some(tr_args(
+            "cpu-unsupported-coloring",
+            &[("coloring", &state.coloring_algorithm.label())],
+        ))
+    } else if state.iteration_coloring != IterationColoring::Smooth {
+        Some(tr_args(
+            "cpu-unsupported-coloring",
+            &[("coloring", &state.iteration_coloring.label())],
+        ))
+    } else if state.boundary_style != BoundaryStyle::Off {
+        Some(tr_args(
+            "cpu-unsupported-boundary",
+            &[("style", &state.boundary_style.label())],
+        ))
+    } else if state.color_balance != [[0.0; 2]; 3] {
+        Some(tr("cpu-unsupported-color-balance"))
+    } else if state.color_cycling {
+        Some(tr("cpu-unsupported-color-cycling"))
+    } else if state.value_formula {
+        Some(tr("cpu-unsupported-value-formula"))
+    } else {
+        None
+    }
+}
+
+/// Color of the value of a pixel, as the fragment shader does with the default coloring.
+fn shade(state: &State, palette: Option<&Palette>, value: f32) -> [u8; 4] {
+    let color = match palette {
+        Some(palette) => palette.sample(state.brightness + state.contrast * value),
+        None => [state.r, state.g, state.b].map(|c| state.brightness + state.contrast * value * c),
+    };
+    let [r, g, b] =
+        color.map(|c| (c.max(0.0).powf(1.0 / state.gamma).min(1.0) * 255.0).round() as u8);
+    [r, g, b, 255]
+}
+
+fn multiply(a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+    [a[0] * b[0] - a[1] * b[1], a[0] * b[1] + a[1] * b[0]]
+}
+
+fn divide(a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+    let norm = b[0] * b[0] + b[1] * b[1];
+    [
+        (a[0] * b[0] + a[1] * b[1]) / norm,
+        (a[1] * b[0] - a[0] * b[1]) / norm,
+    ]
+}
+
+fn distance2(a: [f64; 2], b: [f64; 2]) -> f64 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)
+}
+
+/// Newton's method on the polynomial of the roots of `state` from `z`: the logarithm of the
+/// iterations to reach a root plus the index of the root, as the shader does.
+fn newton(state: &State, mut z: [f64; 2]) -> Option<f64> {
+    let roots = state.newton_roots.map(|[x, y]| [x as f64, y as f64]);
+    for n in 1..=state.iterations() {
+        let [a, b, d] = roots.map(|root| [z[0] - root[0], z[1] - root[1]]);
+        let derivative = multiply(a, b);
+        let derivative = [0, 1].map(|i| derivative[i] + multiply(b, d)[i] + multiply(a, d)[i]);
+        if distance2(derivative, [0.0, 0.0]) < 1e-20 {
+            return None;
+        }
+        let step = divide(multiply(multiply(a, b), d), derivative);
+        z = [z[0] - step[0], z[1] - step[1]];
+        if let Some(root) = roots
+            .iter()
+            .rposition(|&root| distance2(z, root) < TOLERANCE)
+        {
+            return Some((n as f64).ln() + root as f64);
+        }
+    }
+    None
+}
+
+/// Opposite of the Lyapunov exponent of the logistic map with the rates `[x, y]`, `None`
+/// where it is chaotic, as the shader does.
+fn lyapunov(state: &State, rates: [f64; 2]) -> Option<f64> {
+    let iterations = state.iterations() as usize;
+    let warmup = 50.min(iterations / 4);
+    let sequence = state.lyapunov_sequence;
+    let (mut x, mut sum) = (0.5, 0.0);
+    for n in 0..iterations {
+        let r = rates[((sequence.bits() >> (n % sequence.len())) & 1) as usize];
+        if n >= warmup {
+            sum += (r * (1.0 - 2.0 * x)).abs().max(1e-30).ln();
+        }
+        x = r * x * (1.0 - x);
+        if x.abs() > 1e10 {
+            return None;
+        }
+    }
+    let exponent = sum / (iterations - warmup).max(1) as f64;
+    (exponent < 0.0).then_some(-exponent)
+}
+
+/// Newton's method on z³ - 1 relaxed by the Nova relaxation of `state`, plus `c`, from
+/// z = 1: the logarithm of the iterations to converge, as the shader does.
+fn nova(state: &State, c: [f64; 2]) -> Option<f64> {
+    let relaxation = state.nova_relaxation as f64;
+    let mut z = [1.0, 0.0];
+    for n in 1..=state.iterations() {
+        let z2 = multiply(z, z);
+        if distance2(z2, [0.0, 0.0]) < 1e-20 {
+            return None;
+        }
+        let cube = multiply(z2, z);
+        let newton = divide([cube[0] - 1.0, cube[1]], [3.0 * z2[0], 3.0 * z2[1]]);
+        let step = [0, 1].map(|i| relaxation * newton[i] - c[i]);
+        z = [z[0] - step[0], z[1] - step[1]];
+        if distance2(step, [0.0, 0.0]) < TOLERANCE {
+            return Some((n as f64).ln());
+        }
+    }
+    None
+}
+
+/// Iterations of the `kernel` of a plugin from z = 0 to escape, as the shader iterates the
+/// custom fractals: the logarithm of their number.
+fn custom(state: &State, kernel: &mut KernelInstance, c: [f64; 2]) -> Result<Option<f64>> {
+    let cutoff = if state.high_quality { 16.0 } else { 4.0 };
+    let mut z = [0.0, 0.0];
+    for n in 0..state.iterations() {
+        if z[0] * z[0] + z[1] * z[1] > cutoff {
+            return Ok(Some(((n + 1) as f64).ln()));
+        }
+        z = kernel.iterate(z, c)?;
+    }
+    Ok(None)
+}
+
+/// Iterations of the escape time fractals, as the shader counts them: up to a modulus of 4
+/// in high quality and 2 otherwise, smoothed in high quality but for the Julia sets. The
+/// inside of the set keeps the value the shader gives it.
+fn escape_time(state: &State, point: [f64; 2]) -> Option<f64> {
+    let cutoff = if state.high_quality { 16.0 } else { 4.0 };
+    let mut orbit = orbit(state, point)?;
+    let mut z = orbit.next()?;
+    let mut count = None;
+    for n in 0..state.iterations() {
+        if z[0] * z[0] + z[1] * z[1] > cutoff {
+            count = Some(n);
+            break;
+        }
+        z = orbit.next()?;
+    }
+    if state.fractal_type == FractalType::Julia {
+        return Some((count.unwrap_or(1_000_000) as f64 + 1.0).ln());
+    }
+    let mut value = count.unwrap_or(0) as f64 + 1.0;
+    if state.high_quality {
+        let modulus = z[0].hypot(z[1]);
+        value -= (modulus / 4f64.ln()).ln().ln() / (state.exponent as f64).ln();
+    }
+    Some(value.ln())
+}
+
+/// Value of the point before the palette, `None` inside the set.
+fn value(
+    state: &State,
+    kernel: Option<&mut KernelInstance>,
+    point: [f64; 2],
+) -> Result<Option<f64>> {
+    Ok(match (state.fractal_type, kernel) {
+        (FractalType::Newton, _) => newton(state, point),
+        (FractalType::Lyapunov, _) => lyapunov(state, point),
+        (FractalType::Nova, _) => nova(state, point),
+        (FractalType::Custom, Some(kernel)) => custom(state, kernel, point)?,
+        _ => escape_time(state, point),
+    })
+}
+
+/// Shade the pixels of `row`, the row `y` of the image.
+fn render_row(
+    state: &State,
+    palette: Option<&Palette>,
+    mut kernel: Option<KernelInstance>,
+    [width, height]: [usize; 2],
+    y: usize,
+    row: &mut [u8],
+) -> Result<()> {
+    for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+        let offset = [
+            x as f32 + 0.5 - width as f32 / 2.0,
+            y as f32 + 0.5 - height as f32 / 2.0,
+        ];
+        // The interior gets the value 0, like in the Mandelbrot set of the shader
+        let value = value(state, kernel.as_mut(), state.point_at(offset))?;
+        pixel.copy_from_slice(&shade(
+            state,
+            palette,
+            value.map_or(0.0, |value| value as f32),
+        ));
+    }
+    Ok(())
+}
+
+/// RGBA pixels of `state` on the CPU, top row first, on all the cores, in double precision,
+/// with the `kernel` of its plugin. Fails for the views of [unsupported].
+pub fn render(
+    state: State,
+    palette: Option<&Palette>,
+    kernel: Option<&Kernel>,
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>> {
+    if let Some(reason) = unsupported(&state, kernel) {
+        return Err(anyhow!(reason));
+    }
+    let kernel = kernel.filter(|_| state.fractal_type == FractalType::Custom);
+    let size = [width as usize, height as usize];
+    let mut pixels = vec![0; size[0] * size[1] * 4];
+    pixels
+        .par_chunks_mut(size[0] * 4)
+        .enumerate()
+        .try_for_each(|(y, row)| {
+            // An instance per row, the kernels keep their state between the calls
+            let kernel = kernel
+                .map(|kernel| kernel.instance(&state.plugin_parameters))
+                .transpose()?;
+            render_row(&state, palette, kernel, size, y, row)
+        })?;
+    Ok(pixels)
+}
+
+/// What the image shows: the view without its time, the palette and the size in pixels.
+type ImageKey = (State, Option<Palette>, [usize; 2]);
+
+/// Draws the view on the CPU into a texture, when the GPU renderer is unavailable or when
+/// chosen in the settings. The render runs on a thread, the last image stays on screen
+/// meanwhile.
+pub struct CpuRenderer {
+    enabled: bool,
+    /// There is no GPU renderer, the CPU draws whatever the setting.
+    pub forced: bool,
+    texture: Option<TextureHandle>,
+    shown: Option<ImageKey>,
+    pending: Option<(ImageKey, mpsc::Receiver<Result<Vec<u8>>>)>,
+    /// Kernel of the plugin of the view, if it has one.
+    kernel: Option<Kernel>,
+    /// Why the last image could not be rendered.
+    error: Option<String>,
+}
+
+impl CpuRenderer {
+    pub fn load() -> Self {
+        Self {
+            enabled: settings::load(SETTING).is_some_and(|value| value == "on"),
+            forced: false,
+            texture: None,
+            shown: None,
+            pending: None,
+            kernel: None,
+            error: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.enabled || self.forced
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        let response = ui.add_enabled(
+            !self.forced,
+            Checkbox::new(&mut self.enabled, tr("cpu-renderer")),
+        );
+        if response.on_hover_text(tr("cpu-renderer-hint")).changed() {
+            let value = if self.enabled { "on" } else { "off" };
+            if let Err(e) = settings::save(SETTING, value) {
+                error!("Cannot save the CPU renderer setting: {:?}", e);
+            }
+        }
+    }
+
+    /// Show `state` with `palette` in `rect`, rendering it again when it changed, with the
+    /// `kernel` of its plugin.
+    pub fn paint(
+        &mut self,
+        ui: &Ui,
+        rect: Rect,
+        state: &State,
+        palette: Option<Palette>,
+        kernel: Option<Kernel>,
+    ) {
+        self.kernel = kernel;
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        let size = [
+            (rect.width() * pixels_per_point).round().max(1.0) as usize,
+            (rect.height() * pixels_per_point).round().max(1.0) as usize,
+        ];
+        let key = (state.explored(), palette, size);
+
+        if let Some((rendered, receiver)) = &self.pending {
+            match receiver.try_recv() {
+                Ok(result) => {
+                    self.show(ui, rendered.clone(), result);
+                    self.pending = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => ui.ctx().request_repaint_after(POLL_INTERVAL),
+                Err(mpsc::TryRecvError::Disconnected) => self.pending = None,
+            }
+        }
+        if self.pending.is_none() && self.shown.as_ref() != Some(&key) {
+            self.start(ui, key);
+        }
+
+        if let Some(texture) = &self.texture {
+            let uv = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+            ui.painter().image(texture.id(), rect, uv, Color32::WHITE);
+        }
+        if let Some(error) = &self.error {
+            ui.painter().text(
+                rect.center(),
+                Align2::CENTER_CENTER,
+                error,
+                FontId::proportional(16.0),
+                ui.visuals().strong_text_color(),
+            );
+        }
+    }
+
+    /// Render `key` on a thread, or right away on the web.
+    fn start(&mut self, ui: &Ui, key: ImageKey) {
+        let (state, palette, [width, height]) = key.clone();
+        let kernel = self.kernel.clone();
+        let render = move || {
+            render(
+                state,
+                palette.as_ref(),
+                kernel.as_ref(),
+                width as u32,
+                height as u32,
+            )
+        };
+        if cfg!(target_arch = "wasm32") {
+            self.show(ui, key, render());
+            return;
+        }
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || sender.send(render()));
+        self.pending = Some((key, receiver));
+        ui.ctx().request_repaint_after(POLL_INTERVAL);
+    }
+
+    fn show(&mut self, ui: &Ui, key: ImageKey, pixels: Result<Vec<u8>>) {
+        match pixels {
+            Ok(pixels) => {
+                let image = ColorImage::from_rgba_unmultiplied(key.2, &pixels);
+                match &mut self.texture {
+                    Some(texture) => texture.set(image, TextureOptions::LINEAR),
+                    None => {
+                        self.texture = Some(ui.ctx().load_texture(
+                            "cpu-view",
+                            image,
+                            TextureOptions::LINEAR,
+                        ))
+                    }
+                }
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+        self.shown = Some(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_time_fractals() {
+        let mut state = State::new();
+        state.fractal_type = FractalType::Mandelbrot;
+        let pixels = render(state, None, None, 8, 6).unwrap();
+        assert_eq!(pixels.len(), 8 * 6 * 4);
+        // The center of the default view is in the set
+        assert_eq!(&pixels[(3 * 8 + 4) * 4..][..4], &shade(&state, None, 0.0));
+    }
+
+    #[test]
+    fn newton_lyapunov_and_nova() {
+        let mut state = State::new();
+        state.fractal_type = FractalType::Newton;
+        // On a root at once, the basin of the last root
+        let root = state.newton_roots[2].map(f64::from);
+        assert_eq!(value(&state, None, root).unwrap(), Some(2.0));
+        state.fractal_type = FractalType::Nova;
+        // z = 1 is the fixed point for c = 0
+        assert_eq!(value(&state, None, [0.0, 0.0]).unwrap(), Some(0.0));
+        state.fractal_type = FractalType::Lyapunov;
+        // Stable with the rates 2, chaotic with the rates 4
+        assert!(value(&state, None, [2.0, 2.0])
+            .unwrap()
+            .is_some_and(|value| value > 0.0));
+        assert_eq!(value(&state, None, [4.0, 4.0]).unwrap(), None);
+        assert!(render(state, None, None, 4, 4).is_ok());
+    }
+
+    #[test]
+    fn unsupported_views() {
+        let mut state = State::new();
+        state.fractal_type = FractalType::Custom;
+        assert!(unsupported(&state, None).is_some());
+        assert!(render(state, None, None, 4, 4).is_err());
+
+        state.fractal_type = FractalType::Julia;
+        assert_eq!(unsupported(&state, None), None);
+        state.coloring_algorithm = ColoringAlgorithm::OrbitTrap;
+        assert!(render(state, None, None, 4, 4).is_err());
+        state.coloring_algorithm = ColoringAlgorithm::Iterations;
+        state.custom_coloring = true;
+        assert!(render(state, None, None, 4, 4).is_err());
+        state.custom_coloring = false;
+
+        // The effects of the shader on top of the coloring
+        let effects: [fn(&mut State); 5] = [
+            |state| state.iteration_coloring = IterationColoring::Bands,
+            |state| state.boundary_style = BoundaryStyle::Edges,
+            |state| state.color_balance[1] = [0.5, 0.0],
+            |state| state.color_cycling = true,
+            |state| state.value_formula = true,
+        ];
+        for effect in effects {
+            let mut state = state;
+            effect(&mut state);
+            assert!(unsupported(&state, None).is_some());
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use eframe::egui::Ui;
+use log::{error, info};
+
+use super::{external, i18n::tr, settings};
+
+/// Name of the file with the function of the custom fractal, in the configuration directory.
+const FILE_NAME: &str = "custom.glsl";
+/// Delay between two checks of the file for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Written when the file does not exist yet, the function of the Mandelbrot set.
+const TEMPLATE: &str = "\
+// Next value of the orbit of c, starting from z = 0. Saving reloads it.
+// Complex numbers are vec2(real, imaginary).
+vec2 iterate(vec2 z, vec2 c) {
+    return vec2(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + c;
+}
+";
+
+/// Function of [super::FractalType::Custom], from a GLSL file the user edits. The file is
+/// reloaded when it changes.
+pub struct CustomFractal {
+    path: Option<PathBuf>,
+    /// Modification time of the file last loaded.
+    loaded: Option<SystemTime>,
+    last_check: f64,
+}
+
+impl CustomFractal {
+    pub fn new() -> Self {
+        Self {
+            path: settings::path(FILE_NAME),
+            loaded: None,
+            last_check: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Source of the `iterate` function when the file changed since the last call, checked
+    /// every [POLL_INTERVAL] at `time`. The file is created from [TEMPLATE] if missing.
+    pub fn poll(&mut self, time: f64) -> Option<String> {
+        if time - self.last_check < POLL_INTERVAL.as_secs_f64() {
+            return None;
+        }
+        self.last_check = time;
+        let path = self.path.as_ref()?;
+        if !path.exists() {
+            let created = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|()| fs::write(path, TEMPLATE));
+            if let Err(e) = created {
+                error!("Cannot create {}: {:?}", path.display(), e);
+                return None;
+            }
+        }
+
+        let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+        if modified.is_some() && modified == self.loaded {
+            return None;
+        }
+        match fs::read_to_string(path) {
+            Ok(source) => {
+                info!("Loaded the custom fractal from {}", path.display());
+                self.loaded = modified;
+                Some(source)
+            }
+            Err(e) => {
+                error!("Cannot read {}: {:?}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Load the file again on the next [CustomFractal::poll], even if it did not change.
+    pub fn reload(&mut self) {
+        self.loaded = None;
+        self.last_check = f64::NEG_INFINITY;
+    }
+
+    pub fn ui(&self, ui: &mut Ui) {
+        let Some(path) = &self.path else {
+            ui.label(tr("no-configuration-directory"));
+            return;
+        };
+        ui.label(path.display().to_string())
+            .on_hover_text(tr("custom-fractal-hint"));
+        if ui.button(tr("edit-custom-fractal")).clicked() {
+            if let Err(e) = external::open(path) {
+                error!("{:?}", e);
+            }
+        }
+    }
+}
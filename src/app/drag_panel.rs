@@ -2,14 +2,42 @@ use log::info;
 use std::ops::RangeInclusive;
 
 use eframe::{
-    egui::{Frame, Label, Response, Sense, Ui, Widget},
+    egui::{
+        Align2, Color32, FontId, Frame, Id, Rect, Response, Sense, Stroke, Ui, Widget, WidgetInfo,
+        WidgetType,
+    },
     epaint::Vec2,
 };
 
+use super::{accessibility::arrow_keys, i18n::tr, parameter::step_factor};
+
+/// Cells of the grid per side, the snapping step is the range divided by it.
+const GRID_CELLS: f32 = 10.0;
+/// Radius of the marker of the values, in points.
+const MARKER_RADIUS: f32 = 4.0;
+
+/// What a pad remembers between the frames.
+#[derive(Clone, Copy, Default)]
+struct PadMemory {
+    /// Values at the center of the pad, it follows them when they leave it.
+    center: Option<Vec2>,
+    /// Values when the drag started.
+    start: Vec2,
+    /// Drag since then, in fractions of the pad.
+    dragged: Vec2,
+    /// Round the values to the grid.
+    snap: bool,
+}
+
+/// Square pad to change two values together by dragging over it, x to the right and y
+/// down. It shows the values with a marker, Ctrl slows the drag down and Shift locks it to
+/// its main axis. A double click resets the values, the context menu turns on the snapping
+/// to the grid.
 pub struct DragPanel<'a> {
     x: &'a mut f32,
     y: &'a mut f32,
     range: Vec2,
+    default: Option<Vec2>,
 }
 
 impl<'a> DragPanel<'a> {
@@ -26,7 +54,67 @@ impl<'a> DragPanel<'a> {
                 x_range.end() - x_range.start(),
                 y_range.end() - y_range.start(),
             ),
+            default: None,
+        }
+    }
+
+    /// Values restored by a double click.
+    pub fn default_values(mut self, x: f32, y: f32) -> Self {
+        self.default = Some(Vec2::new(x, y));
+        self
+    }
+}
+
+/// `drag` along its main axis only.
+fn lock_axis(drag: Vec2) -> Vec2 {
+    if drag.x.abs() >= drag.y.abs() {
+        Vec2::new(drag.x, 0.0)
+    } else {
+        Vec2::new(0.0, drag.y)
+    }
+}
+
+impl DragPanel<'_> {
+    fn paint(&self, ui: &Ui, rect: Rect, response: &Response, memory: &PadMemory, values: Vec2) {
+        let painter = ui.painter();
+        let visuals = ui.style().interact(response);
+        let grid_stroke = Stroke::new(1.0, ui.visuals().faint_bg_color);
+        if memory.snap {
+            for i in 1..GRID_CELLS as i32 {
+                let t = i as f32 / GRID_CELLS;
+                let x = rect.left() + rect.width() * t;
+                let y = rect.top() + rect.height() * t;
+                painter.vline(x, rect.y_range(), grid_stroke);
+                painter.hline(rect.x_range(), y, grid_stroke);
+            }
         }
+        let axis_stroke = Stroke::new(1.0, visuals.bg_stroke.color);
+        painter.vline(rect.center().x, rect.y_range(), axis_stroke);
+        painter.hline(rect.x_range(), rect.center().y, axis_stroke);
+
+        let center = memory.center.unwrap_or(values);
+        let offset = (values - center) / self.range * rect.size();
+        painter.circle(
+            rect.center() + offset,
+            MARKER_RADIUS,
+            visuals.fg_stroke.color,
+            Stroke::new(1.0, Color32::BLACK),
+        );
+
+        painter.text(
+            rect.center_top() + Vec2::new(0.0, 4.0),
+            Align2::CENTER_TOP,
+            tr("fine-tuning"),
+            FontId::proportional(12.0),
+            ui.visuals().weak_text_color(),
+        );
+        painter.text(
+            rect.center_bottom() - Vec2::new(0.0, 4.0),
+            Align2::CENTER_BOTTOM,
+            format!("{:.5}, {:.5}", values.x, values.y),
+            FontId::monospace(12.0),
+            ui.visuals().text_color(),
+        );
     }
 }
 
@@ -35,23 +123,73 @@ impl Widget for DragPanel<'_> {
         let square_size = Vec2::new(ui.available_width(), ui.available_width()) * 0.5;
         Frame::canvas(ui.style())
             .show(ui, |ui| {
-                let resp = ui.add_sized(
-                    square_size,
-                    Label::new("Fine tuning")
-                        .selectable(false)
-                        .sense(Sense::drag()),
-                );
+                // Clickable to get the keyboard focus
+                let (rect, mut resp) = ui.allocate_exact_size(square_size, Sense::click_and_drag());
+                let id = Id::new("drag panel memory").with(resp.id);
+                let mut memory: PadMemory = ui.data(|d| d.get_temp(id)).unwrap_or_default();
+                let mut values = Vec2::new(*self.x, *self.y);
+
+                if resp.drag_started() {
+                    memory.start = values;
+                    memory.dragged = Vec2::ZERO;
+                }
                 if resp.dragged() {
-                    let points_delta = resp.drag_delta();
-                    let values_delta = (points_delta / resp.rect.width()) * self.range;
+                    let modifiers = ui.input(|i| i.modifiers);
+                    let factor = if modifiers.command { 0.1 } else { 1.0 };
+                    memory.dragged += resp.drag_delta() / resp.rect.width() * factor;
+                    let dragged = if modifiers.shift {
+                        lock_axis(memory.dragged)
+                    } else {
+                        memory.dragged
+                    };
+                    values = memory.start + dragged * self.range;
+                    if memory.snap {
+                        let step = self.range / GRID_CELLS;
+                        values = (values / step).round() * step;
+                    }
                     info!(
-                        "ClickPanel dragged {:?} points, change to x,y {:?}",
-                        points_delta, values_delta
+                        "ClickPanel dragged {:?} points, values {:?}",
+                        resp.drag_delta(),
+                        values
                     );
-                    *self.x += values_delta.x;
-                    *self.y += values_delta.y;
+                    resp.mark_changed();
+                }
+                if resp.double_clicked() {
+                    if let Some(default) = self.default {
+                        values = default;
+                        memory.center = Some(default);
+                        resp.mark_changed();
+                    }
                 }
+
+                // The keys move by a hundredth of the range, in the same direction as a drag
+                let arrows = arrow_keys(ui, &resp);
+                if arrows != Vec2::ZERO {
+                    let factor = step_factor(ui.input(|i| i.modifiers));
+                    values += arrows * Vec2::new(1.0, -1.0) * self.range * 0.01 * factor;
+                    resp.mark_changed();
+                }
+
+                resp.context_menu(|ui| {
+                    ui.checkbox(&mut memory.snap, tr("snap-to-grid"));
+                });
+
+                // Recentered once the values leave the pad, by a drag or from elsewhere
+                let center = memory.center.unwrap_or(values);
+                let offset = (values - center) / self.range;
+                if offset.x.abs() > 0.5 || offset.y.abs() > 0.5 || memory.center.is_none() {
+                    memory.center = Some(values);
+                }
+
+                *self.x = values.x;
+                *self.y = values.y;
+                self.paint(ui, rect, &resp, &memory, values);
+                ui.data_mut(|d| d.insert_temp(id, memory));
+                resp.widget_info(|| {
+                    WidgetInfo::labeled(WidgetType::Other, ui.is_enabled(), tr("fine-tuning"))
+                });
+                resp
             })
-            .response
+            .inner
     }
 }
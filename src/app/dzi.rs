@@ -0,0 +1,273 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs,
+    path::PathBuf,
+};
+
+use anyhow::{Context as _, Result};
+use eframe::egui::Vec2;
+use image::ExtendedColorType;
+use log::error;
+
+use super::{
+    checkpoint::Checkpoint,
+    overlay::{self, Overlay},
+    tiled_export::{export_height, tile_state, TileRenderer},
+    State,
+};
+
+/// Side of the tiles, in pixels.
+const TILE_SIZE: usize = 256;
+/// Pixels repeated on each side shared with a neighbouring tile.
+const OVERLAP: usize = 1;
+/// Tiles rendered per frame, to keep the UI responsive during the export.
+pub const TILES_PER_FRAME: usize = 8;
+
+/// Tile still to render.
+struct Tile {
+    /// Order of the tile in the export, for the checkpoints.
+    index: usize,
+    level: usize,
+    /// Top left corner in the level, in pixels.
+    origin: [usize; 2],
+    path: PathBuf,
+    state: State,
+    size: [i32; 2],
+}
+
+/// Start and end of the pixels of tile `index` along an axis of `length` pixels, with the
+/// overlap on the sides having a neighbour.
+fn tile_span(index: usize, length: usize) -> (usize, usize) {
+    let start = (index * TILE_SIZE).saturating_sub(OVERLAP);
+    let end = ((index + 1) * TILE_SIZE + OVERLAP).min(length);
+    (start, end)
+}
+
+/// Export of the view as a Deep Zoom image: a `.dzi` description, and a `_files` directory
+/// with a directory of PNG tiles per level, as read by OpenSeadragon.
+///
+/// Every level is rendered from the fractal rather than downscaled from the level above, so
+/// the whole image is never held in memory. The tiles are rendered a few per frame.
+///
+/// The tiles written are listed in a [Checkpoint]: an export to the same file of the same
+/// view, cancelled or interrupted, resumes where it stopped.
+pub struct DziExport {
+    /// The `.dzi` file.
+    pub path: PathBuf,
+    tiles: VecDeque<Tile>,
+    /// Tiles requested from the renderer, by id.
+    pending: HashMap<u64, Tile>,
+    next_id: u64,
+    total: usize,
+    /// Indices of the tiles written.
+    written: Vec<usize>,
+    checkpoint: Checkpoint,
+    /// Annotations and scale bar drawn over each level.
+    overlays: Vec<Option<Overlay>>,
+}
+
+impl DziExport {
+    /// Export the view `state` gives on a canvas of `viewport_px` pixels, to an image
+    /// `width` pixels wide, with the overlay `overlay` gives for the width of each level drawn
+    /// over it.
+    pub fn start(
+        path: PathBuf,
+        state: &State,
+        viewport_px: Vec2,
+        width: usize,
+        overlay: impl Fn(usize) -> Option<Overlay>,
+    ) -> Result<Self> {
+        let width = width.max(1);
+        let height = export_height(viewport_px, width);
+        // Level 0 is a single pixel, each level doubles the size up to the full image
+        let max_level = width.max(height).next_power_of_two().trailing_zeros();
+        let level_size = |level: u32| {
+            let scale = (1usize << (max_level - level)) as f32;
+            (width as f32 / scale, height as f32 / scale)
+        };
+        let overlays = (0..=max_level)
+            .map(|level| overlay(level_size(level).0.ceil() as usize))
+            .collect::<Vec<_>>();
+        let parameters = (
+            state,
+            [viewport_px.x, viewport_px.y],
+            width,
+            overlays.iter().any(Option::is_some),
+        );
+        let (checkpoint, written) = Checkpoint::load::<Vec<usize>>(&path, &parameters)?;
+        let written = written.unwrap_or_default();
+        fs::write(
+            &path,
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                 <Image xmlns=\"http://schemas.microsoft.com/deepzoom/2008\" \
+                 Format=\"png\" Overlap=\"{OVERLAP}\" TileSize=\"{TILE_SIZE}\">\n\
+                 \x20 <Size Width=\"{width}\" Height=\"{height}\"/>\n\
+                 </Image>\n"
+            ),
+        )
+        .with_context(|| format!("Cannot write {}", path.display()))?;
+
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let files = path.with_file_name(format!("{stem}_files"));
+        let mut tiles = VecDeque::new();
+        for level in 0..=max_level {
+            let directory = files.join(level.to_string());
+            fs::create_dir_all(&directory)
+                .with_context(|| format!("Cannot create {}", directory.display()))?;
+
+            let (level_width, level_height) = level_size(level);
+
+            let (columns, rows) = (
+                (level_width.ceil() as usize).div_ceil(TILE_SIZE),
+                (level_height.ceil() as usize).div_ceil(TILE_SIZE),
+            );
+            for column in 0..columns {
+                for row in 0..rows {
+                    let (left, right) = tile_span(column, level_width.ceil() as usize);
+                    let (top, bottom) = tile_span(row, level_height.ceil() as usize);
+                    let rect = [left, top, right, bottom].map(|side| side as f32);
+                    tiles.push_back(Tile {
+                        index: tiles.len(),
+                        level: level as usize,
+                        origin: [left, top],
+                        path: directory.join(format!("{column}_{row}.png")),
+                        state: tile_state(state, viewport_px, [level_width, level_height], rect),
+                        size: [(right - left) as i32, (bottom - top) as i32],
+                    });
+                }
+            }
+        }
+
+        let total = tiles.len();
+        let resumed = written.iter().collect::<HashSet<_>>();
+        tiles.retain(|tile| !resumed.contains(&tile.index));
+        Ok(Self {
+            path,
+            total,
+            tiles,
+            pending: HashMap::new(),
+            next_id: 0,
+            written,
+            checkpoint,
+            overlays,
+        })
+    }
+
+    /// Save the tiles rendered since the last frame and request the next ones. Returns
+    /// whether the export is complete.
+    pub fn update(&mut self, renderer: &mut dyn TileRenderer) -> Result<bool> {
+        for (id, pixels) in renderer.take_tiles() {
+            let Some(tile) = self.pending.remove(&id) else {
+                continue;
+            };
+            let [width, height] = tile.size;
+            let mut pixels = pixels?;
+            if let Some(overlay) = &self.overlays[tile.level] {
+                overlay.draw(&mut overlay::Tile {
+                    pixels: &mut pixels,
+                    origin: tile.origin,
+                    size: tile.size.map(|side| side as usize),
+                    deep: false,
+                });
+            }
+            image::save_buffer(
+                &tile.path,
+                &pixels,
+                width as u32,
+                height as u32,
+                ExtendedColorType::Rgba8,
+            )
+            .with_context(|| format!("Cannot write {}", tile.path.display()))?;
+            self.written.push(tile.index);
+        }
+
+        while self.pending.len() < renderer.capacity() {
+            let Some(tile) = self.tiles.pop_front() else {
+                break;
+            };
+            renderer.request_tile(self.next_id, tile.state, tile.size, false);
+            self.pending.insert(self.next_id, tile);
+            self.next_id += 1;
+        }
+
+        if self.is_complete() {
+            self.checkpoint.remove()?;
+            return Ok(true);
+        }
+        if self.checkpoint.is_due() {
+            self.checkpoint.save(&self.written)?;
+        }
+        Ok(false)
+    }
+
+    fn is_complete(&self) -> bool {
+        self.tiles.is_empty() && self.pending.is_empty()
+    }
+
+    /// Fraction of the tiles written.
+    pub fn progress(&self) -> f32 {
+        let left = self.tiles.len() + self.pending.len();
+        1.0 - left as f32 / self.total.max(1) as f32
+    }
+}
+
+impl Drop for DziExport {
+    /// Save the progress of a cancelled export, to resume it.
+    fn drop(&mut self) {
+        if !self.is_complete() {
+            if let Err(e) = self.checkpoint.save(&self.written) {
+                error!("{:#}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::tiled_export::FlatRenderer;
+
+    #[test]
+    fn spans() {
+        // Overlap on the sides with a neighbour only
+        assert_eq!(tile_span(0, 1000), (0, 257));
+        assert_eq!(tile_span(1, 1000), (255, 513));
+        assert_eq!(tile_span(3, 1000), (767, 1000));
+        assert_eq!(tile_span(0, 100), (0, 100));
+    }
+
+    #[test]
+    fn levels_and_tiles() {
+        let path = std::env::temp_dir().join("fractal_gl_dzi_test.dzi");
+        let files = path.with_file_name("fractal_gl_dzi_test_files");
+        let mut export = DziExport::start(
+            path.clone(),
+            &State::new(),
+            Vec2::new(300.0, 200.0),
+            300,
+            |_| None,
+        )
+        .unwrap();
+        let mut renderer = FlatRenderer::new([0xffff, 0, 0, 0xffff]);
+        while !export.update(&mut renderer).unwrap() {}
+        drop(export);
+
+        let description = fs::read_to_string(&path).unwrap();
+        assert!(description.contains("Overlap=\"1\" TileSize=\"256\""));
+        assert!(description.contains("<Size Width=\"300\" Height=\"200\"/>"));
+        // From a single pixel to 300×200, doubling: 10 levels
+        assert_eq!(fs::read_dir(&files).unwrap().count(), 10);
+        let size = |tile: &str| image::image_dimensions(files.join(tile)).unwrap();
+        assert_eq!(size("0/0_0.png"), (1, 1));
+        assert_eq!(size("8/0_0.png"), (150, 100));
+        // The tiles of the full size share a column, the one on the edge is narrower
+        assert_eq!(size("9/0_0.png"), (257, 200));
+        assert_eq!(size("9/1_0.png"), (45, 200));
+        assert!(!files.join("9/2_0.png").exists());
+        assert!(!files.join("9/0_1.png").exists());
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_dir_all(&files).unwrap();
+    }
+}
@@ -0,0 +1,84 @@
+use std::f64::consts::TAU;
+
+use eframe::egui::{vec2, Align2, Color32, FontId, Painter, Pos2, Rect, Stroke};
+
+use super::{i18n::tr, State};
+
+/// Landmarks of the Mandelbrot set: message id of the name, and position of the label.
+const LANDMARKS: [(&str, [f64; 2]); 7] = [
+    ("landmark-main-cardioid", [-0.1, 0.0]),
+    ("landmark-period-2-bulb", [-1.0, 0.0]),
+    ("landmark-period-3-bulb", [-0.1226, 0.7449]),
+    ("landmark-seahorse-valley", [-0.75, 0.1]),
+    ("landmark-elephant-valley", [0.28, 0.02]),
+    ("landmark-period-3-minibrot", [-1.7549, 0.0]),
+    ("landmark-antenna", [-1.9, -0.04]),
+];
+
+/// Points of the main cardioid, the parameters with an attracting fixed point.
+fn cardioid(samples: usize) -> impl Iterator<Item = [f64; 2]> {
+    (0..=samples).map(move |i| {
+        let t = i as f64 / samples as f64 * TAU;
+        // c = w/2 - w²/4 with w = e^(it)
+        [
+            t.cos() / 2.0 - (2.0 * t).cos() / 4.0,
+            t.sin() / 2.0 - (2.0 * t).sin() / 4.0,
+        ]
+    })
+}
+
+/// Points of the circle of center `center` and radius `radius`.
+fn circle(center: [f64; 2], radius: f64, samples: usize) -> impl Iterator<Item = [f64; 2]> {
+    (0..=samples).map(move |i| {
+        let t = i as f64 / samples as f64 * TAU;
+        [center[0] + radius * t.cos(), center[1] + radius * t.sin()]
+    })
+}
+
+/// Outline the main components of the Mandelbrot set and name its landmarks.
+pub fn paint(painter: &Painter, rect: Rect, pixels_per_point: f32, state: &State) {
+    let to_screen = |point: [f64; 2]| {
+        let [x, y] = state.offset_of(point);
+        rect.center() + vec2(x, y) / pixels_per_point
+    };
+    let stroke = Stroke::new(1.0, Color32::from_white_alpha(160));
+    let outline = |points: Vec<Pos2>| {
+        for segment in points.windows(2) {
+            // The painter doesn't like huge coordinates
+            if rect.expand(rect.width()).contains(segment[0]) {
+                painter.line_segment([segment[0], segment[1]], stroke);
+            }
+        }
+    };
+
+    // The period 2 bulb is an exact circle, the period 3 ones are close to it
+    outline(cardioid(256).map(to_screen).collect());
+    outline(circle([-1.0, 0.0], 0.25, 128).map(to_screen).collect());
+    for im in [0.7449, -0.7449] {
+        outline(circle([-0.1226, im], 0.095, 64).map(to_screen).collect());
+    }
+
+    let font = FontId::proportional(13.0);
+    for (id, point) in LANDMARKS {
+        let position = to_screen(point);
+        if !rect.contains(position) {
+            continue;
+        }
+        let text = tr(id);
+        // Shadow to keep the text readable over any color
+        painter.text(
+            position + vec2(1.0, 1.0),
+            Align2::CENTER_CENTER,
+            &text,
+            font.clone(),
+            Color32::BLACK,
+        );
+        painter.text(
+            position,
+            Align2::CENTER_CENTER,
+            text,
+            font.clone(),
+            Color32::WHITE,
+        );
+    }
+}
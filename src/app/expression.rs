@@ -0,0 +1,411 @@
+use anyhow::{anyhow, bail, Result};
+
+/// Arithmetic expression, like `0.3 * sin(t) + 0.1`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Variable(String),
+    Negate(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Function, Box<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Abs,
+    Sqrt,
+    Exp,
+    Log,
+    Floor,
+    Fract,
+}
+
+impl Function {
+    pub const ALL: [Function; 9] = [
+        Function::Sin,
+        Function::Cos,
+        Function::Tan,
+        Function::Abs,
+        Function::Sqrt,
+        Function::Exp,
+        Function::Log,
+        Function::Floor,
+        Function::Fract,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Function::Sin => "sin",
+            Function::Cos => "cos",
+            Function::Tan => "tan",
+            Function::Abs => "abs",
+            Function::Sqrt => "sqrt",
+            Function::Exp => "exp",
+            Function::Log => "log",
+            Function::Floor => "floor",
+            Function::Fract => "fract",
+        }
+    }
+
+    fn apply(self, x: f64) -> f64 {
+        match self {
+            Function::Sin => x.sin(),
+            Function::Cos => x.cos(),
+            Function::Tan => x.tan(),
+            Function::Abs => x.abs(),
+            Function::Sqrt => x.sqrt(),
+            Function::Exp => x.exp(),
+            Function::Log => x.ln(),
+            Function::Floor => x.floor(),
+            Function::Fract => x - x.floor(),
+        }
+    }
+}
+
+impl Expr {
+    /// Parse `source`. The constant `pi` is replaced by its value, other identifiers are
+    /// kept as variables.
+    pub fn parse(source: &str) -> Result<Expr> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            position: 0,
+        };
+        let expr = parser.sum()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => bail!("Unexpected {:?}", token),
+        }
+    }
+
+    /// Names of the variables used by the expression.
+    pub fn variables(&self) -> Vec<&str> {
+        match self {
+            Expr::Number(_) => Vec::new(),
+            Expr::Variable(name) => vec![name.as_str()],
+            Expr::Negate(e) | Expr::Call(_, e) => e.variables(),
+            Expr::Binary(_, a, b) => {
+                let mut variables = a.variables();
+                variables.extend(b.variables());
+                variables
+            }
+        }
+    }
+
+    /// Evaluate the expression, `variable` gives the value of the variables by name.
+    pub fn eval(&self, variable: &impl Fn(&str) -> Option<f64>) -> Result<f64> {
+        Ok(match self {
+            Expr::Number(x) => *x,
+            Expr::Variable(name) => {
+                variable(name).ok_or_else(|| anyhow!("Unknown variable {:?}", name))?
+            }
+            Expr::Negate(e) => -e.eval(variable)?,
+            Expr::Call(function, e) => function.apply(e.eval(variable)?),
+            Expr::Binary(op, a, b) => {
+                let (a, b) = (a.eval(variable)?, b.eval(variable)?);
+                match op {
+                    BinaryOp::Add => a + b,
+                    BinaryOp::Sub => a - b,
+                    BinaryOp::Mul => a * b,
+                    BinaryOp::Div => a / b,
+                    BinaryOp::Pow => a.powf(b),
+                }
+            }
+        })
+    }
+
+    /// GLSL source of the expression, `variable` gives the GLSL expression of the variables by
+    /// name. The functions have the same name in GLSL.
+    pub fn to_glsl(&self, variable: &impl Fn(&str) -> Option<String>) -> Result<String> {
+        Ok(match self {
+            Expr::Number(x) => {
+                let value = *x as f32;
+                if !value.is_finite() {
+                    bail!("{} is too large", x);
+                }
+                // Debug always keeps a decimal point or an exponent, as GLSL floats need
+                format!("{:?}", value)
+            }
+            Expr::Variable(name) => {
+                variable(name).ok_or_else(|| anyhow!("Unknown variable {:?}", name))?
+            }
+            Expr::Negate(e) => format!("(-{})", e.to_glsl(variable)?),
+            Expr::Call(function, e) => format!("{}({})", function.name(), e.to_glsl(variable)?),
+            Expr::Binary(op, a, b) => {
+                let (a, b) = (a.to_glsl(variable)?, b.to_glsl(variable)?);
+                match op {
+                    BinaryOp::Add => format!("({a} + {b})"),
+                    BinaryOp::Sub => format!("({a} - {b})"),
+                    BinaryOp::Mul => format!("({a} * {b})"),
+                    BinaryOp::Div => format!("({a} / {b})"),
+                    BinaryOp::Pow => format!("pow({a}, {b})"),
+                }
+            }
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Symbol(char),
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                // Accept exponents like 1e-3
+                let exponent_sign = (c == '-' || c == '+') && source[..i].ends_with(['e', 'E']);
+                if !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || exponent_sign) {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let number = &source[start..end];
+            tokens.push(Token::Number(
+                number
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid number {:?}", number))?,
+            ));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push(Token::Identifier(source[start..end].to_string()));
+        } else if "+-*/^(),".contains(c) {
+            tokens.push(Token::Symbol(c));
+            chars.next();
+        } else {
+            bail!("Unexpected character {:?} at {}", c, start);
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser, one method per precedence level.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr> {
+        let mut expr = self.product()?;
+        loop {
+            let op = if self.eat('+') {
+                BinaryOp::Add
+            } else if self.eat('-') {
+                BinaryOp::Sub
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr> {
+        let mut expr = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                BinaryOp::Mul
+            } else if self.eat('/') {
+                BinaryOp::Div
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.eat('-') {
+            Ok(Expr::Negate(Box::new(self.unary()?)))
+        } else if self.eat('+') {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    /// `^` is right associative and binds tighter than the unary minus: `-2^2` is -4.
+    fn power(&mut self) -> Result<Expr> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            Ok(Expr::Binary(
+                BinaryOp::Pow,
+                Box::new(base),
+                Box::new(self.unary()?),
+            ))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some(Token::Number(x)) => Ok(Expr::Number(x)),
+            Some(Token::Symbol('(')) => {
+                let expr = self.sum()?;
+                if !self.eat(')') {
+                    bail!("Missing closing parenthesis");
+                }
+                Ok(expr)
+            }
+            Some(Token::Identifier(name)) => {
+                if let Some(function) = Function::ALL.into_iter().find(|f| f.name() == name) {
+                    if !self.eat('(') {
+                        bail!("Missing parenthesis after {}", name);
+                    }
+                    let argument = self.sum()?;
+                    if !self.eat(')') {
+                        bail!("Missing closing parenthesis after the argument of {}", name);
+                    }
+                    Ok(Expr::Call(function, Box::new(argument)))
+                } else if name == "pi" {
+                    Ok(Expr::Number(std::f64::consts::PI))
+                } else {
+                    Ok(Expr::Variable(name))
+                }
+            }
+            Some(token) => bail!("Unexpected {:?}", token),
+            None => bail!("Unexpected end of the expression"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(source: &str) -> f64 {
+        Expr::parse(source)
+            .unwrap()
+            .eval(&|name| (name == "t").then_some(2.0))
+            .unwrap()
+    }
+
+    fn error(source: &str) -> String {
+        Expr::parse(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3"), 7.0);
+        assert_eq!(eval("(1 + 2) * 3"), 9.0);
+        assert_eq!(eval("8 / 4 / 2"), 1.0);
+        assert_eq!(eval("10 - 4 - 3"), 3.0);
+        assert_eq!(eval("2 * 3 ^ 2"), 18.0);
+        // Right associative
+        assert_eq!(eval("2 ^ 3 ^ 2"), 512.0);
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(eval("-2 ^ 2"), -4.0);
+        assert_eq!(eval("(-2) ^ 2"), 4.0);
+        assert_eq!(eval("2 ^ -1"), 0.5);
+        assert_eq!(eval("--t"), 2.0);
+        assert_eq!(eval("3 - -t"), 5.0);
+        assert_eq!(eval("+t"), 2.0);
+        assert_eq!(
+            Expr::parse("-t * 2").unwrap(),
+            Expr::Binary(
+                BinaryOp::Mul,
+                Box::new(Expr::Negate(Box::new(Expr::Variable("t".to_string())))),
+                Box::new(Expr::Number(2.0)),
+            )
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(eval(".5"), 0.5);
+        assert_eq!(eval("1e-3"), 0.001);
+        assert_eq!(eval("2.5E+2"), 250.0);
+        assert_eq!(eval("pi"), std::f64::consts::PI);
+    }
+
+    #[test]
+    fn function_calls() {
+        assert_eq!(eval("abs(-3)"), 3.0);
+        assert_eq!(eval("sqrt(t * 8)"), 4.0);
+        assert_eq!(eval("floor(2.7) + fract(2.75)"), 2.75);
+        assert_eq!(eval("cos(0) + sin(0)"), 1.0);
+        assert_eq!(eval("exp(log(t))"), 2.0);
+        assert_eq!(
+            Expr::parse("0.3 * sin(t) + x").unwrap().variables(),
+            ["t", "x"]
+        );
+    }
+
+    #[test]
+    fn glsl() {
+        let glsl = Expr::parse("-sin(t) ^ 2 / 4")
+            .unwrap()
+            .to_glsl(&|name| (name == "t").then(|| "u_time".to_string()))
+            .unwrap();
+        assert_eq!(glsl, "((-pow(sin(u_time), 2.0)) / 4.0)");
+        assert!(Expr::parse("1e300").unwrap().to_glsl(&|_| None).is_err());
+        assert!(Expr::parse("y").unwrap().to_glsl(&|_| None).is_err());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(error("1 + $"), "Unexpected character '$' at 4");
+        assert_eq!(error("t # 2"), "Unexpected character '#' at 2");
+        assert_eq!(error("1..2"), "Invalid number \"1..2\"");
+        assert_eq!(error("(1 + 2"), "Missing closing parenthesis");
+        assert_eq!(error("sin t"), "Missing parenthesis after sin");
+        assert_eq!(
+            error("sin(t, 2)"),
+            "Missing closing parenthesis after the argument of sin"
+        );
+        assert_eq!(error("1 2"), "Unexpected Number(2.0)");
+        assert_eq!(error("2 *"), "Unexpected end of the expression");
+        assert_eq!(error(")"), "Unexpected Symbol(')')");
+        assert!(Expr::parse("y").unwrap().eval(&|_| None).is_err());
+    }
+}
@@ -0,0 +1,53 @@
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result};
+
+/// Open `path` with the default application of the system.
+pub fn open(path: &Path) -> Result<()> {
+    let mut command = if cfg!(target_os = "windows") {
+        // Not `cmd /C start`, cmd would interpret the `&` and `^` of the file names
+        let mut command = Command::new("explorer");
+        command.arg(path);
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg(path);
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        command.arg(path);
+        command
+    };
+    spawn(&mut command).with_context(|| format!("Cannot open {}", path.display()))
+}
+
+/// Show `path` in the file manager, selecting it when the platform allows it.
+pub fn reveal(path: &Path) -> Result<()> {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("explorer");
+        command.arg(format!("/select,{}", path.display()));
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg("-R").arg(path);
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        command.arg(path.parent().unwrap_or(Path::new(".")));
+        command
+    };
+    spawn(&mut command).with_context(|| format!("Cannot reveal {}", path.display()))
+}
+
+fn spawn(command: &mut Command) -> Result<()> {
+    // Don't wait for the viewer, it stays open as long as the user wants
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use anyhow::{anyhow, bail, Result};
+use eframe::egui::{Color32, Painter, Pos2, Rect, Stroke, TextEdit, Ui};
+
+use super::{
+    accessibility::icon_button,
+    complex::Complex,
+    i18n::{tr, tr_args},
+    State,
+};
+
+/// Radius the rays start from.
+const ESCAPE_RADIUS: f64 = 65536.0;
+/// Points traced per iteration level.
+const SHARPNESS: u32 = 8;
+/// Iteration levels followed toward the boundary of the set.
+const DEPTH: u32 = 64;
+const NEWTON_STEPS: usize = 64;
+
+/// Parse an angle like `1/3`, in turns.
+fn parse_angle(source: &str) -> Result<(u64, u64)> {
+    let (numerator, denominator) = source
+        .split_once('/')
+        .ok_or(anyhow!("The angle must be a fraction like 1/3"))?;
+    let numerator: u64 = numerator.trim().parse()?;
+    let denominator: u64 = denominator.trim().parse()?;
+    if denominator == 0 || numerator >= denominator {
+        bail!("The angle must be between 0 and 1");
+    }
+    // Doubling the numerator must not overflow
+    if denominator > u64::MAX / 2 {
+        bail!("The denominator is too large");
+    }
+    Ok((numerator, denominator))
+}
+
+/// Points of the external ray of angle `numerator/denominator` turns, from far away toward
+/// the Mandelbrot set.
+///
+/// Each point is found with Newton's method from the previous one, the angle doubles at
+/// each iteration level.
+pub fn trace(mut numerator: u64, denominator: u64) -> Vec<Complex> {
+    let angle = |numerator: u64| std::f64::consts::TAU * numerator as f64 / denominator as f64;
+    let polar = |r: f64, a: f64| Complex::new(r * a.cos(), r * a.sin());
+
+    let mut c = polar(ESCAPE_RADIUS, angle(numerator));
+    let mut points = vec![c];
+    for level in 0..DEPTH {
+        if level > 0 {
+            numerator = numerator * 2 % denominator;
+        }
+        for step in 0..SHARPNESS {
+            let r = ESCAPE_RADIUS.powf(0.5f64.powf((step as f64 + 0.5) / SHARPNESS as f64));
+            let target = polar(r, angle(numerator));
+            for _ in 0..NEWTON_STEPS {
+                let (mut z, mut dc) = (Complex::ZERO, Complex::ZERO);
+                for _ in 0..=level {
+                    dc = z * dc * 2.0 + Complex::ONE;
+                    z = z * z + c;
+                }
+                let delta = (z - target) / dc;
+                if !delta.is_finite() {
+                    return points;
+                }
+                c = c - delta;
+                if delta.norm() < 1e-14 * c.norm().max(1e-300) {
+                    break;
+                }
+            }
+            points.push(c);
+        }
+    }
+    points
+}
+
+struct Ray {
+    label: String,
+    points: Vec<Complex>,
+}
+
+/// External rays drawn over the Mandelbrot set.
+pub struct ExternalRays {
+    angle: String,
+    error: Option<String>,
+    rays: Vec<Ray>,
+}
+
+impl ExternalRays {
+    pub fn new() -> Self {
+        Self {
+            angle: "1/3".to_string(),
+            error: None,
+            rays: Vec::new(),
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.angle)
+                    .desired_width(80.0)
+                    .hint_text("p/q"),
+            );
+            if ui.button(tr("trace-ray")).clicked() {
+                match parse_angle(&self.angle) {
+                    Ok((numerator, denominator)) => {
+                        self.error = None;
+                        self.rays.push(Ray {
+                            label: format!("{}/{}", numerator, denominator),
+                            points: trace(numerator, denominator),
+                        });
+                    }
+                    Err(e) => self.error = Some(e.to_string()),
+                }
+            }
+        });
+        if let Some(e) = &self.error {
+            ui.colored_label(Color32::RED, e.as_str());
+        }
+
+        let mut removed = None;
+        for (i, ray) in self.rays.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(tr_args("ray", &[("angle", &ray.label)]));
+                if icon_button(ui.small_button("✖"), tr("remove")).clicked() {
+                    removed = Some(i);
+                }
+            });
+        }
+        if let Some(i) = removed {
+            self.rays.remove(i);
+        }
+    }
+
+    /// Draw the rays over the fractal shown in `rect`.
+    pub fn paint(&self, painter: &Painter, rect: Rect, pixels_per_point: f32, state: &State) {
+        let stroke = Stroke::new(1.5, Color32::WHITE);
+        for ray in &self.rays {
+            let points: Vec<Pos2> = ray
+                .points
+                .iter()
+                .map(|c| {
+                    let [x, y] = state.offset_of([c.re, c.im]);
+                    rect.center() + eframe::egui::vec2(x, y) / pixels_per_point
+                })
+                .collect();
+            for segment in points.windows(2) {
+                // Skip the far away part of the ray, the painter doesn't like huge coordinates
+                if rect.expand(rect.width()).contains(segment[0]) {
+                    painter.line_segment([segment[0], segment[1]], stroke);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,442 @@
+//! Render farm: the tiles of the exports rendered by `fractal_gl worker` processes, on this
+//! machine or others, connected over TCP.
+//!
+//! Every message is a JSON [Message] after its length, as a big endian `u32`. The pixels of
+//! a rendered tile follow their message, compressed in a PNG image.
+
+use std::{
+    collections::VecDeque,
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::{anyhow, ensure, Context as _, Result};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    dzi::TILES_PER_FRAME,
+    i18n::tr,
+    state::{ColoringAlgorithm, FractalType},
+    tiled_export::TileRenderer,
+    Palette, State,
+};
+
+/// Port the workers connect to unless told otherwise.
+pub const DEFAULT_PORT: u16 = 7878;
+/// Workers a tile is sent to before its export fails, when they disconnect while on it.
+const MAX_ATTEMPTS: u32 = 3;
+/// A worker silent for this long while on a tile is considered lost.
+const TILE_TIMEOUT: Duration = Duration::from_secs(600);
+/// Delay between two checks for new workers, and for the farm being stopped.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Longest message but the pixels, a view and a palette are a few kilobytes.
+const MAX_MESSAGE: u32 = 1 << 20;
+
+/// Tile of an export, as sent to a worker.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: u64,
+    pub state: State,
+    /// Palette of the view, when it is colored with one.
+    pub palette: Option<Palette>,
+    pub size: [i32; 2],
+    /// 16 bits per channel instead of 8.
+    pub deep: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum Message {
+    /// To a worker: render a tile.
+    Render(Box<Job>),
+    /// From a worker: the tile `id` is rendered, its PNG image of `bytes` bytes follows.
+    Rendered { id: u64, bytes: u64 },
+    /// From a worker: the tile `id` cannot be rendered.
+    Failed { id: u64, error: String },
+    /// To a worker: no more tiles, it can exit.
+    Shutdown,
+}
+
+pub fn write_message(stream: &mut impl Write, message: &Message) -> Result<()> {
+    let json = serde_json::to_vec(message)?;
+    stream.write_all(&(json.len() as u32).to_be_bytes())?;
+    stream.write_all(&json)?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Next message of `stream`, `None` when it is closed between two messages.
+pub fn read_message(stream: &mut impl Read) -> Result<Option<Message>> {
+    let mut length = [0; 4];
+    match stream.read_exact(&mut length) {
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        read => read?,
+    }
+    let length = u32::from_be_bytes(length);
+    ensure!(length <= MAX_MESSAGE, "Message of {} bytes", length);
+    let mut json = vec![0; length as usize];
+    stream.read_exact(&mut json)?;
+    Ok(Some(serde_json::from_slice(&json)?))
+}
+
+/// RGBA `pixels` of a tile of `size` pixels compressed in a PNG image, with 16 bits per
+/// channel in the native byte order if `deep`.
+pub fn compress_tile(pixels: &[u8], size: [i32; 2], deep: bool) -> Result<Vec<u8>> {
+    let mut compressed = Vec::new();
+    let mut encoder = png::Encoder::new(&mut compressed, size[0] as u32, size[1] as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    if deep {
+        encoder.set_depth(png::BitDepth::Sixteen);
+        // PNG samples are big endian
+        let samples = pixels
+            .chunks_exact(2)
+            .flat_map(|sample| u16::from_ne_bytes([sample[0], sample[1]]).to_be_bytes())
+            .collect::<Vec<_>>();
+        encoder.write_header()?.write_image_data(&samples)?;
+    } else {
+        encoder.write_header()?.write_image_data(pixels)?;
+    }
+    Ok(compressed)
+}
+
+/// Pixels of a PNG image of [compress_tile], checked against the size of `job`.
+pub fn decompress_tile(compressed: &[u8], job: &Job) -> Result<Vec<u8>> {
+    let mut reader = png::Decoder::new(compressed).read_info()?;
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels)?;
+    let depth = if job.deep {
+        png::BitDepth::Sixteen
+    } else {
+        png::BitDepth::Eight
+    };
+    ensure!(
+        [info.width as i32, info.height as i32] == job.size
+            && info.color_type == png::ColorType::Rgba
+            && info.bit_depth == depth,
+        "Tile of the wrong format"
+    );
+    pixels.truncate(info.buffer_size());
+    if job.deep {
+        for sample in pixels.chunks_exact_mut(2) {
+            let value = u16::from_be_bytes([sample[0], sample[1]]);
+            sample.copy_from_slice(&value.to_ne_bytes());
+        }
+    }
+    Ok(pixels)
+}
+
+/// Why the workers cannot render `state`: they only have the view and the palette, not the
+/// formulas, images and plugins of the window. `None` when they can.
+pub fn unsupported(state: &State) -> Option<String> {
+    let custom = state.fractal_type == FractalType::Custom
+        || state.custom_coloring
+        || matches!(
+            state.coloring_algorithm,
+            ColoringAlgorithm::ImageTrap | ColoringAlgorithm::TextureMap
+        );
+    custom.then(|| tr("render-farm-unsupported"))
+}
+
+/// Tile waiting for a worker.
+struct Queued {
+    job: Job,
+    /// [Shared::generation] of the request.
+    generation: u64,
+    /// Workers that disconnected while on it.
+    attempts: u32,
+}
+
+/// Rendered tile, with the generation of its request.
+type Finished = (u64, u64, Result<Vec<u8>>);
+
+/// State of the farm shared with the threads of the workers.
+struct Shared {
+    queue: Mutex<VecDeque<Queued>>,
+    /// Notified when a tile is queued or the farm is stopped.
+    queued: Condvar,
+    /// Incremented when the tiles are forgotten, the older ones are dropped once rendered.
+    generation: AtomicU64,
+    workers: AtomicUsize,
+    stopped: AtomicBool,
+}
+
+impl Shared {
+    /// Next tile to render, `None` once the farm is stopped.
+    fn next(&self) -> Option<Queued> {
+        let mut queue = self.queue.lock().unwrap();
+        loop {
+            if self.stopped.load(Ordering::Relaxed) {
+                return None;
+            }
+            if let Some(queued) = queue.pop_front() {
+                return Some(queued);
+            }
+            queue = self.queued.wait_timeout(queue, POLL_INTERVAL).unwrap().0;
+        }
+    }
+
+    /// Give a tile lost with its worker to another one, or fail it after [MAX_ATTEMPTS].
+    fn retry(&self, mut queued: Queued, error: anyhow::Error, finished: &Sender<Finished>) {
+        if queued.generation != self.generation.load(Ordering::Relaxed) {
+            // Forgotten meanwhile
+            return;
+        }
+        queued.attempts += 1;
+        if queued.attempts < MAX_ATTEMPTS {
+            self.queue.lock().unwrap().push_front(queued);
+            self.queued.notify_one();
+        } else {
+            let error = error.context(format!("The tile was lost by {MAX_ATTEMPTS} workers"));
+            let _ = finished.send((queued.generation, queued.job.id, Err(error)));
+        }
+    }
+}
+
+/// Coordinator of the render farm: it listens for workers and hands them the tiles of the
+/// exports, one at a time each. The tiles of a worker that disconnects go to the others.
+///
+/// Dropped, it asks the workers to exit once their tile is rendered.
+pub struct Farm {
+    pub port: u16,
+    shared: Arc<Shared>,
+    /// Tiles rendered, and failed without reaching a worker.
+    finished: Receiver<Finished>,
+    failed: Sender<Finished>,
+    /// Palette sent with the tiles.
+    palette: Option<Palette>,
+}
+
+impl Farm {
+    /// Listen for workers on every interface, on `port`.
+    pub fn start(port: u16) -> Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))
+            .with_context(|| format!("Cannot listen on port {port}"))?;
+        listener.set_nonblocking(true)?;
+        // The port picked by the system when 0
+        let port = listener.local_addr()?.port();
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(VecDeque::new()),
+            queued: Condvar::new(),
+            generation: AtomicU64::new(0),
+            workers: AtomicUsize::new(0),
+            stopped: AtomicBool::new(false),
+        });
+        let (sender, finished) = mpsc::channel();
+        {
+            let (shared, sender) = (shared.clone(), sender.clone());
+            thread::spawn(move || accept(&listener, &shared, &sender));
+        }
+        info!("Render farm listening on port {}", port);
+        Ok(Self {
+            port,
+            shared,
+            finished,
+            failed: sender,
+            palette: None,
+        })
+    }
+
+    /// Workers connected.
+    pub fn workers(&self) -> usize {
+        self.shared.workers.load(Ordering::Relaxed)
+    }
+
+    /// Color the next tiles with `palette`.
+    pub fn set_palette(&mut self, palette: Option<Palette>) {
+        self.palette = palette;
+    }
+}
+
+impl TileRenderer for Farm {
+    fn request_tile(&mut self, id: u64, state: State, size: [i32; 2], deep: bool) {
+        let generation = self.shared.generation.load(Ordering::Relaxed);
+        if let Some(reason) = unsupported(&state) {
+            let _ = self.failed.send((generation, id, Err(anyhow!(reason))));
+            return;
+        }
+        let job = Job {
+            id,
+            state,
+            palette: self.palette.clone(),
+            size,
+            deep,
+        };
+        self.shared.queue.lock().unwrap().push_back(Queued {
+            job,
+            generation,
+            attempts: 0,
+        });
+        self.shared.queued.notify_one();
+    }
+
+    fn take_tiles(&mut self) -> Vec<(u64, Result<Vec<u8>>)> {
+        let generation = self.shared.generation.load(Ordering::Relaxed);
+        self.finished
+            .try_iter()
+            .filter(|(tile_generation, _, _)| *tile_generation == generation)
+            .map(|(_, id, pixels)| (id, pixels))
+            .collect()
+    }
+
+    fn forget_tiles(&mut self) {
+        let mut queue = self.shared.queue.lock().unwrap();
+        queue.clear();
+        self.shared.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Enough for every worker to find its next tile queued when it sends one.
+    fn capacity(&self) -> usize {
+        (2 * self.workers()).max(TILES_PER_FRAME)
+    }
+}
+
+impl Drop for Farm {
+    fn drop(&mut self) {
+        self.shared.stopped.store(true, Ordering::Relaxed);
+        self.shared.queued.notify_all();
+    }
+}
+
+/// Serve the workers connecting to `listener` until the farm is stopped.
+fn accept(listener: &TcpListener, shared: &Arc<Shared>, finished: &Sender<Finished>) {
+    while !shared.stopped.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, address)) => {
+                info!("Worker {} connected", address);
+                let (shared, finished) = (shared.clone(), finished.clone());
+                thread::spawn(move || {
+                    shared.workers.fetch_add(1, Ordering::Relaxed);
+                    if let Err(e) = serve(stream, &shared, &finished) {
+                        warn!("Worker {} lost: {:#}", address, e);
+                    }
+                    shared.workers.fetch_sub(1, Ordering::Relaxed);
+                });
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                error!("Cannot accept a worker: {}", e);
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+/// Hand the tiles to the worker of `stream` one at a time, until the farm is stopped or the
+/// worker is lost, its tile then going back to the queue.
+fn serve(mut stream: TcpStream, shared: &Shared, finished: &Sender<Finished>) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(TILE_TIMEOUT))?;
+    stream.set_nodelay(true)?;
+    while let Some(queued) = shared.next() {
+        match render(&mut stream, &queued.job) {
+            Ok(pixels) => {
+                let _ = finished.send((queued.generation, queued.job.id, pixels));
+            }
+            Err(e) => {
+                let lost = anyhow!("{:#}", e);
+                shared.retry(queued, e, finished);
+                return Err(lost);
+            }
+        }
+    }
+    write_message(&mut stream, &Message::Shutdown)
+}
+
+/// Pixels of `job` rendered by the worker of `stream`, or why it could not render them.
+/// Fails when the worker is lost.
+fn render(stream: &mut TcpStream, job: &Job) -> Result<Result<Vec<u8>>> {
+    write_message(stream, &Message::Render(Box::new(job.clone())))?;
+    match read_message(stream)?.context("Disconnected")? {
+        Message::Rendered { id, bytes } if id == job.id => {
+            let mut compressed = vec![0; usize::try_from(bytes)?];
+            stream.read_exact(&mut compressed)?;
+            Ok(decompress_tile(&compressed, job))
+        }
+        Message::Failed { id, error } if id == job.id => Ok(Err(anyhow!(error))),
+        _ => Err(anyhow!("Unexpected message")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Worker answering the first `tiles` tiles with pixels of their id then disconnecting,
+    /// or exiting when asked to.
+    fn worker(port: u16, tiles: usize) -> thread::JoinHandle<Result<()>> {
+        thread::spawn(move || {
+            let mut stream = TcpStream::connect(("127.0.0.1", port))?;
+            for _ in 0..tiles {
+                let Some(Message::Render(job)) = read_message(&mut stream)? else {
+                    return Ok(());
+                };
+                let pixels = vec![job.id as u8; (job.size[0] * job.size[1] * 4) as usize];
+                let compressed = compress_tile(&pixels, job.size, job.deep)?;
+                let bytes = compressed.len() as u64;
+                write_message(&mut stream, &Message::Rendered { id: job.id, bytes })?;
+                stream.write_all(&compressed)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Tiles of `farm` rendered within a few seconds, sorted by id.
+    fn wait_for_tiles(farm: &mut Farm, count: usize) -> Vec<(u64, Vec<u8>)> {
+        let mut tiles = Vec::new();
+        for _ in 0..100 {
+            for (id, pixels) in farm.take_tiles() {
+                tiles.push((id, pixels.unwrap()));
+            }
+            if tiles.len() == count {
+                break;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        tiles.sort_by_key(|(id, _)| *id);
+        tiles
+    }
+
+    #[test]
+    fn lost_tiles_go_to_another_worker() {
+        let mut farm = Farm::start(0).unwrap();
+        let port = farm.port;
+        // The first worker disconnects with its second tile
+        let first = worker(port, 1);
+        for id in 0..3 {
+            farm.request_tile(id, State::new(), [2, 3], false);
+        }
+        first.join().unwrap().unwrap();
+        let second = worker(port, 3);
+        let tiles = wait_for_tiles(&mut farm, 3);
+        assert_eq!(tiles.len(), 3);
+        for (id, pixels) in tiles {
+            assert_eq!(pixels, vec![id as u8; 2 * 3 * 4]);
+        }
+        drop(farm);
+        second.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn deep_tiles() {
+        let job = Job {
+            id: 0,
+            state: State::new(),
+            palette: None,
+            size: [2, 1],
+            deep: true,
+        };
+        let pixels = [0x0102u16, 0xfffe, 0, 0xff00, 1, 2, 3, 4]
+            .iter()
+            .flat_map(|sample| sample.to_ne_bytes())
+            .collect::<Vec<_>>();
+        let compressed = compress_tile(&pixels, job.size, true).unwrap();
+        assert_eq!(decompress_tile(&compressed, &job).unwrap(), pixels);
+    }
+}
@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+
+/// Filter of a file dialog: its name and the extensions of its files.
+pub type Filter<'a> = (&'a str, &'a [&'a str]);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn dialog(filters: &[Filter]) -> rfd::FileDialog {
+    filters
+        .iter()
+        .fold(rfd::FileDialog::new(), |dialog, (name, extensions)| {
+            dialog.add_filter(*name, extensions)
+        })
+}
+
+/// File chosen by the user to save `file_name` to, `None` when cancelled.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_file(filters: &[Filter], file_name: &str) -> Option<PathBuf> {
+    dialog(filters).set_file_name(file_name).save_file()
+}
+
+/// File chosen by the user to open, `None` when cancelled.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pick_file(filters: &[Filter]) -> Option<PathBuf> {
+    dialog(filters).pick_file()
+}
+
+/// The browser gives no paths to its files, there is nothing to save to on the web.
+#[cfg(target_arch = "wasm32")]
+pub fn save_file(_filters: &[Filter], _file_name: &str) -> Option<PathBuf> {
+    log::warn!("Files cannot be saved in the browser");
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn pick_file(_filters: &[Filter]) -> Option<PathBuf> {
+    log::warn!("Files cannot be opened in the browser");
+    None
+}
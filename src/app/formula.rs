@@ -0,0 +1,84 @@
+use eframe::egui::{Grid, TextEdit, Ui};
+
+use super::{expression::Expr, i18n::tr};
+
+/// Variables of the coloring formula: name, GLSL expression and message id of the
+/// description.
+const VARIABLES: [(&str, &str, &str); 8] = [
+    ("value", "value", "variable-value"),
+    ("iter", "exp(value)", "variable-iter"),
+    ("x", "location.x", "variable-x"),
+    ("y", "location.y", "variable-y"),
+    ("zx", "z.x", "variable-zx"),
+    ("zy", "z.y", "variable-zy"),
+    ("angle", "atan(z.y, z.x)", "variable-angle"),
+    ("t", "u_time", "variable-t"),
+];
+
+fn compile(source: &str) -> Result<String, String> {
+    let expr = Expr::parse(source).map_err(|e| e.to_string())?;
+    let variable = |name: &str| {
+        VARIABLES
+            .iter()
+            .find(|(variable, _, _)| *variable == name)
+            .map(|(_, glsl, _)| glsl.to_string())
+    };
+    let glsl = expr.to_glsl(&variable).map_err(|e| e.to_string())?;
+    Ok(format!(
+        "float valueFormula(float value, vec2 location, vec2 z) {{\n    return {glsl};\n}}"
+    ))
+}
+
+/// User formula replacing the iteration value before it is colored, like
+/// `sin(iter*0.1 + angle)`.
+pub struct Formula {
+    source: String,
+    error: Option<String>,
+}
+
+impl Formula {
+    pub fn new() -> Self {
+        Self {
+            source: "sin(iter*0.1 + angle)".to_string(),
+            error: None,
+        }
+    }
+
+    /// GLSL of the `valueFormula` function, if the formula is valid.
+    pub fn glsl(&self) -> Option<String> {
+        compile(&self.source).ok()
+    }
+
+    /// Returns the new GLSL of the `valueFormula` function when the formula is edited into a
+    /// valid one.
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<String> {
+        let mut glsl = None;
+        if ui
+            .add(TextEdit::singleline(&mut self.source).code_editor())
+            .changed()
+        {
+            match compile(&self.source) {
+                Ok(compiled) => {
+                    self.error = None;
+                    glsl = Some(compiled);
+                }
+                Err(e) => self.error = Some(e),
+            }
+        }
+        if let Some(e) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, e.as_str());
+        }
+
+        ui.collapsing(tr("variables"), |ui| {
+            Grid::new("Formula variables").show(ui, |ui| {
+                for (name, _, description) in VARIABLES {
+                    ui.monospace(name);
+                    ui.label(tr(description));
+                    ui.end_row();
+                }
+            });
+            ui.label(tr("formula-functions"));
+        });
+        glsl
+    }
+}
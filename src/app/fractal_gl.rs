@@ -1,94 +1,1273 @@
-use std::fs::File;
+use super::{
+    capabilities::Capabilities,
+    color_wheel::rgb_shift,
+    histogram::{self, value_range},
+    palette::PALETTE_SIZE,
+    perturbation::{self, OrbitKey, ORBIT_TEXTURE_WIDTH},
+    quality::Quality,
+    refinement::{Refinement, PREVIEW_DIVISOR},
+    thumbnails::StableHasher,
+    Palette, State,
+};
 
-use super::State;
+use std::collections::HashMap;
 
-use std::io::Read;
-
-use anyhow::{anyhow, Context, Error, Result};
-use eframe::glow::NativeShader;
+use anyhow::{anyhow, bail, Context as _, Result};
+use eframe::glow::{PixelPackData, PixelUnpackData};
 use egui::PaintCallbackInfo;
+use log::{error, warn};
 
-pub struct FractalGl {
+/// Area of the framebuffer the fractal is drawn into, in physical pixels.
+#[derive(Clone, Copy, Debug)]
+pub struct Viewport {
+    pub left_px: f32,
+    /// Distance to the bottom of the framebuffer, where `gl_FragCoord` starts.
+    pub bottom_px: f32,
+    pub width_px: f32,
+    pub height_px: f32,
+}
+
+impl Viewport {
+    pub fn from_paint_info(paint_info: &PaintCallbackInfo) -> Self {
+        let viewport = paint_info.viewport_in_pixels();
+        Self {
+            left_px: viewport.left_px as f32,
+            bottom_px: viewport.from_bottom_px as f32,
+            width_px: viewport.width_px as f32,
+            height_px: viewport.height_px as f32,
+        }
+    }
+
+    /// Value of `u_fractalPosition`, the shader maps a pixel to
+    /// `gl_FragCoord / zoom - u_fractalPosition`.
+    pub fn fractal_position(&self, state: &State) -> [f32; 2] {
+        let zoom = state.zoom as f64;
+        [
+            (state.center_position.x
+                + self.left_px as f64 / zoom // shift to skip the edge
+                + 0.5 * self.width_px as f64 / zoom) as f32, // shift to put the center_position in the middle
+            (state.center_position.y
+                + self.bottom_px as f64 / zoom
+                + 0.5 * self.height_px as f64 / zoom) as f32,
+        ]
+    }
+
+    /// Center of the viewport, in the pixels of `gl_FragCoord`.
+    pub fn center_px(&self) -> [f32; 2] {
+        [
+            self.left_px + 0.5 * self.width_px,
+            self.bottom_px + 0.5 * self.height_px,
+        ]
+    }
+
+    /// Viewport covering a whole offscreen framebuffer.
+    pub fn offscreen(width_px: i32, height_px: i32) -> Self {
+        Self {
+            left_px: 0.0,
+            bottom_px: 0.0,
+            width_px: width_px as f32,
+            height_px: height_px as f32,
+        }
+    }
+}
+
+/// RGBA8 pixels of an offscreen render, top row first.
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+/// Offscreen framebuffer the view is rendered into when supersampled or refined.
+struct ViewTarget {
+    texture: eframe::glow::Texture,
+    framebuffer: eframe::glow::Framebuffer,
+    size: [i32; 2],
+}
+
+/// Locations of the uniforms of a program, queried once it is linked instead of by every
+/// draw.
+struct Uniforms(HashMap<String, eframe::glow::UniformLocation>);
+
+impl Uniforms {
+    fn query(gl: &eframe::glow::Context, program: eframe::glow::Program) -> Self {
+        use eframe::glow::HasContext as _;
+        unsafe {
+            let locations = (0..gl.get_active_uniforms(program))
+                .filter_map(|index| {
+                    let uniform = gl.get_active_uniform(program, index)?;
+                    // Arrays are listed by their first element
+                    let name = uniform.name.trim_end_matches("[0]").to_string();
+                    let location = gl.get_uniform_location(program, &name)?;
+                    Some((name, location))
+                })
+                .collect();
+            Self(locations)
+        }
+    }
+
+    /// Location of the uniform `name`, `None` if the program doesn't use it.
+    fn get(&self, name: &str) -> Option<&eframe::glow::UniformLocation> {
+        self.0.get(name)
+    }
+}
+
+/// Offscreen render of a view, with the texels of its palette when it is not the current one.
+struct ThumbnailRequest {
+    id: u64,
+    state: State,
+    size: [i32; 2],
+    palette: Option<Vec<u8>>,
+}
+
+/// GL objects, they can only be created once a GL context is available.
+struct Resources {
     program: eframe::glow::Program,
+    /// Of [Resources::program], replaced with it when it is rebuilt.
+    uniforms: Uniforms,
+    /// Scales the view rendered offscreen to the canvas.
+    resample_program: eframe::glow::Program,
+    resample_uniforms: Uniforms,
+    /// Created by the first offscreen draw of the view, and resized with the canvas.
+    view_target: Option<ViewTarget>,
     vertex_array: eframe::glow::VertexArray,
+    palette_texture: eframe::glow::Texture,
+    trap_texture: eframe::glow::Texture,
+    exterior_texture: eframe::glow::Texture,
+    /// Reference orbit of the deep zoom, see [perturbation::reference_orbit].
+    orbit_texture: eframe::glow::Texture,
+    /// Lookup table of the histogram coloring, see [histogram::Histogram].
+    histogram_texture: eframe::glow::Texture,
+    /// Measures the GPU time of the draws of the view, `None` without timer queries.
+    timer_query: Option<eframe::glow::Query>,
 }
 
-impl FractalGl {
-    pub fn new(gl: &eframe::glow::Context) -> Result<Self> {
+impl Resources {
+    /// The context was reset or replaced, the objects are gone with it. Asked with
+    /// glIsProgram and glIsTexture rather than glGetError, which would swallow the errors
+    /// meant for other code.
+    fn is_lost(&self, gl: &eframe::glow::Context) -> bool {
         use eframe::glow::HasContext as _;
-        unsafe {
-            let program = gl
-                .create_program()
-                .map_err(|e| anyhow!("Cannot create program: {}", e))?;
-
-            let mut vertex_shader_source = String::new();
-            File::open("assets/vertex.shader")
-                .and_then(|mut x| x.read_to_string(&mut vertex_shader_source))
-                .context("Cannot read the Vertex Shaders")?;
-
-            let mut fragment_shader_source = String::new();
-            File::open("assets/fragment.shader")
-                .and_then(|mut x| x.read_to_string(&mut fragment_shader_source))
-                .context("Cannot read the Fragment Shaders")?;
-
-            let shader_sources = [
-                (glow::VERTEX_SHADER, vertex_shader_source),
-                (glow::FRAGMENT_SHADER, fragment_shader_source),
-            ];
+        unsafe { !gl.is_program(self.program) || !gl.is_texture(self.palette_texture) }
+    }
+}
 
-            let shaders: Vec<_> = shader_sources
-                .iter()
-                .map(|(shader_type, shader_source)| {
-                    let shader = gl
-                        .create_shader(*shader_type)
-                        .map_err(|e| anyhow!("Cannot create shader: {}", e) as Error)?;
-                    gl.shader_source(shader, &format!("{}\n{}", "#version 330", shader_source));
-                    gl.compile_shader(shader);
-                    assert!(
-                        gl.get_shader_compile_status(shader),
-                        "Failed to compile Shader {shader_type} - {}:\n{}",
-                        gl.get_shader_info_log(shader),
-                        shader_source
-                    );
-                    gl.attach_shader(program, shader);
-                    Ok(shader)
-                })
-                .collect::<Result<Vec<NativeShader>>>()?;
+pub struct FractalGl {
+    /// Created by [FractalGl::init], or by the first [FractalGl::paint].
+    resources: Option<Resources>,
+    /// Texels of the current palette.
+    palette: Vec<u8>,
+    /// The palette changed, it must be uploaded by the next [FractalGl::paint].
+    palette_changed: bool,
+    /// Image of the image trap, a transparent pixel until one is loaded.
+    trap_image: Image,
+    trap_changed: bool,
+    /// Texture mapped on the exterior, a white pixel until one is loaded.
+    exterior_image: Image,
+    exterior_changed: bool,
+    /// Lookup table of the histogram coloring, the identity until one is set.
+    histogram: Vec<f32>,
+    histogram_changed: bool,
+    /// Offscreen renders to do during the next [FractalGl::paint].
+    thumbnail_requests: Vec<ThumbnailRequest>,
+    finished_thumbnails: Vec<(u64, Image)>,
+    /// Offscreen renders of export tiles with the current palette, 16 bits per channel if
+    /// asked.
+    tile_requests: Vec<(u64, State, [i32; 2], bool)>,
+    finished_tiles: Vec<(u64, Result<Vec<u8>>)>,
+    /// Offscreen renders timed for the benchmark, drawn the number of times given.
+    benchmark_requests: Vec<(u64, State, [i32; 2], u32)>,
+    /// Median time of the draws of each benchmark request, in seconds.
+    finished_benchmarks: Vec<(u64, Result<f64>)>,
+    vertex_shader_source: String,
+    fragment_shader_source: String,
+    /// Replacements of the sections of the fragment shader, by section name.
+    shader_functions: Vec<(&'static str, String)>,
+    /// The shader functions changed, the program must be rebuilt by the next [FractalGl::paint].
+    rebuild_program: bool,
+    /// Error of the last build of the program, with the GLSL log, until a build succeeds.
+    shader_error: Option<String>,
+    coloring_parameters: [f32; COLORING_PARAMETERS],
+    fractal_parameters: [f32; FRACTAL_PARAMETERS],
+    /// Support of the GL context, queried by [FractalGl::init].
+    capabilities: Option<Capabilities>,
+    /// The renderer could not be set up, nothing is drawn.
+    setup_error: Option<String>,
+    /// The timer query is running, its result is read once available.
+    timing: bool,
+    /// GPU time of the last draw of the view measured, in seconds.
+    gpu_time: Option<f64>,
+    /// View whose reference orbit is in the orbit texture, and its number of points.
+    orbit: Option<(OrbitKey, i32)>,
+    /// View in the view framebuffer, without its time, and the number of frames averaged
+    /// into it by the refinement.
+    accumulation: Option<(State, u32)>,
+    /// The accumulation is not complete yet.
+    refining: bool,
+}
+
+/// Number of values in the `u_coloringParameters` uniform array, keep in sync with the
+/// fragment shader.
+pub const COLORING_PARAMETERS: usize = 16;
+/// Number of values in the `u_fractalParameters` uniform array, keep in sync with the
+/// fragment shader.
+pub const FRACTAL_PARAMETERS: usize = 8;
+
+/// Sections of the fragment shader that can be replaced at runtime. They are delimited by
+/// `// BEGIN <name>` and `// END <name>` comments.
+const COLORING_FUNCTION: &str = "COLORING FUNCTION";
+const VALUE_FORMULA: &str = "VALUE FORMULA";
+const CUSTOM_FRACTAL: &str = "CUSTOM FRACTAL";
+
+const VERTEX_SHADER: &str = include_str!("../../assets/vertex.shader");
+const FRAGMENT_SHADER: &str = include_str!("../../assets/fragment.shader");
+const RESAMPLE_SHADER: &str = include_str!("../../assets/resample.shader");
+
+/// Build a program of the two shaders, after the `#version` line `glsl_version`: the
+/// shaders are written for both OpenGL 3.3 and OpenGL ES 3.0, see
+/// [Capabilities::glsl_version].
+fn compile_program(
+    gl: &eframe::glow::Context,
+    glsl_version: &str,
+    vertex_shader_source: &str,
+    fragment_shader_source: &str,
+) -> Result<eframe::glow::Program> {
+    use eframe::glow::HasContext as _;
+    unsafe {
+        let program = gl
+            .create_program()
+            .map_err(|e| anyhow!("Cannot create program: {}", e))?;
 
+        let shader_sources = [
+            (glow::VERTEX_SHADER, vertex_shader_source),
+            (glow::FRAGMENT_SHADER, fragment_shader_source),
+        ];
+
+        let mut shaders: Vec<eframe::glow::Shader> = Vec::new();
+        let mut compiled = || -> Result<()> {
+            for (shader_type, shader_source) in shader_sources {
+                let shader = gl
+                    .create_shader(shader_type)
+                    .map_err(|e| anyhow!("Cannot create shader: {}", e))?;
+                shaders.push(shader);
+                gl.shader_source(shader, &format!("{}\n{}", glsl_version, shader_source));
+                gl.compile_shader(shader);
+                if !gl.get_shader_compile_status(shader) {
+                    let kind = if shader_type == glow::VERTEX_SHADER {
+                        "vertex"
+                    } else {
+                        "fragment"
+                    };
+                    bail!(
+                        "Cannot compile the {} shader:\n{}",
+                        kind,
+                        gl.get_shader_info_log(shader)
+                    );
+                }
+                gl.attach_shader(program, shader);
+            }
             gl.link_program(program);
             if !gl.get_program_link_status(program) {
-                panic!("{}", gl.get_program_info_log(program));
+                bail!(
+                    "Cannot link the program:\n{}",
+                    gl.get_program_info_log(program)
+                );
             }
+            Ok(())
+        };
+        let result = compiled();
 
-            for shader in shaders {
+        // Deleting the program detaches its shaders, they are not needed once linked
+        if result.is_ok() {
+            for &shader in &shaders {
                 gl.detach_shader(program, shader);
-                gl.delete_shader(shader);
             }
+        } else {
+            gl.delete_program(program);
+        }
+        for shader in shaders {
+            gl.delete_shader(shader);
+        }
+        result.map(|()| program)
+    }
+}
+
+impl FractalGl {
+    /// Renderer with the shaders embedded in the binary. The GL objects are created later, by
+    /// [FractalGl::init].
+    pub fn new() -> Self {
+        Self {
+            resources: None,
+            palette: Palette::grayscale().to_rgba8(),
+            palette_changed: true,
+            trap_image: Image {
+                width: 1,
+                height: 1,
+                pixels: vec![0; 4],
+            },
+            trap_changed: true,
+            exterior_image: Image {
+                width: 1,
+                height: 1,
+                pixels: vec![255; 4],
+            },
+            exterior_changed: true,
+            histogram: histogram::identity(),
+            histogram_changed: true,
+            thumbnail_requests: Vec::new(),
+            finished_thumbnails: Vec::new(),
+            tile_requests: Vec::new(),
+            finished_tiles: Vec::new(),
+            benchmark_requests: Vec::new(),
+            finished_benchmarks: Vec::new(),
+            vertex_shader_source: VERTEX_SHADER.to_string(),
+            fragment_shader_source: FRAGMENT_SHADER.to_string(),
+            shader_functions: Vec::new(),
+            rebuild_program: false,
+            shader_error: None,
+            coloring_parameters: [0.0; COLORING_PARAMETERS],
+            fractal_parameters: [0.0; FRACTAL_PARAMETERS],
+            capabilities: None,
+            setup_error: None,
+            timing: false,
+            gpu_time: None,
+            orbit: None,
+            accumulation: None,
+            refining: false,
+        }
+    }
+
+    /// Replace the shaders, the program is rebuilt during the next [FractalGl::paint].
+    pub fn set_shader_sources(&mut self, vertex: String, fragment: String) {
+        self.vertex_shader_source = vertex;
+        self.fragment_shader_source = fragment;
+        self.rebuild_program = true;
+    }
 
+    /// Create the GL objects.
+    pub fn init(&mut self, gl: &eframe::glow::Context) -> Result<()> {
+        use eframe::glow::HasContext as _;
+        let capabilities = Capabilities::query(gl);
+        let glsl_version = capabilities.glsl_version().with_context(|| {
+            format!(
+                "{} is too old, OpenGL 3.3 or OpenGL ES 3.0 is needed",
+                capabilities.api()
+            )
+        })?;
+        let fragment_source = self.fragment_source();
+        let program = match compile_program(
+            gl,
+            glsl_version,
+            &self.vertex_shader_source,
+            &fragment_source,
+        ) {
+            Ok(program) => {
+                self.shader_error = None;
+                program
+            }
+            Err(e) => {
+                // Nothing to keep yet, start with the shaders of the binary
+                error!(
+                    "Cannot build the program, using the built-in shaders: {:?}",
+                    e
+                );
+                self.shader_error = Some(format!("{:#}", e));
+                compile_program(gl, glsl_version, VERTEX_SHADER, FRAGMENT_SHADER)?
+            }
+        };
+        let resample_program = compile_program(gl, glsl_version, VERTEX_SHADER, RESAMPLE_SHADER)?;
+        unsafe {
             let vertex_array = gl
                 .create_vertex_array()
                 .map_err(|e| anyhow!("Cannot create vertex array: {}", e))?;
 
-            Ok(Self {
+            let palette_texture = gl
+                .create_texture()
+                .map_err(|e| anyhow!("Cannot create palette texture: {}", e))?;
+            let trap_texture = gl
+                .create_texture()
+                .map_err(|e| anyhow!("Cannot create trap texture: {}", e))?;
+            let exterior_texture = gl
+                .create_texture()
+                .map_err(|e| anyhow!("Cannot create exterior texture: {}", e))?;
+            let orbit_texture = gl
+                .create_texture()
+                .map_err(|e| anyhow!("Cannot create orbit texture: {}", e))?;
+            let histogram_texture = gl
+                .create_texture()
+                .map_err(|e| anyhow!("Cannot create histogram texture: {}", e))?;
+
+            self.resources = Some(Resources {
                 program,
+                uniforms: Uniforms::query(gl, program),
+                resample_program,
+                resample_uniforms: Uniforms::query(gl, resample_program),
+                view_target: None,
                 vertex_array,
-            })
+                palette_texture,
+                trap_texture,
+                exterior_texture,
+                orbit_texture,
+                histogram_texture,
+                timer_query: if capabilities.timer_query {
+                    gl.create_query().ok()
+                } else {
+                    None
+                },
+            });
+            // Also binds the texture, it does not exist for `is_texture` before that
+            upload_palette(gl, palette_texture, &self.palette);
+            upload_image(gl, trap_texture, &self.trap_image);
+            upload_image(gl, exterior_texture, &self.exterior_image);
+            upload_histogram(gl, histogram_texture, &self.histogram);
+        }
+        self.palette_changed = false;
+        self.trap_changed = false;
+        self.exterior_changed = false;
+        self.histogram_changed = false;
+        self.rebuild_program = false;
+        self.capabilities = Some(capabilities);
+        self.orbit = None;
+        Ok(())
+    }
+
+    /// [FractalGl::init], once: a failure is kept in [FractalGl::setup_error] rather than
+    /// tried again every frame. Returns whether the renderer is ready.
+    pub fn setup(&mut self, gl: &eframe::glow::Context) -> bool {
+        if self.setup_error.is_some() {
+            return false;
+        }
+        match self.init(gl) {
+            Ok(()) => true,
+            Err(e) => {
+                error!("Cannot set up the renderer: {:?}", e);
+                self.setup_error = Some(format!("{:#}", e));
+                false
+            }
+        }
+    }
+
+    /// Replace the `customColor` function of the fragment shader, the program is rebuilt
+    /// during the next [FractalGl::paint].
+    pub fn set_coloring_function(&mut self, glsl: String) {
+        self.set_shader_function(COLORING_FUNCTION, glsl);
+    }
+
+    /// Replace the `valueFormula` function of the fragment shader, the program is rebuilt
+    /// during the next [FractalGl::paint].
+    pub fn set_value_formula(&mut self, glsl: String) {
+        self.set_shader_function(VALUE_FORMULA, glsl);
+    }
+
+    /// Replace the `iterate` function of the custom fractal type, the program is rebuilt
+    /// during the next [FractalGl::paint].
+    pub fn set_custom_fractal(&mut self, glsl: String) {
+        self.set_shader_function(CUSTOM_FRACTAL, glsl);
+    }
+
+    fn set_shader_function(&mut self, section: &'static str, glsl: String) {
+        self.shader_functions.retain(|(name, _)| *name != section);
+        self.shader_functions.push((section, glsl));
+        self.rebuild_program = true;
+    }
+
+    /// Values of the `u_coloringParameters` uniform used by the `customColor` function.
+    pub fn set_coloring_parameters(&mut self, parameters: [f32; COLORING_PARAMETERS]) {
+        if parameters != self.coloring_parameters {
+            self.accumulation = None;
+        }
+        self.coloring_parameters = parameters;
+    }
+
+    /// Values of the `u_fractalParameters` uniform used by the `iterate` function of the
+    /// plugins.
+    pub fn set_fractal_parameters(&mut self, parameters: [f32; FRACTAL_PARAMETERS]) {
+        if parameters != self.fractal_parameters {
+            self.accumulation = None;
+        }
+        self.fractal_parameters = parameters;
+    }
+
+    /// The fragment shader, with the replaced sections.
+    fn fragment_source(&self) -> String {
+        let mut source = self.fragment_shader_source.clone();
+        for (section, glsl) in &self.shader_functions {
+            let (begin, end) = (format!("// BEGIN {section}"), format!("// END {section}"));
+            let (Some(start), Some(stop)) = (source.find(&begin), source.find(&end)) else {
+                error!("The fragment shader has no {} section", section);
+                continue;
+            };
+            source = format!(
+                "{}{}\n{}\n{}",
+                &source[..start],
+                begin,
+                glsl,
+                &source[stop..]
+            );
+        }
+        source
+    }
+
+    fn rebuild_program(&mut self, gl: &eframe::glow::Context) {
+        use eframe::glow::HasContext as _;
+        // Before the resources are borrowed
+        let fragment_source = self.fragment_source();
+        let (Some(resources), Some(glsl_version)) = (
+            &mut self.resources,
+            self.capabilities
+                .as_ref()
+                .and_then(Capabilities::glsl_version),
+        ) else {
+            return;
+        };
+        match compile_program(
+            gl,
+            glsl_version,
+            &self.vertex_shader_source,
+            &fragment_source,
+        ) {
+            Ok(program) => {
+                unsafe { gl.delete_program(resources.program) };
+                resources.program = program;
+                resources.uniforms = Uniforms::query(gl, program);
+                self.shader_error = None;
+            }
+            Err(e) => {
+                error!(
+                    "Cannot rebuild the program, keeping the previous one: {:?}",
+                    e
+                );
+                self.shader_error = Some(format!("{:#}", e));
+            }
+        }
+    }
+
+    /// What the GL context supports, known once the renderer is set up.
+    pub fn capabilities(&self) -> Option<&Capabilities> {
+        self.capabilities.as_ref()
+    }
+
+    /// Why the renderer cannot draw on this GL context, if it cannot.
+    pub fn setup_error(&self) -> Option<&str> {
+        self.setup_error.as_deref()
+    }
+
+    /// Error of the last build of the program with the GLSL log, the previous program is
+    /// still used.
+    pub fn shader_error(&self) -> Option<&str> {
+        self.shader_error.as_deref()
+    }
+
+    pub fn destroy(&mut self, gl: &eframe::glow::Context) {
+        use eframe::glow::HasContext as _;
+        if let Some(resources) = self.resources.take() {
+            unsafe {
+                gl.delete_program(resources.program);
+                gl.delete_program(resources.resample_program);
+                if let Some(target) = resources.view_target {
+                    gl.delete_framebuffer(target.framebuffer);
+                    gl.delete_texture(target.texture);
+                }
+                gl.delete_vertex_array(resources.vertex_array);
+                gl.delete_texture(resources.palette_texture);
+                gl.delete_texture(resources.trap_texture);
+                gl.delete_texture(resources.exterior_texture);
+                gl.delete_texture(resources.orbit_texture);
+                gl.delete_texture(resources.histogram_texture);
+                if let Some(query) = resources.timer_query {
+                    gl.delete_query(query);
+                }
+            }
+        }
+    }
+
+    /// The palette is sent to the GPU during the next [FractalGl::paint].
+    pub fn set_palette(&mut self, palette: &Palette) {
+        self.palette = palette.to_rgba8();
+        self.palette_changed = true;
+    }
+
+    /// The image of the image trap is sent to the GPU during the next [FractalGl::paint].
+    pub fn set_trap_image(&mut self, image: Image) {
+        self.trap_image = image;
+        self.trap_changed = true;
+    }
+
+    /// The texture of the exterior is sent to the GPU during the next [FractalGl::paint].
+    pub fn set_exterior_image(&mut self, image: Image) {
+        self.exterior_image = image;
+        self.exterior_changed = true;
+    }
+
+    /// The lookup table of the histogram coloring, [histogram::HISTOGRAM_SIZE] values, is
+    /// sent to the GPU during the next [FractalGl::paint].
+    pub fn set_histogram(&mut self, histogram: Vec<f32>) {
+        self.histogram = histogram;
+        self.histogram_changed = true;
+    }
+
+    /// The thumbnail is rendered during the next [FractalGl::paint], and can be retrieved with
+    /// [FractalGl::take_thumbnails] after that.
+    /// It uses `palette` if given, the current palette otherwise.
+    pub fn request_thumbnail(
+        &mut self,
+        id: u64,
+        state: State,
+        size: [i32; 2],
+        palette: Option<&Palette>,
+    ) {
+        self.thumbnail_requests.push(ThumbnailRequest {
+            id,
+            state,
+            size,
+            palette: palette.map(Palette::to_rgba8),
+        });
+    }
+
+    pub fn has_pending_thumbnails(&self) -> bool {
+        !self.thumbnail_requests.is_empty()
+    }
+
+    pub fn take_thumbnails(&mut self) -> Vec<(u64, Image)> {
+        std::mem::take(&mut self.finished_thumbnails)
+    }
+
+    /// Feed `hasher` with everything the renders depend on besides the [State]: the shader
+    /// with its replaced functions, the palette, the images and the uniform arrays.
+    pub fn hash_look(&self, hasher: &mut StableHasher) {
+        hasher.write(self.fragment_source().as_bytes());
+        hasher.write(&self.palette);
+        for image in [&self.trap_image, &self.exterior_image] {
+            hasher.write(
+                &[image.width as u64, image.height as u64]
+                    .map(u64::to_le_bytes)
+                    .concat(),
+            );
+            hasher.write(&image.pixels);
+        }
+        for values in [
+            &self.histogram[..],
+            &self.coloring_parameters,
+            &self.fractal_parameters,
+        ] {
+            let bytes: Vec<u8> = values
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect();
+            hasher.write(&bytes);
+        }
+    }
+
+    /// Like [FractalGl::request_thumbnail], for the tiles of an export. Their RGBA pixels are
+    /// retrieved with [FractalGl::take_tiles], with 16 bits per channel in the native byte
+    /// order if `deep`.
+    pub fn request_tile(&mut self, id: u64, state: State, size: [i32; 2], deep: bool) {
+        self.tile_requests.push((id, state, size, deep));
+    }
+
+    pub fn take_tiles(&mut self) -> Vec<(u64, Result<Vec<u8>>)> {
+        std::mem::take(&mut self.finished_tiles)
+    }
+
+    /// Time `frames` draws of `state` on an offscreen framebuffer of `size` pixels during the
+    /// next [FractalGl::paint]. The median is retrieved with [FractalGl::take_benchmarks].
+    pub fn request_benchmark(&mut self, id: u64, state: State, size: [i32; 2], frames: u32) {
+        self.benchmark_requests.push((id, state, size, frames));
+    }
+
+    pub fn take_benchmarks(&mut self) -> Vec<(u64, Result<f64>)> {
+        std::mem::take(&mut self.finished_benchmarks)
+    }
+
+    /// Drop the tiles of a cancelled export, rendered or not.
+    pub fn forget_tiles(&mut self) {
+        self.tile_requests.clear();
+        self.finished_tiles.clear();
+    }
+
+    pub fn paint(
+        &mut self,
+        gl: &eframe::glow::Context,
+        state: State,
+        quality: Quality,
+        refinement: Option<Refinement>,
+        paint_info: PaintCallbackInfo,
+    ) {
+        if self
+            .resources
+            .as_ref()
+            .is_some_and(|resources| resources.is_lost(gl))
+        {
+            // Nothing to delete, the objects went away with the old context
+            warn!("The GL context was lost, recreating the renderer");
+            self.resources = None;
+            self.timing = false;
+            self.orbit = None;
+            self.accumulation = None;
+        }
+        if self.resources.is_none() && !self.setup(gl) {
+            return;
+        }
+        if self.palette_changed
+            || self.trap_changed
+            || self.exterior_changed
+            || self.histogram_changed
+            || self.rebuild_program
+        {
+            // The refinement accumulated an outdated view
+            self.accumulation = None;
+        }
+        if let Some(resources) = &self.resources {
+            if std::mem::take(&mut self.palette_changed) {
+                upload_palette(gl, resources.palette_texture, &self.palette);
+            }
+            if std::mem::take(&mut self.trap_changed) {
+                upload_image(gl, resources.trap_texture, &self.trap_image);
+            }
+            if std::mem::take(&mut self.exterior_changed) {
+                upload_image(gl, resources.exterior_texture, &self.exterior_image);
+            }
+            if std::mem::take(&mut self.histogram_changed) {
+                upload_histogram(gl, resources.histogram_texture, &self.histogram);
+            }
+        }
+        if std::mem::take(&mut self.rebuild_program) {
+            self.rebuild_program(gl);
+        }
+
+        let mut palette_replaced = false;
+        for request in std::mem::take(&mut self.thumbnail_requests) {
+            let ThumbnailRequest {
+                id,
+                state,
+                size: [width, height],
+                palette,
+            } = request;
+            if let (Some(palette), Some(resources)) = (&palette, &self.resources) {
+                upload_palette(gl, resources.palette_texture, palette);
+                palette_replaced = true;
+            }
+            match self.render_offscreen(gl, state, width, height) {
+                Ok(image) => self.finished_thumbnails.push((id, image)),
+                Err(e) => error!("Cannot render thumbnail: {:?}", e),
+            }
+        }
+        if let (true, Some(resources)) = (palette_replaced, &self.resources) {
+            upload_palette(gl, resources.palette_texture, &self.palette);
+        }
+        for (id, state, [width, height], deep) in std::mem::take(&mut self.tile_requests) {
+            let pixels = self.render_offscreen_pixels(gl, state, width, height, deep);
+            self.finished_tiles.push((id, pixels));
+        }
+        for (id, state, size, frames) in std::mem::take(&mut self.benchmark_requests) {
+            let time = self.time_offscreen(gl, state, size, frames);
+            self.finished_benchmarks.push((id, time));
+        }
+
+        let viewport = Viewport::from_paint_info(&paint_info);
+        let state = State {
+            supersampling: quality.shader_samples(state.supersampling),
+            ..state
+        };
+        let jitter = quality.jitter.then_some(0);
+        self.refining = false;
+        if quality.supersampling == 1 && refinement.is_none() {
+            self.draw_timed(gl, &state, viewport, jitter);
+        } else if let Err(e) = self.draw_view(gl, &state, quality, refinement, viewport) {
+            error!("Cannot render the view offscreen: {:?}", e);
+            self.draw_timed(gl, &state, viewport, jitter);
+        }
+    }
+
+    /// GPU time of a recent draw of the view, in seconds, if the driver can measure it.
+    pub fn gpu_time(&self) -> Option<f64> {
+        self.gpu_time
+    }
+
+    /// Framebuffer the view is rendered into offscreen, of `size` pixels. It is created on
+    /// first use, and replaced when the size changes.
+    fn view_framebuffer(
+        &mut self,
+        gl: &eframe::glow::Context,
+        size: [i32; 2],
+    ) -> Result<eframe::glow::Framebuffer> {
+        use eframe::glow::HasContext as _;
+        let resources = self.resources.as_mut().context("No renderer")?;
+        if let Some(target) = &resources.view_target {
+            if target.size == size {
+                return Ok(target.framebuffer);
+            }
+        }
+        self.accumulation = None;
+        let framebuffer = unsafe {
+            if let Some(target) = resources.view_target.take() {
+                gl.delete_framebuffer(target.framebuffer);
+                gl.delete_texture(target.texture);
+            }
+            let previous_framebuffer = gl.get_parameter_framebuffer(glow::FRAMEBUFFER_BINDING);
+            let texture = gl
+                .create_texture()
+                .map_err(|e| anyhow!("Cannot create view texture: {}", e))?;
+            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::RGBA8 as i32,
+                size[0],
+                size[1],
+                0,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                PixelUnpackData::Slice(None),
+            );
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_MIN_FILTER,
+                glow::NEAREST as i32,
+            );
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            let framebuffer = gl
+                .create_framebuffer()
+                .map_err(|e| anyhow!("Cannot create view framebuffer: {}", e))?;
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(texture),
+                0,
+            );
+            let complete =
+                gl.check_framebuffer_status(glow::FRAMEBUFFER) == glow::FRAMEBUFFER_COMPLETE;
+            gl.bind_framebuffer(glow::FRAMEBUFFER, previous_framebuffer);
+            if !complete {
+                gl.delete_framebuffer(framebuffer);
+                gl.delete_texture(texture);
+                bail!("View framebuffer of {}x{} is incomplete", size[0], size[1]);
+            }
+            resources.view_target = Some(ViewTarget {
+                texture,
+                framebuffer,
+                size,
+            });
+            framebuffer
+        };
+        Ok(framebuffer)
+    }
+
+    /// Draw `state` into the view framebuffer, [Quality::supersampling] times larger than
+    /// `viewport` along each axis, and scale it into `viewport` of the current framebuffer.
+    /// With a `refinement`, the view is coarse while it changes and refined over the next
+    /// frames once it is idle.
+    fn draw_view(
+        &mut self,
+        gl: &eframe::glow::Context,
+        state: &State,
+        quality: Quality,
+        refinement: Option<Refinement>,
+        viewport: Viewport,
+    ) -> Result<()> {
+        use eframe::glow::HasContext as _;
+        let [width, height] = [viewport.width_px as i32, viewport.height_px as i32];
+        let max_size = unsafe { gl.get_parameter_i32(glow::MAX_TEXTURE_SIZE) };
+        // Lower, down to none, when the texture would be too large for the GPU
+        let factor = quality
+            .supersampling
+            .min(max_size / width.max(height).max(1))
+            .max(1);
+        let framebuffer = self.view_framebuffer(gl, [width * factor, height * factor])?;
+        let jitter = quality.jitter.then_some(0);
+
+        // Pixels drawn per pixel of the viewport, the jitter seed of the samples, and the
+        // weight of the frame averaged with the previous ones
+        let (scale, pass) = match refinement {
+            Some(Refinement::Preview) => {
+                self.accumulation = None;
+                (1.0 / PREVIEW_DIVISOR as f32, Some((jitter, None)))
+            }
+            Some(Refinement::Refine { frames }) => {
+                let view = State {
+                    time: 0.0,
+                    ..*state
+                };
+                let count = match self.accumulation {
+                    Some((accumulated, count)) if accumulated == view => count,
+                    _ => 0,
+                };
+                self.refining = count + 1 < frames;
+                let pass = (count < frames).then(|| {
+                    self.accumulation = Some((view, count + 1));
+                    match count {
+                        0 => (jitter, None),
+                        // Each frame has its own random samples
+                        _ => (Some(count), Some(1.0 / (count + 1) as f32)),
+                    }
+                });
+                (factor as f32, pass)
+            }
+            None => {
+                self.accumulation = None;
+                (factor as f32, Some((jitter, None)))
+            }
+        };
+
+        unsafe {
+            if let Some((jitter, weight)) = pass {
+                let size = [width, height].map(|size| (size as f32 * scale).ceil() as i32);
+                let previous_framebuffer = gl.get_parameter_framebuffer(glow::FRAMEBUFFER_BINDING);
+                let mut previous_viewport = [0; 4];
+                gl.get_parameter_i32_slice(glow::VIEWPORT, &mut previous_viewport);
+                let scissor_enabled = gl.is_enabled(glow::SCISSOR_TEST);
+
+                gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+                gl.disable(glow::SCISSOR_TEST);
+                gl.viewport(0, 0, size[0], size[1]);
+                match weight {
+                    Some(weight) => {
+                        gl.enable(glow::BLEND);
+                        gl.blend_color(0.0, 0.0, 0.0, weight);
+                        gl.blend_func(glow::CONSTANT_ALPHA, glow::ONE_MINUS_CONSTANT_ALPHA);
+                    }
+                    None => {
+                        gl.disable(glow::BLEND);
+                        // Transparent where the magnifier lens draws nothing
+                        gl.clear_color(0.0, 0.0, 0.0, 0.0);
+                        gl.clear(glow::COLOR_BUFFER_BIT);
+                    }
+                }
+                let scaled = State {
+                    zoom: state.zoom * scale,
+                    ..*state
+                };
+                self.draw_timed(gl, &scaled, Viewport::offscreen(size[0], size[1]), jitter);
+
+                gl.bind_framebuffer(glow::FRAMEBUFFER, previous_framebuffer);
+                let [x, y, w, h] = previous_viewport;
+                gl.viewport(x, y, w, h);
+                if scissor_enabled {
+                    gl.enable(glow::SCISSOR_TEST);
+                }
+            }
+
+            let Some(resources) = &self.resources else {
+                return Ok(());
+            };
+            let Some(target) = &resources.view_target else {
+                return Ok(());
+            };
+            let (texels, pixels) = match refinement {
+                Some(Refinement::Preview) => (1, PREVIEW_DIVISOR),
+                _ => (factor, 1),
+            };
+            let uniforms = &resources.resample_uniforms;
+            gl.use_program(Some(resources.resample_program));
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, Some(target.texture));
+            gl.uniform_1_i32(uniforms.get("u_image"), 0);
+            gl.uniform_1_i32(uniforms.get("u_factor"), texels);
+            gl.uniform_1_i32(uniforms.get("u_divisor"), pixels);
+            let (x, y) = (viewport.left_px, viewport.bottom_px);
+            gl.uniform_2_f32(uniforms.get("u_origin"), x, y);
+            // The texels are premultiplied by their coverage of the lens
+            gl.enable(glow::BLEND);
+            gl.blend_func(glow::ONE, glow::ONE_MINUS_SRC_ALPHA);
+            gl.bind_vertex_array(Some(resources.vertex_array));
+            gl.draw_arrays(glow::TRIANGLES, 0, 6);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+        }
+        Ok(())
+    }
+
+    /// The view is refined over the next frames, it needs them to be drawn.
+    pub fn is_refining(&self) -> bool {
+        self.refining
+    }
+
+    /// [FractalGl::draw], timed by the timer query when it is not busy with a previous draw.
+    /// The result is read in a later frame, once available, to not wait for the GPU.
+    fn draw_timed(
+        &mut self,
+        gl: &eframe::glow::Context,
+        state: &State,
+        viewport: Viewport,
+        jitter: Option<u32>,
+    ) {
+        use eframe::glow::HasContext as _;
+        let Some(query) = self.resources.as_ref().and_then(|r| r.timer_query) else {
+            self.draw(gl, state, viewport, jitter);
+            return;
+        };
+        unsafe {
+            if self.timing && gl.get_query_parameter_u32(query, glow::QUERY_RESULT_AVAILABLE) != 0 {
+                let nanoseconds = gl.get_query_parameter_u32(query, glow::QUERY_RESULT);
+                self.gpu_time = Some(nanoseconds as f64 * 1e-9);
+                self.timing = false;
+            }
+            if self.timing {
+                self.draw(gl, state, viewport, jitter);
+            } else {
+                gl.begin_query(glow::TIME_ELAPSED, query);
+                self.draw(gl, state, viewport, jitter);
+                gl.end_query(glow::TIME_ELAPSED);
+                self.timing = true;
+            }
         }
     }
 
-    pub fn destroy(&self, gl: &eframe::glow::Context) {
+    /// Render `state` into a new framebuffer and read it back. The GL state used by egui
+    /// (framebuffer, viewport and scissor) is restored afterward.
+    pub fn render_offscreen(
+        &mut self,
+        gl: &eframe::glow::Context,
+        state: State,
+        width: i32,
+        height: i32,
+    ) -> Result<Image> {
+        let pixels = self.render_offscreen_pixels(gl, state, width, height, false)?;
+        Ok(Image {
+            width: width as usize,
+            height: height as usize,
+            pixels,
+        })
+    }
+
+    /// RGBA pixels of [FractalGl::render_offscreen], top row first. With 16 bits per channel
+    /// in the native byte order if `deep`, 8 bits otherwise.
+    pub fn render_offscreen_pixels(
+        &mut self,
+        gl: &eframe::glow::Context,
+        state: State,
+        width: i32,
+        height: i32,
+        deep: bool,
+    ) -> Result<Vec<u8>> {
         use eframe::glow::HasContext as _;
+        if deep && !self.capabilities.as_ref().is_some_and(|c| c.norm16) {
+            bail!("This GPU cannot render 16 bits per channel");
+        }
+        let (internal_format, data_type, pixel_bytes) = if deep {
+            (glow::RGBA16, glow::UNSIGNED_SHORT, 8)
+        } else {
+            (glow::RGBA8, glow::UNSIGNED_BYTE, 4)
+        };
         unsafe {
-            gl.delete_program(self.program);
-            gl.delete_vertex_array(self.vertex_array);
+            let previous_framebuffer = gl.get_parameter_framebuffer(glow::FRAMEBUFFER_BINDING);
+            let mut previous_viewport = [0; 4];
+            gl.get_parameter_i32_slice(glow::VIEWPORT, &mut previous_viewport);
+            let scissor_enabled = gl.is_enabled(glow::SCISSOR_TEST);
+
+            let texture = gl
+                .create_texture()
+                .map_err(|e| anyhow!("Cannot create offscreen texture: {}", e))?;
+            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                internal_format as i32,
+                width,
+                height,
+                0,
+                glow::RGBA,
+                data_type,
+                PixelUnpackData::Slice(None),
+            );
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_MIN_FILTER,
+                glow::NEAREST as i32,
+            );
+            gl.bind_texture(glow::TEXTURE_2D, None);
+
+            let framebuffer = gl
+                .create_framebuffer()
+                .map_err(|e| anyhow!("Cannot create offscreen framebuffer: {}", e))?;
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(texture),
+                0,
+            );
+
+            let result =
+                if gl.check_framebuffer_status(glow::FRAMEBUFFER) != glow::FRAMEBUFFER_COMPLETE {
+                    Err(anyhow!(
+                        "Offscreen framebuffer of {}x{} is incomplete",
+                        width,
+                        height
+                    ))
+                } else {
+                    gl.disable(glow::SCISSOR_TEST);
+                    gl.viewport(0, 0, width, height);
+                    self.draw(gl, &state, Viewport::offscreen(width, height), None);
+
+                    let row = width as usize * pixel_bytes;
+                    let mut pixels = vec![0u8; row * height as usize];
+                    gl.read_pixels(
+                        0,
+                        0,
+                        width,
+                        height,
+                        glow::RGBA,
+                        data_type,
+                        PixelPackData::Slice(Some(pixels.as_mut_slice())),
+                    );
+                    // OpenGL rows go from the bottom to the top
+                    Ok(pixels.chunks_exact(row).rev().flatten().copied().collect())
+                };
+
+            gl.bind_framebuffer(glow::FRAMEBUFFER, previous_framebuffer);
+            gl.delete_framebuffer(framebuffer);
+            gl.delete_texture(texture);
+            let [x, y, w, h] = previous_viewport;
+            gl.viewport(x, y, w, h);
+            if scissor_enabled {
+                gl.enable(glow::SCISSOR_TEST);
+            }
+            result
         }
     }
 
-    pub fn paint(&self, gl: &eframe::glow::Context, state: State, paint_info: PaintCallbackInfo) {
+    /// Median time of `frames` draws of `state` into the view framebuffer resized to `size`,
+    /// in seconds. Measured by timer queries when available, waiting for the GPU to finish
+    /// otherwise. The view is drawn again from scratch afterward.
+    fn time_offscreen(
+        &mut self,
+        gl: &eframe::glow::Context,
+        state: State,
+        size: [i32; 2],
+        frames: u32,
+    ) -> Result<f64> {
         use eframe::glow::HasContext as _;
+        let framebuffer = self.view_framebuffer(gl, size)?;
+        self.accumulation = None;
+        let timer_query = self.capabilities.as_ref().is_some_and(|c| c.timer_query);
+        let mut times = Vec::new();
         unsafe {
-            gl.use_program(Some(self.program));
+            let previous_framebuffer = gl.get_parameter_framebuffer(glow::FRAMEBUFFER_BINDING);
+            let mut previous_viewport = [0; 4];
+            gl.get_parameter_i32_slice(glow::VIEWPORT, &mut previous_viewport);
+            let scissor_enabled = gl.is_enabled(glow::SCISSOR_TEST);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+            gl.disable(glow::SCISSOR_TEST);
+            gl.viewport(0, 0, size[0], size[1]);
+            let viewport = Viewport::offscreen(size[0], size[1]);
+
+            // The first draw compiles lazily on some drivers and uploads the reference orbit
+            self.draw(gl, &state, viewport, None);
+            gl.finish();
+            let query = if timer_query {
+                gl.create_query().ok()
+            } else {
+                None
+            };
+            for _ in 0..frames.max(1) {
+                if let Some(query) = query {
+                    gl.begin_query(glow::TIME_ELAPSED, query);
+                    self.draw(gl, &state, viewport, None);
+                    gl.end_query(glow::TIME_ELAPSED);
+                    // Waits for the result, the benchmark is not interactive
+                    let nanoseconds = gl.get_query_parameter_u32(query, glow::QUERY_RESULT);
+                    times.push(nanoseconds as f64 * 1e-9);
+                } else {
+                    let start = web_time::Instant::now();
+                    self.draw(gl, &state, viewport, None);
+                    gl.finish();
+                    times.push(start.elapsed().as_secs_f64());
+                }
+            }
+            if let Some(query) = query {
+                gl.delete_query(query);
+            }
 
+            gl.bind_framebuffer(glow::FRAMEBUFFER, previous_framebuffer);
+            let [x, y, w, h] = previous_viewport;
+            gl.viewport(x, y, w, h);
+            if scissor_enabled {
+                gl.enable(glow::SCISSOR_TEST);
+            }
+        }
+        times.sort_by(f64::total_cmp);
+        Ok(times[times.len() / 2])
+    }
+
+    /// Compute and upload the reference orbit of `state`, unless it is the one uploaded.
+    /// Returns its number of points.
+    fn update_orbit(&mut self, gl: &eframe::glow::Context, state: &State) -> i32 {
+        let key = OrbitKey::new(state);
+        match self.orbit {
+            Some((uploaded, length)) if uploaded == key => length,
+            _ => {
+                let Some(resources) = &self.resources else {
+                    return 0;
+                };
+                let orbit = perturbation::reference_orbit(&key);
+                upload_orbit(gl, resources.orbit_texture, &orbit);
+                let length = orbit.len() as i32;
+                self.orbit = Some((key, length));
+                length
+            }
+        }
+    }
+
+    /// Draw `state` into `viewport` of the current framebuffer, with the samples of each
+    /// pixel at random positions drawn from the seed `jitter`, if any.
+    fn draw(
+        &mut self,
+        gl: &eframe::glow::Context,
+        state: &State,
+        viewport: Viewport,
+        jitter: Option<u32>,
+    ) {
+        use eframe::glow::HasContext as _;
+        let orbit = perturbation::is_deep(state).then(|| self.update_orbit(gl, state));
+        let Some(resources) = &self.resources else {
+            return;
+        };
+        unsafe {
+            gl.use_program(Some(resources.program));
+            self.set_uniforms(gl, &resources.uniforms, state, viewport, jitter, orbit);
+
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, Some(resources.palette_texture));
+            gl.active_texture(glow::TEXTURE1);
+            gl.bind_texture(glow::TEXTURE_2D, Some(resources.trap_texture));
+            gl.active_texture(glow::TEXTURE2);
+            gl.bind_texture(glow::TEXTURE_2D, Some(resources.exterior_texture));
+            gl.active_texture(glow::TEXTURE3);
+            gl.bind_texture(glow::TEXTURE_2D, Some(resources.orbit_texture));
+            gl.active_texture(glow::TEXTURE4);
+            gl.bind_texture(glow::TEXTURE_2D, Some(resources.histogram_texture));
+
+            gl.bind_vertex_array(Some(resources.vertex_array));
+            gl.draw_arrays(glow::TRIANGLES, 0, 6);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            gl.active_texture(glow::TEXTURE3);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            gl.active_texture(glow::TEXTURE2);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            gl.active_texture(glow::TEXTURE1);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            // egui expects the first texture unit to be active
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+        }
+    }
+
+    /// Values of the uniforms of the fractal program for `state`, it must be in use. `orbit`
+    /// is the length of the reference orbit of the deep zoom, `None` without perturbation.
+    fn set_uniforms(
+        &self,
+        gl: &eframe::glow::Context,
+        uniforms: &Uniforms,
+        state: &State,
+        viewport: Viewport,
+        jitter: Option<u32>,
+        orbit: Option<i32>,
+    ) {
+        use eframe::glow::HasContext as _;
+        unsafe {
             let mappings = [
                 ("u_fractalZoom", state.zoom),
                 ("u_brightness", state.brightness),
@@ -97,49 +1276,352 @@ impl FractalGl {
                 ("u_r", state.r),
                 ("u_g", state.g),
                 ("u_b", state.b),
+                ("u_time", state.time),
             ];
 
             for (label, value) in mappings.iter() {
-                gl.uniform_1_f32(
-                    gl.get_uniform_location(self.program, label).as_ref(),
-                    *value,
-                );
+                gl.uniform_1_f32(uniforms.get(label), *value);
             }
 
             gl.uniform_1_i32(
-                gl.get_uniform_location(self.program, "u_highQuality")
-                    .as_ref(),
+                uniforms.get("u_highQuality"),
                 if state.high_quality { 1 } else { 0 },
             );
+            gl.uniform_1_i32(uniforms.get("u_maxIterations"), state.iterations() as i32);
+            gl.uniform_1_i32(uniforms.get("u_supersampling"), state.supersampling);
+            gl.uniform_1_i32(
+                uniforms.get("u_jitter"),
+                if jitter.is_some() { 1 } else { 0 },
+            );
+            gl.uniform_1_i32(uniforms.get("u_jitterSeed"), jitter.unwrap_or(0) as i32);
+            let radius = if state.circular {
+                0.5 * viewport.width_px.min(viewport.height_px)
+            } else {
+                0.0
+            };
+            gl.uniform_3_f32(
+                uniforms.get("u_circle"),
+                viewport.left_px + 0.5 * viewport.width_px,
+                viewport.bottom_px + 0.5 * viewport.height_px,
+                radius,
+            );
+
+            gl.uniform_1_i32(
+                uniforms.get("u_usePalette"),
+                if state.use_palette { 1 } else { 0 },
+            );
 
+            // Strength of the shadows, midtones and highlights wheels at their rim
+            for ((name, strength), offset) in
+                [("u_lift", 0.1), ("u_balanceGamma", 0.3), ("u_gain", 0.3)]
+                    .into_iter()
+                    .zip(state.color_balance)
+            {
+                let [r, g, b] = rgb_shift(offset).map(|shift| shift * strength);
+                gl.uniform_3_f32(uniforms.get(name), r, g, b);
+            }
+
+            gl.uniform_1_i32(uniforms.get("u_palette"), 0);
+
+            gl.uniform_1_i32(uniforms.get("u_trap"), 1);
+            gl.uniform_1_f32(uniforms.get("u_trapScale"), state.trap_scale);
+            gl.uniform_1_f32(uniforms.get("u_trapRotation"), state.trap_rotation);
+            gl.uniform_1_i32(uniforms.get("u_trapShape"), state.trap_shape as i32);
+            let [x, y] = state.trap_center;
+            gl.uniform_2_f32(uniforms.get("u_trapCenter"), x, y);
+            gl.uniform_1_f32(uniforms.get("u_trapSize"), state.trap_size);
+
+            gl.uniform_1_i32(uniforms.get("u_exterior"), 2);
+            gl.uniform_1_f32(uniforms.get("u_textureBlend"), state.texture_blend);
+            gl.uniform_1_i32(
+                uniforms.get("u_textureMirror"),
+                if state.texture_mirror { 1 } else { 0 },
+            );
+
+            gl.uniform_1_i32(
+                uniforms.get("u_customColoring"),
+                if state.custom_coloring { 1 } else { 0 },
+            );
             gl.uniform_1_i32(
-                gl.get_uniform_location(self.program, "u_fractal_type")
-                    .as_ref(),
-                state.fractal_type as i32,
+                uniforms.get("u_valueFormula"),
+                if state.value_formula { 1 } else { 0 },
+            );
+            gl.uniform_1_f32_slice(
+                uniforms.get("u_coloringParameters"),
+                &self.coloring_parameters,
+            );
+            gl.uniform_1_f32_slice(
+                uniforms.get("u_fractalParameters"),
+                &self.fractal_parameters,
             );
 
-            // Not happy about needing to call this method here and pass around the paint_info,
-            // but ViewportInPixels (type of vieport) isn't publicly available so I couldn't find
-            // a way to pass it as argument, and creating a whole new type was a bit overkill.
-            let viewport = paint_info.viewport_in_pixels();
+            gl.uniform_1_i32(
+                uniforms.get("u_coloringAlgorithm"),
+                state.coloring_algorithm as i32,
+            );
+            gl.uniform_1_f32(uniforms.get("u_stripeDensity"), state.stripe_density);
+            gl.uniform_1_f32(uniforms.get("u_stripeMix"), state.stripe_mix);
+            gl.uniform_1_f32(uniforms.get("u_tiaBlend"), state.tia_blend);
+            gl.uniform_1_f32(uniforms.get("u_curvatureMix"), state.curvature_mix);
+            gl.uniform_1_f32(uniforms.get("u_decompositionMix"), state.decomposition_mix);
+            gl.uniform_1_i32(
+                uniforms.get("u_fieldLines"),
+                if state.field_lines { 1 } else { 0 },
+            );
+
+            gl.uniform_1_i32(uniforms.get("u_fractal_type"), state.fractal_type as i32);
+
+            let [x, y] = viewport.fractal_position(state);
+            gl.uniform_2_f32(uniforms.get("u_fractalPosition"), x, y);
 
-            let u_fractal_position = gl.get_uniform_location(self.program, "u_fractalPosition");
             gl.uniform_2_f32(
-                u_fractal_position.as_ref(),
-                // The viewport is
-                state.center_position.x
-                    + viewport.left_px as f32 / state.zoom // shift to skip the edge
-                    + 0.5 * viewport.width_px as f32 / state.zoom, // shift to put the center_position in the middle
-                state.center_position.y
-                    + viewport.top_px as f32 / state.zoom
-                    + 0.5 * viewport.height_px as f32 / state.zoom,
+                uniforms.get("u_viewCenter"),
+                -state.center_position.x as f32,
+                -state.center_position.y as f32,
             );
 
-            let c_julia = gl.get_uniform_location(self.program, "u_cJulia");
-            gl.uniform_2_f32(c_julia.as_ref(), state.c_julia.x, state.c_julia.y);
+            gl.uniform_1_i32(
+                uniforms.get("u_perturbation"),
+                if orbit.is_some() { 1 } else { 0 },
+            );
+            gl.uniform_1_i32(uniforms.get("u_referenceOrbit"), 3);
+            gl.uniform_1_i32(uniforms.get("u_referenceLength"), orbit.unwrap_or(0));
+            gl.uniform_1_i32(
+                uniforms.get("u_iterationColoring"),
+                state.iteration_coloring as i32,
+            );
+            gl.uniform_1_i32(uniforms.get("u_boundaryStyle"), state.boundary_style as i32);
+            gl.uniform_1_f32(uniforms.get("u_boundaryWidth"), state.boundary_width);
+            gl.uniform_1_f32(uniforms.get("u_exponent"), state.exponent);
+            gl.uniform_1_i32(uniforms.get("u_histogram"), 4);
+            gl.uniform_1_f32(uniforms.get("u_histogramRange"), value_range(state));
+            let [x, y] = viewport.center_px();
+            gl.uniform_2_f32(uniforms.get("u_viewportCenter"), x, y);
+            gl.uniform_matrix_2_f32_slice(uniforms.get("u_skew"), false, &state.skew);
+            gl.uniform_1_f32(uniforms.get("u_rotation"), state.rotation);
 
-            gl.bind_vertex_array(Some(self.vertex_array));
-            gl.draw_arrays(glow::TRIANGLES, 0, 6);
+            let [x, y] = [state.c_julia.x, state.c_julia.y];
+            gl.uniform_2_f32(uniforms.get("u_cJulia"), x, y);
+
+            gl.uniform_2_f32_slice(
+                uniforms.get("u_newtonRoots"),
+                state.newton_roots.as_flattened(),
+            );
+            gl.uniform_1_i32(
+                uniforms.get("u_lyapunovSequence"),
+                state.lyapunov_sequence.bits() as i32,
+            );
+            gl.uniform_1_i32(
+                uniforms.get("u_lyapunovLength"),
+                state.lyapunov_sequence.len() as i32,
+            );
+            gl.uniform_1_f32(uniforms.get("u_novaRelaxation"), state.nova_relaxation);
+            // The time only moves the colors while they cycle
+            let phase = if state.color_cycling {
+                (state.time * state.cycling_speed).fract()
+            } else {
+                0.0
+            };
+            gl.uniform_1_f32(uniforms.get("u_colorPhase"), phase);
         }
     }
 }
+
+/// Send the RGBA8 texels of a palette to `texture`.
+fn upload_palette(gl: &eframe::glow::Context, texture: eframe::glow::Texture, palette: &[u8]) {
+    use eframe::glow::HasContext as _;
+    unsafe {
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            glow::RGBA8 as i32,
+            PALETTE_SIZE as i32,
+            1,
+            0,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            PixelUnpackData::Slice(Some(palette)),
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MIN_FILTER,
+            glow::LINEAR as i32,
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MAG_FILTER,
+            glow::LINEAR as i32,
+        );
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, glow::REPEAT as i32);
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_WRAP_T,
+            glow::CLAMP_TO_EDGE as i32,
+        );
+    }
+}
+
+/// Send the points of a reference orbit to `texture`, in rows of [ORBIT_TEXTURE_WIDTH]
+/// texels.
+fn upload_orbit(gl: &eframe::glow::Context, texture: eframe::glow::Texture, orbit: &[[f32; 2]]) {
+    use eframe::glow::HasContext as _;
+    let height = orbit.len().div_ceil(ORBIT_TEXTURE_WIDTH).max(1);
+    let mut texels = vec![0u8; ORBIT_TEXTURE_WIDTH * height * 8];
+    for (texel, point) in texels.chunks_exact_mut(8).zip(orbit) {
+        texel[..4].copy_from_slice(&point[0].to_ne_bytes());
+        texel[4..].copy_from_slice(&point[1].to_ne_bytes());
+    }
+    unsafe {
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            glow::RG32F as i32,
+            ORBIT_TEXTURE_WIDTH as i32,
+            height as i32,
+            0,
+            glow::RG,
+            glow::FLOAT,
+            PixelUnpackData::Slice(Some(&texels)),
+        );
+        // Read with texelFetch, without filtering nor mipmaps
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MIN_FILTER,
+            glow::NEAREST as i32,
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MAG_FILTER,
+            glow::NEAREST as i32,
+        );
+        gl.bind_texture(glow::TEXTURE_2D, None);
+    }
+}
+
+/// Send the lookup table of the histogram coloring to `texture`, one texel per value.
+fn upload_histogram(gl: &eframe::glow::Context, texture: eframe::glow::Texture, lut: &[f32]) {
+    use eframe::glow::HasContext as _;
+    let texels: Vec<u8> = lut.iter().flat_map(|value| value.to_ne_bytes()).collect();
+    unsafe {
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            glow::R32F as i32,
+            lut.len() as i32,
+            1,
+            0,
+            glow::RED,
+            glow::FLOAT,
+            PixelUnpackData::Slice(Some(&texels)),
+        );
+        // Float textures cannot be filtered on WebGL 2, the shader interpolates the texels
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MIN_FILTER,
+            glow::NEAREST as i32,
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MAG_FILTER,
+            glow::NEAREST as i32,
+        );
+        gl.bind_texture(glow::TEXTURE_2D, None);
+    }
+}
+
+/// Send the RGBA8 pixels of an image loaded by the user to `texture`.
+fn upload_image(gl: &eframe::glow::Context, texture: eframe::glow::Texture, image: &Image) {
+    use eframe::glow::HasContext as _;
+    unsafe {
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            glow::RGBA8 as i32,
+            image.width as i32,
+            image.height as i32,
+            0,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            PixelUnpackData::Slice(Some(&image.pixels)),
+        );
+        // Sampled with textureLod at level 0, there are no mipmaps
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MIN_FILTER,
+            glow::LINEAR as i32,
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MAG_FILTER,
+            glow::LINEAR as i32,
+        );
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, glow::REPEAT as i32);
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, glow::REPEAT as i32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Position;
+
+    /// Point of the fractal the shader computes for the pixel at `frag_coord`.
+    fn shader_location(viewport: &Viewport, state: &State, frag_coord: [f32; 2]) -> [f32; 2] {
+        let [x, y] = viewport.fractal_position(state);
+        [
+            frag_coord[0] / state.zoom - x,
+            frag_coord[1] / state.zoom - y,
+        ]
+    }
+
+    fn check_center(viewport: Viewport) {
+        let mut state = State::new();
+        state.center_position = Position { x: 0.3, y: -0.2 };
+        let center = [
+            viewport.left_px + viewport.width_px / 2.0,
+            viewport.bottom_px + viewport.height_px / 2.0,
+        ];
+        let [x, y] = shader_location(&viewport, &state, center);
+        let [expected_x, expected_y] = state.point_at([0.0, 0.0]);
+        assert!((x as f64 - expected_x).abs() < 1e-5, "{x} != {expected_x}");
+        assert!((y as f64 - expected_y).abs() < 1e-5, "{y} != {expected_y}");
+    }
+
+    #[test]
+    fn center_of_the_viewport_is_the_center_of_the_view() {
+        check_center(Viewport::offscreen(800, 600));
+    }
+
+    #[test]
+    fn center_with_panels_around_the_canvas() {
+        // Side panel on the left, toolbar above and status bar below
+        check_center(Viewport {
+            left_px: 300.0,
+            bottom_px: 24.0,
+            width_px: 900.0,
+            height_px: 640.0,
+        });
+    }
+
+    #[test]
+    fn pixels_map_like_the_ui() {
+        let mut state = State::new();
+        state.zoom = 250.0;
+        let viewport = Viewport {
+            left_px: 300.0,
+            bottom_px: 24.0,
+            width_px: 900.0,
+            height_px: 640.0,
+        };
+        // 10 pixels right and 20 pixels up from the center
+        let frag_coord = [300.0 + 450.0 + 10.0, 24.0 + 320.0 + 20.0];
+        let [x, y] = shader_location(&viewport, &state, frag_coord);
+        let [expected_x, expected_y] = state.point_at([10.0, -20.0]);
+        assert!((x as f64 - expected_x).abs() < 1e-5);
+        assert!((y as f64 - expected_y).abs() < 1e-5);
+    }
+}
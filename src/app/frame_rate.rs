@@ -0,0 +1,185 @@
+use std::time::Duration;
+
+use eframe::egui::{vec2, Align2, Color32, ComboBox, Context, FontId, Painter, Rect, Ui};
+use log::error;
+use web_time::Instant;
+
+use super::{
+    i18n::{tr, tr_args},
+    settings,
+};
+
+/// Names of the settings.
+const VSYNC_SETTING: &str = "vsync";
+const OVERLAY_SETTING: &str = "performance-overlay";
+const BATTERY_SAVER_SETTING: &str = "battery-saver";
+
+/// Frame rate of the animations with the battery saver.
+const BATTERY_SAVER_FPS: f64 = 30.0;
+const MARGIN: f32 = 8.0;
+const PADDING: f32 = 6.0;
+
+/// Highest frame rate, in frames per second. `None` is unlimited.
+const FPS_CAPS: [Option<u32>; 4] = [Some(30), Some(60), Some(120), None];
+
+fn cap_name(cap: Option<u32>) -> String {
+    match cap {
+        Some(fps) => format!("{fps} FPS"),
+        None => tr("unlimited"),
+    }
+}
+
+fn save(name: &str, value: bool) {
+    let value = if value { "on" } else { "off" };
+    if let Err(e) = settings::save(name, value) {
+        error!("Cannot save the {} setting: {:?}", name, e);
+    }
+}
+
+/// Vsync and frame-rate cap settings, the measured frame time, and the repaints of the
+/// animations: the app only repaints continuously while something moves.
+pub struct FrameRate {
+    /// Vsync of the running app, it can only change with a restart.
+    vsync_active: bool,
+    vsync: bool,
+    cap: Option<u32>,
+    last_frame: Option<Instant>,
+    /// Smoothed duration of a frame, in seconds.
+    frame_time: f64,
+    /// Frame rate, frame time and GPU time over the canvas.
+    pub show_overlay: bool,
+    /// The animations repaint at [BATTERY_SAVER_FPS] at most.
+    battery_saver: bool,
+    /// A continuous repaint was requested during the current frame.
+    continuous: bool,
+    /// The same, for the previous frame: the frame time is only meaningful if it was.
+    was_continuous: bool,
+}
+
+impl FrameRate {
+    pub fn new(vsync_active: bool) -> Self {
+        Self {
+            vsync_active,
+            vsync: vsync_active,
+            cap: None,
+            last_frame: None,
+            frame_time: 0.0,
+            show_overlay: settings::load(OVERLAY_SETTING).is_some_and(|v| v == "on"),
+            battery_saver: settings::load(BATTERY_SAVER_SETTING).is_some_and(|v| v == "on"),
+            continuous: false,
+            was_continuous: false,
+        }
+    }
+
+    /// Ask for the next frame of an animation, right away or at the battery saver rate.
+    pub fn request_repaint(&mut self, ctx: &Context) {
+        self.continuous = true;
+        if self.battery_saver {
+            ctx.request_repaint_after(Duration::from_secs_f64(1.0 / BATTERY_SAVER_FPS));
+        } else {
+            ctx.request_repaint();
+        }
+    }
+
+    /// Vsync saved from the settings, enabled if there is none.
+    pub fn load_vsync() -> bool {
+        settings::load(VSYNC_SETTING).is_none_or(|vsync| vsync != "off")
+    }
+
+    /// Called at the end of every frame, waits until the frame lasted long enough for the cap.
+    /// The browser paces the frames on the web, where the thread cannot sleep.
+    pub fn limit(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let (Some(fps), Some(last_frame)) = (self.cap, self.last_frame) {
+            let frame = Duration::from_secs_f64(1.0 / fps as f64);
+            if let Some(remaining) = frame.checked_sub(last_frame.elapsed()) {
+                std::thread::sleep(remaining);
+            }
+        }
+        let now = Instant::now();
+        if let (Some(last_frame), true) = (self.last_frame, self.was_continuous) {
+            // The time between two frames of input says nothing of the rendering speed
+            let elapsed = (now - last_frame).as_secs_f64();
+            self.frame_time += 0.1 * (elapsed - self.frame_time);
+        }
+        self.last_frame = Some(now);
+        self.was_continuous = std::mem::take(&mut self.continuous);
+    }
+
+    /// Frame rate, frame time and `gpu_time`, in seconds, in the top left corner of `rect`.
+    /// While nothing moves, no frame is drawn without input and the overlay says so.
+    pub fn paint_overlay(&self, painter: &Painter, rect: Rect, gpu_time: Option<f64>) {
+        let mut lines = Vec::new();
+        if self.was_continuous && self.frame_time > 0.0 {
+            lines.push(tr_args(
+                "frame-time",
+                &[
+                    ("time", &format!("{:.1}", self.frame_time * 1000.0)),
+                    ("fps", &format!("{:.0}", 1.0 / self.frame_time)),
+                ],
+            ));
+        } else {
+            lines.push(tr("repaint-on-input"));
+        }
+        if let Some(time) = gpu_time {
+            lines.push(tr_args(
+                "gpu-time",
+                &[("time", &format!("{:.2}", time * 1000.0))],
+            ));
+        }
+
+        let galley =
+            painter.layout_no_wrap(lines.join("\n"), FontId::monospace(12.0), Color32::WHITE);
+        let text_rect = Align2::LEFT_TOP.anchor_size(
+            rect.left_top() + vec2(MARGIN + PADDING, MARGIN + PADDING),
+            galley.size(),
+        );
+        painter.rect_filled(
+            text_rect.expand(PADDING),
+            4.0,
+            Color32::from_black_alpha(160),
+        );
+        painter.galley(text_rect.min, galley, Color32::WHITE);
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        if ui.checkbox(&mut self.vsync, tr("vsync")).changed() {
+            save(VSYNC_SETTING, self.vsync);
+        }
+        if self.vsync != self.vsync_active {
+            ui.label(tr("restart-to-apply"));
+        }
+
+        ComboBox::from_label(tr("frame-rate-cap"))
+            .selected_text(cap_name(self.cap))
+            .show_ui(ui, |ui| {
+                for cap in FPS_CAPS {
+                    ui.selectable_value(&mut self.cap, cap, cap_name(cap));
+                }
+            });
+
+        if ui
+            .checkbox(&mut self.battery_saver, tr("battery-saver"))
+            .on_hover_text(tr("battery-saver-hint"))
+            .changed()
+        {
+            save(BATTERY_SAVER_SETTING, self.battery_saver);
+        }
+        if ui
+            .checkbox(&mut self.show_overlay, tr("performance-overlay"))
+            .changed()
+        {
+            save(OVERLAY_SETTING, self.show_overlay);
+        }
+
+        if self.frame_time > 0.0 {
+            ui.label(tr_args(
+                "frame-time",
+                &[
+                    ("time", &format!("{:.1}", self.frame_time * 1000.0)),
+                    ("fps", &format!("{:.0}", 1.0 / self.frame_time)),
+                ],
+            ));
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use eframe::egui::{
+    self, load::SizedTexture, vec2, Key, ScrollArea, Sense, Spinner, Stroke, TextEdit, Vec2,
+};
+
+use super::{
+    bookmarks::Bookmarks,
+    i18n::tr,
+    thumbnails::{self, Thumbnails, THUMBNAIL_SIZE},
+    FractalGl, State,
+};
+
+/// Bookmark being renamed from the gallery.
+struct Renaming {
+    index: usize,
+    name: String,
+    /// The text field has been focused once.
+    focused: bool,
+}
+
+/// What was done to the bookmarks during a frame, applied once they are all drawn.
+enum Action {
+    Open(Box<State>),
+    Remove(usize),
+    Rename(usize, String),
+}
+
+/// Window of the bookmarks as thumbnails, to find a view by its look rather than its name.
+/// The thumbnails are rendered when first shown, and cached on the disk.
+pub struct Gallery {
+    pub open: bool,
+    /// Thumbnails of the views, by their [thumbnails::key].
+    thumbnails: HashMap<u64, u64>,
+    /// [thumbnails::look] the thumbnails were rendered with.
+    look: u64,
+    renaming: Option<Renaming>,
+}
+
+impl Gallery {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            thumbnails: HashMap::new(),
+            look: 0,
+            renaming: None,
+        }
+    }
+
+    /// Tile of the bookmark `index`, the thumbnail of its view above its name.
+    fn tile_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        index: usize,
+        (name, state): (&str, &State),
+        thumbnails: &mut Thumbnails,
+        fractal: &mut FractalGl,
+        viewport_px: Vec2,
+    ) -> Option<Action> {
+        let mut action = None;
+        let size = vec2(THUMBNAIL_SIZE[0] as f32, THUMBNAIL_SIZE[1] as f32);
+        let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+        let key = thumbnails::key(state, self.look);
+        // Only the tiles scrolled into view are rendered
+        if !self.thumbnails.contains_key(&key) && ui.is_rect_visible(rect) {
+            let id = thumbnails.request_cached(ui.ctx(), fractal, *state, viewport_px, self.look);
+            self.thumbnails.insert(key, id);
+        }
+        match self.thumbnails.get(&key).and_then(|id| thumbnails.get(*id)) {
+            Some(texture) => {
+                egui::Image::new(SizedTexture::new(texture.id(), size)).paint_at(ui, rect)
+            }
+            None => Spinner::new().paint_at(ui, rect.shrink(rect.height() / 4.0)),
+        }
+        if response.hovered() {
+            let stroke = Stroke::new(2.0, ui.visuals().selection.stroke.color);
+            ui.painter().rect_stroke(rect, 0.0, stroke);
+        }
+        if response.clicked() {
+            action = Some(Action::Open(Box::new(*state)));
+        }
+        response.context_menu(|ui| {
+            if ui.button(tr("rename")).clicked() {
+                self.renaming = Some(Renaming {
+                    index,
+                    name: name.to_string(),
+                    focused: false,
+                });
+                ui.close_menu();
+            }
+            if ui.button(tr("remove")).clicked() {
+                action = Some(Action::Remove(index));
+                ui.close_menu();
+            }
+        });
+
+        match &mut self.renaming {
+            Some(renaming) if renaming.index == index => {
+                let edit = ui.add(TextEdit::singleline(&mut renaming.name).desired_width(size.x));
+                if !renaming.focused {
+                    edit.request_focus();
+                    renaming.focused = true;
+                } else if edit.lost_focus() {
+                    if !ui.input(|i| i.key_pressed(Key::Escape)) {
+                        action = Some(Action::Rename(index, renaming.name.trim().to_string()));
+                    }
+                    self.renaming = None;
+                }
+            }
+            _ => {
+                ui.add(egui::Label::new(name).truncate());
+            }
+        }
+        action
+    }
+
+    /// Returns the view of the thumbnail clicked, if any.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        bookmarks: &mut Bookmarks,
+        thumbnails: &mut Thumbnails,
+        fractal: &mut FractalGl,
+        viewport_px: Vec2,
+    ) -> Option<State> {
+        // The thumbnails of another palette or coloring are outdated
+        let look = thumbnails::look(fractal);
+        if look != self.look {
+            for (_, id) in self.thumbnails.drain() {
+                thumbnails.forget(id);
+            }
+            self.look = look;
+        }
+        let mut open = self.open;
+        let mut action = None;
+        egui::Window::new(tr("gallery"))
+            .open(&mut open)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                if bookmarks.is_empty() {
+                    ui.label(tr("gallery-empty"));
+                    return;
+                }
+                ui.label(tr("gallery-hint"));
+                ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    ui.horizontal_wrapped(|ui| {
+                        for (index, bookmark) in bookmarks.entries().enumerate() {
+                            ui.vertical(|ui| {
+                                ui.set_width(THUMBNAIL_SIZE[0] as f32);
+                                let tile = self.tile_ui(
+                                    ui,
+                                    index,
+                                    bookmark,
+                                    thumbnails,
+                                    fractal,
+                                    viewport_px,
+                                );
+                                action = action.take().or(tile);
+                            });
+                        }
+                    });
+                });
+            });
+        self.open = open;
+
+        match action? {
+            Action::Open(state) => return Some(*state),
+            Action::Remove(index) => {
+                if let Some((_, state)) = bookmarks.entries().nth(index) {
+                    let key = thumbnails::key(state, self.look);
+                    if let Some(id) = self.thumbnails.remove(&key) {
+                        thumbnails.forget(id);
+                    }
+                }
+                bookmarks.remove(index);
+                self.renaming = None;
+            }
+            Action::Rename(index, name) => bookmarks.rename(index, name),
+        }
+        None
+    }
+}
@@ -0,0 +1,117 @@
+//! Render with the real shaders on a headless EGL context. Run with a software
+//! implementation on machines without a GPU or display:
+//! `EGL_PLATFORM=surfaceless LIBGL_ALWAYS_SOFTWARE=1 cargo test --features gl-tests`
+
+use clap::ValueEnum;
+use eframe::egui::{PaintCallbackInfo, Pos2, Rect, Vec2};
+
+use super::{
+    cpu_renderer, headless, quality::Quality, render_cli::Fractal, FractalGl, FractalType, State,
+};
+
+/// Every type: the ones of the command line, and the custom one with the iteration of the
+/// shader.
+fn fractal_types() -> impl Iterator<Item = FractalType> {
+    Fractal::value_variants()
+        .iter()
+        .map(|fractal| FractalType::from(*fractal))
+        .chain([FractalType::Custom])
+}
+
+fn render(fractal_type: FractalType, high_quality: bool) {
+    let gl = headless::context().expect("Cannot create the GL context");
+    let mut fractal = FractalGl::new();
+    fractal.init(&gl).expect("Cannot compile the shaders");
+
+    let mut state = State::new();
+    state.fractal_type = fractal_type;
+    state.high_quality = high_quality;
+    state.zoom = 16.0;
+    let image = fractal
+        .render_offscreen(&gl, state, 64, 64)
+        .unwrap_or_else(|e| panic!("Cannot render {fractal_type:?}: {e:?}"));
+
+    assert_eq!(image.pixels.len(), 64 * 64 * 4);
+    // Both the inside and the outside of the set are in view
+    let first = &image.pixels[..4];
+    assert!(
+        image.pixels.chunks_exact(4).any(|pixel| pixel != first),
+        "{fractal_type:?} is uniform"
+    );
+    fractal.destroy(&gl);
+}
+
+#[test]
+fn every_type() {
+    for fractal_type in fractal_types() {
+        render(fractal_type, false);
+    }
+}
+
+#[test]
+fn every_type_high_quality() {
+    for fractal_type in fractal_types() {
+        render(fractal_type, true);
+    }
+}
+
+#[test]
+fn lost_context() {
+    let gl = headless::context().expect("Cannot create the GL context");
+    let mut fractal = FractalGl::new();
+    fractal.init(&gl).expect("Cannot compile the shaders");
+    // A new context replaces the one of the renderer, without its objects
+    drop(gl);
+    let gl = headless::context().expect("Cannot create the GL context");
+
+    let size = Vec2::splat(64.0);
+    let viewport = Rect::from_min_size(Pos2::ZERO, size);
+    fractal.paint(
+        &gl,
+        State::new(),
+        Quality::default(),
+        None,
+        PaintCallbackInfo {
+            viewport,
+            clip_rect: viewport,
+            pixels_per_point: 1.0,
+            screen_size_px: [64, 64],
+        },
+    );
+    let mut state = State::new();
+    state.zoom = 16.0;
+    let image = fractal.render_offscreen(&gl, state, 64, 64).unwrap();
+    // Drawn with the objects recreated by the paint
+    let first = &image.pixels[..4];
+    assert!(image.pixels.chunks_exact(4).any(|pixel| pixel != first));
+    fractal.destroy(&gl);
+}
+
+/// The CPU renderer draws the views it supports as the shader does, but for the pixels where
+/// the single precision of the GPU ends in another color.
+#[test]
+fn cpu_matches_gpu() {
+    let gl = headless::context().expect("Cannot create the GL context");
+    let mut fractal = FractalGl::new();
+    fractal.init(&gl).expect("Cannot compile the shaders");
+    let types = fractal_types().filter(|&fractal_type| fractal_type != FractalType::Custom);
+    for (fractal_type, high_quality) in types.flat_map(|t| [(t, false), (t, true)]) {
+        let mut state = State::new();
+        state.fractal_type = fractal_type;
+        state.high_quality = high_quality;
+        state.zoom = 16.0;
+        assert_eq!(cpu_renderer::unsupported(&state, None), None);
+        let gpu = fractal.render_offscreen(&gl, state, 64, 64).unwrap().pixels;
+        let cpu = cpu_renderer::render(state, None, None, 64, 64).unwrap();
+        let different = gpu
+            .chunks_exact(4)
+            .zip(cpu.chunks_exact(4))
+            .filter(|(gpu, cpu)| gpu.iter().zip(*cpu).any(|(a, b)| a.abs_diff(*b) > 8))
+            .count();
+        assert!(
+            different < 64 * 64 / 50,
+            "{fractal_type:?} differs in {different} pixels"
+        );
+    }
+    fractal.destroy(&gl);
+}
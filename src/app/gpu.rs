@@ -0,0 +1,153 @@
+use std::{
+    env,
+    path::Path,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use anyhow::{bail, Result};
+use eframe::egui::{ComboBox, Ui};
+use log::{error, info, warn};
+
+use super::{
+    i18n::{tr, tr_args},
+    settings,
+};
+
+/// Name of the setting holding the preference.
+const SETTING: &str = "gpu";
+
+/// Asks the NVIDIA Optimus driver of Windows for the discrete GPU when 1. Exported from the
+/// executable by build.rs, the driver reads it once loaded with the GL context.
+#[no_mangle]
+pub static NvOptimusEnablement: AtomicU32 = AtomicU32::new(0);
+/// The same for the AMD switchable graphics.
+#[no_mangle]
+pub static AmdPowerXpressRequestHighPerformance: AtomicU32 = AtomicU32::new(0);
+
+/// GPU to render with on systems having both an integrated and a discrete one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GpuPreference {
+    /// Let the system choose.
+    Default,
+    Discrete,
+    Integrated,
+}
+
+impl GpuPreference {
+    const ALL: [GpuPreference; 3] = [
+        GpuPreference::Default,
+        GpuPreference::Discrete,
+        GpuPreference::Integrated,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            GpuPreference::Default => "default",
+            GpuPreference::Discrete => "discrete",
+            GpuPreference::Integrated => "integrated",
+        }
+    }
+
+    /// Preference named `name`, as in the `--gpu` command line option.
+    pub fn parse(name: &str) -> Result<Self> {
+        match Self::ALL.into_iter().find(|gpu| gpu.name() == name.trim()) {
+            Some(gpu) => Ok(gpu),
+            None => bail!("Unknown GPU {name:?}, expected default, discrete or integrated"),
+        }
+    }
+
+    /// Preference saved from the settings, the system default if there is none.
+    pub fn load() -> Self {
+        let Some(name) = settings::load(SETTING) else {
+            return GpuPreference::Default;
+        };
+        Self::parse(&name).unwrap_or_else(|e| {
+            error!("Invalid GPU preference: {:?}", e);
+            GpuPreference::Default
+        })
+    }
+
+    /// Set the hints picking the GPU, before the GL context is created. Variables already set
+    /// by the user are kept.
+    pub fn apply(self) {
+        if self == GpuPreference::Default {
+            return;
+        }
+        if cfg!(target_os = "windows") && self == GpuPreference::Discrete {
+            NvOptimusEnablement.store(1, Ordering::Relaxed);
+            AmdPowerXpressRequestHighPerformance.store(1, Ordering::Relaxed);
+            info!("Requested the {} GPU", self.name());
+            return;
+        }
+        if !cfg!(target_os = "linux") {
+            // Otherwise Windows and macOS take the preference from the system graphics settings
+            warn!(
+                "Cannot select the {} GPU on this platform, use the system settings",
+                self.name()
+            );
+            return;
+        }
+        let mut hints = match self {
+            // Mesa PRIME, and the NVIDIA PRIME render offload
+            GpuPreference::Discrete => vec![("DRI_PRIME", "1"), ("__NV_PRIME_RENDER_OFFLOAD", "1")],
+            _ => vec![("DRI_PRIME", "0")],
+        };
+        // The offload also needs the NVIDIA GLX library, which only exists with its driver:
+        // asked for without it, GLX would find no library at all
+        if self == GpuPreference::Discrete && Path::new("/proc/driver/nvidia/version").exists() {
+            hints.push(("__GLX_VENDOR_LIBRARY_NAME", "nvidia"));
+        }
+        for (name, value) in hints {
+            if env::var_os(name).is_none() {
+                env::set_var(name, value);
+            }
+        }
+        info!("Requested the {} GPU", self.name());
+    }
+}
+
+/// Settings entry for the GPU, the change only applies after a restart.
+pub struct Gpu {
+    /// Preference the running app started with.
+    active: GpuPreference,
+    selected: GpuPreference,
+    /// GL renderer and vendor strings.
+    adapter: Option<String>,
+}
+
+impl Gpu {
+    pub fn new(active: GpuPreference, adapter: Option<String>) -> Self {
+        Self {
+            active,
+            selected: GpuPreference::load(),
+            adapter,
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        let unknown = tr("unknown");
+        ui.label(tr_args(
+            "gpu-in-use",
+            &[("adapter", &self.adapter.as_deref().unwrap_or(&unknown))],
+        ));
+
+        let mut changed = false;
+        ComboBox::from_label(tr("preferred-gpu"))
+            .selected_text(self.selected.name())
+            .show_ui(ui, |ui| {
+                for gpu in GpuPreference::ALL {
+                    changed |= ui
+                        .selectable_value(&mut self.selected, gpu, gpu.name())
+                        .changed();
+                }
+            });
+        if changed {
+            if let Err(e) = settings::save(SETTING, self.selected.name()) {
+                error!("Cannot save the GPU preference: {:?}", e);
+            }
+        }
+        if self.selected != self.active {
+            ui.label(tr("restart-to-apply"));
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use eframe::egui::{pos2, vec2, Color32, Rect, Sense, Shape, Stroke, Ui};
+
+use super::{
+    i18n::tr,
+    palette::{ColorStop, Palette},
+    palette_strip::PaletteStrip,
+};
+
+/// Height of the row of stop handles under the gradient.
+const HANDLE_HEIGHT: f32 = 12.0;
+
+fn to_color32([r, g, b]: [f32; 3]) -> Color32 {
+    Color32::from_rgb((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8)
+}
+
+/// Editor of the color stops of a palette: the handles under the gradient are dragged to
+/// move the stops between their neighbours, and a double-click on the gradient adds one.
+pub struct GradientEditor {
+    /// Stop whose color is edited.
+    selected: Option<usize>,
+}
+
+impl GradientEditor {
+    pub fn new() -> Self {
+        Self { selected: None }
+    }
+
+    /// Returns whether `palette` changed.
+    pub fn ui(&mut self, ui: &mut Ui, palette: &mut Palette) -> bool {
+        let mut changed = false;
+        let strip = ui
+            .add(PaletteStrip::new(palette).sense(Sense::click()))
+            .on_hover_text(tr("gradient-editor-hint"));
+        if strip.double_clicked() {
+            if let Some(pointer) = strip.interact_pointer_pos() {
+                let position =
+                    ((pointer.x - strip.rect.left()) / strip.rect.width()).clamp(0.0, 0.999);
+                let color = palette.sample(position);
+                palette.stops.push(ColorStop { position, color });
+                self.sort(palette, palette.stops.len() - 1);
+                changed = true;
+            }
+        }
+
+        let (row, _) =
+            ui.allocate_exact_size(vec2(strip.rect.width(), HANDLE_HEIGHT), Sense::hover());
+        let mut dragged = None;
+        for (i, stop) in palette.stops.iter().enumerate() {
+            let x = row.left() + stop.position * row.width();
+            let handle =
+                Rect::from_center_size(pos2(x, row.center().y), vec2(HANDLE_HEIGHT, HANDLE_HEIGHT));
+            let response = ui.interact(handle, ui.id().with(("stop", i)), Sense::click_and_drag());
+            if response.clicked() || response.drag_started() {
+                self.selected = Some(i);
+            }
+            if response.dragged() {
+                dragged = Some((i, response.drag_delta().x / row.width()));
+            }
+            let stroke = if self.selected == Some(i) {
+                Stroke::new(2.0, ui.visuals().strong_text_color())
+            } else {
+                Stroke::new(1.0, ui.visuals().weak_text_color())
+            };
+            ui.painter().add(Shape::convex_polygon(
+                vec![
+                    pos2(x, row.top()),
+                    pos2(x + HANDLE_HEIGHT / 2.0, row.bottom()),
+                    pos2(x - HANDLE_HEIGHT / 2.0, row.bottom()),
+                ],
+                to_color32(stop.color),
+                stroke,
+            ));
+        }
+        if let Some((i, delta)) = dragged {
+            // Between its neighbours, the handles keep their order and their ids
+            let low = i.checked_sub(1).map_or(0.0, |j| palette.stops[j].position);
+            let high = palette.stops.get(i + 1).map_or(0.999, |stop| stop.position);
+            let stop = &mut palette.stops[i];
+            stop.position = (stop.position + delta).clamp(low, high);
+            changed = true;
+        }
+
+        let Some(i) = self.selected.filter(|&i| i < palette.stops.len()) else {
+            return changed;
+        };
+        ui.horizontal(|ui| {
+            changed |= ui
+                .color_edit_button_rgb(&mut palette.stops[i].color)
+                .changed();
+            ui.label(format!("{:.0}%", palette.stops[i].position * 100.0));
+            // A gradient needs a stop to have a color
+            if palette.stops.len() > 1 && ui.button(tr("remove")).clicked() {
+                palette.stops.remove(i);
+                self.selected = None;
+                changed = true;
+            }
+        });
+        changed
+    }
+
+    /// Sort the stops after the stop `index` was added, and select it.
+    fn sort(&mut self, palette: &mut Palette, index: usize) {
+        let moved = palette.stops[index];
+        palette
+            .stops
+            .sort_by(|a, b| a.position.total_cmp(&b.position));
+        self.selected = palette.stops.iter().position(|stop| *stop == moved);
+    }
+}
@@ -0,0 +1,45 @@
+//! OpenGL without a window, on EGL. Mesa's llvmpipe provides it on machines without a GPU,
+//! with `LIBGL_ALWAYS_SOFTWARE=1`.
+
+use anyhow::{anyhow, Result};
+use khronos_egl as egl;
+
+/// GL context without any surface, [super::FractalGl::render_offscreen] brings its own
+/// framebuffer.
+pub fn context() -> Result<eframe::glow::Context> {
+    let egl = egl::Instance::new(egl::Static);
+    let display =
+        unsafe { egl.get_display(egl::DEFAULT_DISPLAY) }.ok_or(anyhow!("No EGL display"))?;
+    egl.initialize(display)?;
+    egl.bind_api(egl::OPENGL_API)?;
+
+    // Pbuffer configs, the only ones of the surfaceless platform of Mesa
+    let config_attributes = [
+        egl::SURFACE_TYPE,
+        egl::PBUFFER_BIT,
+        egl::RENDERABLE_TYPE,
+        egl::OPENGL_BIT,
+        egl::NONE,
+    ];
+    let config = egl
+        .choose_first_config(display, &config_attributes)?
+        .ok_or(anyhow!("No OpenGL EGL config"))?;
+    let context_attributes = [
+        egl::CONTEXT_MAJOR_VERSION,
+        3,
+        egl::CONTEXT_MINOR_VERSION,
+        3,
+        egl::CONTEXT_OPENGL_PROFILE_MASK,
+        egl::CONTEXT_OPENGL_CORE_PROFILE_BIT,
+        egl::NONE,
+    ];
+    let context = egl.create_context(display, config, None, &context_attributes)?;
+    egl.make_current(display, None, None, Some(context))?;
+
+    Ok(unsafe {
+        eframe::glow::Context::from_loader_function(|name| {
+            egl.get_proc_address(name)
+                .map_or(std::ptr::null(), |function| function as *const _)
+        })
+    })
+}
@@ -0,0 +1,138 @@
+use eframe::egui::{
+    self, vec2, Align2, Color32, Context, FontId, Id, Key, Order, Rect, Sense, Stroke, Ui,
+};
+
+use super::i18n::tr;
+
+/// Space between an annotated control and its note, in points.
+const MARGIN: f32 = 12.0;
+const PADDING: f32 = 4.0;
+/// Width over which the notes wrap, in points.
+const NOTE_WIDTH: f32 = 360.0;
+
+/// Parts of the UI explained by the overlay.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Topic {
+    Canvas,
+    FractalType,
+    JuliaPanel,
+    Coloring,
+    MouseControls,
+    Nudge,
+    Bookmarks,
+    Files,
+    Help,
+}
+
+impl Topic {
+    const ALL: [Topic; 9] = [
+        Topic::Canvas,
+        Topic::FractalType,
+        Topic::JuliaPanel,
+        Topic::Coloring,
+        Topic::MouseControls,
+        Topic::Nudge,
+        Topic::Bookmarks,
+        Topic::Files,
+        Topic::Help,
+    ];
+
+    fn note(self) -> String {
+        tr(match self {
+            Topic::Canvas => "overlay-canvas",
+            Topic::FractalType => "overlay-type",
+            Topic::JuliaPanel => "overlay-julia",
+            Topic::Coloring => "overlay-coloring",
+            Topic::MouseControls => "overlay-mouse-controls",
+            Topic::Nudge => "overlay-nudge",
+            Topic::Bookmarks => "overlay-bookmarks",
+            Topic::Files => "overlay-files",
+            Topic::Help => "overlay-help",
+        })
+    }
+}
+
+/// Notes over the whole window on what the controls do and the gestures they take, next
+/// to the controls. Unlike the tour, all of them at once.
+pub struct HelpOverlay {
+    pub open: bool,
+    /// Where the controls of the topics were drawn this frame, if they are visible.
+    targets: [Option<Rect>; Topic::ALL.len()],
+}
+
+impl HelpOverlay {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            targets: [None; Topic::ALL.len()],
+        }
+    }
+
+    /// Record where the control of `topic` is, called while drawing it in `ui`.
+    pub fn annotate(&mut self, ui: &Ui, topic: Topic, rect: Rect) {
+        if !ui.is_rect_visible(rect) {
+            return;
+        }
+        if let Some(index) = Topic::ALL.iter().position(|t| *t == topic) {
+            self.targets[index] = Some(rect);
+        }
+    }
+
+    /// Draw the notes over everything else, a click or Escape closes them.
+    pub fn show(&mut self, ctx: &Context) {
+        let targets = std::mem::replace(&mut self.targets, [None; Topic::ALL.len()]);
+        if !self.open {
+            return;
+        }
+        let screen = ctx.screen_rect();
+        egui::Area::new(Id::new("Help overlay"))
+            .order(Order::Foreground)
+            .fixed_pos(screen.min)
+            .show(ctx, |ui| {
+                let response = ui.allocate_rect(screen, Sense::click());
+                let painter = ui.painter();
+                painter.rect_filled(screen, 0.0, Color32::from_black_alpha(120));
+                for (topic, rect) in Topic::ALL.iter().zip(targets) {
+                    let Some(rect) = rect else {
+                        continue;
+                    };
+                    painter.rect_stroke(rect.expand(2.0), 4.0, Stroke::new(2.0, Color32::YELLOW));
+                    let galley = painter.layout(
+                        topic.note(),
+                        FontId::proportional(13.0),
+                        Color32::WHITE,
+                        NOTE_WIDTH,
+                    );
+                    // Over the middle of the canvas, on the right of the controls of the panel
+                    let note = if *topic == Topic::Canvas {
+                        Align2::CENTER_CENTER.anchor_size(rect.center(), galley.size())
+                    } else {
+                        let anchor = rect.right_center() + vec2(MARGIN, 0.0);
+                        Align2::LEFT_CENTER.anchor_size(anchor, galley.size())
+                    };
+                    // Kept in the window, the menu bar is at the top
+                    let note = note.translate(vec2(
+                        (screen.right() - PADDING - note.right()).min(0.0),
+                        (screen.top() + PADDING - note.top()).max(0.0),
+                    ));
+                    painter.rect(
+                        note.expand(PADDING),
+                        4.0,
+                        Color32::from_black_alpha(220),
+                        Stroke::new(1.0, Color32::YELLOW),
+                    );
+                    painter.galley(note.min, galley, Color32::WHITE);
+                }
+                painter.text(
+                    screen.center_bottom() - vec2(0.0, 2.0 * MARGIN),
+                    Align2::CENTER_BOTTOM,
+                    tr("overlay-close"),
+                    FontId::proportional(14.0),
+                    Color32::WHITE,
+                );
+                if response.clicked() || ui.input(|i| i.key_pressed(Key::Escape)) {
+                    self.open = false;
+                }
+            });
+    }
+}
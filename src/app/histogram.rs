@@ -0,0 +1,103 @@
+use eframe::egui::Vec2;
+
+use super::{orbit::smooth_iterations, FractalType, State};
+
+/// Entries of the lookup table, keep in sync with the fragment shader.
+pub const HISTOGRAM_SIZE: usize = 256;
+/// Points sampled across the view, along x and y.
+const SAMPLES: [usize; 2] = [64, 40];
+
+/// What the histogram of a view depends on, to compute it again only when it changes.
+#[derive(Clone, Copy, PartialEq)]
+struct HistogramKey {
+    fractal_type: FractalType,
+    center: [f64; 2],
+    zoom: f32,
+    c_julia: [f32; 2],
+    skew: [f32; 4],
+    rotation: f32,
+    iterations: u32,
+    viewport_px: [f32; 2],
+}
+
+impl HistogramKey {
+    fn new(state: &State, viewport_px: Vec2) -> Self {
+        Self {
+            fractal_type: state.fractal_type,
+            center: [state.center_position.x, state.center_position.y],
+            zoom: state.zoom,
+            c_julia: [state.c_julia.x, state.c_julia.y],
+            skew: state.skew,
+            rotation: state.rotation,
+            iterations: state.iterations(),
+            viewport_px: [viewport_px.x, viewport_px.y],
+        }
+    }
+}
+
+/// Highest value of the shader for `state`, the log of its iteration budget. The lookup
+/// table covers the values up to it.
+pub fn value_range(state: &State) -> f32 {
+    (state.iterations() as f32 + 1.0).ln()
+}
+
+/// Lookup table leaving the values as they are.
+pub fn identity() -> Vec<f32> {
+    (0..HISTOGRAM_SIZE)
+        .map(|i| i as f32 / (HISTOGRAM_SIZE - 1) as f32)
+        .collect()
+}
+
+/// Lookup table equalizing `values`, the shader values of the escaping points: entry `i`
+/// is the fraction of them below `range * i / (HISTOGRAM_SIZE - 1)`. Each band of the
+/// palette then covers as many pixels.
+fn equalize(mut values: Vec<f32>, range: f32) -> Vec<f32> {
+    if values.is_empty() {
+        // Nothing escapes
+        return identity();
+    }
+    values.sort_by(f32::total_cmp);
+    let step = range / (HISTOGRAM_SIZE - 1) as f32;
+    (0..HISTOGRAM_SIZE)
+        .map(|i| {
+            let below = values.partition_point(|value| *value <= i as f32 * step);
+            below as f32 / values.len() as f32
+        })
+        .collect()
+}
+
+/// Histogram equalization of the iteration counts of the view, sampled on the CPU.
+pub struct Histogram {
+    key: Option<HistogramKey>,
+}
+
+impl Histogram {
+    pub fn new() -> Self {
+        Self { key: None }
+    }
+
+    /// Lookup table of the view `state` gives on a canvas of `viewport_px` pixels, when it
+    /// changed since the last call.
+    pub fn update(&mut self, state: &State, viewport_px: Vec2) -> Option<Vec<f32>> {
+        let key = HistogramKey::new(state, viewport_px);
+        if self.key == Some(key) {
+            return None;
+        }
+        self.key = Some(key);
+
+        let [columns, rows] = SAMPLES;
+        let values = (0..columns * rows)
+            .filter_map(|i| {
+                let (column, row) = (i % columns, i / columns);
+                let offset = [
+                    ((column as f32 + 0.5) / columns as f32 - 0.5) * viewport_px.x,
+                    ((row as f32 + 0.5) / rows as f32 - 0.5) * viewport_px.y,
+                ];
+                let iterations = smooth_iterations(state, state.point_at(offset))?;
+                // Like the shader, which counts from 1
+                Some((iterations.max(0.0) + 1.0).ln() as f32)
+            })
+            .collect();
+        Some(equalize(values, value_range(state)))
+    }
+}
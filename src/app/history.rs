@@ -0,0 +1,121 @@
+use std::{collections::VecDeque, time::Duration};
+
+use eframe::egui::Context;
+
+use super::{skew, State};
+
+/// Maximum number of changes that can be undone.
+const MAX_HISTORY: usize = 100;
+/// A change is recorded once the state did not change for this long, so that the steps of a
+/// scroll make a single change.
+const SETTLE_TIME: Duration = Duration::from_millis(400);
+
+/// `state` without what changes by itself: the time and the automatic skew.
+fn normalized(state: &State) -> State {
+    State {
+        time: 0.0,
+        skew: if state.auto_skew {
+            skew::IDENTITY
+        } else {
+            state.skew
+        },
+        ..*state
+    }
+}
+
+fn same_state(a: &State, b: &State) -> bool {
+    normalized(a) == normalized(b)
+}
+
+/// Changes of the view and of the parameters, to undo and redo them.
+pub struct History {
+    undo: VecDeque<State>,
+    redo: Vec<State>,
+    /// State after the last change recorded.
+    recorded: Option<State>,
+    /// Change not recorded yet, and the time it was last seen.
+    pending: Option<(State, f64)>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            recorded: None,
+            pending: None,
+        }
+    }
+
+    /// Called every frame with the current state. A change is recorded once it settled,
+    /// and not while `busy`, when a drag or an animation is in progress.
+    pub fn observe(&mut self, ctx: &Context, state: &State, busy: bool) {
+        let Some(recorded) = &self.recorded else {
+            self.recorded = Some(*state);
+            return;
+        };
+        if same_state(recorded, state) {
+            self.pending = None;
+            return;
+        }
+        let time = ctx.input(|i| i.time);
+        match self.pending {
+            Some((pending, since)) if same_state(&pending, state) => {
+                if !busy && time - since >= SETTLE_TIME.as_secs_f64() {
+                    self.record(*state);
+                }
+            }
+            _ => {
+                self.pending = Some((*state, time));
+                // Without input there would be no frame to notice the change settled
+                ctx.request_repaint_after(SETTLE_TIME);
+            }
+        }
+    }
+
+    fn record(&mut self, state: State) {
+        if let Some(recorded) = self.recorded.replace(state) {
+            self.undo.push_back(recorded);
+            if self.undo.len() > MAX_HISTORY {
+                self.undo.pop_front();
+            }
+        }
+        self.redo.clear();
+        self.pending = None;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || self.pending.is_some()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty() && self.pending.is_none()
+    }
+
+    /// State before the last change of `current`, if any.
+    pub fn undo(&mut self, current: &State) -> Option<State> {
+        if self.pending.is_some() {
+            // Undo the change in progress too
+            self.record(*current);
+        }
+        let previous = self.undo.pop_back()?;
+        self.redo.extend(self.recorded.replace(previous));
+        Some(State {
+            time: current.time,
+            ..previous
+        })
+    }
+
+    /// State after the last change undone, if any.
+    pub fn redo(&mut self, current: &State) -> Option<State> {
+        if self.pending.is_some() {
+            return None;
+        }
+        let next = self.redo.pop()?;
+        self.undo.extend(self.recorded.replace(next));
+        Some(State {
+            time: current.time,
+            ..next
+        })
+    }
+}
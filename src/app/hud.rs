@@ -0,0 +1,51 @@
+use eframe::egui::{vec2, Align2, Color32, FontId, Painter, Rect};
+
+use super::{format_magnification, i18n::tr_args, State};
+
+const MARGIN: f32 = 8.0;
+const PADDING: f32 = 6.0;
+
+/// Decimals that tell apart the pixels of a view of `zoom`.
+pub fn decimals(zoom: f32) -> usize {
+    ((zoom as f64).log10().ceil() as usize + 1).clamp(2, 17)
+}
+
+/// `re` and `im` with the [decimals] of a view of `zoom`.
+fn format_point([re, im]: [f64; 2], zoom: f32) -> [String; 2] {
+    let decimals = decimals(zoom);
+    [format!("{re:.decimals$}"), format!("{im:+.decimals$}")]
+}
+
+/// Draw in the top right corner of `rect` the point under the cursor at `pointer_px` from
+/// the center, if any, the center of the view, its magnification and its iterations.
+pub fn paint(
+    painter: &Painter,
+    rect: Rect,
+    state: &State,
+    viewport_height_px: f32,
+    pointer_px: Option<[f32; 2]>,
+) {
+    let mut lines = Vec::new();
+    if let Some(offset) = pointer_px {
+        let [re, im] = format_point(state.point_at(offset), state.zoom);
+        lines.push(tr_args("hud-pointer", &[("re", &re), ("im", &im)]));
+    }
+    let [re, im] = format_point(state.point_at([0.0, 0.0]), state.zoom);
+    lines.push(tr_args("hud-center", &[("re", &re), ("im", &im)]));
+    let magnification = format_magnification(state.magnification(viewport_height_px));
+    lines.push(tr_args("magnification", &[("value", &magnification)]));
+    lines.push(tr_args("hud-iterations", &[("count", &state.iterations())]));
+
+    let galley = painter.layout_no_wrap(lines.join("\n"), FontId::monospace(12.0), Color32::WHITE);
+    let text_rect = Align2::RIGHT_TOP.anchor_size(
+        rect.right_top() + vec2(-MARGIN - PADDING, MARGIN + PADDING),
+        galley.size(),
+    );
+    // Dark background so the text stays readable over light areas of the fractal
+    painter.rect_filled(
+        text_rect.expand(PADDING),
+        4.0,
+        Color32::from_black_alpha(160),
+    );
+    painter.galley(text_rect.min, galley, Color32::WHITE);
+}
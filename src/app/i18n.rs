@@ -0,0 +1,254 @@
+//! Translations of the UI, from `assets/locales/<language>.ftl` built into the binary.
+//!
+//! Only the messages of the Fluent syntax are supported: `id = text`, where the text can
+//! hold `{ $name }` placeables filled by [tr_args] and go on over indented lines.
+
+use std::{collections::HashMap, fmt::Display, sync::RwLock};
+
+use eframe::egui::{ComboBox, Ui};
+use log::error;
+
+use super::settings;
+
+/// Code and name of the shipped languages, the first one is the fallback.
+const LANGUAGES: [(&str, &str); 2] = [("en", "English"), ("fr", "Français")];
+/// Messages of the languages, in the order of [LANGUAGES].
+const FILES: [&str; LANGUAGES.len()] = [
+    include_str!("../../assets/locales/en.ftl"),
+    include_str!("../../assets/locales/fr.ftl"),
+];
+
+/// Name of the setting holding the language.
+const SETTING: &str = "language";
+
+struct Catalog {
+    language: &'static str,
+    messages: HashMap<String, String>,
+    /// Messages of the fallback language, for the ids missing from the translation.
+    fallback: HashMap<String, String>,
+}
+
+static CATALOG: RwLock<Option<Catalog>> = RwLock::new(None);
+
+fn parse(content: &str) -> HashMap<String, String> {
+    let mut messages = HashMap::new();
+    // Message being read, and its lines
+    let mut current: Option<(String, Vec<String>)> = None;
+    let mut finish = |current: &mut Option<(String, Vec<String>)>| {
+        if let Some((id, mut lines)) = current.take() {
+            while lines.last().is_some_and(|line| line.is_empty()) {
+                lines.pop();
+            }
+            messages.insert(id, lines.join("\n"));
+        }
+    };
+    for line in content.lines() {
+        let indented = line.starts_with([' ', '\t']);
+        if indented || line.trim().is_empty() {
+            // Continuation of the message, blank lines included when more text follows
+            if let Some((_, lines)) = &mut current {
+                let text = line.trim();
+                if !(lines.is_empty() && text.is_empty()) {
+                    lines.push(text.to_string());
+                }
+            }
+            continue;
+        }
+        finish(&mut current);
+        if line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((id, text)) => {
+                let text = text.trim();
+                let lines = if text.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![text.to_string()]
+                };
+                current = Some((id.trim().to_string(), lines));
+            }
+            None => error!("Invalid line in the translations: {}", line),
+        }
+    }
+    finish(&mut current);
+    messages
+}
+
+fn read(language: &str) -> HashMap<String, String> {
+    LANGUAGES
+        .iter()
+        .zip(FILES)
+        .find(|((code, _), _)| *code == language)
+        .map(|(_, content)| parse(content))
+        .unwrap_or_default()
+}
+
+fn load(language: &'static str) -> Catalog {
+    let fallback = read(LANGUAGES[0].0);
+    let messages = if language == LANGUAGES[0].0 {
+        fallback.clone()
+    } else {
+        read(language)
+    };
+    Catalog {
+        language,
+        messages,
+        fallback,
+    }
+}
+
+/// Language saved in the settings, or else the one of the system when it is available.
+fn initial_language() -> &'static str {
+    // The tests look the widgets up by their English labels
+    if cfg!(test) {
+        return LANGUAGES[0].0;
+    }
+    let system = std::env::var("LANG").unwrap_or_default();
+    let preferred = settings::load(SETTING).unwrap_or(system);
+    LANGUAGES
+        .into_iter()
+        .map(|(code, _)| code)
+        .find(|code| preferred.starts_with(code))
+        .unwrap_or(LANGUAGES[0].0)
+}
+
+fn with_catalog<T>(f: impl FnOnce(&Catalog) -> T) -> T {
+    if let Some(catalog) = CATALOG.read().unwrap().as_ref() {
+        return f(catalog);
+    }
+    let mut catalog = CATALOG.write().unwrap();
+    f(catalog.get_or_insert_with(|| load(initial_language())))
+}
+
+/// Text of the message `id` in the current language.
+pub fn tr(id: &str) -> String {
+    with_catalog(|catalog| {
+        catalog
+            .messages
+            .get(id)
+            .or_else(|| catalog.fallback.get(id))
+            .cloned()
+            .unwrap_or_else(|| id.to_string())
+    })
+}
+
+/// Text of the message `id`, its `{ $name }` placeables replaced by the matching `args`.
+pub fn tr_args(id: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = tr(id);
+    for (name, value) in args {
+        text = text.replace(&format!("{{ ${name} }}"), &value.to_string());
+    }
+    text
+}
+
+/// Selector of the language, the change applies from the next frame.
+pub fn language_ui(ui: &mut Ui) {
+    let current = with_catalog(|catalog| catalog.language);
+    let name = |code: &str| {
+        LANGUAGES
+            .into_iter()
+            .find(|(language, _)| *language == code)
+            .map_or(code.to_string(), |(_, name)| name.to_string())
+    };
+    ComboBox::from_label(tr("language"))
+        .selected_text(name(current))
+        .show_ui(ui, |ui| {
+            for (code, language) in LANGUAGES {
+                if ui.selectable_label(code == current, language).clicked() && code != current {
+                    *CATALOG.write().unwrap() = Some(load(code));
+                    if let Err(e) = settings::save(SETTING, code) {
+                        error!("Cannot save the language: {:?}", e);
+                    }
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+
+    /// Ids given as literals to [tr] and [tr_args] in the sources under `dir`.
+    fn used_ids(dir: &Path, ids: &mut Vec<String>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                used_ids(&path, ids);
+                continue;
+            }
+            if path.extension().is_none_or(|extension| extension != "rs") {
+                continue;
+            }
+            let source = fs::read_to_string(&path).unwrap();
+            for call in ["tr(", "tr_args("] {
+                for (start, _) in source.match_indices(call) {
+                    // Not the end of a longer name, like `attr(`
+                    let before = source[..start].chars().next_back();
+                    if before.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                        continue;
+                    }
+                    let arguments = source[start + call.len()..].trim_start();
+                    let Some(literal) = arguments.strip_prefix('"') else {
+                        continue;
+                    };
+                    let id = &literal[..literal.find('"').unwrap_or(0)];
+                    if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                        ids.push(id.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn used_ids_are_translated() {
+        let mut ids = Vec::new();
+        used_ids(&Path::new(env!("CARGO_MANIFEST_DIR")).join("src"), &mut ids);
+        assert!(ids.len() > 100, "Only {} ids found", ids.len());
+        for ((language, _), content) in LANGUAGES.iter().zip(FILES) {
+            let messages = parse(content);
+            let missing: Vec<_> = ids
+                .iter()
+                .filter(|id| !messages.contains_key(*id))
+                .collect();
+            assert!(missing.is_empty(), "Missing in {language}: {missing:?}");
+        }
+    }
+
+    #[test]
+    fn languages_have_the_same_ids() {
+        let fallback = parse(FILES[0]);
+        for ((language, _), content) in LANGUAGES.iter().zip(FILES).skip(1) {
+            let messages = parse(content);
+            let missing: Vec<_> = fallback
+                .keys()
+                .filter(|id| !messages.contains_key(*id))
+                .collect();
+            assert!(missing.is_empty(), "Missing in {language}: {missing:?}");
+            let extra: Vec<_> = messages
+                .keys()
+                .filter(|id| !fallback.contains_key(*id))
+                .collect();
+            assert!(extra.is_empty(), "Only in {language}: {extra:?}");
+        }
+    }
+
+    #[test]
+    fn multiline_messages() {
+        let messages = parse(
+            "# Comment\n\
+             single = One line\n\
+             multi =\n    First line\n    second line\n\n    after a blank line\n\n\
+             next = { $count } items\n",
+        );
+        assert_eq!(messages["single"], "One line");
+        assert_eq!(
+            messages["multi"],
+            "First line\nsecond line\n\nafter a blank line"
+        );
+        assert_eq!(messages["next"], "{ $count } items");
+    }
+}
@@ -0,0 +1,96 @@
+use std::path::Path;
+
+use anyhow::{ensure, Context, Result};
+
+use super::palette::{ColorStop, Palette};
+
+/// Number of colors extracted from the image.
+const CLUSTERS: usize = 8;
+const ITERATIONS: usize = 16;
+/// The image is downscaled to at most this size on each side before the clustering.
+const SAMPLE_SIZE: u32 = 128;
+
+fn luminance([r, g, b]: [f32; 3]) -> f32 {
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+fn distance_sq(a: [f32; 3], b: [f32; 3]) -> f32 {
+    (0..3).map(|i| (a[i] - b[i]) * (a[i] - b[i])).sum()
+}
+
+/// Dominant colors of `pixels` by k-means, sorted from the darkest to the lightest.
+///
+/// The clusters start at evenly spaced quantiles of the luminance, so they spread along it
+/// instead of all landing in the most common hue.
+fn dominant_colors(mut pixels: Vec<[f32; 3]>, clusters: usize) -> Vec<[f32; 3]> {
+    if pixels.is_empty() {
+        return Vec::new();
+    }
+    pixels.sort_by(|a, b| luminance(*a).total_cmp(&luminance(*b)));
+    let mut centers: Vec<[f32; 3]> = (0..clusters)
+        .map(|i| pixels[(2 * i + 1) * pixels.len() / (2 * clusters)])
+        .collect();
+
+    for _ in 0..ITERATIONS {
+        let mut sums = vec![([0.0f32; 3], 0usize); clusters];
+        for pixel in &pixels {
+            let nearest = (0..clusters)
+                .min_by(|&a, &b| {
+                    distance_sq(*pixel, centers[a]).total_cmp(&distance_sq(*pixel, centers[b]))
+                })
+                .unwrap_or(0);
+            let (sum, count) = &mut sums[nearest];
+            for c in 0..3 {
+                sum[c] += pixel[c];
+            }
+            *count += 1;
+        }
+        // A cluster without pixels keeps its center
+        for (center, (sum, count)) in centers.iter_mut().zip(sums) {
+            if count > 0 {
+                *center = sum.map(|channel| channel / count as f32);
+            }
+        }
+    }
+
+    centers.sort_by(|a, b| luminance(*a).total_cmp(&luminance(*b)));
+    centers.dedup();
+    centers
+}
+
+/// Gradient of the dominant colors of the image at `path`, from dark to light.
+pub fn load(path: &Path) -> Result<Palette> {
+    let image = image::open(path)
+        .with_context(|| format!("Cannot read {}", path.display()))?
+        .thumbnail(SAMPLE_SIZE, SAMPLE_SIZE)
+        .to_rgb8();
+    let pixels = image
+        .pixels()
+        .map(|pixel| pixel.0.map(|c| c as f32 / 255.0))
+        .collect();
+    let colors = dominant_colors(pixels, CLUSTERS);
+    ensure!(!colors.is_empty(), "{} has no pixels", path.display());
+
+    // The palette is cyclic: go up to the lightest color and back down to the darkest
+    let steps = 2 * colors.len().saturating_sub(1);
+    let stops = colors
+        .iter()
+        .enumerate()
+        .flat_map(|(i, color)| {
+            let up = ColorStop {
+                position: i as f32 / steps.max(1) as f32,
+                color: *color,
+            };
+            let down = (i > 0 && i + 1 < colors.len()).then_some(ColorStop {
+                position: 1.0 - i as f32 / steps as f32,
+                color: *color,
+            });
+            std::iter::once(up).chain(down)
+        })
+        .collect();
+
+    let name = path.file_stem().map_or("Image".to_string(), |stem| {
+        stem.to_string_lossy().into_owned()
+    });
+    Ok(Palette::new(name, stops))
+}
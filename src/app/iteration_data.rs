@@ -0,0 +1,112 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    thread::{self, JoinHandle},
+};
+
+use anyhow::{anyhow, ensure, Context, Result};
+
+use super::{i18n::tr_args, orbit::smooth_iterations, FractalType, State};
+
+/// Smooth iteration count of each pixel of a `width` x `height` view of `state`, row by row
+/// from the top. NaN inside the set.
+fn compute(state: &State, width: usize, height: usize) -> Vec<f64> {
+    (0..width * height)
+        .map(|i| {
+            let (column, row) = (i % width, i / width);
+            let offset = [
+                column as f32 + 0.5 - width as f32 / 2.0,
+                row as f32 + 0.5 - height as f32 / 2.0,
+            ];
+            smooth_iterations(state, state.point_at(offset)).unwrap_or(f64::NAN)
+        })
+        .collect()
+}
+
+/// NumPy `.npy` file of a `height` x `width` array of float64.
+fn write_npy(file: &mut impl Write, values: &[f64], width: usize, height: usize) -> Result<()> {
+    let mut header = format!(
+        "{{'descr': '<f8', 'fortran_order': False, 'shape': ({}, {}), }}",
+        height, width
+    );
+    // The magic, version and length take 10 bytes, the data starts on a multiple of 64
+    let padding = 63 - (10 + header.len()) % 64;
+    header.push_str(&" ".repeat(padding));
+    header.push('\n');
+
+    file.write_all(b"\x93NUMPY\x01\x00")?;
+    file.write_all(&(header.len() as u16).to_le_bytes())?;
+    file.write_all(header.as_bytes())?;
+    for value in values {
+        file.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// One line of comma-separated values per row of pixels.
+fn write_csv(file: &mut impl Write, values: &[f64], width: usize) -> Result<()> {
+    for row in values.chunks(width) {
+        let line: Vec<String> = row.iter().map(f64::to_string).collect();
+        writeln!(file, "{}", line.join(","))?;
+    }
+    Ok(())
+}
+
+fn save(path: &Path, values: &[f64], width: usize, height: usize) -> Result<()> {
+    let mut file = BufWriter::new(
+        File::create(path).with_context(|| format!("Cannot create {}", path.display()))?,
+    );
+    if path.extension().is_some_and(|extension| extension == "csv") {
+        write_csv(&mut file, values, width)?;
+    } else {
+        write_npy(&mut file, values, width, height)?;
+    }
+    file.flush()?;
+    Ok(())
+}
+
+/// Export of the smooth iteration count of each pixel of the view, as a NumPy array or CSV
+/// depending on the extension of the file.
+///
+/// The values are computed on the CPU in double precision, in a background thread.
+pub struct IterationExport {
+    thread: JoinHandle<Result<PathBuf>>,
+}
+
+impl IterationExport {
+    pub fn start(path: PathBuf, state: &State, size_px: [usize; 2]) -> Result<Self> {
+        ensure!(
+            !matches!(
+                state.fractal_type,
+                FractalType::Custom
+                    | FractalType::Newton
+                    | FractalType::Lyapunov
+                    | FractalType::Nova
+            ),
+            tr_args(
+                "iteration-data-unsupported",
+                &[("fractal", &state.fractal_type.label())]
+            )
+        );
+        let state = *state;
+        let [width, height] = size_px.map(|size| size.max(1));
+        let thread = thread::spawn(move || {
+            let values = compute(&state, width, height);
+            save(&path, &values, width, height)?;
+            Ok(path)
+        });
+        Ok(Self { thread })
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// File written, once [IterationExport::is_finished].
+    pub fn join(self) -> Result<PathBuf> {
+        self.thread
+            .join()
+            .map_err(|_| anyhow!("The export of the iterations panicked"))?
+    }
+}
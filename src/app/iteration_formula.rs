@@ -0,0 +1,217 @@
+use anyhow::{bail, Result};
+use eframe::egui::{Color32, TextEdit, Ui};
+
+use super::{
+    expression::{BinaryOp, Expr, Function},
+    i18n::tr,
+};
+
+/// Complex versions of the functions of the formulas, before the `iterate` function.
+/// `complexMultiply` and `complexDivide` come from the fragment shader.
+const HELPERS: &str = "\
+vec2 formulaExp(vec2 z) {
+    return exp(z.x) * vec2(cos(z.y), sin(z.y));
+}
+
+vec2 formulaLog(vec2 z) {
+    return vec2(log(length(z)), atan(z.y, z.x));
+}
+
+vec2 formulaPow(vec2 a, vec2 b) {
+    return dot(a, a) == 0.0 ? vec2(0.0) : formulaExp(complexMultiply(b, formulaLog(a)));
+}
+
+vec2 formulaSqrt(vec2 z) {
+    float r = length(z);
+    return vec2(sqrt(0.5 * (r + z.x)), (z.y < 0.0 ? -1.0 : 1.0) * sqrt(0.5 * (r - z.x)));
+}
+
+vec2 formulaSin(vec2 z) {
+    return vec2(sin(z.x) * cosh(z.y), cos(z.x) * sinh(z.y));
+}
+
+vec2 formulaCos(vec2 z) {
+    return vec2(cos(z.x) * cosh(z.y), -sin(z.x) * sinh(z.y));
+}
+
+vec2 formulaTan(vec2 z) {
+    return complexDivide(formulaSin(z), formulaCos(z));
+}
+";
+
+/// Largest integer power expanded into multiplications, more precise than [HELPERS]'
+/// `formulaPow`.
+const MAX_EXPANDED_POWER: f64 = 8.0;
+
+/// GLSL of the complex expression `expr`, as a `vec2`.
+fn to_glsl(expr: &Expr) -> Result<String> {
+    Ok(match expr {
+        Expr::Number(x) => {
+            let value = *x as f32;
+            if !value.is_finite() {
+                bail!("{} is too large", x);
+            }
+            format!("vec2({:?}, 0.0)", value)
+        }
+        Expr::Variable(name) => match name.as_str() {
+            "z" | "c" => name.clone(),
+            "i" => "vec2(0.0, 1.0)".to_string(),
+            _ => bail!("Unknown variable {:?}, only z, c and i exist", name),
+        },
+        Expr::Negate(e) => format!("(-{})", to_glsl(e)?),
+        Expr::Call(function, e) => {
+            let argument = to_glsl(e)?;
+            match function {
+                Function::Sin => format!("formulaSin({argument})"),
+                Function::Cos => format!("formulaCos({argument})"),
+                Function::Tan => format!("formulaTan({argument})"),
+                Function::Exp => format!("formulaExp({argument})"),
+                Function::Log => format!("formulaLog({argument})"),
+                Function::Sqrt => format!("formulaSqrt({argument})"),
+                // Of each part, as the Burning Ship does
+                Function::Abs | Function::Floor | Function::Fract => {
+                    format!("{}({argument})", function.name())
+                }
+            }
+        }
+        Expr::Binary(op, a, b) => {
+            let left = to_glsl(a)?;
+            match op {
+                BinaryOp::Add => format!("({left} + {})", to_glsl(b)?),
+                BinaryOp::Sub => format!("({left} - {})", to_glsl(b)?),
+                BinaryOp::Mul => format!("complexMultiply({left}, {})", to_glsl(b)?),
+                BinaryOp::Div => format!("complexDivide({left}, {})", to_glsl(b)?),
+                BinaryOp::Pow => match **b {
+                    Expr::Number(n)
+                        if n.fract() == 0.0 && (1.0..=MAX_EXPANDED_POWER).contains(&n) =>
+                    {
+                        let mut glsl = left.clone();
+                        for _ in 1..n as usize {
+                            glsl = format!("complexMultiply({glsl}, {left})");
+                        }
+                        glsl
+                    }
+                    _ => format!("formulaPow({left}, {})", to_glsl(b)?),
+                },
+            }
+        }
+    })
+}
+
+/// GLSL of the `iterate` function of the formula `source`, like `z = z^3 + c`, the `z =`
+/// being optional.
+fn compile(source: &str) -> Result<String> {
+    let source = source.trim();
+    let source = source
+        .strip_prefix('z')
+        .and_then(|rest| rest.trim_start().strip_prefix('='))
+        .unwrap_or(source);
+    let glsl = to_glsl(&Expr::parse(source)?)?;
+    Ok(format!(
+        "{HELPERS}\nvec2 iterate(vec2 z, vec2 c) {{\n    return {glsl};\n}}"
+    ))
+}
+
+/// Iteration of the custom fractal type typed as a formula of the complex numbers `z` and
+/// `c`, instead of written in GLSL.
+pub struct IterationFormula {
+    source: String,
+    error: Option<String>,
+}
+
+impl IterationFormula {
+    pub fn new() -> Self {
+        Self {
+            source: "z = z^3 + c".to_string(),
+            error: None,
+        }
+    }
+
+    /// GLSL of the `iterate` function, if the formula is valid.
+    pub fn glsl(&self) -> Option<String> {
+        compile(&self.source).ok()
+    }
+
+    /// Returns the new GLSL of the `iterate` function when the formula is edited into a
+    /// valid one.
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<String> {
+        let mut glsl = None;
+        if ui
+            .add(TextEdit::singleline(&mut self.source).code_editor())
+            .on_hover_text(tr("iteration-formula-hint"))
+            .changed()
+        {
+            match compile(&self.source) {
+                Ok(compiled) => {
+                    self.error = None;
+                    glsl = Some(compiled);
+                }
+                Err(e) => self.error = Some(e.to_string()),
+            }
+        }
+        if let Some(e) = &self.error {
+            ui.colored_label(Color32::RED, e.as_str());
+        }
+        glsl
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returned expression of the `iterate` function of `source`.
+    fn iterate(source: &str) -> String {
+        let glsl = compile(source).unwrap();
+        assert!(glsl.starts_with(HELPERS));
+        glsl.split_once("vec2 iterate(vec2 z, vec2 c) {\n    return ")
+            .and_then(|(_, rest)| rest.strip_suffix(";\n}"))
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn mandelbrot() {
+        let expected = "(complexMultiply(z, z) + c)";
+        assert_eq!(iterate("z = z^2 + c"), expected);
+        assert_eq!(iterate("  z=z^2+c "), expected);
+        assert_eq!(iterate("z^2 + c"), expected);
+        assert_eq!(iterate("z * z + c"), expected);
+    }
+
+    #[test]
+    fn powers() {
+        assert_eq!(
+            iterate("z^3 + c"),
+            "(complexMultiply(complexMultiply(z, z), z) + c)"
+        );
+        assert_eq!(iterate("z^1"), "z");
+        assert_eq!(iterate("z^2.5"), "formulaPow(z, vec2(2.5, 0.0))");
+        assert_eq!(iterate("z^9"), "formulaPow(z, vec2(9.0, 0.0))");
+        assert_eq!(iterate("z^c"), "formulaPow(z, c)");
+    }
+
+    #[test]
+    fn functions_and_constants() {
+        assert_eq!(
+            iterate("abs(z)^2 + c"),
+            "(complexMultiply(abs(z), abs(z)) + c)"
+        );
+        assert_eq!(iterate("sin(z) / c"), "complexDivide(formulaSin(z), c)");
+        assert_eq!(
+            iterate("exp(-z) + 0.5 * i"),
+            "(formulaExp((-z)) + complexMultiply(vec2(0.5, 0.0), vec2(0.0, 1.0)))"
+        );
+    }
+
+    #[test]
+    fn invalid_formulas() {
+        for source in ["z = ", "z^2 + x", "z^2 +", "sin z", "z = 1e300", "z ; c"] {
+            assert!(compile(source).is_err(), "{source:?}");
+        }
+        let mut formula = IterationFormula::new();
+        assert!(formula.glsl().is_some());
+        formula.source = "z^2 + d".to_string();
+        assert!(formula.glsl().is_none());
+    }
+}
@@ -0,0 +1,206 @@
+use std::f64::consts::TAU;
+
+use eframe::egui::{Button, ComboBox, DragValue, Slider, Ui};
+
+use super::i18n::{tr, tr_args};
+
+/// Longest pause kept between two recorded samples, in seconds.
+const MAX_SAMPLE_GAP: f64 = 0.1;
+/// Time taken to go back from the last point to the first one when looping, in seconds.
+const LOOP_BACK_DURATION: f64 = 0.5;
+
+/// Duration of a loop of the circle and of the lemniscate at speed 1, in seconds.
+const SHAPE_PERIOD: f64 = 20.0;
+
+/// Trajectory of the Julia constant.
+#[derive(Clone, Copy, PartialEq)]
+enum PathShape {
+    /// Drawn with the DragPanel.
+    Recorded,
+    Circle,
+    /// Figure eight of Bernoulli, crossing itself at the center.
+    Lemniscate,
+}
+
+impl PathShape {
+    const ALL: [PathShape; 3] = [
+        PathShape::Recorded,
+        PathShape::Circle,
+        PathShape::Lemniscate,
+    ];
+
+    fn label(self) -> String {
+        tr(match self {
+            PathShape::Recorded => "path-recorded",
+            PathShape::Circle => "path-circle",
+            PathShape::Lemniscate => "path-lemniscate",
+        })
+    }
+
+    /// Point at the fraction `t` of a loop of radius 1 around 0.
+    fn point(self, t: f64) -> [f64; 2] {
+        let angle = t * TAU;
+        match self {
+            PathShape::Circle => [angle.cos(), angle.sin()],
+            PathShape::Lemniscate => {
+                let scale = 1.0 / (1.0 + angle.sin().powi(2));
+                [angle.cos() * scale, angle.sin() * angle.cos() * scale]
+            }
+            // Played from its samples
+            PathShape::Recorded => [0.0, 0.0],
+        }
+    }
+}
+
+/// Julia constant moving along a circle, a lemniscate or a trajectory drawn with the
+/// DragPanel, replayed in a loop for the morphing Julia sets.
+pub struct JuliaPath {
+    shape: PathShape,
+    /// Center and radius of the circle and of the lemniscate.
+    center: [f32; 2],
+    radius: f32,
+    /// Time since the start of the recording, and the Julia constant at that time.
+    samples: Vec<(f64, [f32; 2])>,
+    recording: bool,
+    /// Time of the last recorded drag event, to skip the pauses between drags.
+    last_record_time: Option<f64>,
+    playing: bool,
+    playback_time: f64,
+    speed: f32,
+}
+
+impl JuliaPath {
+    pub fn new() -> Self {
+        Self {
+            shape: PathShape::Recorded,
+            // Circle of the classic animation, its Julia sets go through many shapes
+            center: [0.0, 0.0],
+            radius: 0.7885,
+            samples: Vec::new(),
+            recording: false,
+            last_record_time: None,
+            playing: false,
+            playback_time: 0.0,
+            speed: 1.0,
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ComboBox::from_label(tr("path-shape"))
+            .selected_text(self.shape.label())
+            .show_ui(ui, |ui| {
+                for shape in PathShape::ALL {
+                    ui.selectable_value(&mut self.shape, shape, shape.label());
+                }
+            });
+        if self.shape != PathShape::Recorded {
+            self.recording = false;
+            ui.horizontal(|ui| {
+                ui.label(tr("path-center"));
+                let [x, y] = &mut self.center;
+                ui.add(DragValue::new(x).speed(0.005));
+                ui.add(DragValue::new(y).speed(0.005).suffix("i"));
+            });
+            ui.add(
+                Slider::new(&mut self.radius, 0.01..=2.0)
+                    .logarithmic(true)
+                    .text(tr("path-radius")),
+            );
+        }
+        ui.horizontal(|ui| {
+            if self.shape == PathShape::Recorded {
+                let label = if self.recording {
+                    tr("path-stop")
+                } else {
+                    tr("path-record")
+                };
+                if ui
+                    .button(label)
+                    .on_hover_text(tr("path-record-hint"))
+                    .clicked()
+                {
+                    self.recording = !self.recording;
+                    if self.recording {
+                        self.samples.clear();
+                        self.last_record_time = None;
+                        self.playing = false;
+                    }
+                }
+            }
+
+            let can_play =
+                self.shape != PathShape::Recorded || (self.samples.len() > 1 && !self.recording);
+            self.playing &= can_play;
+            let label = if self.playing {
+                tr("path-pause")
+            } else {
+                tr("path-play")
+            };
+            if ui.add_enabled(can_play, Button::new(label)).clicked() {
+                self.playing = !self.playing;
+            }
+        });
+        ui.add(
+            Slider::new(&mut self.speed, 0.1..=4.0)
+                .logarithmic(true)
+                .text(tr("path-speed")),
+        );
+        if let (PathShape::Recorded, Some((duration, _))) = (self.shape, self.samples.last()) {
+            ui.label(tr_args(
+                "path-length",
+                &[
+                    ("points", &self.samples.len()),
+                    ("duration", &format!("{:.1}", duration)),
+                ],
+            ));
+        }
+    }
+
+    /// Add a point to the path, `time` is the time of the input event in seconds.
+    pub fn record(&mut self, time: f64, x: f32, y: f32) {
+        if !self.recording {
+            return;
+        }
+        let elapsed = match (self.last_record_time, self.samples.last()) {
+            (Some(last_time), Some((last_elapsed, _))) => {
+                last_elapsed + (time - last_time).clamp(0.0, MAX_SAMPLE_GAP)
+            }
+            _ => 0.0,
+        };
+        self.samples.push((elapsed, [x, y]));
+        self.last_record_time = Some(time);
+    }
+
+    /// Move the playback forward by `dt` seconds and write the Julia constant at that time.
+    pub fn advance(&mut self, dt: f64, x: &mut f32, y: &mut f32) {
+        if self.shape != PathShape::Recorded {
+            self.playback_time = (self.playback_time + dt * self.speed as f64) % SHAPE_PERIOD;
+            let [u, v] = self.shape.point(self.playback_time / SHAPE_PERIOD);
+            *x = self.center[0] + self.radius * u as f32;
+            *y = self.center[1] + self.radius * v as f32;
+            return;
+        }
+        let (Some(first), Some(last)) = (self.samples.first(), self.samples.last()) else {
+            return;
+        };
+        let loop_duration = last.0 + LOOP_BACK_DURATION;
+        self.playback_time = (self.playback_time + dt * self.speed as f64) % loop_duration;
+        let time = self.playback_time;
+
+        let (from, to, ratio) = match self.samples.iter().position(|(t, _)| *t > time) {
+            Some(i) => {
+                let (from, to) = (&self.samples[i - 1], &self.samples[i]);
+                (from.1, to.1, (time - from.0) / (to.0 - from.0))
+            }
+            // Between the last point and the first one
+            None => (last.1, first.1, (time - last.0) / LOOP_BACK_DURATION),
+        };
+        let ratio = ratio as f32;
+        *x = from[0] + (to[0] - from[0]) * ratio;
+        *y = from[1] + (to[1] - from[1]) * ratio;
+    }
+}
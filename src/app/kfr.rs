@@ -0,0 +1,282 @@
+use std::{fmt::Write as _, fs, path::Path};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use super::{perturbation, state::MAX_ITERATIONS, FractalType, Position, State};
+
+/// Significant digits of a double, the precision of the center of the views.
+const DOUBLE_DIGITS: usize = 17;
+
+/// Location read from a Kalles Fraktaler `.kfr` parameter file.
+///
+/// The center and the zoom are kept as the decimal strings found in the file, deep-zoom
+/// locations carry far more digits than a float can hold. They are only rounded when
+/// applied to a [State], and written back as they are.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KfrLocation {
+    pub re: String,
+    pub im: String,
+    /// Kalles Fraktaler magnification: at zoom 1 the view is 4 units high.
+    pub zoom: String,
+    pub iterations: Option<u32>,
+}
+
+/// Whether `value` is a decimal number like `-1.25E-3`, of any length.
+fn is_decimal(value: &str) -> bool {
+    let value = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let (mantissa, exponent) = match value.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (value, None),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    !(integer.is_empty() && fraction.is_empty())
+        && digits(integer)
+        && digits(fraction)
+        && exponent.is_none_or(|exponent| {
+            let exponent = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+            !exponent.is_empty() && digits(exponent)
+        })
+}
+
+/// Significant digits of the decimal number `value`.
+fn significant_digits(value: &str) -> usize {
+    let mantissa = value.split(['e', 'E']).next().unwrap_or(value);
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    digits.trim_start_matches('0').len()
+}
+
+fn decimal(value: &str, name: &str) -> Result<String> {
+    if !is_decimal(value) {
+        bail!("Invalid {name} {value:?}");
+    }
+    Ok(value.to_string())
+}
+
+impl KfrLocation {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+        Self::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut re = None;
+        let mut im = None;
+        let mut zoom = None;
+        let mut iterations = None;
+
+        // Every line is a `Key: Value` pair, we only care about the location ones
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "Re" => re = Some(decimal(value, "real part")?),
+                "Im" => im = Some(decimal(value, "imaginary part")?),
+                "Zoom" => {
+                    let value = decimal(value, "zoom")?;
+                    if value.starts_with('-') || significant_digits(&value) == 0 {
+                        bail!("Invalid zoom {value:?}");
+                    }
+                    zoom = Some(value);
+                }
+                "Iterations" => {
+                    iterations = Some(
+                        value
+                            .parse::<u32>()
+                            .with_context(|| format!("Invalid iteration count {value:?}"))?,
+                    )
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            re: re.ok_or(anyhow!("Missing the Re entry"))?,
+            im: im.ok_or(anyhow!("Missing the Im entry"))?,
+            zoom: zoom.ok_or(anyhow!("Missing the Zoom entry"))?,
+            iterations,
+        })
+    }
+
+    /// Location of the view described by `state`. Only the Mandelbrot set can be exported.
+    pub fn from_state(state: &State, viewport_height_px: f32) -> Result<Self> {
+        if state.fractal_type != FractalType::Mandelbrot {
+            bail!(
+                "Only the Mandelbrot set can be exported, not {:?}",
+                state.fractal_type
+            );
+        }
+        Ok(Self {
+            re: (-state.center_position.x).to_string(),
+            im: (-state.center_position.y).to_string(),
+            zoom: format!("{:E}", state.magnification(viewport_height_px)),
+            iterations: Some(state.iterations()),
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut content = String::new();
+        writeln!(content, "Re: {}\r", self.re)?;
+        writeln!(content, "Im: {}\r", self.im)?;
+        writeln!(content, "Zoom: {}\r", self.zoom)?;
+        if let Some(iterations) = self.iterations {
+            writeln!(content, "Iterations: {}\r", iterations)?;
+        }
+        writeln!(content, "FractalType: 0\r")?;
+        writeln!(content, "Power: 2\r")?;
+        fs::write(path, content).with_context(|| format!("Cannot write {}", path.display()))
+    }
+
+    /// Whether the center has more digits than [State] keeps, it is then rounded by
+    /// [KfrLocation::apply].
+    pub fn is_rounded(&self) -> bool {
+        significant_digits(&self.re).max(significant_digits(&self.im)) > DOUBLE_DIGITS
+    }
+
+    /// Move `state` to this location, rounded to the precision of the renderer. Kalles
+    /// Fraktaler only renders the Mandelbrot set, and zooms far deeper than the
+    /// [perturbation::deepest_zoom] of the renderer.
+    pub fn apply(&self, state: &mut State, viewport_height_px: f32) -> Result<()> {
+        // Validated by the parser, but the fields are public
+        let number = |value: &str| {
+            value
+                .parse::<f64>()
+                .with_context(|| format!("Invalid number {value:?}"))
+        };
+        let (re, im) = (number(&self.re)?, number(&self.im)?);
+        let zoom = number(&self.zoom)? * viewport_height_px as f64 / 4.0;
+        let deepest = perturbation::deepest_zoom([re, im]) as f64;
+        if !(zoom > 0.0 && zoom <= deepest) {
+            bail!(
+                "Zoom {} is out of reach of the renderer, its deepest zoom at this center is {:.0E}",
+                self.zoom,
+                deepest * 4.0 / viewport_height_px as f64
+            );
+        }
+
+        if let Some(iterations) = self.iterations {
+            state.max_iterations = iterations.clamp(16, MAX_ITERATIONS);
+            state.auto_iterations = false;
+        }
+
+        // center_position holds the opposite of the coordinate at the center of the view
+        state.center_position = Position { x: -re, y: -im };
+        state.zoom = zoom as f32;
+        state.fractal_type = FractalType::Mandelbrot;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "Re: -1.74995768370609350360221450607069970727110579726252077930242837820286008082972804887218672784431700831100544507655659531379747541999999995\r\n\
+        Im: 0.00000000000000000278793706563379402178294753790944364927085054500163081379043930650189386849765202169477470552201325772332454726999999995\r\n\
+        Zoom: 1.56013E128\r\n\
+        Iterations: 152000\r\n\
+        IterDiv: 0.010000\r\n\
+        ColorMethod: 7\r\n";
+
+    #[test]
+    fn parse_keeps_the_digits() {
+        let location = KfrLocation::parse(SAMPLE).unwrap();
+        assert!(location.re.starts_with("-1.7499576837060935036022145"));
+        assert!(location.re.ends_with("541999999995"));
+        assert!(location.im.ends_with("999999995"));
+        assert_eq!(location.zoom, "1.56013E128");
+        assert_eq!(location.iterations, Some(152000));
+        assert!(location.is_rounded());
+    }
+
+    #[test]
+    fn parse_without_iterations() {
+        let location = KfrLocation::parse("Re: -0.75\nIm: 0.1\nZoom: 4\n").unwrap();
+        assert_eq!(location.iterations, None);
+        assert!(!location.is_rounded());
+    }
+
+    #[test]
+    fn missing_entries() {
+        for content in ["Im: 0\nZoom: 1\n", "Re: 0\nZoom: 1\n", "Re: 0\nIm: 0\n", ""] {
+            assert!(KfrLocation::parse(content).is_err(), "{content:?}");
+        }
+    }
+
+    #[test]
+    fn malformed_entries() {
+        for content in [
+            "Re: 1.2.3\nIm: 0\nZoom: 1\n",
+            "Re: 0\nIm: abc\nZoom: 1\n",
+            "Re: 0\nIm: 0\nZoom: -1\n",
+            "Re: 0\nIm: 0\nZoom: 0\n",
+            "Re: 0\nIm: 0\nZoom: 1E\n",
+            "Re: 0\nIm: 0\nZoom: 1\nIterations: many\n",
+            "Re: nan\nIm: 0\nZoom: 1\n",
+        ] {
+            assert!(KfrLocation::parse(content).is_err(), "{content:?}");
+        }
+    }
+
+    #[test]
+    fn apply() {
+        let location =
+            KfrLocation::parse("Re: -0.75\nIm: 0.1\nZoom: 2\nIterations: 500\n").unwrap();
+        let mut state = State::new();
+        location.apply(&mut state, 1000.0).unwrap();
+        assert_eq!(state.fractal_type, FractalType::Mandelbrot);
+        assert_eq!(state.point_at([0.0, 0.0]), [-0.75, 0.1]);
+        assert_eq!(state.zoom, 500.0);
+        assert_eq!(state.max_iterations, 500);
+    }
+
+    #[test]
+    fn deep_zoom_is_out_of_reach() {
+        let location = KfrLocation::parse(SAMPLE).unwrap();
+        let mut state = State::new();
+        let error = location.apply(&mut state, 1000.0).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("deepest zoom at this center is 3E12"),
+            "{error}"
+        );
+        assert_eq!(state, State::new());
+
+        // The precision of the center ends long before the f32 zoom
+        let location = KfrLocation::parse("Re: -0.75\nIm: 0.1\nZoom: 1E13\n").unwrap();
+        assert!(location.apply(&mut state, 1000.0).is_err());
+        let location = KfrLocation::parse("Re: -0.75\nIm: 0.1\nZoom: 1E12\n").unwrap();
+        location.apply(&mut state, 1000.0).unwrap();
+        assert_eq!(state.zoom, 2.5e14);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut state = State::new();
+        state.fractal_type = FractalType::Mandelbrot;
+        state.center_position = Position {
+            x: 0.743643887037151,
+            y: -0.13182590420533,
+        };
+        state.zoom = 1.5e6;
+        let location = KfrLocation::from_state(&state, 800.0).unwrap();
+        let mut content = String::new();
+        for (key, value) in [
+            ("Re", &location.re),
+            ("Im", &location.im),
+            ("Zoom", &location.zoom),
+        ] {
+            content += &format!("{key}: {value}\r\n");
+        }
+        assert_eq!(KfrLocation::parse(&content).unwrap().re, location.re);
+
+        let mut applied = State::new();
+        location.apply(&mut applied, 800.0).unwrap();
+        assert_eq!(applied.center_position, state.center_position);
+        assert_eq!(applied.zoom, state.zoom);
+    }
+}
@@ -0,0 +1,140 @@
+use eframe::egui::{Button, TextEdit, Ui};
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use super::{i18n::tr, parameter::Parameter, settings};
+
+/// Name of the setting holding the layouts saved by the user.
+const LAYOUTS_SETTING: &str = "layouts.json";
+
+/// What is shown around and over the fractal.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Layout {
+    pub name: String,
+    pub settings_panel: bool,
+    pub scale_bar: bool,
+    pub explainer: bool,
+    pub annotations: bool,
+    /// Parameters of the toolbar above the canvas.
+    pub pinned: Vec<Parameter>,
+}
+
+impl Layout {
+    fn builtin() -> [Layout; 3] {
+        [
+            Layout {
+                name: tr("layout-exploring"),
+                settings_panel: true,
+                scale_bar: true,
+                explainer: false,
+                annotations: true,
+                pinned: vec![Parameter::Zoom],
+            },
+            Layout {
+                name: tr("layout-presenting"),
+                settings_panel: false,
+                scale_bar: false,
+                explainer: false,
+                annotations: false,
+                pinned: Vec::new(),
+            },
+            Layout {
+                name: tr("layout-editing-colors"),
+                settings_panel: true,
+                scale_bar: false,
+                explainer: false,
+                annotations: false,
+                pinned: vec![Parameter::Contrast, Parameter::Brightness, Parameter::Gamma],
+            },
+        ]
+    }
+}
+
+/// The built-in layouts, and the ones saved by the user.
+pub struct Layouts {
+    saved: Vec<Layout>,
+    /// Name typed for the next layout to save.
+    new_name: String,
+}
+
+impl Layouts {
+    pub fn new() -> Self {
+        Self {
+            saved: Vec::new(),
+            new_name: String::new(),
+        }
+    }
+
+    /// Layouts saved from the previous runs.
+    pub fn load() -> Self {
+        let saved = settings::load(LAYOUTS_SETTING).map_or(Vec::new(), |content| {
+            serde_json::from_str(&content).unwrap_or_else(|e| {
+                error!("Cannot load the layouts: {:?}", e);
+                Vec::new()
+            })
+        });
+        Self {
+            saved,
+            new_name: String::new(),
+        }
+    }
+
+    fn save(&self) {
+        let saved = serde_json::to_string_pretty(&self.saved)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| settings::save(LAYOUTS_SETTING, &content));
+        if let Err(e) = saved {
+            error!("Cannot save the layouts: {:?}", e);
+        }
+    }
+
+    /// Content of the layouts menu. Returns the layout to switch to, if one was picked.
+    /// `current` is saved under the name typed, if asked.
+    pub fn menu_ui(&mut self, ui: &mut Ui, current: Layout) -> Option<Layout> {
+        let mut picked = None;
+        for layout in Layout::builtin()
+            .into_iter()
+            .chain(self.saved.iter().cloned())
+        {
+            if ui.button(&layout.name).clicked() {
+                picked = Some(layout);
+                ui.close_menu();
+            }
+        }
+        ui.separator();
+
+        let mut removed = None;
+        for (i, layout) in self.saved.iter().enumerate() {
+            if ui
+                .button(format!("{} {}", tr("remove"), layout.name))
+                .clicked()
+            {
+                removed = Some(i);
+            }
+        }
+        if let Some(i) = removed {
+            self.saved.remove(i);
+            self.save();
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.new_name)
+                    .hint_text(tr("layout-name"))
+                    .desired_width(120.0),
+            );
+            let name = self.new_name.trim().to_string();
+            if ui
+                .add_enabled(!name.is_empty(), Button::new(tr("save-layout")))
+                .clicked()
+            {
+                // Saving under an existing name replaces it
+                self.saved.retain(|layout| layout.name != name);
+                self.saved.push(Layout { name, ..current });
+                self.new_name.clear();
+                self.save();
+            }
+        });
+        picked
+    }
+}
@@ -0,0 +1,52 @@
+use eframe::egui::{Color32, Painter, Pos2, Slider, Stroke, Ui};
+
+use super::{i18n::tr, Position, State};
+
+/// Diameter of the lens on the screen, in points.
+pub const DIAMETER: f32 = 200.0;
+
+/// Circle following the pointer, showing a magnified and more detailed view under it.
+pub struct Lens {
+    pub enabled: bool,
+    magnification: f32,
+}
+
+impl Lens {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            magnification: 4.0,
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.enabled, tr("magnifier"));
+            ui.add_enabled(
+                self.enabled,
+                Slider::new(&mut self.magnification, 4.0..=8.0).suffix("×"),
+            );
+        });
+    }
+
+    /// View of the lens centered on `point`, drawn in its own square viewport.
+    pub fn state(&self, state: &State, point: [f64; 2]) -> State {
+        State {
+            center_position: Position {
+                x: -point[0],
+                y: -point[1],
+            },
+            zoom: state.zoom * self.magnification,
+            high_quality: true,
+            circular: true,
+            ..*state
+        }
+    }
+
+    /// Rim of the lens centered on `center`.
+    pub fn paint(&self, painter: &Painter, center: Pos2) {
+        let radius = DIAMETER / 2.0;
+        painter.circle_stroke(center, radius, Stroke::new(3.0, Color32::BLACK));
+        painter.circle_stroke(center, radius, Stroke::new(1.0, Color32::WHITE));
+    }
+}
@@ -0,0 +1,166 @@
+use eframe::egui::{Checkbox, ComboBox, Grid, Slider, Ui};
+
+use super::{
+    accessibility::icon_button,
+    i18n::{tr, tr_args},
+    parameter::Parameter,
+    State,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Waveform {
+    Sine,
+    Triangle,
+    /// A new random value every period.
+    Random,
+}
+
+impl Waveform {
+    const ALL: [Waveform; 3] = [Waveform::Sine, Waveform::Triangle, Waveform::Random];
+
+    fn label(self) -> String {
+        tr(match self {
+            Waveform::Sine => "waveform-sine",
+            Waveform::Triangle => "waveform-triangle",
+            Waveform::Random => "waveform-random",
+        })
+    }
+
+    /// Value of the waveform in `[-1, 1]`, `cycles` is the number of periods since the start.
+    fn value(self, cycles: f64, seed: u64) -> f32 {
+        let phase = cycles.rem_euclid(1.0) as f32;
+        match self {
+            Waveform::Sine => (phase * std::f32::consts::TAU).sin(),
+            Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            Waveform::Random => {
+                let hash = splitmix64(seed ^ cycles.floor() as i64 as u64);
+                (hash >> 40) as f32 / (1u64 << 24) as f32 * 2.0 - 1.0
+            }
+        }
+    }
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// Name of the LFO `index`, counted from 1.
+fn lfo_name(index: usize) -> String {
+    tr_args("lfo", &[("number", &(index + 1))])
+}
+
+/// Low frequency oscillator.
+struct Lfo {
+    waveform: Waveform,
+    /// In Hz.
+    rate: f32,
+    /// Fraction of the slider range of the modulated parameters.
+    depth: f32,
+    /// Offset in the period, in `[0, 1)`.
+    phase: f32,
+    /// Parameters modulated by this LFO.
+    targets: Vec<Parameter>,
+    seed: u64,
+}
+
+/// LFOs, and the modulation matrix routing them to the parameters.
+pub struct Modulators {
+    lfos: Vec<Lfo>,
+    next_seed: u64,
+}
+
+impl Modulators {
+    pub fn new() -> Self {
+        Self {
+            lfos: Vec::new(),
+            next_seed: 0,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.lfos.iter().any(|lfo| !lfo.targets.is_empty())
+    }
+
+    /// Apply the modulations to the state about to be rendered, at `state.time`.
+    pub fn modulate(&self, state: &mut State) {
+        for lfo in &self.lfos {
+            let cycles = state.time as f64 * lfo.rate as f64 + lfo.phase as f64;
+            let offset = lfo.waveform.value(cycles, lfo.seed) * lfo.depth;
+            for parameter in &lfo.targets {
+                let value = parameter.value(state);
+                parameter.set(state, parameter.shift(value, offset));
+            }
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        let mut removed = None;
+        for (i, lfo) in self.lfos.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.strong(lfo_name(i));
+                ComboBox::from_id_salt(("LFO waveform", i))
+                    .selected_text(lfo.waveform.label())
+                    .show_ui(ui, |ui| {
+                        for waveform in Waveform::ALL {
+                            ui.selectable_value(&mut lfo.waveform, waveform, waveform.label());
+                        }
+                    });
+                if icon_button(ui.small_button("✖"), tr("remove")).clicked() {
+                    removed = Some(i);
+                }
+            });
+            ui.add(
+                Slider::new(&mut lfo.rate, 0.01..=10.0)
+                    .logarithmic(true)
+                    .suffix(" Hz")
+                    .text(tr("rate")),
+            );
+            ui.add(Slider::new(&mut lfo.depth, 0.0..=0.5).text(tr("depth")));
+            ui.add(Slider::new(&mut lfo.phase, 0.0..=1.0).text(tr("phase")));
+        }
+        if let Some(i) = removed {
+            self.lfos.remove(i);
+        }
+        if ui.button(tr("add-lfo")).clicked() {
+            self.lfos.push(Lfo {
+                waveform: Waveform::Sine,
+                rate: 0.2,
+                depth: 0.1,
+                phase: 0.0,
+                targets: Vec::new(),
+                seed: splitmix64(self.next_seed),
+            });
+            self.next_seed += 1;
+        }
+
+        if self.lfos.is_empty() {
+            return;
+        }
+        ui.separator();
+        Grid::new("Modulation matrix").striped(true).show(ui, |ui| {
+            ui.label("");
+            for i in 0..self.lfos.len() {
+                ui.label(lfo_name(i));
+            }
+            ui.end_row();
+
+            for parameter in Parameter::ALL {
+                ui.label(parameter.label());
+                for lfo in &mut self.lfos {
+                    let mut routed = lfo.targets.contains(&parameter);
+                    if ui.add(Checkbox::without_text(&mut routed)).changed() {
+                        if routed {
+                            lfo.targets.push(parameter);
+                        } else {
+                            lfo.targets.retain(|p| *p != parameter);
+                        }
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    }
+}
@@ -0,0 +1,45 @@
+use std::{fmt::Write as _, fs, path::Path};
+
+use anyhow::{Context, Result};
+
+use super::kfr::KfrLocation;
+
+/// Write `location` as a Mandel Machine location file. Mandel Machine uses the same
+/// magnification convention as Kalles Fraktaler, with `key = value` lines.
+pub fn save(location: &KfrLocation, path: &Path) -> Result<()> {
+    let mut content = String::new();
+    writeln!(content, "re = {}", location.re)?;
+    writeln!(content, "im = {}", location.im)?;
+    writeln!(content, "mag = {}", location.zoom)?;
+    if let Some(iterations) = location.iterations {
+        writeln!(content, "iter = {}", iterations)?;
+    }
+    fs::write(path, content).with_context(|| format!("Cannot write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_digits() {
+        let location = KfrLocation::parse(
+            "Re: -1.7499576837060935036022145\n\
+            Im: 0.0000000000000000027879\n\
+            Zoom: 1.56013E28\n\
+            Iterations: 5000\n",
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join("fractal_gl_mandel_machine_test.mmf");
+        save(&location, &path).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            content,
+            "re = -1.7499576837060935036022145\n\
+            im = 0.0000000000000000027879\n\
+            mag = 1.56013E28\n\
+            iter = 5000\n"
+        );
+    }
+}
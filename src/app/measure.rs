@@ -0,0 +1,92 @@
+use eframe::egui::{vec2, Align2, Color32, FontId, Painter, Pos2, Rect, Stroke};
+
+use super::{complex::Complex, i18n::tr_args, State};
+
+/// Distance between two points of the complex plane, picked by clicking on the canvas.
+pub struct Measure {
+    /// Clicks on the canvas pick points instead of recentering the view.
+    pub active: bool,
+    start: Option<Complex>,
+    end: Option<Complex>,
+}
+
+impl Measure {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            start: None,
+            end: None,
+        }
+    }
+
+    /// Add a point, a third click starts a new measurement.
+    pub fn click(&mut self, point: Complex) {
+        match (self.start, self.end) {
+            (Some(_), None) => self.end = Some(point),
+            _ => {
+                self.start = Some(point);
+                self.end = None;
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.start = None;
+        self.end = None;
+    }
+
+    /// Draw the measurement, to `pointer` while the second point isn't picked.
+    pub fn paint(
+        &self,
+        painter: &Painter,
+        rect: Rect,
+        pixels_per_point: f32,
+        state: &State,
+        pointer: Option<Complex>,
+    ) {
+        let Some(start) = self.start else {
+            return;
+        };
+        let Some(end) = self.end.or(pointer) else {
+            return;
+        };
+        let to_screen = |point: Complex| {
+            let [x, y] = state.offset_of([point.re, point.im]);
+            rect.center() + vec2(x, y) / pixels_per_point
+        };
+        let (a, b) = (to_screen(start), to_screen(end));
+
+        // Dark outline to stay visible over light areas of the fractal
+        for stroke in [
+            Stroke::new(4.0, Color32::BLACK),
+            Stroke::new(2.0, Color32::WHITE),
+        ] {
+            painter.line_segment([a, b], stroke);
+        }
+        for point in [a, b] {
+            painter.circle(point, 4.0, Color32::WHITE, Stroke::new(1.0, Color32::BLACK));
+        }
+
+        let delta = end - start;
+        let angle = delta.im.atan2(delta.re).to_degrees();
+        let text = tr_args(
+            "measurement",
+            &[
+                ("re", &format!("{:+.6e}", delta.re)),
+                ("im", &format!("{:+.6e}", delta.im)),
+                ("distance", &format!("{:.6e}", delta.norm())),
+                ("angle", &format!("{:.2}", angle)),
+            ],
+        );
+        let anchor = Pos2::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0) + vec2(8.0, 8.0);
+        let font = FontId::monospace(12.0);
+        painter.text(
+            anchor + vec2(1.0, 1.0),
+            Align2::LEFT_TOP,
+            &text,
+            font.clone(),
+            Color32::BLACK,
+        );
+        painter.text(anchor, Align2::LEFT_TOP, text, font, Color32::WHITE);
+    }
+}
@@ -0,0 +1,118 @@
+use eframe::egui::{vec2, Color32, Painter, Pos2, Rect, Response, Shape, Stroke};
+
+use super::{skew, FractalType, Position, State};
+
+/// Size of the minimap on the screen, in points.
+const SIZE: [f32; 2] = [160.0, 120.0];
+const MARGIN: f32 = 16.0;
+/// Height of the complex plane shown by the minimap, enough for the whole fractals.
+const OVERVIEW_HEIGHT: f32 = 3.5;
+
+/// Middle of the interesting part of the fractal.
+fn overview_center(fractal_type: FractalType) -> [f64; 2] {
+    match fractal_type {
+        FractalType::Mandelbrot | FractalType::BurningShip => [-0.5, 0.0],
+        _ => [0.0, 0.0],
+    }
+}
+
+/// Corner window with the whole fractal and the outline of the view, clicking in it moves
+/// the view there.
+pub struct Minimap {
+    pub enabled: bool,
+}
+
+impl Minimap {
+    pub fn new() -> Self {
+        Self { enabled: false }
+    }
+
+    /// Bottom right corner of the canvas `rect`, the scale bar is on the left.
+    pub fn rect(canvas: Rect) -> Rect {
+        let size = vec2(SIZE[0], SIZE[1]);
+        Rect::from_min_size(canvas.right_bottom() - size - vec2(MARGIN, MARGIN), size)
+    }
+
+    /// Whole fractal of `state`, drawn in the viewport of `rect`.
+    pub fn state(state: &State, rect: Rect, pixels_per_point: f32) -> State {
+        let [x, y] = overview_center(state.fractal_type);
+        State {
+            center_position: Position { x: -x, y: -y },
+            zoom: rect.height() * pixels_per_point / OVERVIEW_HEIGHT,
+            skew: skew::IDENTITY,
+            auto_skew: false,
+            high_quality: false,
+            supersampling: 1,
+            circular: false,
+            ..*state
+        }
+    }
+
+    /// Point of the complex plane under `pos`, in the minimap `rect` showing `overview`.
+    fn point_at(overview: &State, rect: Rect, pixels_per_point: f32, pos: Pos2) -> [f64; 2] {
+        let offset = (pos - rect.center()) * pixels_per_point;
+        overview.point_at([offset.x, offset.y])
+    }
+
+    /// Point clicked or dragged to in the minimap, where the view goes.
+    pub fn interact(
+        response: &Response,
+        rect: Rect,
+        overview: &State,
+        pixels_per_point: f32,
+    ) -> Option<[f64; 2]> {
+        if !(response.clicked() || response.dragged()) {
+            return None;
+        }
+        let pos = response.interact_pointer_pos()?;
+        Some(Self::point_at(overview, rect, pixels_per_point, pos))
+    }
+
+    /// Frame of the minimap `rect` and outline of the view `state` gives on a canvas of
+    /// `viewport_px` pixels.
+    pub fn paint(
+        painter: &Painter,
+        rect: Rect,
+        overview: &State,
+        state: &State,
+        viewport_px: [f32; 2],
+        pixels_per_point: f32,
+    ) {
+        let painter = painter.with_clip_rect(rect);
+        let [half_width, half_height] = viewport_px.map(|side| side / 2.0);
+        // Corners in order around the view, the skew can make it any parallelogram
+        let corners: Vec<Pos2> = [
+            [-half_width, -half_height],
+            [half_width, -half_height],
+            [half_width, half_height],
+            [-half_width, half_height],
+        ]
+        .into_iter()
+        .map(|corner| {
+            let [x, y] = overview.offset_of(state.point_at(corner));
+            rect.center() + vec2(x, y) / pixels_per_point
+        })
+        .collect();
+
+        let outline = Rect::from_points(&corners);
+        if outline.width().max(outline.height()) < 4.0 {
+            // Too small to see, a marker shows where it is
+            let center = outline.center();
+            for stroke in [
+                Stroke::new(3.0, Color32::BLACK),
+                Stroke::new(1.0, Color32::WHITE),
+            ] {
+                painter.line_segment([center - vec2(6.0, 0.0), center + vec2(6.0, 0.0)], stroke);
+                painter.line_segment([center - vec2(0.0, 6.0), center + vec2(0.0, 6.0)], stroke);
+            }
+        } else {
+            for stroke in [
+                Stroke::new(3.0, Color32::BLACK),
+                Stroke::new(1.0, Color32::WHITE),
+            ] {
+                painter.add(Shape::closed_line(corners.clone(), stroke));
+            }
+        }
+        painter.rect_stroke(rect, 0.0, Stroke::new(1.0, Color32::WHITE));
+    }
+}
@@ -0,0 +1,75 @@
+use super::complex::Complex;
+
+/// Largest preperiod and period looked for.
+const MAX_PREPERIOD: u32 = 8;
+const MAX_PERIOD: u32 = 4;
+const NEWTON_STEPS: usize = 64;
+const TOLERANCE: f64 = 1e-12;
+
+/// Point `c` where the orbit of 0 becomes periodic after a few iterations.
+#[derive(Clone, Copy, Debug)]
+pub struct Misiurewicz {
+    pub c: Complex,
+    /// Number of iterations before reaching the cycle.
+    pub preperiod: u32,
+    pub period: u32,
+}
+
+/// `z_n` and its derivative with respect to `c`, for `z_0 = 0` and `z_{n+1} = z_n² + c`.
+fn orbit(c: Complex, n: u32) -> (Complex, Complex) {
+    let (mut z, mut dz) = (Complex::ZERO, Complex::ZERO);
+    for _ in 0..n {
+        dz = z * dz * 2.0 + Complex::ONE;
+        z = z * z + c;
+    }
+    (z, dz)
+}
+
+/// Solve `z_{preperiod + period} = z_preperiod` with Newton's method, starting from `c`.
+fn newton(mut c: Complex, preperiod: u32, period: u32) -> Option<Complex> {
+    for _ in 0..NEWTON_STEPS {
+        let (z_cycle, dz_cycle) = orbit(c, preperiod + period);
+        let (z_pre, dz_pre) = orbit(c, preperiod);
+        let step = (z_cycle - z_pre) / (dz_cycle - dz_pre);
+        if !step.is_finite() {
+            return None;
+        }
+        c = c - step;
+        if step.norm() < TOLERANCE {
+            return Some(c);
+        }
+    }
+    None
+}
+
+/// The preperiod is exact when the orbit wasn't already periodic one iteration earlier.
+fn is_strictly_preperiodic(c: Complex, preperiod: u32, period: u32) -> bool {
+    let (z_cycle, _) = orbit(c, preperiod + period - 1);
+    let (z_pre, _) = orbit(c, preperiod - 1);
+    (z_cycle - z_pre).norm() > 1e-8
+}
+
+/// Misiurewicz point closest to `start` among those found with Newton's method for small
+/// preperiods and periods.
+pub fn find(start: Complex) -> Option<Misiurewicz> {
+    let mut best: Option<Misiurewicz> = None;
+    for preperiod in 1..=MAX_PREPERIOD {
+        for period in 1..=MAX_PERIOD {
+            let Some(c) = newton(start, preperiod, period) else {
+                continue;
+            };
+            if !is_strictly_preperiodic(c, preperiod, period) {
+                continue;
+            }
+            let closer = best.is_none_or(|best| (c - start).norm() < (best.c - start).norm());
+            if closer {
+                best = Some(Misiurewicz {
+                    c,
+                    preperiod,
+                    period,
+                });
+            }
+        }
+    }
+    best
+}
@@ -1,229 +1,3301 @@
-use eframe::egui::{self, CollapsingHeader, PointerButton, ScrollArea, Slider};
-use log::info;
+use eframe::egui::{
+    self, collapsing_header::CollapsingState, CollapsingHeader, PointerButton, ScrollArea,
+};
+use log::{info, warn};
 
-use egui::{mutex::Mutex, ComboBox, Pos2};
-use std::sync::Arc;
+use egui::{load::SizedTexture, mutex::Mutex, Button, ComboBox, Pos2};
+use std::{
+    f32::consts::{PI, TAU},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 mod state;
-pub use state::{FractalType, State};
+pub use state::{
+    BoundaryStyle, ColoringAlgorithm, FractalType, IterationColoring, LyapunovSequence, PluginId,
+    State, TrapShape,
+};
 
 mod position;
 pub use position::Position;
 
+mod about;
+use about::{About, Diagnostics};
+
+mod accessibility;
+use accessibility::{arrow_keys, icon_button, is_typing};
+
+mod animation;
+use animation::Animations;
+
+mod angle_dial;
+use angle_dial::AngleDial;
+
+mod annotations;
+use annotations::Annotations;
+
+mod appearance;
+use appearance::Appearance;
+
+mod audio;
+use audio::AudioReactive;
+
+mod benchmark;
+use benchmark::Benchmark;
+
+mod bigtiff;
+use bigtiff::BigTiffExport;
+
+mod bookmarks;
+use bookmarks::Bookmarks;
+
+mod capabilities;
+
+mod checkpoint;
+
+mod color_wheel;
+use color_wheel::ColorWheel;
+
+mod coloring_graph;
+use coloring_graph::ColoringGraph;
+
+mod complex;
+use complex::Complex;
+
+mod composite;
+use composite::{CompositeExport, CompositeLayout};
+
+mod controls;
+use controls::Controls;
+
+mod coordinates;
+
+mod cpu_renderer;
+use cpu_renderer::CpuRenderer;
+
+mod custom_fractal;
+use custom_fractal::CustomFractal;
+
 mod drag_panel;
 use drag_panel::DragPanel;
 
+mod dzi;
+use dzi::DziExport;
+
+mod expression;
+
+mod explainer;
+
+mod external;
+
+mod external_ray;
+use external_ray::ExternalRays;
+
+mod farm;
+use farm::Farm;
+
+mod file_dialog;
+
+mod formula;
+use formula::Formula;
+
 mod fractal_gl;
 use fractal_gl::FractalGl;
 
-use anyhow::{self, Error, Result};
+mod frame_rate;
+pub use frame_rate::FrameRate;
+
+mod gallery;
+use gallery::Gallery;
+
+#[cfg(all(test, feature = "gl-tests"))]
+mod gl_tests;
+
+mod gpu;
+use gpu::Gpu;
+pub use gpu::GpuPreference;
+
+mod gradient_editor;
+use gradient_editor::GradientEditor;
+
+#[cfg(feature = "headless")]
+mod headless;
+
+mod help_overlay;
+use help_overlay::{HelpOverlay, Topic};
+
+mod histogram;
+use histogram::Histogram;
+
+mod history;
+use history::History;
+
+mod hud;
+
+mod i18n;
+use i18n::{tr, tr_args};
+
+mod image_palette;
+
+mod iteration_data;
+use iteration_data::IterationExport;
+
+mod iteration_formula;
+use iteration_formula::IterationFormula;
+
+mod julia_path;
+use julia_path::JuliaPath;
+
+mod kfr;
+use kfr::KfrLocation;
+
+mod layout;
+use layout::{Layout, Layouts};
+
+mod lens;
+use lens::Lens;
+
+mod lfo;
+use lfo::Modulators;
+
+mod mandel_machine;
+
+mod measure;
+use measure::Measure;
+
+mod minimap;
+use minimap::Minimap;
+
+mod misiurewicz;
+use misiurewicz::Misiurewicz;
+
+mod nucleus;
+
+mod nudge;
+use nudge::Nudge;
+
+mod orbit;
+
+mod overlay;
+use overlay::{ImageCanvas, Overlay};
+
+mod palette;
+use palette::Interpolation;
+pub use palette::Palette;
+
+mod palette_previews;
+use palette_previews::PalettePreviews;
+
+mod palette_strip;
+
+mod parameter;
+use parameter::Parameter;
+
+mod period;
+
+mod perturbation;
+
+mod pinned;
+use pinned::Pinned;
+
+mod plugins;
+use plugins::Plugins;
+
+mod png;
+use png::PngExport;
+
+mod preset;
+
+mod probe;
+
+mod quality;
+use quality::Quality;
+
+mod random_palette;
+use random_palette::RandomPalette;
+
+mod recent;
+use recent::Recent;
+
+mod refinement;
+use refinement::Progressive;
+
+mod region;
+use region::Region;
+
+mod render_cli;
+pub use render_cli::render_from_args;
+
+mod render_stats;
+use render_stats::RenderStats;
+
+mod replay;
+use replay::Replay;
+
+mod scale_bar;
+
+mod screenshot;
+
+mod script;
+use script::{ScriptConsole, ScriptStep};
+
+mod settings;
+
+mod shader_watch;
+use shader_watch::ShaderWatch;
+
+mod share;
+use share::Share;
+
+mod skew;
+
+mod startup;
+pub use startup::StartupArgs;
+
+mod status;
+use status::Status;
+
+mod tabs;
+use tabs::Tabs;
+
+mod thumbnails;
+use thumbnails::{Thumbnails, THUMBNAIL_SIZE};
+
+mod tiled_export;
+use tiled_export::{TileRenderer as _, TiledExport};
+
+mod timeline;
+use timeline::{Timeline, TimelineAction};
+
+mod transition;
+use transition::Transition;
+
+mod tutorial;
+use tutorial::{Step, Tutorial};
+
+mod ugr;
+
+mod user_image;
+
+mod video;
+use video::{SequenceAction, VideoExport, ZoomSequence};
+
+mod worker;
+pub use worker::worker_from_args;
+
+mod zoom_box;
+use zoom_box::ZoomBox;
+
+#[cfg(test)]
+mod ui_tests;
+
+use anyhow::{self, Result};
+
+/// Repaint delay while the window is in the background.
+const UNFOCUSED_REPAINT: std::time::Duration = std::time::Duration::from_secs(1);
+/// Duration of the moves of the view following a click, in seconds.
+const NAVIGATION_DURATION: f64 = 0.3;
+
+#[derive(Clone, Copy)]
+enum LocationFormat {
+    Kfr,
+    MandelMachine,
+}
 
 pub struct FractalApp {
     /// Behind an `Arc<Mutex<…>>` so we can pass it to [`egui::PaintCallback`] and paint later.
     fractal: Arc<Mutex<FractalGl>>,
     state: State,
+    /// Size of the fractal canvas in physical pixels, as of the last frame.
+    viewport_px: egui::Vec2,
+    /// Physical pixels per point of the screen, as of the last frame.
+    pixels_per_point: f32,
+    /// Magnification of the startup options, applied once the size of the canvas is known.
+    startup_magnification: Option<f64>,
+    palettes: Vec<Palette>,
+    selected_palette: usize,
+    palette_previews: PalettePreviews,
+    gradient_editor: GradientEditor,
+    random_palette: RandomPalette,
+    /// Index of the palette last generated by [RandomPalette], replaced by the next one.
+    random_palette_index: Option<usize>,
+    /// Last file written by an export, offered to be opened from the UI.
+    last_export: Option<PathBuf>,
+    /// Location imported last and the view it gave, exported again with all its digits
+    /// while the view is unchanged.
+    opened_location: Option<(KfrLocation, State)>,
+    status: Status,
+    /// Width of the tiled exports, in pixels.
+    export_width: usize,
+    /// 16 bits per channel in the BigTIFF exports.
+    export_deep: bool,
+    /// Draw the annotations and the scale bar over the exported images, as on the screen.
+    export_overlays: bool,
+    tiled_export: Option<TiledExport>,
+    /// Workers rendering the tiled exports instead of the GPU of the window.
+    farm: Option<Farm>,
+    /// Port the render farm listens on.
+    farm_port: u16,
+    iteration_export: Option<IterationExport>,
+    recent: Recent,
+    history: History,
+    /// Views in the other tabs, they swap with [FractalApp::state] and [FractalApp::history].
+    tabs: Tabs,
+    /// Equalization of the view for [IterationColoring::Histogram].
+    histogram: Histogram,
+    bookmarks: Bookmarks,
+    share: Share,
+    zoom_sequence: ZoomSequence,
+    timeline: Timeline,
+    thumbnails: Thumbnails,
+    pinned: Pinned,
+    quality: Quality,
+    progressive: Progressive,
+    julia_path: JuliaPath,
+    animations: Animations,
+    audio: AudioReactive,
+    modulators: Modulators,
+    coloring_graph: ColoringGraph,
+    formula: Formula,
+    /// Position of the pointer over the fractal, in physical pixels from its center.
+    pointer_offset_px: Option<[f32; 2]>,
+    /// Last Misiurewicz point found, offered to be zoomed on.
+    misiurewicz: Option<Misiurewicz>,
+    /// Move of the view in progress.
+    transition: Option<Transition>,
+    /// Center, zoom and fractal the skew was estimated for.
+    skew_view: Option<[f64; 6]>,
+    external_rays: ExternalRays,
+    show_settings: bool,
+    layouts: Layouts,
+    replay: Replay,
+    custom_fractal: CustomFractal,
+    plugins: Plugins,
+    /// Plugin whose function is in the shader, see [FractalApp::apply_plugin].
+    shader_plugin: Option<PluginId>,
+    iteration_formula: IterationFormula,
+    /// The custom fractal type iterates [FractalApp::iteration_formula], instead of the
+    /// custom fractal file when no plugin is selected.
+    use_iteration_formula: bool,
+    /// Sequence of the Lyapunov fractal as typed, until it is valid.
+    lyapunov_text: String,
+    /// Shaders reloaded when they change, with `--dev`.
+    shader_watch: Option<ShaderWatch>,
+    render_stats: RenderStats,
+    /// A click on the Mandelbrot set switches to the Julia set of the point clicked.
+    pick_julia_seed: bool,
+    /// The Mandelbrot set beside the Julia set of the point under the pointer.
+    split_view: bool,
+    /// Point of the Julia set of the split view, the last one under the pointer.
+    split_julia_c: [f64; 2],
+    show_scale_bar: bool,
+    /// Mark the center of the view, the point of the coordinates typed in.
+    show_crosshair: bool,
+    /// Readout of the orbit of the point under the pointer, see [probe].
+    show_probe: bool,
+    /// Coordinates, magnification and iterations over the canvas.
+    show_hud: bool,
+    /// Name the regions of the Mandelbrot set over the view.
+    show_explainer: bool,
+    measure: Measure,
+    annotations: Annotations,
+    region: Region,
+    zoom_box: ZoomBox,
+    lens: Lens,
+    minimap: Minimap,
+    gpu: Gpu,
+    cpu_renderer: CpuRenderer,
+    frame_rate: FrameRate,
+    about: About,
+    benchmark: Benchmark,
+    gallery: Gallery,
+    composite: CompositeExport,
+    script: ScriptConsole,
+    appearance: Appearance,
+    controls: Controls,
+    nudge: Nudge,
+    tutorial: Tutorial,
+    help_overlay: HelpOverlay,
+    /// Time the window lost the focus, if it is in the background.
+    unfocused_since: Option<f64>,
+    /// Time spent in the background, the animations don't move during it.
+    paused_time: f64,
 }
 
 impl FractalApp {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Result<Self> {
-        let gl = cc
-            .gl
-            .as_ref()
-            .ok_or(Error::msg("Glow context unavailable"))?;
-        Ok(Self {
-            fractal: Arc::new(Mutex::new(FractalGl::new(gl)?)),
+    /// `gpu` and `vsync` are the options the GL context was created with, `args` the ones of
+    /// the command line.
+    pub fn new(
+        cc: &eframe::CreationContext<'_>,
+        gpu: GpuPreference,
+        vsync: bool,
+        args: StartupArgs,
+    ) -> Result<Self> {
+        let mut fractal = FractalGl::new();
+        // Without a renderer the app still starts, and draws on the CPU
+        let gpu_ready = cc.gl.as_ref().is_some_and(|gl| fractal.setup(gl));
+
+        let mut app = Self::with_renderer(fractal);
+        if let Some(gl) = &cc.gl {
+            let diagnostics = Diagnostics::query(gl);
+            info!("Rendering with {}", diagnostics.adapter());
+            app.gpu = Gpu::new(gpu, Some(diagnostics.adapter()));
+            app.about = About::new(Some(diagnostics));
+        } else {
+            warn!("Glow context unavailable, rendering on the CPU");
+        }
+        app.cpu_renderer.forced = !gpu_ready;
+        app.appearance.apply(&cc.egui_ctx);
+        app.frame_rate = FrameRate::new(vsync);
+        // Not in the app without GL context, it would cover the UI of the tests
+        app.tutorial = Tutorial::load();
+        app.annotations = Annotations::load();
+        app.layouts = Layouts::load();
+        app.bookmarks = Bookmarks::load();
+        app.plugins = Plugins::load();
+        app.script = ScriptConsole::load();
+        app.composite = CompositeExport::load();
+        if args.dev {
+            app.shader_watch = Some(ShaderWatch::new());
+        }
+        if let Some(state) = State::load() {
+            app.state = state;
+        }
+        args.apply(&mut app.state)?;
+        app.startup_magnification = args.magnification();
+        if args.fullscreen {
+            cc.egui_ctx
+                .send_viewport_cmd(egui::ViewportCommand::Fullscreen(true));
+        }
+        if let Some(path) = &args.replay {
+            app.play_replay(path);
+        }
+        Ok(app)
+    }
+
+    /// App drawing with `fractal`, its GL objects are created by the first paint if needed.
+    fn with_renderer(mut fractal: FractalGl) -> Self {
+        let coloring_graph = ColoringGraph::new();
+        fractal.set_coloring_function(coloring_graph.to_glsl());
+        fractal.set_coloring_parameters(coloring_graph.parameters());
+        let formula = Formula::new();
+        if let Some(glsl) = formula.glsl() {
+            fractal.set_value_formula(glsl);
+        }
+        Self {
+            fractal: Arc::new(Mutex::new(fractal)),
             state: State::new(),
-        })
+            viewport_px: egui::Vec2::ZERO,
+            pixels_per_point: 1.0,
+            startup_magnification: None,
+            palettes: Palette::presets(),
+            selected_palette: 0,
+            palette_previews: PalettePreviews::new(),
+            gradient_editor: GradientEditor::new(),
+            random_palette: RandomPalette::new(),
+            random_palette_index: None,
+            last_export: None,
+            opened_location: None,
+            status: Status::new(),
+            export_width: 3840,
+            export_deep: false,
+            export_overlays: true,
+            tiled_export: None,
+            farm: None,
+            farm_port: farm::DEFAULT_PORT,
+            iteration_export: None,
+            recent: Recent::new(),
+            history: History::new(),
+            tabs: Tabs::new(),
+            histogram: Histogram::new(),
+            bookmarks: Bookmarks::new(),
+            share: Share::new(),
+            zoom_sequence: ZoomSequence::new(),
+            timeline: Timeline::new(),
+            thumbnails: Thumbnails::new(),
+            pinned: Pinned::new(),
+            quality: Quality::load(),
+            progressive: Progressive::load(),
+            julia_path: JuliaPath::new(),
+            animations: Animations::new(),
+            audio: AudioReactive::new(),
+            modulators: Modulators::new(),
+            coloring_graph,
+            formula,
+            pointer_offset_px: None,
+            misiurewicz: None,
+            transition: None,
+            skew_view: None,
+            external_rays: ExternalRays::new(),
+            show_settings: true,
+            layouts: Layouts::new(),
+            replay: Replay::Idle,
+            custom_fractal: CustomFractal::new(),
+            plugins: Plugins::new(),
+            shader_plugin: None,
+            iteration_formula: IterationFormula::new(),
+            use_iteration_formula: false,
+            lyapunov_text: String::new(),
+            shader_watch: None,
+            render_stats: RenderStats::new(),
+            pick_julia_seed: false,
+            split_view: false,
+            split_julia_c: [-0.76, -0.08],
+            show_scale_bar: false,
+            show_crosshair: false,
+            show_probe: false,
+            show_hud: false,
+            show_explainer: false,
+            measure: Measure::new(),
+            annotations: Annotations::new(),
+            region: Region::new(),
+            zoom_box: ZoomBox::new(),
+            lens: Lens::new(),
+            minimap: Minimap::new(),
+            gpu: Gpu::new(GpuPreference::Default, None),
+            cpu_renderer: CpuRenderer::load(),
+            frame_rate: FrameRate::new(true),
+            about: About::new(None),
+            benchmark: Benchmark::new(),
+            gallery: Gallery::new(),
+            composite: CompositeExport::new(),
+            script: ScriptConsole::new(),
+            appearance: Appearance::load(),
+            controls: Controls::load(),
+            nudge: Nudge::load(),
+            tutorial: Tutorial::new(),
+            help_overlay: HelpOverlay::new(),
+            unfocused_since: None,
+            paused_time: 0.0,
+        }
+    }
+
+    fn select_palette(&mut self, index: usize) {
+        self.selected_palette = index;
+        self.fractal.lock().set_palette(&self.palettes[index]);
+    }
+
+    /// Switch to the Julia set of `c`, seen whole.
+    fn pick_julia_seed(&mut self, c: [f64; 2]) {
+        info!("Julia seed {} {}", c[0], c[1]);
+        self.state.c_julia.x = c[0] as f32;
+        self.state.c_julia.y = c[1] as f32;
+        self.state.fractal_type = FractalType::Julia;
+        self.state.center_position = Position { x: 0.0, y: 0.0 };
+        self.state.zoom = State::new().zoom;
+        self.transition = None;
+        self.pick_julia_seed = false;
+    }
+
+    /// Switch to the custom fractal type, with the function of the plugin `plugin` and the
+    /// default values of its parameters, or of the custom fractal file if none.
+    fn select_custom_fractal(&mut self, plugin: Option<PluginId>) {
+        self.state.fractal_type = FractalType::Custom;
+        self.use_iteration_formula = false;
+        self.state.plugin = plugin;
+        if let Some(plugin) = plugin.and_then(|id| self.plugins.get(id)) {
+            self.state.plugin_parameters = plugin.defaults();
+        }
+        if plugin.is_none() {
+            self.custom_fractal.reload();
+        }
+        self.apply_plugin();
+    }
+
+    /// Switch to the custom fractal type, iterating the formula typed by the user.
+    fn select_iteration_formula(&mut self) {
+        self.state.fractal_type = FractalType::Custom;
+        self.use_iteration_formula = true;
+        self.state.plugin = None;
+        self.shader_plugin = None;
+        if let Some(glsl) = self.iteration_formula.glsl() {
+            self.fractal.lock().set_custom_fractal(glsl);
+        }
+    }
+
+    /// Put the function of the plugin of the view in the shader, once the view changed
+    /// plugin: picked, undone, or loaded from a preset or a link.
+    fn apply_plugin(&mut self) {
+        if self.state.plugin == self.shader_plugin {
+            return;
+        }
+        self.shader_plugin = self.state.plugin;
+        let Some(id) = self.state.plugin else {
+            // Back to the custom fractal file
+            self.use_iteration_formula = false;
+            self.custom_fractal.reload();
+            return;
+        };
+        match self.plugins.get(id) {
+            Some(plugin) => self.fractal.lock().set_custom_fractal(plugin.glsl()),
+            None => {
+                let e = anyhow::anyhow!(tr_args("plugin-missing", &[("plugin", &id)]));
+                self.status.error(tr("cannot-load-plugin"), &e);
+            }
+        }
+    }
+
+    /// What is shown now, unnamed.
+    fn layout(&self) -> Layout {
+        Layout {
+            name: String::new(),
+            settings_panel: self.show_settings,
+            scale_bar: self.show_scale_bar,
+            explainer: self.show_explainer,
+            annotations: self.annotations.visible,
+            pinned: self.pinned.parameters().to_vec(),
+        }
+    }
+
+    fn apply_layout(&mut self, layout: Layout) {
+        info!("Switching to the layout {}", layout.name);
+        self.show_settings = layout.settings_panel;
+        self.show_scale_bar = layout.scale_bar;
+        self.show_explainer = layout.explainer;
+        self.annotations.visible = layout.annotations;
+        self.pinned.set_parameters(layout.pinned);
+    }
+
+    /// Select a generated palette, in place of the previous one generated.
+    fn audition_palette(&mut self, palette: Palette) {
+        let index = match self.random_palette_index {
+            Some(index) => {
+                self.palettes[index] = palette;
+                index
+            }
+            None => {
+                self.palettes.push(palette);
+                self.palettes.len() - 1
+            }
+        };
+        self.random_palette_index = Some(index);
+        self.palette_previews.invalidate();
+        self.select_palette(index);
+    }
+
+    fn export_location(&mut self, format: LocationFormat) {
+        let (filter, extension) = match format {
+            LocationFormat::Kfr => (tr("filter-kfr"), "kfr"),
+            LocationFormat::MandelMachine => (tr("filter-mmf"), "mmf"),
+        };
+        let Some(path) =
+            file_dialog::save_file(&[(&filter, &[extension])], &format!("location.{extension}"))
+        else {
+            return;
+        };
+
+        let location = match &self.opened_location {
+            Some((location, state)) if *state == self.state => Ok(location.clone()),
+            _ => KfrLocation::from_state(&self.state, self.viewport_px.y.max(1.0)),
+        };
+        let result = location.and_then(|location| match format {
+            LocationFormat::Kfr => location.save(&path),
+            LocationFormat::MandelMachine => mandel_machine::save(&location, &path),
+        });
+        match result {
+            Ok(()) => {
+                self.exported(path);
+            }
+            Err(e) => self.status.error(cannot_export(&path), &e),
+        }
+    }
+
+    fn export_dzi(&mut self) {
+        let Some(path) =
+            file_dialog::save_file(&[(&tr("filter-deep-zoom"), &["dzi"])], "fractal.dzi")
+        else {
+            return;
+        };
+
+        let overlay = |width| self.export_overlay(width);
+        match DziExport::start(
+            path,
+            &self.state,
+            self.viewport_px,
+            self.export_width,
+            overlay,
+        ) {
+            Ok(export) => self.start_tiled_export(TiledExport::Dzi(export)),
+            Err(e) => self.status.error(tr("status-export-failed"), &e),
+        }
+    }
+
+    fn export_big_tiff(&mut self) {
+        let Some(path) =
+            file_dialog::save_file(&[(&tr("filter-bigtiff"), &["tif", "tiff"])], "fractal.tif")
+        else {
+            return;
+        };
+
+        let (width, deep) = (self.export_width, self.export_deep);
+        let overlay = self.export_overlay(width);
+        match BigTiffExport::start(path, &self.state, self.viewport_px, width, deep, overlay) {
+            Ok(export) => self.start_tiled_export(TiledExport::BigTiff(export)),
+            Err(e) => self.status.error(tr("status-export-failed"), &e),
+        }
+    }
+
+    fn export_png(&mut self) {
+        let Some(path) = file_dialog::save_file(&[(&tr("filter-png"), &["png"])], "fractal.png")
+        else {
+            return;
+        };
+
+        let (width, deep) = (self.export_width, self.export_deep);
+        let overlay = self.export_overlay(width);
+        match PngExport::start(path, &self.state, self.viewport_px, width, deep, overlay) {
+            Ok(export) => self.start_tiled_export(TiledExport::Png(export)),
+            Err(e) => self.status.error(tr("status-export-failed"), &e),
+        }
+    }
+
+    /// Annotations and scale bar of the view, as on the screen, for an export `width` pixels
+    /// wide. `None` if they are not exported.
+    fn export_overlay(&self, width: usize) -> Option<Overlay> {
+        if !self.export_overlays {
+            return None;
+        }
+        // Drawn in points of the screen, scaled to the pixels of the image
+        let viewport = self.viewport_px / self.pixels_per_point;
+        let mut canvas = ImageCanvas::new(width as f32 / viewport.x.max(1.0));
+        let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, viewport);
+        if self.show_scale_bar {
+            scale_bar::paint(&mut canvas, rect, self.pixels_per_point, self.state.zoom);
+        }
+        self.annotations
+            .paint(&mut canvas, rect, self.pixels_per_point, &self.state);
+        Some(canvas.finish())
+    }
+
+    /// Export the views of the layout of the composite export in one PNG image.
+    fn export_composite(&mut self) {
+        let Some(path) = file_dialog::save_file(&[(&tr("filter-png"), &["png"])], "fractal.png")
+        else {
+            return;
+        };
+
+        let (size, panels) = match self.composite.layout {
+            CompositeLayout::SideBySide => composite::side_by_side(
+                &self.state,
+                self.split_julia_c,
+                self.viewport_px,
+                self.export_width,
+            ),
+            CompositeLayout::Wallpaper => {
+                composite::wallpaper(&self.state, self.viewport_px, &self.composite.monitors)
+            }
+        };
+        match PngExport::from_panels(path, size, &panels, self.export_deep) {
+            Ok(export) => self.start_tiled_export(TiledExport::Png(export)),
+            Err(e) => self.status.error(tr("status-export-failed"), &e),
+        }
+    }
+
+    /// Save the view at the size of the canvas, next to a JSON file of its [State] that
+    /// dropping the image back on the window opens.
+    fn screenshot(&mut self) {
+        let path = screenshot::new_path();
+        if let Err(e) = screenshot::save_view(&path, &self.state) {
+            self.status.error(cannot_save(&path), &e);
+            return;
+        }
+        let width = self.viewport_px.x.max(1.0) as usize;
+        match PngExport::start(path, &self.state, self.viewport_px, width, false, None) {
+            Ok(export) => self.start_tiled_export(TiledExport::Png(export)),
+            Err(e) => self.status.error(tr("status-export-failed"), &e),
+        }
+    }
+
+    /// Open the view of a screenshot dropped on the window.
+    fn open_dropped_files(&mut self, ctx: &egui::Context) {
+        let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+        for path in dropped.into_iter().filter_map(|file| file.path) {
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "toml")
+            {
+                self.open_preset(&path);
+                continue;
+            }
+            match screenshot::load_view(&path) {
+                Ok(state) => self.open_link(state),
+                Err(e) => self.status.error(cannot_open(&path), &e),
+            }
+        }
+    }
+
+    fn play_script_step(&mut self, step: ScriptStep) {
+        match step {
+            ScriptStep::View(state) => {
+                self.state = state;
+                self.transition = None;
+            }
+            ScriptStep::Snapshot(path, state) => {
+                let width = self.viewport_px.x.max(1.0) as usize;
+                match PngExport::start(path, &state, self.viewport_px, width, false, None) {
+                    Ok(export) => self.start_tiled_export(TiledExport::Png(export)),
+                    Err(e) => self.status.error(tr("status-export-failed"), &e),
+                }
+            }
+        }
+    }
+
+    fn start_tiled_export(&mut self, export: TiledExport) {
+        info!("Exporting to {}", export.path().display());
+        if export.progress() > 0.0 {
+            let file = file_name(export.path());
+            self.status
+                .info(tr_args("status-export-resumed", &[("file", &file)]));
+        }
+        if let Some(farm) = &mut self.farm {
+            let palette = self.state.use_palette;
+            farm.set_palette(palette.then(|| self.palettes[self.selected_palette].clone()));
+        }
+        self.tiled_export = Some(export);
+    }
+
+    /// Render the next tiles of the export in progress.
+    fn update_tiled_export(&mut self, ctx: &egui::Context) {
+        let Some(export) = &mut self.tiled_export else {
+            return;
+        };
+        let result = match &mut self.farm {
+            Some(farm) => export.update(ctx, farm),
+            None => export.update(ctx, &mut *self.fractal.lock()),
+        };
+        match result {
+            Ok(false) => {}
+            Ok(true) => {
+                let path = export.path().to_path_buf();
+                self.tiled_export = None;
+                self.exported(path);
+            }
+            Err(e) => {
+                self.status.error(cannot_export(export.path()), &e);
+                self.cancel_tiled_export();
+            }
+        }
+    }
+
+    fn export_video(&mut self) {
+        let Some(path) = save_video_file("zoom.mp4") else {
+            return;
+        };
+
+        match VideoExport::start(path, &self.zoom_sequence, self.viewport_px) {
+            Ok(export) => self.start_tiled_export(TiledExport::Video(export)),
+            Err(e) => self.status.error(tr("status-export-failed"), &e),
+        }
+    }
+
+    fn export_timeline(&mut self) {
+        let Some(path) = save_video_file("timeline.mp4") else {
+            return;
+        };
+
+        let frames = self.timeline.frames();
+        let (width, fps) = (self.timeline.width(), timeline::EXPORT_FPS);
+        match VideoExport::from_frames(path, frames, width, fps, self.viewport_px) {
+            Ok(export) => self.start_tiled_export(TiledExport::Video(export)),
+            Err(e) => self.status.error(tr("status-export-failed"), &e),
+        }
+    }
+
+    /// Keyframes of the timeline, its playback and its export.
+    fn timeline_ui(&mut self, ui: &mut egui::Ui) {
+        if self.tiled_export_progress_ui(ui) {
+            return;
+        }
+        match self.timeline.ui(ui, &self.state) {
+            Some(TimelineAction::Show(state)) => {
+                self.state = State {
+                    time: self.state.time,
+                    ..*state
+                };
+                self.transition = None;
+            }
+            Some(TimelineAction::Export) => self.export_timeline(),
+            None => {}
+        }
+    }
+
+    /// Keyframes of the zoom video, its preview and its export.
+    fn zoom_video_ui(&mut self, ui: &mut egui::Ui) {
+        if self.tiled_export_progress_ui(ui) {
+            return;
+        }
+        match self.zoom_sequence.ui(ui, &self.state) {
+            Some(SequenceAction::Preview(preview)) => {
+                let (state, transition) = *preview;
+                self.state = state;
+                self.transition = Some(transition);
+            }
+            Some(SequenceAction::Export) => self.export_video(),
+            None => {}
+        }
+    }
+
+    fn export_iterations(&mut self) {
+        let Some(path) = file_dialog::save_file(
+            &[(&tr("filter-npy"), &["npy"]), (&tr("filter-csv"), &["csv"])],
+            "iterations.npy",
+        ) else {
+            return;
+        };
+
+        let size_px = [self.viewport_px.x, self.viewport_px.y].map(|size| size as usize);
+        match IterationExport::start(path, &self.state, size_px) {
+            Ok(export) => self.iteration_export = Some(export),
+            Err(e) => self.status.error(tr("status-export-failed"), &e),
+        }
+    }
+
+    /// Pick up the result of the export of the iterations, once computed.
+    fn update_iteration_export(&mut self, ctx: &egui::Context) {
+        let Some(export) = self.iteration_export.take() else {
+            return;
+        };
+        if !export.is_finished() {
+            self.iteration_export = Some(export);
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            return;
+        }
+        match export.join() {
+            Ok(path) => {
+                self.exported(path);
+            }
+            Err(e) => self.status.error(tr("status-export-failed"), &e),
+        }
+    }
+
+    fn cancel_tiled_export(&mut self) {
+        self.tiled_export = None;
+        self.fractal.lock().forget_tiles();
+        if let Some(farm) = &mut self.farm {
+            farm.forget_tiles();
+        }
+    }
+
+    /// Progress of the export in progress, with a button to cancel it. Returns whether an
+    /// export is in progress.
+    fn tiled_export_progress_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let Some(progress) = self.tiled_export.as_ref().map(TiledExport::progress) else {
+            return false;
+        };
+        ui.horizontal(|ui| {
+            ui.add(egui::ProgressBar::new(progress).show_percentage());
+            if ui.button(tr("cancel")).clicked() {
+                self.cancel_tiled_export();
+            }
+        });
+        true
+    }
+
+    fn tiled_export_ui(&mut self, ui: &mut egui::Ui) {
+        if self.tiled_export_progress_ui(ui) {
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.label(tr("export-width"));
+            ui.add(
+                egui::DragValue::new(&mut self.export_width)
+                    .range(256..=1 << 20)
+                    .suffix(" px"),
+            );
+            for (label, width) in [("4K", 3840), ("8K", 7680), ("16K", 16384)] {
+                if ui
+                    .selectable_label(self.export_width == width, label)
+                    .clicked()
+                {
+                    self.export_width = width;
+                }
+            }
+        });
+        // Unknown until the renderer is set up
+        let norm16 = self
+            .fractal
+            .lock()
+            .capabilities()
+            .is_none_or(|capabilities| capabilities.norm16);
+        if !norm16 {
+            self.export_deep = false;
+        }
+        ui.add_enabled(
+            norm16,
+            egui::Checkbox::new(&mut self.export_deep, tr("sixteen-bit-channels")),
+        )
+        .on_disabled_hover_text(tr("sixteen-bit-unsupported"));
+        ui.checkbox(&mut self.export_overlays, tr("export-overlays"))
+            .on_hover_text(tr("export-overlays-hint"));
+        self.render_farm_ui(ui);
+        if ui
+            .button(tr("export-png"))
+            .on_hover_text(tr("export-png-hint"))
+            .clicked()
+        {
+            self.export_png();
+        }
+        if ui
+            .button(tr("composite-export"))
+            .on_hover_text(tr("composite-export-hint"))
+            .clicked()
+        {
+            self.composite.open = true;
+        }
+        if ui
+            .button(tr("export-deep-zoom"))
+            .on_hover_text(tr("export-deep-zoom-hint"))
+            .clicked()
+        {
+            self.export_dzi();
+        }
+        if ui
+            .button(tr("export-big-tiff"))
+            .on_hover_text(tr("export-big-tiff-hint"))
+            .clicked()
+        {
+            self.export_big_tiff();
+        }
+        ui.horizontal(|ui| {
+            let idle = self.iteration_export.is_none();
+            if ui
+                .add_enabled(idle, Button::new(tr("export-iterations")))
+                .on_hover_text(tr("export-iterations-hint"))
+                .clicked()
+            {
+                self.export_iterations();
+            }
+            if !idle {
+                ui.spinner();
+            }
+        });
+    }
+
+    /// Workers of the render farm rendering the tiled exports, instead of the GPU of the
+    /// window while enabled.
+    fn render_farm_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let mut enabled = self.farm.is_some();
+            if ui
+                .checkbox(&mut enabled, tr("render-farm"))
+                .on_hover_text(tr("render-farm-hint"))
+                .changed()
+            {
+                self.farm = None;
+                if enabled {
+                    match Farm::start(self.farm_port) {
+                        Ok(farm) => self.farm = Some(farm),
+                        Err(e) => self.status.error(tr("status-render-farm-failed"), &e),
+                    }
+                }
+            }
+            match &self.farm {
+                Some(farm) => {
+                    ui.label(tr_args(
+                        "render-farm-workers",
+                        &[
+                            ("count", &farm.workers().to_string()),
+                            ("port", &farm.port.to_string()),
+                        ],
+                    ));
+                    // Workers connect at any time
+                    ui.ctx()
+                        .request_repaint_after(std::time::Duration::from_secs(1));
+                }
+                None => {
+                    ui.label(tr("render-farm-port"));
+                    ui.add(egui::DragValue::new(&mut self.farm_port).range(1024..=u16::MAX));
+                }
+            }
+        });
+    }
+
+    /// Tell the user `path` is written, with the actions of [FractalApp::last_export_ui].
+    fn exported(&mut self, path: PathBuf) {
+        self.status.exported(exported(&path), path.clone());
+        self.last_export = Some(path);
+    }
+
+    fn last_export_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(path) = self.last_export.clone() else {
+            return;
+        };
+        ui.label(exported(&path));
+        ui.horizontal(|ui| {
+            if ui.button(tr("open")).clicked() {
+                if let Err(e) = external::open(&path) {
+                    self.status.error(cannot_open(&path), &e);
+                }
+            }
+            if ui.button(tr("reveal-in-folder")).clicked() {
+                if let Err(e) = external::reveal(&path) {
+                    self.status.error(cannot_open(&path), &e);
+                }
+            }
+        });
+    }
+
+    fn coloring_graph_ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(
+            &mut self.state.custom_coloring,
+            tr("use-the-coloring-graph"),
+        );
+        let changed = self.coloring_graph.ui(ui);
+        let mut fractal = self.fractal.lock();
+        if changed {
+            fractal.set_coloring_function(self.coloring_graph.to_glsl());
+        }
+        fractal.set_coloring_parameters(self.coloring_graph.parameters());
+    }
+
+    fn analysis_ui(&mut self, ui: &mut egui::Ui) {
+        let (description, c) = match self.state.fractal_type {
+            FractalType::Julia => (
+                tr("julia-constant"),
+                [self.state.c_julia.x as f64, self.state.c_julia.y as f64],
+            ),
+            _ => match self.pointer_offset_px {
+                Some(offset) => (tr("cursor"), self.state.point_at(offset)),
+                None => (tr("view-center"), self.state.point_at([0.0, 0.0])),
+            },
+        };
+        ui.label(format!("{}: {:.8} {:+.8}i", description, c[0], c[1]));
+        // The analysis is for z² + c, not for the other fractal types
+        if !self.state.fractal_type.is_quadratic() || self.state.exponent != 2.0 {
+            return;
+        }
+        match period::period(c) {
+            Some(period) => ui.label(tr_args("hyperbolic-component", &[("period", &period)])),
+            None => ui.label(tr("not-in-a-hyperbolic-component")),
+        };
+
+        if self.state.fractal_type != FractalType::Mandelbrot {
+            return;
+        }
+        ui.separator();
+        ui.label(tr("external-rays"));
+        self.external_rays.ui(ui);
+
+        ui.separator();
+        if ui
+            .button(tr("find-nearby-minibrot"))
+            .on_hover_text(tr("find-nearby-minibrot-hint"))
+            .clicked()
+        {
+            self.find_minibrot();
+        }
+
+        ui.label(tr("misiurewicz-hint"));
+        if let Some(point) = self.misiurewicz {
+            ui.label(tr_args(
+                "misiurewicz-point",
+                &[
+                    ("preperiod", &point.preperiod),
+                    ("period", &point.period),
+                    ("re", &format!("{:.12}", point.c.re)),
+                    ("im", &format!("{:+.12}", point.c.im)),
+                ],
+            ));
+            if ui.button(tr("center-and-zoom")).clicked() {
+                self.state.center_position.x = -point.c.re;
+                self.state.center_position.y = -point.c.im;
+                self.state.zoom *= 8.0;
+            }
+        }
+    }
+
+    /// Estimate the skew again when the view changed.
+    fn update_skew(&mut self) {
+        if !self.state.auto_skew {
+            self.state.skew = skew::IDENTITY;
+            self.skew_view = None;
+            return;
+        }
+        let view = [
+            self.state.center_position.x,
+            self.state.center_position.y,
+            self.state.zoom as f64,
+            self.state.c_julia.x as f64,
+            self.state.c_julia.y as f64,
+            self.state.fractal_type as i32 as f64,
+        ];
+        if self.skew_view != Some(view) {
+            let radius = (self.viewport_px.y.max(1.0) / 2.0 / self.state.zoom) as f64;
+            self.state.skew = skew::estimate(&self.state, radius);
+            self.skew_view = Some(view);
+        }
+    }
+
+    /// Center and zoom the view is at, or moving to.
+    fn target_view(&self) -> (Position, f32) {
+        self.transition.as_ref().map_or(
+            (self.state.center_position, self.state.zoom),
+            |transition| transition.target(),
+        )
+    }
+
+    /// Move the view to `center` and `zoom` over `duration` seconds, at once with the
+    /// reduced motion.
+    fn fly_to(&mut self, center: Position, zoom: f32, duration: f64) {
+        if self.appearance.reduced_motion() {
+            self.state.center_position = center;
+            self.state.zoom = zoom;
+            self.transition = None;
+        } else {
+            self.transition = Some(Transition::new(&self.state, center, zoom, duration));
+        }
+    }
+
+    /// Fly to the minibrot closest to the center of the view.
+    fn find_minibrot(&mut self) {
+        let [re, im] = self.state.point_at([0.0, 0.0]);
+        let radius = (self.viewport_px.y.max(1.0) / 2.0 / self.state.zoom) as f64;
+        let c = Complex::new(re, im);
+        if !nucleus::within_precision(c, radius) {
+            self.status.info(tr("minibrot-too-deep"));
+            return;
+        }
+        let Some(nucleus) = nucleus::find(c, radius) else {
+            self.status.info(tr("minibrot-not-found"));
+            return;
+        };
+        // Make the minibrot fill about half of the height of the view
+        let zoom = self.viewport_px.y.max(1.0) / (4.0 * nucleus.size as f32);
+        if !(zoom.is_finite() && zoom > 0.0) {
+            self.status.info(tr("minibrot-too-small"));
+            return;
+        }
+        let period = nucleus.period;
+        self.status
+            .info(tr_args("minibrot-found", &[("period", &period)]));
+        let center = Position {
+            x: -nucleus.c.re,
+            y: -nucleus.c.im,
+        };
+        self.fly_to(center, zoom, 3.0);
+    }
+
+    fn recent_ui(&mut self, ui: &mut egui::Ui) {
+        let size = egui::vec2(THUMBNAIL_SIZE[0] as f32, THUMBNAIL_SIZE[1] as f32);
+        let mut selected = None;
+        for entry in self.recent.entries() {
+            ui.horizontal(|ui| {
+                match self.thumbnails.get(entry.thumbnail) {
+                    Some(texture) => ui.image(SizedTexture::new(texture.id(), size)),
+                    None => ui.add_sized(size, egui::Spinner::new()),
+                };
+                ui.vertical(|ui| {
+                    let magnification = entry.state.magnification(self.viewport_px.y.max(1.0));
+                    ui.label(format!(
+                        "{} {}",
+                        entry.state.fractal_type.label(),
+                        format_magnification(magnification)
+                    ));
+                    ui.label(recent::format_age(entry.visited));
+                    if ui.button(tr("go")).clicked() {
+                        selected = Some(entry.state);
+                    }
+                });
+            });
+        }
+        if let Some(state) = selected {
+            self.state = state;
+        }
+    }
+
+    /// Log of the shaders which failed to build, until they are fixed. The view keeps the
+    /// last program that built.
+    fn show_shader_error(&mut self, ctx: &egui::Context) {
+        let Some(log) = self.fractal.lock().shader_error().map(str::to_string) else {
+            return;
+        };
+        egui::Window::new(tr("shader-error"))
+            .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -8.0])
+            .collapsible(true)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.colored_label(ui.visuals().error_fg_color, tr("shader-error-hint"));
+                ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    ui.add(
+                        egui::Label::new(egui::RichText::new(&log).monospace()).selectable(true),
+                    );
+                });
+                if ui.button(tr("copy-shader-error")).clicked() {
+                    ui.ctx().copy_text(log.clone());
+                }
+            });
+    }
+
+    /// Whether something moves the view or the parameters without the user.
+    fn is_animated(&self) -> bool {
+        self.julia_path.is_playing()
+            || self.transition.is_some()
+            || self.animations.is_active()
+            || self.audio.is_active()
+            || self.modulators.is_active()
+            || self.timeline.is_playing()
+            || self.state.color_cycling
+            || self.script.is_playing()
+    }
+
+    /// Record the changes in the history once they settled, a drag making a single change.
+    fn track_history(&mut self, ctx: &egui::Context) {
+        let busy =
+            self.is_animated() || self.replay.is_playing() || ctx.input(|i| i.pointer.any_down());
+        self.history.observe(ctx, &self.state, busy);
+    }
+
+    /// Show the view of a shared link or of a screenshot, keeping the time running.
+    fn open_link(&mut self, state: State) {
+        info!("Opening a shared view");
+        self.state = State {
+            time: self.state.time,
+            ..state
+        };
+        self.transition = None;
+    }
+
+    fn undo(&mut self) {
+        if let Some(state) = self.history.undo(&self.state) {
+            self.state = state;
+            self.transition = None;
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(state) = self.history.redo(&self.state) {
+            self.state = state;
+            self.transition = None;
+        }
+    }
+
+    /// Multiply the zoom by `factor`, keeping the point under the pointer in place, or the
+    /// center when the pointer is not over the fractal.
+    fn zoom_at_pointer(&mut self, factor: f32) {
+        self.zoom_at(self.pointer_offset_px.unwrap_or([0.0, 0.0]), factor);
+    }
+
+    /// Multiply the zoom by `factor`, keeping the point at `offset` pixels from the center
+    /// in place.
+    fn zoom_at(&mut self, offset: [f32; 2], factor: f32) {
+        let anchor = self.state.point_at(offset);
+        let deepest = perturbation::deepest_zoom(self.state.point_at([0.0, 0.0]));
+        self.state.zoom = (self.state.zoom * factor).min(deepest);
+        let moved = self.state.point_at(offset);
+        self.state.center_position.x += moved[0] - anchor[0];
+        self.state.center_position.y += moved[1] - anchor[1];
+    }
+
+    /// Fly to the view filled by `selection`, a rectangle of the canvas `rect` in UI points,
+    /// whole but with the aspect ratio of the canvas.
+    fn zoom_to_selection(
+        &mut self,
+        selection: egui::Rect,
+        rect: egui::Rect,
+        pixels_per_point: f32,
+    ) {
+        let (center, zoom) = zoom_box::target(&self.state, selection, rect, pixels_per_point);
+        info!(
+            "Zoom on the box {:?}: {}×",
+            selection,
+            zoom / self.state.zoom
+        );
+        self.fly_to(center, zoom, NAVIGATION_DURATION);
+    }
+
+    /// Move the view by `steps` tenths of its size, x to the right and y up.
+    fn pan(&mut self, steps: egui::Vec2) {
+        let step = self.viewport_px * 0.1;
+        let [re, im] = self.state.point_at([steps.x * step.x, -steps.y * step.y]);
+        self.state.center_position = Position { x: -re, y: -im };
+    }
+
+    /// Shortcuts of the whole window, unless a text field has the focus and its own use of
+    /// the keys: WASD to pan, H for the high quality, the number keys for the fractal types,
+    /// F1 for the help overlay, F11 for the full screen, F12 for a screenshot, Ctrl+S to export
+    /// a PNG image, Ctrl+Z and Ctrl+Shift+Z to undo and redo, the brackets to nudge a parameter.
+    fn keyboard_shortcuts(&mut self, ctx: &egui::Context) {
+        use egui::Key;
+        if is_typing(ctx) {
+            return;
+        }
+        let (wasd, zoom, high_quality, help, fullscreen, fractal_type) = ctx.input(|i| {
+            let count = |key| i.num_presses(key) as f32;
+            // Without modifiers, Ctrl+S saves
+            let wasd = if i.modifiers.is_none() {
+                egui::Vec2::new(count(Key::D) - count(Key::A), count(Key::W) - count(Key::S))
+            } else {
+                egui::Vec2::ZERO
+            };
+            // Ctrl with +/- scales the interface, + may take Shift
+            let zoom = if i.modifiers.command {
+                0.0
+            } else {
+                count(Key::Plus) + count(Key::Equals) - count(Key::Minus)
+            };
+            let number_keys = [
+                Key::Num1,
+                Key::Num2,
+                Key::Num3,
+                Key::Num4,
+                Key::Num5,
+                Key::Num6,
+                Key::Num7,
+            ];
+            let fractal_type = number_keys
+                .into_iter()
+                .zip(FractalType::BUILTIN)
+                .find(|(key, _)| i.key_pressed(*key))
+                .map(|(_, fractal_type)| fractal_type);
+            (
+                wasd,
+                zoom,
+                i.key_pressed(Key::H),
+                i.key_pressed(Key::F1),
+                i.key_pressed(Key::F11),
+                fractal_type,
+            )
+        });
+        if wasd != egui::Vec2::ZERO {
+            self.pan(wasd);
+        }
+        if zoom != 0.0 {
+            self.transition = None;
+            self.zoom_at_pointer(self.controls.click_step.powf(zoom));
+        }
+        if high_quality {
+            self.state.high_quality = !self.state.high_quality;
+        }
+        if help {
+            self.help_overlay.open = !self.help_overlay.open;
+        }
+        if fullscreen {
+            toggle_fullscreen(ctx);
+        }
+        if let Some(fractal_type) = fractal_type {
+            self.state.fractal_type = fractal_type;
+        }
+        self.nudge.update(ctx, &mut self.state);
+
+        // A link pasted anywhere but in a text field opens its view
+        let linked = ctx.input(|i| {
+            i.events.iter().find_map(|event| match event {
+                egui::Event::Paste(text) => share::from_link(text).ok(),
+                _ => None,
+            })
+        });
+        if let Some(state) = linked {
+            self.open_link(state);
+        }
+
+        let save = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, Key::S);
+        if self.tiled_export.is_none() && ctx.input_mut(|i| i.consume_shortcut(&save)) {
+            self.export_png();
+        }
+        // Web pages cannot write files without a dialog
+        if !cfg!(target_arch = "wasm32")
+            && self.tiled_export.is_none()
+            && ctx.input(|i| i.key_pressed(Key::F12))
+        {
+            self.screenshot();
+        }
+        let redo =
+            egui::KeyboardShortcut::new(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, Key::Z);
+        let undo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, Key::Z);
+        // Ctrl+Z also matches with Shift held, it is checked last
+        if ctx.input_mut(|i| i.consume_shortcut(&redo)) {
+            self.redo();
+        } else if ctx.input_mut(|i| i.consume_shortcut(&undo)) {
+            self.undo();
+        }
+    }
+
+    /// Record the view in the recent list once the user stopped on it.
+    fn track_recent(&mut self, ctx: &egui::Context) {
+        let mut fractal = self.fractal.lock();
+        self.thumbnails.receive(ctx, &mut fractal);
+        if let Some(state) = self.recent.observe(ctx, &self.state) {
+            let thumbnail =
+                self.thumbnails
+                    .request(ctx, &mut fractal, state, self.viewport_px, None);
+            for dropped in self.recent.push(state, thumbnail) {
+                self.thumbnails.forget(dropped);
+            }
+        }
+    }
+
+    fn import_ugr(&mut self) {
+        let Some(path) = file_dialog::pick_file(&[(&tr("filter-gradients"), &["ugr"])]) else {
+            return;
+        };
+
+        match ugr::load(&path) {
+            Ok(palettes) => {
+                info!(
+                    "Imported {} gradients from {}",
+                    palettes.len(),
+                    path.display()
+                );
+                let first_new = self.palettes.len();
+                self.palettes.extend(palettes);
+                if first_new < self.palettes.len() {
+                    self.select_palette(first_new);
+                    self.state.use_palette = true;
+                }
+            }
+            Err(e) => self.status.error(cannot_open(&path), &e),
+        }
+    }
+
+    fn import_image_palette(&mut self) {
+        let Some(path) =
+            file_dialog::pick_file(&[(&tr("filter-images"), &["png", "jpg", "jpeg", "webp"])])
+        else {
+            return;
+        };
+
+        match image_palette::load(&path) {
+            Ok(palette) => {
+                info!("Created palette {} from {}", palette.name, path.display());
+                self.palettes.push(palette);
+                self.select_palette(self.palettes.len() - 1);
+                self.state.use_palette = true;
+            }
+            Err(e) => self.status.error(cannot_open(&path), &e),
+        }
+    }
+
+    /// Pick an image and load it for the texture `name`, the image goes to `set`.
+    fn load_texture(&mut self, name: &str, set: fn(&mut FractalGl, user_image::Image)) {
+        let Some(path) =
+            file_dialog::pick_file(&[(&tr("filter-images"), &["png", "jpg", "jpeg", "webp"])])
+        else {
+            return;
+        };
+
+        match user_image::load(&path) {
+            Ok(image) => {
+                info!(
+                    "Loaded {} image {} ({}x{})",
+                    name,
+                    path.display(),
+                    image.width,
+                    image.height
+                );
+                set(&mut self.fractal.lock(), image);
+            }
+            Err(e) => self.status.error(cannot_open(&path), &e),
+        }
+    }
+
+    fn replay_ui(&mut self, ui: &mut egui::Ui) {
+        if self.replay.is_recording() {
+            if ui.button(tr("stop-recording")).clicked() {
+                self.replay.stop();
+            }
+        } else if ui
+            .add_enabled(!self.replay.is_playing(), Button::new(tr("record-input")))
+            .clicked()
+        {
+            self.record_replay();
+        }
+        if ui
+            .add_enabled(self.replay.is_idle(), Button::new(tr("play-replay")))
+            .on_hover_text(tr("play-replay-hint"))
+            .clicked()
+        {
+            self.pick_replay();
+        }
+    }
+
+    fn record_replay(&mut self) {
+        let Some(path) =
+            file_dialog::save_file(&[(&tr("filter-replay"), &["replay"])], "session.replay")
+        else {
+            return;
+        };
+
+        if let Err(e) = self.replay.record(path.clone(), &self.state) {
+            self.status.error(cannot_save(&path), &e);
+        }
+    }
+
+    fn pick_replay(&mut self) {
+        let Some(path) = file_dialog::pick_file(&[(&tr("filter-replay"), &["replay"])]) else {
+            return;
+        };
+        self.play_replay(&path);
+    }
+
+    fn play_replay(&mut self, path: &Path) {
+        match self.replay.play(path) {
+            Ok(state) => {
+                self.state = state;
+                self.transition = None;
+            }
+            Err(e) => self.status.error(cannot_open(path), &e),
+        }
+    }
+
+    fn save_preset(&mut self) {
+        let Some(path) =
+            file_dialog::save_file(&[(&tr("filter-preset"), &["toml"])], "preset.toml")
+        else {
+            return;
+        };
+
+        match preset::save(&path, &self.state) {
+            Ok(()) => self
+                .status
+                .info(tr_args("status-saved", &[("file", &file_name(&path))])),
+            Err(e) => self.status.error(cannot_save(&path), &e),
+        }
+    }
+
+    fn load_preset(&mut self) {
+        let Some(path) = file_dialog::pick_file(&[(&tr("filter-preset"), &["toml"])]) else {
+            return;
+        };
+        self.open_preset(&path);
+    }
+
+    fn open_preset(&mut self, path: &Path) {
+        match preset::load(path) {
+            Ok((name, state)) => {
+                self.status
+                    .info(tr_args("status-opened", &[("file", &name)]));
+                self.open_link(state);
+            }
+            Err(e) => self.status.error(cannot_open(path), &e),
+        }
+    }
+
+    fn import_kfr(&mut self) {
+        let Some(path) = file_dialog::pick_file(&[(&tr("filter-kfr"), &["kfr"])]) else {
+            return;
+        };
+
+        let result = KfrLocation::load(&path).and_then(|location| {
+            location.apply(&mut self.state, self.viewport_px.y.max(1.0))?;
+            Ok(location)
+        });
+        match result {
+            Ok(location) => {
+                // The digits beyond a double are lost on the way, the view is only close
+                let message = if location.is_rounded() {
+                    "status-opened-rounded"
+                } else {
+                    "status-opened"
+                };
+                self.status
+                    .info(tr_args(message, &[("file", &file_name(&path))]));
+                self.opened_location = Some((location, self.state));
+            }
+            Err(e) => self.status.error(cannot_open(&path), &e),
+        }
+    }
+}
+
+/// Name of the file at `path`, for the messages.
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn exported(path: &Path) -> String {
+    tr_args("exported", &[("file", &file_name(path))])
+}
+
+fn cannot_export(path: &Path) -> String {
+    tr_args("status-cannot-export", &[("file", &file_name(path))])
+}
+
+fn cannot_open(path: &Path) -> String {
+    tr_args("status-cannot-open", &[("file", &file_name(path))])
+}
+
+fn cannot_save(path: &Path) -> String {
+    tr_args("status-cannot-save", &[("file", &file_name(path))])
+}
+
+/// Magnification like `3.2e12×`, in scientific notation once it gets large.
+fn format_magnification(magnification: f64) -> String {
+    if magnification < 1e4 {
+        format!("{:.1}×", magnification)
+    } else {
+        format!("{:.1e}×", magnification)
     }
 }
 
 impl eframe::App for FractalApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::SidePanel::left("Settings").show(ctx, |ui| {
+        let start = web_time::Instant::now();
+        self.show(ctx);
+        let gpu_time = self.fractal.lock().gpu_time();
+        self.render_stats.record(
+            ctx.input(|i| i.time),
+            start.elapsed().as_secs_f64(),
+            gpu_time,
+            &self.state,
+            self.viewport_px.y,
+        );
+        self.frame_rate.limit();
+    }
+
+    fn raw_input_hook(&mut self, _ctx: &egui::Context, raw_input: &mut egui::RawInput) {
+        self.replay.hook(raw_input);
+    }
+
+    fn on_exit(&mut self, gl: Option<&eframe::glow::Context>) {
+        self.replay.stop();
+        self.state.save();
+        if let Some(gl) = gl {
+            self.fractal.lock().destroy(gl);
+        }
+    }
+}
+
+impl FractalApp {
+    fn show(&mut self, ctx: &egui::Context) {
+        // Everything moving is paused while the window is in the background
+        let focused = ctx.input(|i| i.viewport().focused).unwrap_or(true);
+        let minimized = ctx.input(|i| i.viewport().minimized).unwrap_or(false);
+        let time = ctx.input(|i| i.time);
+        if !focused || minimized {
+            self.unfocused_since.get_or_insert(time);
+        } else if let Some(since) = self.unfocused_since.take() {
+            self.paused_time += time - since;
+        }
+        let time = time - self.paused_time;
+
+        if minimized {
+            // Nothing is visible: no paint and no repaint request, restoring the window
+            // triggers the next frame
+            return;
+        }
+
+        if self.is_animated() && !focused {
+            ctx.request_repaint_after(UNFOCUSED_REPAINT);
+        }
+
+        if self.julia_path.is_playing() && focused {
+            self.julia_path.advance(
+                ctx.input(|i| i.stable_dt) as f64,
+                &mut self.state.c_julia.x,
+                &mut self.state.c_julia.y,
+            );
+            self.frame_rate.request_repaint(ctx);
+        }
+
+        if self.timeline.is_playing() && focused {
+            self.timeline
+                .advance(ctx.input(|i| i.stable_dt) as f64, &mut self.state);
+            self.frame_rate.request_repaint(ctx);
+        }
+
+        if let Some(transition) = self.transition.as_mut().filter(|_| focused) {
+            if !transition.advance(ctx.input(|i| i.stable_dt) as f64, &mut self.state) {
+                self.transition = None;
+            }
+            self.frame_rate.request_repaint(ctx);
+        }
+
+        if self.state.fractal_type == FractalType::Custom {
+            self.apply_plugin();
+            let mut fractal = self.fractal.lock();
+            if self.state.plugin.is_none() && !self.use_iteration_formula {
+                if let Some(glsl) = self.custom_fractal.poll(ctx.input(|i| i.time)) {
+                    fractal.set_custom_fractal(glsl);
+                }
+            }
+            fractal.set_fractal_parameters(self.state.plugin_parameters);
+            // Keep checking for changes, the file is edited in another application
+            ctx.request_repaint_after(custom_fractal::POLL_INTERVAL);
+        }
+
+        if let Some(watch) = &mut self.shader_watch {
+            if let Some((vertex, fragment)) = watch.poll(ctx.input(|i| i.time)) {
+                self.fractal.lock().set_shader_sources(vertex, fragment);
+            }
+            ctx.request_repaint_after(shader_watch::POLL_INTERVAL);
+        }
+
+        if self.replay.is_playing() {
+            // One recorded frame per frame, however long they take to render
+            ctx.request_repaint();
+        }
+
+        self.keyboard_shortcuts(ctx);
+        self.open_dropped_files(ctx);
+        self.update_skew();
+
+        if focused {
+            self.state.time = time as f32;
+            if self.animations.is_active() {
+                self.animations.apply(&mut self.state, time);
+                self.frame_rate.request_repaint(ctx);
+            }
+            if self.audio.is_active() || self.modulators.is_active() || self.state.color_cycling {
+                self.frame_rate.request_repaint(ctx);
+            }
+        }
+
+        egui::SidePanel::left("Settings").show_animated(ctx, self.show_settings, |ui| {
             ScrollArea::new([false, true]).show(ui, |ui| {
-                CollapsingHeader::new("Global parameters")
-                    .default_open(true)
-                    .show(ui, |ui| {
-                        ui.add(
-                            Slider::new(&mut self.state.zoom, 1.0..=5000.0)
-                                .logarithmic(true)
-                                .clamping(egui::SliderClamping::Never)
-                                .text("Zoom"),
-                        );
-                        ui.checkbox(&mut self.state.high_quality, "High Quality");
+                let reset = section_with_reset(ui, "global-parameters", |ui| {
+                    self.pinned
+                        .slider_ui(ui, &mut self.state, self.viewport_px.y, Parameter::Zoom);
+                    coordinates::ui(ui, &mut self.state, self.viewport_px.y.max(1.0));
+                    self.pinned.slider_ui(
+                        ui,
+                        &mut self.state,
+                        self.viewport_px.y,
+                        Parameter::Rotation,
+                    );
+                    ui.checkbox(&mut self.state.high_quality, tr("high-quality"));
+                    self.quality.ui(ui);
+                    self.progressive.ui(ui);
+                    self.pinned.slider_ui(
+                        ui,
+                        &mut self.state,
+                        self.viewport_px.y,
+                        Parameter::MaxIterations,
+                    );
+                    auto_iterations_ui(ui, &mut self.state);
+                    ui.checkbox(&mut self.state.auto_skew, tr("auto-skew"))
+                        .on_hover_text(tr("auto-skew-hint"));
+                    ui.checkbox(&mut self.show_scale_bar, tr("scale-bar"));
+                    ui.checkbox(&mut self.show_crosshair, tr("crosshair"))
+                        .on_hover_text(tr("crosshair-hint"));
+                    ui.checkbox(&mut self.show_probe, tr("probe"))
+                        .on_hover_text(tr("probe-hint"));
+                    ui.checkbox(&mut self.show_hud, tr("hud"))
+                        .on_hover_text(tr("hud-hint"));
+                    ui.checkbox(&mut self.show_explainer, tr("explain-regions"))
+                        .on_hover_text(tr("explain-regions-hint"));
+                    if ui
+                        .checkbox(&mut self.measure.active, tr("measure"))
+                        .on_hover_text(tr("measure-hint"))
+                        .changed()
+                    {
+                        self.measure.clear();
+                    }
+                    ui.toggle_value(&mut self.zoom_box.active, tr("zoom-box"))
+                        .on_hover_text(tr("zoom-box-hint"));
+                    ui.horizontal(|ui| {
+                        ui.toggle_value(&mut self.region.selecting, tr("refine-region"))
+                            .on_hover_text(tr("refine-region-hint"));
+                        if ui.button(tr("clear")).clicked() {
+                            self.region.clear();
+                        }
+                    });
+                    self.lens.ui(ui);
+                    ui.checkbox(&mut self.minimap.enabled, tr("minimap"))
+                        .on_hover_text(tr("minimap-hint"));
 
-                        ComboBox::from_label("Type")
-                            .selected_text(format!("{:?}", self.state.fractal_type))
-                            .show_ui(ui, |ui| {
-                                ui.selectable_value(
-                                    &mut self.state.fractal_type,
-                                    FractalType::Julia,
-                                    format!("{:?}", FractalType::Julia),
-                                );
+                    let custom = self.state.fractal_type == FractalType::Custom;
+                    let formula = custom && self.use_iteration_formula;
+                    let plugin = self.state.plugin.filter(|_| custom);
+                    let selected_text = match plugin {
+                        Some(id) => self
+                            .plugins
+                            .get(id)
+                            .map_or_else(|| id.to_string(), |plugin| plugin.name.clone()),
+                        None if formula => tr("iteration-formula"),
+                        None => self.state.fractal_type.label(),
+                    };
+                    let mut picked_custom = None;
+                    let mut picked_formula = false;
+                    let fractal_type = ComboBox::from_label(tr("type"))
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            for fractal_type in FractalType::BUILTIN {
                                 ui.selectable_value(
                                     &mut self.state.fractal_type,
-                                    FractalType::Mandelbrot,
-                                    format!("{:?}", FractalType::Mandelbrot),
+                                    fractal_type,
+                                    fractal_type.label(),
                                 );
-                            });
-                    });
+                            }
+                            if ui
+                                .selectable_label(
+                                    custom && plugin.is_none() && !formula,
+                                    FractalType::Custom.label(),
+                                )
+                                .clicked()
+                            {
+                                picked_custom = Some(None);
+                            }
+                            if ui
+                                .selectable_label(formula, tr("iteration-formula"))
+                                .clicked()
+                            {
+                                picked_formula = true;
+                            }
+                            for candidate in self.plugins.iter() {
+                                if ui
+                                    .selectable_label(plugin == Some(candidate.id), &candidate.name)
+                                    .clicked()
+                                {
+                                    picked_custom = Some(Some(candidate.id));
+                                }
+                            }
+                        });
+                    if let Some(plugin) = picked_custom {
+                        self.select_custom_fractal(plugin);
+                    }
+                    if picked_formula {
+                        self.select_iteration_formula();
+                    }
+                    self.tutorial
+                        .target(Step::FractalType, fractal_type.response.rect);
+                    self.help_overlay
+                        .annotate(ui, Topic::FractalType, fractal_type.response.rect);
+                    if self.state.fractal_type == FractalType::Custom {
+                        if self.state.plugin.is_some() {
+                            self.plugins.ui(ui, &mut self.state);
+                        } else if self.use_iteration_formula {
+                            if let Some(glsl) = self.iteration_formula.ui(ui) {
+                                self.fractal.lock().set_custom_fractal(glsl);
+                            }
+                        } else {
+                            self.custom_fractal.ui(ui);
+                        }
+                    }
+                    if self.state.fractal_type.is_power() {
+                        self.pinned.slider_ui(
+                            ui,
+                            &mut self.state,
+                            self.viewport_px.y,
+                            Parameter::Exponent,
+                        );
+                    }
+                    if self.state.fractal_type == FractalType::Mandelbrot {
+                        ui.toggle_value(&mut self.pick_julia_seed, tr("pick-julia-seed"))
+                            .on_hover_text(tr("pick-julia-seed-hint"));
+                        ui.checkbox(&mut self.split_view, tr("split-view"))
+                            .on_hover_text(tr("split-view-hint"));
+                    }
+                });
+                if reset {
+                    self.transition = None;
+                    self.state.reset_global();
+                }
 
                 ui.separator();
 
+                let default = State::new();
                 if self.state.fractal_type == FractalType::Julia {
-                    CollapsingHeader::new("Julia parameters")
-                        .default_open(true)
-                        .show(ui, |ui| {
-                            ui.add(DragPanel::new(
+                    let reset = section_with_reset(ui, "julia-parameters", |ui| {
+                        let pad = ui.add(
+                            DragPanel::new(
                                 &mut self.state.c_julia.x,
                                 &mut self.state.c_julia.y,
                                 -0.2..=0.2,
                                 -0.2..=0.2,
-                            ));
-
-                            ui.add(
-                                Slider::new(&mut self.state.c_julia.x, -1.0..=1.0)
-                                    .text("Julia 1")
-                                    .clamping(egui::SliderClamping::Never),
+                            )
+                            .default_values(default.c_julia.x, default.c_julia.y),
+                        );
+                        self.tutorial.target(Step::JuliaPanel, pad.rect);
+                        self.help_overlay.annotate(ui, Topic::JuliaPanel, pad.rect);
+                        if pad.dragged() {
+                            self.julia_path.record(
+                                ui.input(|i| i.time),
+                                self.state.c_julia.x,
+                                self.state.c_julia.y,
                             );
-                            ui.add(
-                                Slider::new(&mut self.state.c_julia.y, -1.0..=1.0)
-                                    .text("Julia 2")
-                                    .clamping(egui::SliderClamping::Never),
+                        }
+                        self.julia_path.ui(ui);
+
+                        self.pinned.slider_ui(
+                            ui,
+                            &mut self.state,
+                            self.viewport_px.y,
+                            Parameter::JuliaX,
+                        );
+                        self.pinned.slider_ui(
+                            ui,
+                            &mut self.state,
+                            self.viewport_px.y,
+                            Parameter::JuliaY,
+                        );
+
+                        ui.horizontal(|ui| {
+                            julia_angle_dial(ui, &mut self.state);
+                            ui.vertical(|ui| {
+                                for parameter in [Parameter::JuliaRadius, Parameter::JuliaAngle] {
+                                    self.pinned.slider_ui(
+                                        ui,
+                                        &mut self.state,
+                                        self.viewport_px.y,
+                                        parameter,
+                                    );
+                                }
+                            });
+                        });
+                    });
+                    if reset {
+                        self.state.reset_julia();
+                    }
+
+                    ui.separator();
+                }
+                if self.state.fractal_type == FractalType::Newton {
+                    CollapsingHeader::new(tr("newton-roots"))
+                        .default_open(true)
+                        .show(ui, |ui| newton_roots_ui(ui, &mut self.state.newton_roots));
+
+                    ui.separator();
+                }
+                if self.state.fractal_type == FractalType::Lyapunov {
+                    CollapsingHeader::new(tr("lyapunov-parameters"))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            lyapunov_ui(ui, &mut self.lyapunov_text, &mut self.state);
+                            if ui
+                                .button(tr("lyapunov-view"))
+                                .on_hover_text(tr("lyapunov-view-hint"))
+                                .clicked()
+                            {
+                                // The rates between 2 and 4, where the logistic map is
+                                // interesting
+                                let zoom = self.viewport_px.min_elem().max(1.0) / 2.0;
+                                self.fly_to(
+                                    Position { x: -3.0, y: -3.0 },
+                                    zoom,
+                                    NAVIGATION_DURATION,
+                                );
+                            }
+                        });
+
+                    ui.separator();
+                }
+                if self.state.fractal_type == FractalType::Nova {
+                    CollapsingHeader::new(tr("nova-parameters"))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            self.pinned.slider_ui(
+                                ui,
+                                &mut self.state,
+                                self.viewport_px.y,
+                                Parameter::NovaRelaxation,
                             );
                         });
 
                     ui.separator();
                 }
-                CollapsingHeader::new("Color parameters")
-                    .default_open(true)
-                    .show(ui, |ui| {
-                        ui.add(DragPanel::new(
+                let reset = section_with_reset(ui, "color-parameters", |ui| {
+                    ui.add(
+                        DragPanel::new(
                             &mut self.state.contrast,
                             &mut self.state.brightness,
                             -0.5..=0.5,
                             -0.5..=0.5,
-                        ));
+                        )
+                        .default_values(default.contrast, default.brightness),
+                    );
 
-                        ui.add(
-                            Slider::new(&mut self.state.contrast, -1.0..=1.0)
-                                .text("Contrast")
-                                .clamping(egui::SliderClamping::Never),
-                        );
-                        ui.add(
-                            Slider::new(&mut self.state.brightness, -2.0..=2.0)
-                                .text("Brightness")
-                                .clamping(egui::SliderClamping::Never),
-                        );
-                        ui.add(
-                            Slider::new(&mut self.state.gamma, 0.1..=3.0)
-                                .text("Gamma")
-                                .clamping(egui::SliderClamping::Never),
+                    self.pinned.slider_ui(
+                        ui,
+                        &mut self.state,
+                        self.viewport_px.y,
+                        Parameter::Contrast,
+                    );
+                    self.pinned.slider_ui(
+                        ui,
+                        &mut self.state,
+                        self.viewport_px.y,
+                        Parameter::Brightness,
+                    );
+                    self.pinned.slider_ui(
+                        ui,
+                        &mut self.state,
+                        self.viewport_px.y,
+                        Parameter::Gamma,
+                    );
+                    color_balance_ui(ui, &mut self.state.color_balance);
+                    ui.separator();
+
+                    ComboBox::from_label(tr("algorithm"))
+                        .selected_text(self.state.coloring_algorithm.label())
+                        .show_ui(ui, |ui| {
+                            for algorithm in ColoringAlgorithm::ALL {
+                                ui.selectable_value(
+                                    &mut self.state.coloring_algorithm,
+                                    algorithm,
+                                    algorithm.label(),
+                                );
+                            }
+                        });
+                    match self.state.coloring_algorithm {
+                        ColoringAlgorithm::Iterations => {
+                            iteration_coloring_ui(ui, &mut self.state.iteration_coloring);
+                        }
+                        ColoringAlgorithm::Period | ColoringAlgorithm::AtomDomain => {}
+                        ColoringAlgorithm::StripeAverage => {
+                            self.pinned.slider_ui(
+                                ui,
+                                &mut self.state,
+                                self.viewport_px.y,
+                                Parameter::StripeDensity,
+                            );
+                            self.pinned.slider_ui(
+                                ui,
+                                &mut self.state,
+                                self.viewport_px.y,
+                                Parameter::StripeMix,
+                            );
+                        }
+                        ColoringAlgorithm::TriangleInequality => {
+                            self.pinned.slider_ui(
+                                ui,
+                                &mut self.state,
+                                self.viewport_px.y,
+                                Parameter::TiaBlend,
+                            );
+                        }
+                        ColoringAlgorithm::CurvatureAverage => {
+                            self.pinned.slider_ui(
+                                ui,
+                                &mut self.state,
+                                self.viewport_px.y,
+                                Parameter::CurvatureMix,
+                            );
+                        }
+                        ColoringAlgorithm::BinaryDecomposition => {
+                            self.pinned.slider_ui(
+                                ui,
+                                &mut self.state,
+                                self.viewport_px.y,
+                                Parameter::DecompositionMix,
+                            );
+                            ui.checkbox(&mut self.state.field_lines, tr("field-lines"));
+                        }
+                        ColoringAlgorithm::TextureMap => {
+                            if ui.button(tr("load-exterior-texture")).clicked() {
+                                self.load_texture("exterior", FractalGl::set_exterior_image);
+                            }
+                            self.pinned.slider_ui(
+                                ui,
+                                &mut self.state,
+                                self.viewport_px.y,
+                                Parameter::TextureBlend,
+                            );
+                            ui.checkbox(&mut self.state.texture_mirror, tr("mirror-texture"))
+                                .on_hover_text(tr("mirror-texture-hint"));
+                        }
+                        ColoringAlgorithm::ImageTrap => {
+                            if ui.button(tr("load-trap-image")).clicked() {
+                                self.load_texture("trap", FractalGl::set_trap_image);
+                            }
+                            self.pinned.slider_ui(
+                                ui,
+                                &mut self.state,
+                                self.viewport_px.y,
+                                Parameter::TrapScale,
+                            );
+                            ui.horizontal(|ui| {
+                                let dial = ui.add(AngleDial::new(&mut self.state.trap_rotation));
+                                let label = ui.label(tr("trap-rotation"));
+                                dial.labelled_by(label.id);
+                            });
+                        }
+                        ColoringAlgorithm::OrbitTrap => orbit_trap_ui(ui, &mut self.state),
+                    }
+                    boundary_ui(ui, &mut self.state);
+                    ui.separator();
+
+                    ui.checkbox(&mut self.state.use_palette, tr("use-palette"));
+                    if self.state.use_palette {
+                        let mut selected = self.selected_palette;
+                        ComboBox::from_label(tr("palette"))
+                            .selected_text(self.palettes[selected].name.as_str())
+                            .show_ui(ui, |ui| {
+                                for (i, palette) in self.palettes.iter().enumerate() {
+                                    ui.selectable_value(&mut selected, i, palette.name.as_str());
+                                }
+                            });
+                        if selected != self.selected_palette {
+                            self.select_palette(selected);
+                        }
+                        let palette = &mut self.palettes[self.selected_palette];
+                        let mut oklab = palette.interpolation == Interpolation::Oklab;
+                        if ui
+                            .checkbox(&mut oklab, tr("oklab-interpolation"))
+                            .on_hover_text(tr("oklab-interpolation-hint"))
+                            .changed()
+                        {
+                            palette.interpolation = if oklab {
+                                Interpolation::Oklab
+                            } else {
+                                Interpolation::Rgb
+                            };
+                            self.palette_previews.invalidate();
+                            self.select_palette(self.selected_palette);
+                        }
+                        let palette = &mut self.palettes[self.selected_palette];
+                        if self.gradient_editor.ui(ui, palette) {
+                            self.palette_previews.invalidate();
+                            self.select_palette(self.selected_palette);
+                        }
+                        self.palette_previews.update(
+                            ui.ctx(),
+                            &mut self.fractal.lock(),
+                            &mut self.thumbnails,
+                            &self.state,
+                            self.viewport_px,
+                            &self.palettes,
                         );
+                        if let Some(index) = self.palette_previews.ui(
+                            ui,
+                            &self.thumbnails,
+                            &self.palettes,
+                            self.selected_palette,
+                        ) {
+                            self.select_palette(index);
+                        }
+                        if let Some(palette) = self.random_palette.ui(ui) {
+                            self.audition_palette(palette);
+                        }
                         ui.separator();
+                    }
+
+                    ui.checkbox(&mut self.state.color_cycling, tr("cycle-colors"))
+                        .on_hover_text(tr("cycle-colors-hint"));
+                    if self.state.color_cycling {
+                        self.pinned.slider_ui(
+                            ui,
+                            &mut self.state,
+                            self.viewport_px.y,
+                            Parameter::CyclingSpeed,
+                        );
+                    }
 
-                        ui.add(
-                            Slider::new(&mut self.state.r, 0.0..=1.0)
-                                .text("Red")
-                                .clamping(egui::SliderClamping::Never),
+                    // The multipliers only color without the palette
+                    if !self.state.use_palette {
+                        self.pinned.slider_ui(
+                            ui,
+                            &mut self.state,
+                            self.viewport_px.y,
+                            Parameter::Red,
                         );
-                        ui.add(
-                            Slider::new(&mut self.state.g, 0.0..=1.0)
-                                .text("Green")
-                                .clamping(egui::SliderClamping::Never),
+                        self.pinned.slider_ui(
+                            ui,
+                            &mut self.state,
+                            self.viewport_px.y,
+                            Parameter::Green,
                         );
-                        ui.add(
-                            Slider::new(&mut self.state.b, 0.0..=1.0)
-                                .text("Blue")
-                                .clamping(egui::SliderClamping::Never),
+                        self.pinned.slider_ui(
+                            ui,
+                            &mut self.state,
+                            self.viewport_px.y,
+                            Parameter::Blue,
                         );
+                    }
+                });
+                if reset {
+                    self.state.reset_colors();
+                }
+
+                ui.separator();
+
+                let coloring = CollapsingHeader::new(tr("coloring-formula"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.checkbox(&mut self.state.value_formula, tr("use-the-formula"));
+                        if let Some(glsl) = self.formula.ui(ui) {
+                            self.fractal.lock().set_value_formula(glsl);
+                        }
+                    });
+                self.help_overlay
+                    .annotate(ui, Topic::Coloring, coloring.header_response.rect);
+
+                ui.separator();
+
+                CollapsingHeader::new(tr("coloring-graph"))
+                    .default_open(false)
+                    .show(ui, |ui| self.coloring_graph_ui(ui));
+
+                ui.separator();
+
+                CollapsingHeader::new(tr("animation"))
+                    .default_open(false)
+                    .show(ui, |ui| self.animations.ui(ui));
+
+                ui.separator();
+
+                CollapsingHeader::new(tr("modulation"))
+                    .default_open(false)
+                    .show(ui, |ui| self.modulators.ui(ui));
+
+                ui.separator();
+
+                CollapsingHeader::new(tr("audio"))
+                    .default_open(false)
+                    .show(ui, |ui| self.audio.ui(ui));
+
+                ui.separator();
+
+                CollapsingHeader::new(tr("analysis"))
+                    .default_open(false)
+                    .show(ui, |ui| self.analysis_ui(ui));
+
+                ui.separator();
+
+                CollapsingHeader::new(tr("annotations"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        if let Some(point) = self.annotations.ui(ui) {
+                            self.state.center_position.x = -point.re;
+                            self.state.center_position.y = -point.im;
+                        }
+                    });
+
+                ui.separator();
+
+                CollapsingHeader::new(tr("language"))
+                    .default_open(false)
+                    .show(ui, i18n::language_ui);
+
+                ui.separator();
+
+                CollapsingHeader::new(tr("accessibility"))
+                    .default_open(false)
+                    .show(ui, |ui| self.appearance.ui(ui));
+
+                ui.separator();
+
+                let controls = CollapsingHeader::new(tr("mouse-controls"))
+                    .default_open(false)
+                    .show(ui, |ui| self.controls.ui(ui));
+                self.help_overlay
+                    .annotate(ui, Topic::MouseControls, controls.header_response.rect);
+
+                ui.separator();
+
+                let nudge = CollapsingHeader::new(tr("nudge"))
+                    .default_open(false)
+                    .show(ui, |ui| self.nudge.ui(ui));
+                self.help_overlay
+                    .annotate(ui, Topic::Nudge, nudge.header_response.rect);
+
+                ui.separator();
+
+                CollapsingHeader::new(tr("gpu"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.gpu.ui(ui);
+                        self.cpu_renderer.ui(ui);
+                    });
+
+                ui.separator();
+
+                CollapsingHeader::new(tr("frame-rate"))
+                    .default_open(false)
+                    .show(ui, |ui| self.frame_rate.ui(ui));
+
+                ui.separator();
+
+                CollapsingHeader::new(tr("recent"))
+                    .default_open(false)
+                    .show(ui, |ui| self.recent_ui(ui));
+
+                ui.separator();
+
+                let bookmarks = CollapsingHeader::new(tr("bookmarks"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        if let Some(state) = self.bookmarks.ui(ui, &self.state) {
+                            self.state = state;
+                            self.transition = None;
+                        }
+                        if ui.button(tr("open-gallery")).clicked() {
+                            self.gallery.open = true;
+                        }
+                        ui.separator();
+                        if let Some(state) = self.share.ui(ui, &self.state) {
+                            self.open_link(state);
+                        }
+                    });
+                self.help_overlay
+                    .annotate(ui, Topic::Bookmarks, bookmarks.header_response.rect);
+
+                ui.separator();
+
+                CollapsingHeader::new(tr("zoom-video"))
+                    .default_open(false)
+                    .show(ui, |ui| self.zoom_video_ui(ui));
+
+                CollapsingHeader::new(tr("timeline"))
+                    .default_open(false)
+                    .show(ui, |ui| self.timeline_ui(ui));
+
+                ui.separator();
+
+                let files = CollapsingHeader::new(tr("files"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        if ui.button(tr("load-preset")).clicked() {
+                            self.load_preset();
+                        }
+                        if ui
+                            .button(tr("save-preset"))
+                            .on_hover_text(tr("save-preset-hint"))
+                            .clicked()
+                        {
+                            self.save_preset();
+                        }
+                        ui.separator();
+                        if ui.button(tr("import-kfr")).clicked() {
+                            self.import_kfr();
+                        }
+                        if ui.button(tr("import-ugr")).clicked() {
+                            self.import_ugr();
+                        }
+                        if ui.button(tr("palette-from-image")).clicked() {
+                            self.import_image_palette();
+                        }
+                        ui.separator();
+                        if ui.button(tr("export-kfr")).clicked() {
+                            self.export_location(LocationFormat::Kfr);
+                        }
+                        if ui.button(tr("export-mandel-machine")).clicked() {
+                            self.export_location(LocationFormat::MandelMachine);
+                        }
+                        self.tiled_export_ui(ui);
+                        self.last_export_ui(ui);
+                        ui.separator();
+                        self.replay_ui(ui);
+                        if ui.button(tr("script-console")).clicked() {
+                            self.script.open = true;
+                        }
                     });
+                self.tutorial
+                    .target(Step::Export, files.header_response.rect);
+                self.help_overlay
+                    .annotate(ui, Topic::Files, files.header_response.rect);
 
                 ui.separator();
 
-                if ui.button("Exit").clicked() {
-                    std::process::exit(0);
+                let help = ui.horizontal(|ui| {
+                    ui.menu_button(tr("help"), |ui| {
+                        if ui.button(tr("tutorial-button")).clicked() {
+                            self.tutorial.start();
+                            ui.close_menu();
+                        }
+                        if ui.button(tr("benchmark-button")).clicked() {
+                            self.benchmark.open = true;
+                            ui.close_menu();
+                        }
+                        if ui.button(tr("about-button")).clicked() {
+                            self.about.open = true;
+                            ui.close_menu();
+                        }
+                    });
+                    if icon_button(ui.button("?"), tr("help-overlay")).clicked() {
+                        self.help_overlay.open = true;
+                    }
+                });
+                self.help_overlay
+                    .annotate(ui, Topic::Help, help.response.rect);
+                if ui
+                    .button(tr("reset-view"))
+                    .on_hover_text(tr("reset-view-hint"))
+                    .clicked()
+                {
+                    self.transition = None;
+                    self.state.reset_view();
+                }
+                if ui
+                    .button(tr("new-tab"))
+                    .on_hover_text(tr("new-tab-hint"))
+                    .clicked()
+                {
+                    self.tabs.open(&mut self.state, &mut self.history);
+                    self.transition = None;
+                }
+                let fullscreen = ui.input(|i| i.viewport().fullscreen.unwrap_or(false));
+                if ui
+                    .selectable_label(fullscreen, tr("fullscreen"))
+                    .on_hover_text(tr("fullscreen-hint"))
+                    .clicked()
+                {
+                    toggle_fullscreen(ui.ctx());
+                }
+                // A page cannot close itself
+                if !cfg!(target_arch = "wasm32") && ui.button(tr("exit")).clicked() {
+                    ui.ctx().send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+            });
+        });
+        if self.tabs.len() > 1 {
+            egui::TopBottomPanel::top("Tabs").show(ctx, |ui| {
+                if self.tabs.ui(ui, &mut self.state, &mut self.history) {
+                    self.transition = None;
+                }
+            });
+        }
+        if !self.pinned.is_empty() {
+            egui::TopBottomPanel::top("Pinned").show(ctx, |ui| {
+                self.pinned
+                    .toolbar_ui(ui, &mut self.state, self.viewport_px.y);
+            });
+        }
+        egui::TopBottomPanel::bottom("Status").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.menu_button(tr("layouts"), |ui| {
+                    let current = self.layout();
+                    if let Some(layout) = self.layouts.menu_ui(ui, current) {
+                        self.apply_layout(layout);
+                    }
+                });
+                ui.toggle_value(&mut self.show_settings, tr("settings-panel"));
+                ui.separator();
+                if ui
+                    .add_enabled(self.history.can_undo(), Button::new(tr("undo")))
+                    .on_hover_text("Ctrl+Z")
+                    .clicked()
+                {
+                    self.undo();
+                }
+                if ui
+                    .add_enabled(self.history.can_redo(), Button::new(tr("redo")))
+                    .on_hover_text("Ctrl+Shift+Z")
+                    .clicked()
+                {
+                    self.redo();
+                }
+                ui.separator();
+                let magnification = self.state.magnification(self.viewport_px.y.max(1.0));
+                ui.label(tr_args(
+                    "magnification",
+                    &[("value", &format_magnification(magnification))],
+                ));
+                ui.separator();
+                ui.label(tr_args(
+                    "pixel-size",
+                    &[("value", &format!("{:.2e}", self.state.pixel_size()))],
+                ));
+                if perturbation::is_deep(&self.state) {
+                    ui.separator();
+                    ui.label(tr("perturbation"))
+                        .on_hover_text(tr("perturbation-hint"));
+                }
+                let center = self.state.point_at([0.0, 0.0]);
+                if self.state.zoom >= perturbation::deepest_zoom(center) {
+                    ui.separator();
+                    ui.label(tr("deepest-zoom"))
+                        .on_hover_text(tr("deepest-zoom-hint"));
                 }
+                self.status.ui(ui);
             });
         });
         egui::CentralPanel::default().show(ctx, |ui| {
-            egui::Frame::canvas(ui.style()).show(ui, |ui| {
-                self.custom_painting(ui);
+            if self.split_view && self.state.fractal_type == FractalType::Mandelbrot {
+                let size = ui.available_size();
+                let half = egui::vec2((size.x - ui.spacing().item_spacing.x) / 2.0, size.y);
+                ui.horizontal(|ui| {
+                    ui.allocate_ui(half, |ui| {
+                        egui::Frame::canvas(ui.style()).show(ui, |ui| self.custom_painting(ui));
+                    });
+                    ui.allocate_ui(half, |ui| {
+                        egui::Frame::canvas(ui.style()).show(ui, |ui| self.julia_preview(ui));
+                    });
+                });
+            } else {
+                egui::Frame::canvas(ui.style()).show(ui, |ui| {
+                    self.custom_painting(ui);
+                });
+            }
+        });
+        self.about.show(ctx, &self.render_stats);
+        self.benchmark.show(ctx);
+        self.benchmark.update(ctx, &mut self.fractal.lock());
+        self.script.show(ctx, &self.state);
+        let side_by_side = self.state.fractal_type == FractalType::Mandelbrot;
+        if self.composite.show(ctx, side_by_side, self.export_width) && self.tiled_export.is_none()
+        {
+            self.export_composite();
+        }
+        if let Some(step) = self.script.next_step(ctx, self.tiled_export.is_some()) {
+            self.play_script_step(step);
+        }
+        if self.gallery.open {
+            let picked = self.gallery.show(
+                ctx,
+                &mut self.bookmarks,
+                &mut self.thumbnails,
+                &mut self.fractal.lock(),
+                self.viewport_px,
+            );
+            if let Some(state) = picked {
+                self.state = state;
+                self.transition = None;
+            }
+        }
+        self.tutorial.show(ctx);
+        self.help_overlay.show(ctx);
+        self.show_shader_error(ctx);
+        self.track_recent(ctx);
+        self.track_history(ctx);
+        self.update_tiled_export(ctx);
+        self.update_iteration_export(ctx);
+    }
+}
+
+/// Section open at first, titled by the translation of `key`, with a reset button beside the
+/// title. True when the button is clicked.
+fn section_with_reset(ui: &mut egui::Ui, key: &str, add_body: impl FnOnce(&mut egui::Ui)) -> bool {
+    let mut reset = false;
+    let id = ui.make_persistent_id(key);
+    CollapsingState::load_with_default_open(ui.ctx(), id, true)
+        .show_header(ui, |ui| {
+            ui.label(tr(key));
+            reset = icon_button(ui.small_button("⟲"), tr("reset-section")).clicked();
+        })
+        .body(add_body);
+    reset
+}
+
+/// Shadows, midtones and highlights color wheels.
+fn color_balance_ui(ui: &mut egui::Ui, color_balance: &mut [[f32; 2]; 3]) {
+    ui.horizontal(|ui| {
+        for (offset, name) in color_balance
+            .iter_mut()
+            .zip(["shadows", "midtones", "highlights"])
+        {
+            ui.vertical(|ui| {
+                ui.add(ColorWheel::new(offset, tr(name)));
+                ui.label(tr(name));
             });
+        }
+        if ui.button(tr("reset")).clicked() {
+            *color_balance = [[0.0; 2]; 3];
+        }
+    });
+}
+
+/// Dial turning the Julia constant around the origin, next to its
+/// [Parameter::JuliaRadius] and [Parameter::JuliaAngle]: many families of interesting
+/// constants are circles or rays around the origin.
+fn julia_angle_dial(ui: &mut egui::Ui, state: &mut State) {
+    let mut argument = Parameter::JuliaAngle.value(state).to_radians();
+    if ui.add(AngleDial::new(&mut argument)).changed() {
+        Parameter::JuliaAngle.set(state, argument.to_degrees());
+    }
+}
+
+/// `angle` in radians brought back between -π and π.
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+/// Whether the iteration budget follows the zoom, below its [Parameter::MaxIterations].
+fn auto_iterations_ui(ui: &mut egui::Ui, state: &mut State) {
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut state.auto_iterations, tr("auto-iterations"))
+            .on_hover_text(tr("auto-iterations-hint"));
+        if state.auto_iterations {
+            ui.label(tr_args(
+                "current-iterations",
+                &[("iterations", &state.iterations())],
+            ));
+        }
+    });
+}
+
+/// Video file to export to, asked to the user.
+fn save_video_file(file_name: &str) -> Option<PathBuf> {
+    file_dialog::save_file(
+        &[
+            (&tr("filter-mp4"), &["mp4"]),
+            (&tr("filter-webm"), &["webm"]),
+            (&tr("filter-png-sequence"), &["png"]),
+        ],
+        file_name,
+    )
+}
+
+/// Switch the window between borderless fullscreen and its previous geometry.
+fn toggle_fullscreen(ctx: &egui::Context) {
+    let current = ctx.input(|i| i.viewport().fullscreen.unwrap_or(false));
+    ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(!current));
+}
+
+/// Edit the roots of the polynomial of the Newton fractal.
+fn newton_roots_ui(ui: &mut egui::Ui, roots: &mut [[f32; 2]; 3]) {
+    for (i, [x, y]) in roots.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(tr_args("newton-root", &[("index", &(i + 1))]));
+            ui.add(egui::DragValue::new(x).speed(0.01));
+            ui.add(egui::DragValue::new(y).speed(0.01).suffix("i"));
         });
     }
+    if ui.button(tr("reset")).clicked() {
+        *roots = state::default_newton_roots();
+    }
+}
 
-    fn on_exit(&mut self, gl: Option<&eframe::glow::Context>) {
-        if let Some(gl) = gl {
-            self.fractal.lock().destroy(gl);
+/// Text field of the sequence of the Lyapunov fractal, `text` keeps what is typed until it
+/// is a valid sequence.
+fn lyapunov_ui(ui: &mut egui::Ui, text: &mut String, state: &mut State) {
+    let response = ui
+        .horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(text)
+                    .char_limit(state::MAX_LYAPUNOV_LENGTH)
+                    .desired_width(160.0),
+            );
+            let label = ui.label(tr("lyapunov-sequence"));
+            response.labelled_by(label.id)
+        })
+        .inner
+        .on_hover_text(tr("lyapunov-sequence-hint"));
+    let parsed = text.parse::<LyapunovSequence>();
+    if response.has_focus() {
+        match parsed {
+            Ok(sequence) => state.lyapunov_sequence = sequence,
+            Err(e) => {
+                ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+            }
+        }
+    } else if parsed.ok() != Some(state.lyapunov_sequence) {
+        // Left invalid, or changed elsewhere by the history or a preset
+        *text = state.lyapunov_sequence.to_string();
+    }
+}
+
+fn iteration_coloring_ui(ui: &mut egui::Ui, coloring: &mut IterationColoring) {
+    ComboBox::from_label(tr("iteration-coloring"))
+        .selected_text(coloring.label())
+        .show_ui(ui, |ui| {
+            for choice in IterationColoring::ALL {
+                ui.selectable_value(coloring, choice, choice.label());
+            }
+        })
+        .response
+        .on_hover_text(tr("iteration-coloring-hint"));
+}
+
+/// Style and width of the boundary drawn from the distance estimate.
+fn boundary_ui(ui: &mut egui::Ui, state: &mut State) {
+    ui.add_enabled_ui(state.fractal_type.has_distance_estimate(), |ui| {
+        ComboBox::from_label(tr("boundary"))
+            .selected_text(state.boundary_style.label())
+            .show_ui(ui, |ui| {
+                for style in BoundaryStyle::ALL {
+                    ui.selectable_value(&mut state.boundary_style, style, style.label());
+                }
+            })
+            .response
+            .on_hover_text(tr("boundary-hint"))
+            .on_disabled_hover_text(tr("boundary-unsupported"));
+        if state.boundary_style != BoundaryStyle::Off {
+            ui.horizontal(|ui| {
+                ui.label(tr("boundary-width"));
+                ui.add(
+                    egui::DragValue::new(&mut state.boundary_width)
+                        .speed(0.05)
+                        .range(0.25..=20.0)
+                        .suffix(" px"),
+                );
+            });
+        }
+    });
+}
+
+/// Shape, position and size of the orbit trap.
+fn orbit_trap_ui(ui: &mut egui::Ui, state: &mut State) {
+    ComboBox::from_label(tr("trap-coloring"))
+        .selected_text(state.trap_shape.label())
+        .show_ui(ui, |ui| {
+            for shape in TrapShape::ALL {
+                ui.selectable_value(&mut state.trap_shape, shape, shape.label());
+            }
+        });
+    ui.horizontal(|ui| {
+        ui.label(tr("trap-center"));
+        let [x, y] = &mut state.trap_center;
+        ui.add(egui::DragValue::new(x).speed(0.01));
+        ui.add(egui::DragValue::new(y).speed(0.01).suffix("i"));
+    });
+    match state.trap_shape {
+        TrapShape::Point => {}
+        TrapShape::Line => {
+            ui.horizontal(|ui| {
+                let dial = ui.add(AngleDial::new(&mut state.trap_rotation));
+                let label = ui.label(tr("trap-angle"));
+                dial.labelled_by(label.id);
+            });
+        }
+        TrapShape::Ring => {
+            ui.horizontal(|ui| {
+                ui.label(tr("trap-size"));
+                ui.add(
+                    egui::DragValue::new(&mut state.trap_size)
+                        .speed(0.01)
+                        .range(0.001..=10.0),
+                );
+            });
         }
     }
 }
 
 impl FractalApp {
+    /// The state with the temporary modulations applied, they must not accumulate into
+    /// [FractalApp::state] from one frame to the next.
+    fn rendered_state(&self) -> State {
+        let mut state = self.state;
+        self.modulators.modulate(&mut state);
+        self.audio.modulate(&mut state);
+        state
+    }
+
+    /// Julia set of the split view, for the point under the pointer in the Mandelbrot set.
+    /// A click switches to it.
+    fn julia_preview(&mut self, ui: &mut egui::Ui) {
+        if let Some(offset) = self.pointer_offset_px {
+            self.split_julia_c = self.state.point_at(offset);
+        }
+        let c = self.split_julia_c;
+        let (rect, response) = ui.allocate_exact_size(ui.available_size(), egui::Sense::click());
+
+        let data = composite::julia_view(&self.rendered_state(), c);
+        // Only shader-side sampling, like the lens
+        let quality = Quality {
+            supersampling: 1,
+            ..self.quality
+        };
+        let fractal = self.fractal.clone();
+        let callback = egui_glow::CallbackFn::new(move |info, painter| {
+            fractal
+                .lock()
+                .paint(painter.gl(), data, quality, None, info)
+        });
+        if rect.is_positive() {
+            ui.painter().add(egui::PaintCallback {
+                rect,
+                callback: Arc::new(callback),
+            });
+        }
+        ui.painter().text(
+            rect.left_top() + egui::vec2(8.0, 8.0),
+            egui::Align2::LEFT_TOP,
+            format!("c = {:.6} {:+.6}i", c[0], c[1]),
+            egui::FontId::monospace(12.0),
+            egui::Color32::WHITE,
+        );
+
+        if response
+            .on_hover_text(tr("split-julia-hint"))
+            .clicked_by(PointerButton::Primary)
+        {
+            self.pick_julia_seed(c);
+        }
+    }
+
+    /// Pan with two fingers and pinch to zoom around the center of the fingers. Returns
+    /// whether a gesture is in progress, the first finger does not drag the view then.
+    fn touch_gestures(&mut self, ui: &egui::Ui, rect: egui::Rect) -> bool {
+        let Some(touch) = ui.input(|i| i.multi_touch()) else {
+            return false;
+        };
+        if !rect.contains(touch.center_pos) {
+            return false;
+        }
+        self.transition = None;
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        // The point under the previous center follows the fingers, then the zoom keeps it
+        // there
+        let change = Position::drag(pixels_per_point, self.state.zoom, touch.translation_delta)
+            .rotated(self.state.rotation);
+        self.state.center_position = self.state.center_position + change;
+        let offset = (touch.center_pos - rect.center()) * pixels_per_point;
+        self.zoom_at([offset.x, offset.y], touch.zoom_delta);
+        true
+    }
+
     fn custom_painting(&mut self, ui: &mut egui::Ui) {
         let (rect, response) =
             ui.allocate_exact_size(ui.available_size(), egui::Sense::click_and_drag());
+        self.pixels_per_point = ui.ctx().pixels_per_point();
+        self.viewport_px = rect.size() * self.pixels_per_point;
+        if let Some(magnification) = self.startup_magnification.take() {
+            let zoom = (magnification * self.viewport_px.y.max(1.0) as f64 / 4.0) as f32;
+            if zoom.is_finite() && zoom > 0.0 {
+                self.state.zoom = zoom;
+            } else {
+                warn!("The zoom {magnification:e} is out of reach of the renderer");
+            }
+        }
+        self.tutorial.target(Step::Canvas, rect);
+        self.help_overlay.annotate(ui, Topic::Canvas, rect);
+        self.pointer_offset_px = response.hover_pos().map(|pos| {
+            let offset = (pos - rect.center()) * ui.ctx().pixels_per_point();
+            [offset.x, offset.y]
+        });
+
+        if self.pick_julia_seed
+            && self.state.fractal_type == FractalType::Mandelbrot
+            && response.hovered()
+        {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Crosshair);
+        }
+
+        // Not while typing a note
+        let offset = self
+            .pointer_offset_px
+            .filter(|_| !ui.ctx().wants_keyboard_input());
+        if let Some(offset) = offset {
+            if self.state.fractal_type == FractalType::Mandelbrot
+                && ui.input(|i| i.key_pressed(egui::Key::M))
+            {
+                let [re, im] = self.state.point_at(offset);
+                self.misiurewicz = misiurewicz::find(Complex::new(re, im));
+                match self.misiurewicz {
+                    Some(point) => {
+                        info!("Found Misiurewicz point {:?}", point);
+                        self.status.info(tr_args(
+                            "misiurewicz-found",
+                            &[("preperiod", &point.preperiod), ("period", &point.period)],
+                        ));
+                    }
+                    None => self.status.info(tr("misiurewicz-not-found")),
+                }
+            }
+            if ui.input(|i| i.key_pressed(egui::Key::N)) {
+                let [re, im] = self.state.point_at(offset);
+                self.annotations.add(Complex::new(re, im));
+            }
+        }
 
-        let scroll_delta = ui.input(|i| i.smooth_scroll_delta);
-        if scroll_delta.y > 0.0 {
-            self.state.zoom *= 1.1;
-        } else if scroll_delta.y < 0.0 {
-            self.state.zoom *= 0.9;
+        // The smoothed scroll zooms over several frames
+        let scroll_delta = if self.appearance.reduced_motion() {
+            ui.input(|i| i.raw_scroll_delta)
+        } else {
+            ui.input(|i| i.smooth_scroll_delta)
+        };
+        let step = self.controls.click_step;
+        if scroll_delta.y != 0.0 {
+            self.transition = None;
+            self.zoom_at_pointer(self.controls.scroll_zoom(scroll_delta.y));
         } else if response.double_clicked_by(PointerButton::Primary) {
-            let old_zoom_level = self.state.zoom;
-            self.state.zoom *= 1.2;
-            info!(
-                "Zoom level change: {} -> {}",
-                old_zoom_level, self.state.zoom
-            );
-        } else if response.clicked_by(PointerButton::Primary) {
+            // The first click already started moving to the point clicked
+            let (center, zoom) = self.target_view();
+            info!("Zoom level change: {} -> {}", zoom, zoom * step);
+            self.fly_to(center, zoom * step, NAVIGATION_DURATION);
+        } else if response.clicked_by(PointerButton::Primary)
+            && self.state.fractal_type == FractalType::Mandelbrot
+            && (self.pick_julia_seed || ui.input(|i| i.modifiers.command))
+        {
+            if let Some(offset) = self.pointer_offset_px {
+                self.pick_julia_seed(self.state.point_at(offset));
+            }
+        } else if response.clicked_by(PointerButton::Primary) && self.measure.active {
+            if let Some(offset) = self.pointer_offset_px {
+                let [re, im] = self.state.point_at(offset);
+                self.measure.click(Complex::new(re, im));
+            }
+        } else if response.double_clicked_by(PointerButton::Secondary) {
+            let (center, zoom) = self.target_view();
+            info!("Zoom level change: {} -> {}", zoom, zoom / step);
+            self.fly_to(center, zoom / step, NAVIGATION_DURATION);
+        } else if self.controls.click_to_recenter
+            && response.clicked_by(self.controls.recenter_button)
+        {
             let pixels_per_point = ui.ctx().pixels_per_point();
 
-            let new_center_screen = Position::from_screen_space(
-                pixels_per_point,
-                response
-                    .interact_pointer_pos()
-                    .unwrap_or(Pos2 { x: 0.0, y: 0.0 }),
-            );
-            let current_center = Position::from_screen_space(pixels_per_point, rect.center());
-            let diff_gl_space = (current_center - new_center_screen) / self.state.zoom;
+            let pointer = response
+                .interact_pointer_pos()
+                .unwrap_or(Pos2 { x: 0.0, y: 0.0 });
+            let change =
+                Position::recenter(pixels_per_point, self.state.zoom, rect.center(), pointer)
+                    .rotated(self.state.rotation);
 
             info!(
-                "new_center_screen: {:?}, current_center: {:?}, diff gl space: {:?}",
-                new_center_screen, current_center, diff_gl_space
+                "Recenter on {:?} (canvas center {:?}): {:?}",
+                pointer,
+                rect.center(),
+                change
             );
-            self.state.center_position.x += diff_gl_space.x;
-            self.state.center_position.y -= diff_gl_space.y;
-        } else if response.double_clicked_by(PointerButton::Secondary) {
-            let old_zoom_level = self.state.zoom;
-            self.state.zoom /= 1.2;
-            info!(
-                "Zoom level change: {} -> {}",
-                old_zoom_level, self.state.zoom
+            self.fly_to(
+                self.state.center_position + change,
+                self.state.zoom,
+                NAVIGATION_DURATION,
             );
         }
 
-        if response.dragged() && response.drag_delta().length_sq() > 0.0 {
-            let drag_in_gl_space = response.drag_delta() * response.ctx.pixels_per_point();
-            info!("Dragged: {:?} pixels ", drag_in_gl_space);
+        let touching = self.touch_gestures(ui, rect);
+        // Boxes are drawn with the secondary button, or the primary one in the tool mode
+        let box_button = if self.zoom_box.active {
+            Some(PointerButton::Primary)
+        } else {
+            Some(PointerButton::Secondary).filter(|button| *button != self.controls.pan_button)
+        };
+        if self.region.selecting {
+            self.region.interact(&response, rect, &self.state);
+        } else if let Some(selection) = self.zoom_box.interact(&response, box_button) {
+            self.zoom_to_selection(selection, rect, ui.ctx().pixels_per_point());
+        } else if !touching
+            && !self.zoom_box.is_dragging()
+            && response.dragged_by(self.controls.pan_button)
+            && response.drag_delta().length_sq() > 0.0
+        {
+            self.transition = None;
+            let pointer = response.interact_pointer_pos();
+            if let Some(pointer) = pointer.filter(|_| ui.input(|i| i.modifiers.shift_only())) {
+                // Turn the view with the angle swept by the pointer around the center
+                let before = pointer - response.drag_delta() - rect.center();
+                let after = pointer - rect.center();
+                let angle = after.y.atan2(after.x) - before.y.atan2(before.x);
+                self.state.rotation = wrap_angle(self.state.rotation + angle);
+            } else {
+                let change = Position::drag(
+                    response.ctx.pixels_per_point(),
+                    self.state.zoom,
+                    response.drag_delta(),
+                )
+                .rotated(self.state.rotation);
+                info!("Dragged: {:?} points", response.drag_delta());
+                self.state.center_position = self.state.center_position + change;
+            }
+        }
 
-            self.state.center_position.x += drag_in_gl_space.x / self.state.zoom;
-            self.state.center_position.y -= drag_in_gl_space.y / self.state.zoom;
+        // Keyboard navigation, once the view has the focus from Tab or a click
+        if response.clicked() {
+            response.request_focus();
+        }
+        let arrows = arrow_keys(ui, &response);
+        if arrows != egui::Vec2::ZERO {
+            self.pan(arrows);
+        }
+        if self.measure.active && ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.measure.clear();
         }
+        response.widget_info(|| {
+            let [re, im] = self.state.point_at([0.0, 0.0]);
+            let magnification = self.state.magnification(self.viewport_px.y.max(1.0));
+            egui::WidgetInfo::labeled(
+                egui::WidgetType::Other,
+                true,
+                tr_args(
+                    "fractal-view",
+                    &[
+                        ("re", &re),
+                        ("im", &im),
+                        ("magnification", &format_magnification(magnification)),
+                    ],
+                ),
+            )
+        });
+
+        // The other ways to zoom stop at the precision of the center too
+        let deepest = perturbation::deepest_zoom(self.state.point_at([0.0, 0.0]));
+        self.state.zoom = self.state.zoom.min(deepest);
 
         // Clone locals so we can move them into the paint callback:
-        let data = self.state;
+        let data = self.rendered_state();
+        if data.iteration_coloring == IterationColoring::Histogram {
+            if let Some(histogram) = self.histogram.update(&data, self.viewport_px) {
+                self.fractal.lock().set_histogram(histogram);
+            }
+        }
+        let quality = self.quality;
+        let interacting = response.dragged()
+            || scroll_delta != egui::Vec2::ZERO
+            || ui.input(|i| i.multi_touch().is_some());
+        let refinement = self
+            .progressive
+            .observe(ui.ctx(), &data, self.is_animated(), interacting);
         let fractal = self.fractal.clone();
+        let ctx = ui.ctx().clone();
 
         let callback = egui_glow::CallbackFn::new(move |info, painter| {
-            fractal.lock().paint(painter.gl(), data, info)
+            let mut fractal = fractal.lock();
+            fractal.paint(painter.gl(), data, quality, refinement, info);
+            if fractal.is_refining() {
+                // The next frames average more samples into the view
+                ctx.request_repaint();
+            }
         });
+        // The region and the lens are only sampled in the shader, they would resize the
+        // framebuffer of the view
+        let quality = Quality {
+            supersampling: 1,
+            ..quality
+        };
+
+        // A collapsed canvas has nothing to show
+        if rect.is_positive() && self.cpu_renderer.is_active() {
+            let palette = data
+                .use_palette
+                .then(|| self.palettes[self.selected_palette].clone());
+            let kernel = self.plugins.kernel(&data);
+            self.cpu_renderer.paint(ui, rect, &data, palette, kernel);
+        } else if rect.is_positive() {
+            let callback = egui::PaintCallback {
+                rect,
+                callback: Arc::new(callback),
+            };
+            ui.painter().add(callback);
+
+            // Second draw of the whole view at the highest quality, clipped to the region
+            if let Some(region) =
+                self.region
+                    .screen_rect(rect, ui.ctx().pixels_per_point(), &self.state)
+            {
+                let mut data = data;
+                data.high_quality = true;
+                data.supersampling = region::SUPERSAMPLING;
+                let fractal = self.fractal.clone();
+                let callback = egui_glow::CallbackFn::new(move |info, painter| {
+                    fractal
+                        .lock()
+                        .paint(painter.gl(), data, quality, None, info)
+                });
+                ui.painter()
+                    .with_clip_rect(region)
+                    .add(egui::PaintCallback {
+                        rect,
+                        callback: Arc::new(callback),
+                    });
+                self.region.paint(ui.painter(), region);
+            }
+
+            // The lens is a third draw in its own viewport around the pointer
+            if let (true, Some(pointer), Some(offset)) = (
+                self.lens.enabled,
+                response.hover_pos(),
+                self.pointer_offset_px,
+            ) {
+                let lens_rect =
+                    egui::Rect::from_center_size(pointer, egui::Vec2::splat(lens::DIAMETER));
+                let lens_state = self.lens.state(&data, data.point_at(offset));
+                let fractal = self.fractal.clone();
+                let callback = egui_glow::CallbackFn::new(move |info, painter| {
+                    fractal
+                        .lock()
+                        .paint(painter.gl(), lens_state, quality, None, info)
+                });
+                ui.painter()
+                    .with_clip_rect(lens_rect)
+                    .add(egui::PaintCallback {
+                        rect: lens_rect,
+                        callback: Arc::new(callback),
+                    });
+                self.lens.paint(ui.painter(), pointer);
+            }
+
+            // The minimap is a last draw of the whole fractal in a corner
+            if self.minimap.enabled {
+                let pixels_per_point = ui.ctx().pixels_per_point();
+                let minimap_rect = Minimap::rect(rect);
+                let overview = Minimap::state(&data, minimap_rect, pixels_per_point);
+                let minimap = ui.interact(
+                    minimap_rect,
+                    ui.id().with("Minimap"),
+                    egui::Sense::click_and_drag(),
+                );
+                if let Some([re, im]) =
+                    Minimap::interact(&minimap, minimap_rect, &overview, pixels_per_point)
+                {
+                    let center = Position { x: -re, y: -im };
+                    if minimap.clicked() {
+                        self.fly_to(center, self.state.zoom, NAVIGATION_DURATION);
+                    } else {
+                        self.transition = None;
+                        self.state.center_position = center;
+                    }
+                }
+                let fractal = self.fractal.clone();
+                let callback = egui_glow::CallbackFn::new(move |info, painter| {
+                    fractal
+                        .lock()
+                        .paint(painter.gl(), overview, quality, None, info)
+                });
+                ui.painter()
+                    .with_clip_rect(minimap_rect)
+                    .add(egui::PaintCallback {
+                        rect: minimap_rect,
+                        callback: Arc::new(callback),
+                    });
+                Minimap::paint(
+                    ui.painter(),
+                    minimap_rect,
+                    &overview,
+                    &self.state,
+                    [self.viewport_px.x, self.viewport_px.y],
+                    pixels_per_point,
+                );
+            }
+        }
 
-        let callback = egui::PaintCallback {
+        let setup_error = self.fractal.lock().setup_error().map(str::to_string);
+        if let Some(error) = setup_error {
+            // Above the image of the CPU renderer
+            ui.painter().text(
+                rect.center_top() + egui::vec2(0.0, 8.0),
+                egui::Align2::CENTER_TOP,
+                tr_args("renderer-unavailable", &[("error", &error)]),
+                egui::FontId::proportional(13.0),
+                ui.visuals().strong_text_color(),
+            );
+        }
+        if self.show_scale_bar {
+            scale_bar::paint(
+                &mut ui.painter(),
+                rect,
+                ui.ctx().pixels_per_point(),
+                self.state.zoom,
+            );
+        }
+        if self.show_crosshair {
+            coordinates::paint_crosshair(ui.painter(), rect);
+        }
+        if let Some(offset) = self.pointer_offset_px.filter(|_| self.show_probe) {
+            let pixels_per_point = ui.ctx().pixels_per_point();
+            probe::paint(ui.painter(), rect, pixels_per_point, &self.state, offset);
+        }
+        self.zoom_box.paint(ui.painter());
+        if self.show_hud {
+            hud::paint(
+                ui.painter(),
+                rect,
+                &self.state,
+                self.viewport_px.y.max(1.0),
+                self.pointer_offset_px,
+            );
+        }
+        if self.frame_rate.show_overlay {
+            let gpu_time = self.fractal.lock().gpu_time();
+            self.frame_rate.paint_overlay(ui.painter(), rect, gpu_time);
+        }
+        if self.state.fractal_type == FractalType::Mandelbrot {
+            self.external_rays
+                .paint(ui.painter(), rect, ui.ctx().pixels_per_point(), &self.state);
+            if self.show_explainer {
+                explainer::paint(ui.painter(), rect, ui.ctx().pixels_per_point(), &self.state);
+            }
+        }
+        self.annotations.paint(
+            &mut ui.painter(),
             rect,
-            callback: Arc::new(callback),
-        };
-        ui.painter().add(callback);
+            ui.ctx().pixels_per_point(),
+            &self.state,
+        );
+        if self.measure.active {
+            let pointer = self.pointer_offset_px.map(|offset| {
+                let [re, im] = self.state.point_at(offset);
+                Complex::new(re, im)
+            });
+            self.measure.paint(
+                ui.painter(),
+                rect,
+                ui.ctx().pixels_per_point(),
+                &self.state,
+                pointer,
+            );
+        }
     }
 }
@@ -0,0 +1,91 @@
+use super::complex::Complex;
+
+/// Longest period looked for.
+const MAX_PERIOD: u32 = 10_000;
+const NEWTON_STEPS: usize = 64;
+const TOLERANCE: f64 = 1e-15;
+/// Smallest radius looked at, relative to the point: below it the digits of `f64` can't
+/// tell the minibrots apart.
+const PRECISION: f64 = 1e-12;
+
+/// Center of a minibrot: the orbit of 0 comes back to 0 after `period` iterations.
+#[derive(Clone, Copy, Debug)]
+pub struct Nucleus {
+    pub c: Complex,
+    pub period: u32,
+    /// Approximate radius of the minibrot.
+    pub size: f64,
+}
+
+/// Period of the most prominent minibrot near `c`: the iteration where the orbit of 0
+/// comes closer to 0 than ever before, among the ones smaller than `radius`.
+fn nearby_period(c: Complex, radius: f64) -> Option<u32> {
+    let mut z = Complex::ZERO;
+    let mut closest = f64::INFINITY;
+    let mut period = None;
+    for n in 1..=MAX_PERIOD {
+        z = z * z + c;
+        let distance = z.norm();
+        if distance > 2.0 {
+            break;
+        }
+        if distance < closest {
+            closest = distance;
+            if distance < radius {
+                period = Some(n);
+            }
+        }
+    }
+    period
+}
+
+/// Solve `z_period(c) = 0` with Newton's method, `None` if it doesn't converge.
+fn newton(mut c: Complex, period: u32) -> Option<Complex> {
+    for _ in 0..NEWTON_STEPS {
+        let (mut z, mut dz) = (Complex::ZERO, Complex::ZERO);
+        for _ in 0..period {
+            dz = z * dz * 2.0 + Complex::ONE;
+            z = z * z + c;
+        }
+        let step = z / dz;
+        if !step.is_finite() {
+            return None;
+        }
+        c = c - step;
+        if step.norm() <= TOLERANCE * c.norm().max(1.0) {
+            return Some(c);
+        }
+    }
+    None
+}
+
+/// Size estimate of the minibrot of nucleus `c`, from the derivatives along its cycle.
+fn size(c: Complex, period: u32) -> f64 {
+    let mut z = Complex::ZERO;
+    let mut l = Complex::ONE;
+    let mut b = Complex::ONE;
+    for _ in 1..period {
+        z = z * z + c;
+        l = z * l * 2.0;
+        b = b + Complex::ONE / l;
+    }
+    1.0 / (b * l * l).norm()
+}
+
+/// Whether the minibrots within `radius` of `c` can be located: the computations are done
+/// with `f64`, deeper views are out of reach.
+pub fn within_precision(c: Complex, radius: f64) -> bool {
+    radius > PRECISION * c.norm().max(1.0)
+}
+
+/// Minibrot close to `c`, `radius` is the distance around `c` worth looking at.
+pub fn find(c: Complex, radius: f64) -> Option<Nucleus> {
+    let period = nearby_period(c, radius)?;
+    let nucleus = newton(c, period)?;
+    let size = size(nucleus, period);
+    size.is_finite().then_some(Nucleus {
+        c: nucleus,
+        period,
+        size,
+    })
+}
@@ -0,0 +1,100 @@
+use std::ops::RangeInclusive;
+
+use eframe::egui::{ComboBox, Context, DragValue, Id, Key, Ui};
+use log::error;
+
+use super::{
+    i18n::tr,
+    parameter::{step_factor, Parameter},
+    settings, State,
+};
+
+/// Name of the setting holding the step.
+const STEP_SETTING: &str = "nudge-step";
+/// Range of the step, in percents of the range of the slider.
+const STEP_RANGE: RangeInclusive<f32> = 0.01..=10.0;
+
+/// Where the controls of a parameter leave it when they get the focus or are dragged.
+fn selected_id() -> Id {
+    Id::new("nudged parameter")
+}
+
+/// Make `parameter` the one the brackets change, once its controls are used.
+pub fn select(ctx: &Context, parameter: Parameter) {
+    ctx.data_mut(|d| d.insert_temp(selected_id(), parameter));
+}
+
+/// Changes of a parameter with the bracket keys: `]` increases it and `[` decreases it by
+/// a step, 10 times finer with Shift and 10 times coarser with Ctrl. Fine tuning is easier
+/// than with the sliders.
+pub struct Nudge {
+    /// Parameter changed, the last one whose controls were used unless picked in the list.
+    parameter: Parameter,
+    /// Step, in percents of the range of the slider.
+    step: f32,
+}
+
+impl Nudge {
+    /// Step saved from the previous runs.
+    pub fn load() -> Self {
+        Self {
+            parameter: Parameter::Contrast,
+            step: settings::load(STEP_SETTING)
+                .and_then(|value| value.parse().ok())
+                .filter(|step| STEP_RANGE.contains(step))
+                .unwrap_or(1.0),
+        }
+    }
+
+    /// Apply the presses of the brackets to `state`. Call when no text field has the focus.
+    pub fn update(&mut self, ctx: &Context, state: &mut State) {
+        let selected = ctx.data_mut(|d| {
+            let parameter = d.get_temp::<Parameter>(selected_id());
+            d.remove::<Parameter>(selected_id());
+            parameter
+        });
+        if let Some(parameter) = selected {
+            self.parameter = parameter;
+        }
+        // The presses are counted whatever the modifiers, Shift gives braces on most layouts
+        // but egui then falls back to the physical bracket keys
+        let (steps, factor) = ctx.input(|i| {
+            let up = i.num_presses(Key::CloseBracket) as f32;
+            let down = i.num_presses(Key::OpenBracket) as f32;
+            (up - down, step_factor(i.modifiers))
+        });
+        if steps != 0.0 {
+            let value = self.parameter.value(state);
+            let shifted = self
+                .parameter
+                .shift(value, steps * self.step / 100.0 * factor);
+            self.parameter.set(state, shifted);
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.label(tr("nudge-hint"));
+        ComboBox::from_label(tr("nudge-parameter"))
+            .selected_text(self.parameter.label())
+            .show_ui(ui, |ui| {
+                for parameter in Parameter::ALL {
+                    ui.selectable_value(&mut self.parameter, parameter, parameter.label());
+                }
+            });
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                DragValue::new(&mut self.step)
+                    .range(STEP_RANGE)
+                    .speed(0.05)
+                    .suffix(" %"),
+            );
+            ui.label(tr("nudge-step"));
+            // Saved once the value is released, not on every frame of the drag
+            if response.drag_stopped() || (response.changed() && !response.dragged()) {
+                if let Err(e) = settings::save(STEP_SETTING, &self.step.to_string()) {
+                    error!("Cannot save the {} setting: {:?}", STEP_SETTING, e);
+                }
+            }
+        });
+    }
+}
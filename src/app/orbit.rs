@@ -0,0 +1,59 @@
+use std::iter;
+
+use super::{FractalType, State};
+
+/// Endless orbit of `point`, from its first value of z. `None` for the fractals which are
+/// not computed on the CPU.
+pub fn orbit(state: &State, [x, y]: [f64; 2]) -> Option<impl Iterator<Item = [f64; 2]>> {
+    let (z, c) = match state.fractal_type {
+        FractalType::Julia => ([x, y], [state.c_julia.x as f64, state.c_julia.y as f64]),
+        FractalType::Mandelbrot | FractalType::BurningShip | FractalType::Tricorn => {
+            ([0.0, 0.0], [x, y])
+        }
+        // The custom function only exists in the shader, and the other ones do not escape
+        FractalType::Custom | FractalType::Newton | FractalType::Lyapunov | FractalType::Nova => {
+            return None
+        }
+    };
+    let (fractal_type, exponent) = (state.fractal_type, state.exponent as f64);
+    Some(iter::successors(Some(z), move |&z| {
+        let z = match fractal_type {
+            FractalType::BurningShip => [z[0].abs(), z[1].abs()],
+            FractalType::Tricorn => [z[0], -z[1]],
+            _ => z,
+        };
+        let [x, y] = power(z, exponent);
+        Some([x + c[0], y + c[1]])
+    }))
+}
+
+/// Iteration at which the orbit of `point` escapes, and the smooth iteration count. `None`
+/// inside the set.
+pub fn escape(state: &State, point: [f64; 2]) -> Option<(u32, f64)> {
+    let exponent = state.exponent as f64;
+    (0..state.iterations())
+        .zip(orbit(state, point)?)
+        .find_map(|(n, z)| {
+            let modulus2 = z[0] * z[0] + z[1] * z[1];
+            (modulus2 > 256.0).then(|| (n, n as f64 - (modulus2.ln() / 2.0).ln() / exponent.ln()))
+        })
+}
+
+/// Smooth iteration count at `point`, `None` inside the set.
+pub fn smooth_iterations(state: &State, point: [f64; 2]) -> Option<f64> {
+    escape(state, point).map(|(_, smooth)| smooth)
+}
+
+/// `z` to the power `exponent`, by the polar form unless squaring, like the shader.
+fn power(z: [f64; 2], exponent: f64) -> [f64; 2] {
+    if exponent == 2.0 {
+        return [z[0] * z[0] - z[1] * z[1], 2.0 * z[0] * z[1]];
+    }
+    let modulus2 = z[0] * z[0] + z[1] * z[1];
+    if modulus2 == 0.0 {
+        return [0.0, 0.0];
+    }
+    let modulus = modulus2.powf(exponent / 2.0);
+    let angle = exponent * z[1].atan2(z[0]);
+    [modulus * angle.cos(), modulus * angle.sin()]
+}
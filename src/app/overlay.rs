@@ -0,0 +1,287 @@
+use eframe::egui::{
+    epaint::{text::Fonts, FontImage},
+    Align2, Color32, FontDefinitions, FontId, Painter, Pos2, Rect, Stroke, Vec2,
+};
+
+/// Largest side of the font atlas of the exports, glyphs of huge exports are large.
+const MAX_ATLAS_SIDE: usize = 8192;
+/// Coverage gamma of the glyphs, as egui draws them.
+const TEXT_GAMMA: f32 = 0.55;
+/// Smallest text drawn, in pixels of the image, as on the small levels of a Deep Zoom.
+const MIN_TEXT_PIXELS: f32 = 4.0;
+
+/// Where the annotations and the scale bar are drawn: the canvas on the screen, or an
+/// exported image. Positions and sizes are in points of the screen.
+pub trait Canvas {
+    fn circle(&mut self, center: Pos2, radius: f32, fill: Color32, stroke: Stroke);
+    fn line_segment(&mut self, points: [Pos2; 2], stroke: Stroke);
+    fn text(&mut self, pos: Pos2, anchor: Align2, text: &str, font: FontId, color: Color32);
+}
+
+impl Canvas for &Painter {
+    fn circle(&mut self, center: Pos2, radius: f32, fill: Color32, stroke: Stroke) {
+        Painter::circle(self, center, radius, fill, stroke);
+    }
+
+    fn line_segment(&mut self, points: [Pos2; 2], stroke: Stroke) {
+        Painter::line_segment(self, points, stroke);
+    }
+
+    fn text(&mut self, pos: Pos2, anchor: Align2, text: &str, font: FontId, color: Color32) {
+        Painter::text(self, pos, anchor, text, font, color);
+    }
+}
+
+/// Shape of an [Overlay], in pixels of the image.
+enum Shape {
+    Circle {
+        center: Pos2,
+        radius: f32,
+        fill: Color32,
+        stroke: Stroke,
+    },
+    Segment {
+        points: [Pos2; 2],
+        stroke: Stroke,
+    },
+    /// Glyph of the font atlas, `size` texels from `atlas` copied `size` pixels from `min`.
+    Glyph {
+        min: [i64; 2],
+        atlas: [usize; 2],
+        size: [usize; 2],
+        color: Color32,
+    },
+}
+
+/// [Canvas] of an image at `pixels_per_point` pixels per point of the screen, turned into
+/// an [Overlay] once drawn.
+pub struct ImageCanvas {
+    pixels_per_point: f32,
+    fonts: Fonts,
+    shapes: Vec<Shape>,
+}
+
+impl ImageCanvas {
+    pub fn new(pixels_per_point: f32) -> Self {
+        let fonts = Fonts::new(pixels_per_point, MAX_ATLAS_SIDE, FontDefinitions::default());
+        Self {
+            pixels_per_point,
+            fonts,
+            shapes: Vec::new(),
+        }
+    }
+
+    pub fn finish(self) -> Overlay {
+        Overlay {
+            atlas: self.fonts.image(),
+            shapes: self.shapes,
+        }
+    }
+}
+
+impl Canvas for ImageCanvas {
+    fn circle(&mut self, center: Pos2, radius: f32, fill: Color32, stroke: Stroke) {
+        self.shapes.push(Shape::Circle {
+            center: center * self.pixels_per_point,
+            radius: radius * self.pixels_per_point,
+            fill,
+            stroke: Stroke::new(stroke.width * self.pixels_per_point, stroke.color),
+        });
+    }
+
+    fn line_segment(&mut self, points: [Pos2; 2], stroke: Stroke) {
+        self.shapes.push(Shape::Segment {
+            points: points.map(|point| point * self.pixels_per_point),
+            stroke: Stroke::new(stroke.width * self.pixels_per_point, stroke.color),
+        });
+    }
+
+    fn text(&mut self, pos: Pos2, anchor: Align2, text: &str, font: FontId, color: Color32) {
+        if font.size * self.pixels_per_point < MIN_TEXT_PIXELS {
+            return;
+        }
+        let galley = self.fonts.layout_no_wrap(text.to_owned(), font, color);
+        let origin = anchor.anchor_size(pos, galley.size()).min;
+        for glyph in galley.rows.iter().flat_map(|row| &row.glyphs) {
+            let uv = glyph.uv_rect;
+            if uv.is_nothing() {
+                continue;
+            }
+            let min = ((origin + glyph.pos.to_vec2() + uv.offset) * self.pixels_per_point).round();
+            self.shapes.push(Shape::Glyph {
+                min: [min.x as i64, min.y as i64],
+                atlas: uv.min.map(usize::from),
+                size: [0, 1].map(|axis| usize::from(uv.max[axis] - uv.min[axis])),
+                color,
+            });
+        }
+    }
+}
+
+/// Annotations and scale bar of an exported image, drawn over its tiles as they are rendered.
+pub struct Overlay {
+    atlas: FontImage,
+    shapes: Vec<Shape>,
+}
+
+/// Pixels of a tile of an image: `size` pixels from `origin` in the image, RGBA with 16 bits
+/// per channel in the native byte order if `deep`.
+pub struct Tile<'a> {
+    pub pixels: &'a mut [u8],
+    pub origin: [usize; 2],
+    pub size: [usize; 2],
+    pub deep: bool,
+}
+
+impl Tile<'_> {
+    /// Blend `color` at `coverage` over the pixel `[x, y]` of the image, if in the tile.
+    fn blend(&mut self, [x, y]: [i64; 2], color: Color32, coverage: f32) {
+        let [left, top] = self.origin.map(|side| side as i64);
+        let (column, row) = (x - left, y - top);
+        if coverage <= 0.0
+            || !(0..self.size[0] as i64).contains(&column)
+            || !(0..self.size[1] as i64).contains(&row)
+        {
+            return;
+        }
+        let alpha = coverage.min(1.0) * color.a() as f32 / 255.0;
+        let index = row as usize * self.size[0] + column as usize;
+        let source = [color.r(), color.g(), color.b(), 255];
+        if self.deep {
+            let pixel = &mut self.pixels[index * 8..][..8];
+            for (sample, source) in pixel.chunks_exact_mut(2).zip(source) {
+                let value = u16::from_ne_bytes([sample[0], sample[1]]) as f32;
+                let blended = value + (source as f32 * 257.0 - value) * alpha;
+                sample.copy_from_slice(&(blended.round() as u16).to_ne_bytes());
+            }
+        } else {
+            let pixel = &mut self.pixels[index * 4..][..4];
+            for (channel, source) in pixel.iter_mut().zip(source) {
+                let value = *channel as f32;
+                *channel = (value + (source as f32 - value) * alpha).round() as u8;
+            }
+        }
+    }
+
+    /// Pixels of the image in the tile around `rect`.
+    fn pixels_around(&self, rect: Rect) -> impl Iterator<Item = [i64; 2]> {
+        let [left, top] = self.origin.map(|side| side as i64);
+        let (columns, rows) = (
+            (rect.left().floor() as i64).max(left)
+                ..(rect.right().ceil() as i64 + 1).min(left + self.size[0] as i64),
+            (rect.top().floor() as i64).max(top)
+                ..(rect.bottom().ceil() as i64 + 1).min(top + self.size[1] as i64),
+        );
+        rows.flat_map(move |y| columns.clone().map(move |x| [x, y]))
+    }
+}
+
+/// Distance of `point` to the segment `[a, b]`.
+fn segment_distance(point: Pos2, [a, b]: [Pos2; 2]) -> f32 {
+    let along = b - a;
+    let t = ((point - a).dot(along) / along.length_sq().max(f32::EPSILON)).clamp(0.0, 1.0);
+    point.distance(a + t * along)
+}
+
+impl Overlay {
+    /// Draw the shapes over `tile`, antialiased.
+    pub fn draw(&self, tile: &mut Tile) {
+        for shape in &self.shapes {
+            match *shape {
+                Shape::Circle {
+                    center,
+                    radius,
+                    fill,
+                    stroke,
+                } => {
+                    let outer = radius + stroke.width / 2.0;
+                    let rect = Rect::from_center_size(center, Vec2::splat(2.0 * outer));
+                    for [x, y] in tile.pixels_around(rect).collect::<Vec<_>>() {
+                        let distance = Pos2::new(x as f32 + 0.5, y as f32 + 0.5).distance(center);
+                        tile.blend([x, y], fill, radius - distance + 0.5);
+                        let from_edge = (distance - radius).abs();
+                        tile.blend([x, y], stroke.color, stroke.width / 2.0 - from_edge + 0.5);
+                    }
+                }
+                Shape::Segment { points, stroke } => {
+                    let rect = Rect::from_two_pos(points[0], points[1]).expand(stroke.width);
+                    for [x, y] in tile.pixels_around(rect).collect::<Vec<_>>() {
+                        let center = Pos2::new(x as f32 + 0.5, y as f32 + 0.5);
+                        let distance = segment_distance(center, points);
+                        tile.blend([x, y], stroke.color, stroke.width / 2.0 - distance + 0.5);
+                    }
+                }
+                Shape::Glyph {
+                    min,
+                    atlas,
+                    size,
+                    color,
+                } => {
+                    for row in 0..size[1] {
+                        for column in 0..size[0] {
+                            let texel = (atlas[1] + row) * self.atlas.size[0] + atlas[0] + column;
+                            let coverage = self.atlas.pixels[texel].powf(TEXT_GAMMA);
+                            let pixel = [min[0] + column as i64, min[1] + row as i64];
+                            tile.blend(pixel, color, coverage);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shapes_at_the_image_resolution() {
+        // Twice as many pixels as points
+        let mut canvas = ImageCanvas::new(2.0);
+        canvas.circle(Pos2::new(4.0, 4.0), 2.0, Color32::RED, Stroke::NONE);
+        let overlay = canvas.finish();
+
+        // The right half of an image of 16×16 pixels
+        let mut pixels = vec![0; 8 * 16 * 4];
+        let mut tile = Tile {
+            pixels: &mut pixels,
+            origin: [8, 0],
+            size: [8, 16],
+            deep: false,
+        };
+        overlay.draw(&mut tile);
+        let pixel = |x: usize, y: usize| &pixels[(y * 8 + x) * 4..][..4];
+        // The circle spans the pixels 4 to 12 of the image
+        assert_eq!(pixel(0, 8), [255, 0, 0, 255]);
+        assert_eq!(pixel(2, 8), [255, 0, 0, 255]);
+        assert_eq!(pixel(5, 8), [0, 0, 0, 0]);
+        assert_eq!(pixel(0, 1), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn text() {
+        let mut canvas = ImageCanvas::new(4.0);
+        canvas.text(
+            Pos2::ZERO,
+            Align2::LEFT_TOP,
+            "M",
+            FontId::proportional(10.0),
+            Color32::WHITE,
+        );
+        let overlay = canvas.finish();
+        let mut pixels = vec![0; 64 * 64 * 8];
+        overlay.draw(&mut Tile {
+            pixels: &mut pixels,
+            origin: [0, 0],
+            size: [64, 64],
+            deep: true,
+        });
+        // Glyph of about 30 pixels at 4 pixels per point
+        let covered = pixels
+            .chunks_exact(8)
+            .filter(|pixel| pixel[..2] == u16::MAX.to_ne_bytes())
+            .count();
+        assert!((100..1500).contains(&covered), "{covered} pixels");
+    }
+}
@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+
+/// Number of texels in the palette texture sent to the fragment shader.
+pub const PALETTE_SIZE: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ColorStop {
+    /// Location of the stop in the gradient, in `[0, 1)`.
+    pub position: f32,
+    /// sRGB as displayed, every channel in `[0, 1]`.
+    pub color: [f32; 3],
+}
+
+/// Color space the colors between two stops are interpolated in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Interpolation {
+    /// Straight lines between the sRGB values, the midpoints of distant hues are muddy.
+    Rgb,
+    /// Perceptually uniform, the lightness and the saturation change evenly.
+    Oklab,
+}
+
+/// Cyclic color gradient: after the last stop, colors blend back into the first one.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Palette {
+    pub name: String,
+    /// Sorted by position.
+    pub stops: Vec<ColorStop>,
+    pub interpolation: Interpolation,
+}
+
+impl Palette {
+    pub fn new(name: impl Into<String>, mut stops: Vec<ColorStop>) -> Self {
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        Self {
+            name: name.into(),
+            stops,
+            interpolation: Interpolation::Oklab,
+        }
+    }
+
+    pub fn grayscale() -> Self {
+        Self::new(
+            "Grayscale",
+            vec![
+                ColorStop {
+                    position: 0.0,
+                    color: [0.0, 0.0, 0.0],
+                },
+                ColorStop {
+                    position: 0.5,
+                    color: [1.0, 1.0, 1.0],
+                },
+            ],
+        )
+    }
+
+    /// Palette of `stops`, given as positions and `0xRRGGBB` colors.
+    fn from_hex(name: &str, stops: &[(f32, u32)]) -> Self {
+        let stops = stops
+            .iter()
+            .map(|&(position, hex)| ColorStop {
+                position,
+                color: [16, 8, 0].map(|shift| ((hex >> shift) & 0xFF) as f32 / 255.0),
+            })
+            .collect();
+        Self::new(name, stops)
+    }
+
+    /// Built-in palettes, offered before any is imported.
+    pub fn presets() -> Vec<Self> {
+        vec![
+            Self::grayscale(),
+            Self::from_hex(
+                "Classic",
+                &[
+                    (0.0, 0x000764),
+                    (0.16, 0x206BCB),
+                    (0.42, 0xEDFFFF),
+                    (0.6425, 0xFFAA00),
+                    (0.8575, 0x000200),
+                ],
+            ),
+            Self::from_hex(
+                "Fire",
+                &[
+                    (0.0, 0x000000),
+                    (0.25, 0x8B0000),
+                    (0.5, 0xFF4500),
+                    (0.75, 0xFFD700),
+                    (0.9, 0xFFFFE0),
+                ],
+            ),
+            Self::from_hex(
+                "Ocean",
+                &[
+                    (0.0, 0x001022),
+                    (0.3, 0x004E7C),
+                    (0.55, 0x2EA8C7),
+                    (0.75, 0xB8F1F0),
+                    (0.9, 0x0B6A88),
+                ],
+            ),
+        ]
+    }
+
+    /// Color at `t`, only the fractional part of `t` is used.
+    pub fn sample(&self, t: f32) -> [f32; 3] {
+        let (Some(first), Some(last)) = (self.stops.first(), self.stops.last()) else {
+            return [0.0; 3];
+        };
+        let t = t.rem_euclid(1.0);
+
+        // Find the two stops around t, wrapping around the end of the gradient
+        let (from, to, to_position) = match self.stops.iter().position(|s| s.position > t) {
+            Some(0) => (last, first, first.position + 1.0),
+            Some(i) => (&self.stops[i - 1], &self.stops[i], self.stops[i].position),
+            None => (last, first, first.position + 1.0),
+        };
+        let from_position = if from.position > t {
+            from.position - 1.0
+        } else {
+            from.position
+        };
+
+        let span = to_position - from_position;
+        let ratio = if span > 0.0 {
+            (t - from_position) / span
+        } else {
+            0.0
+        };
+        match self.interpolation {
+            Interpolation::Rgb => lerp(from.color, to.color, ratio),
+            Interpolation::Oklab => oklab_to_srgb(lerp(
+                srgb_to_oklab(from.color),
+                srgb_to_oklab(to.color),
+                ratio,
+            )),
+        }
+    }
+
+    /// Texels of the palette texture, as RGBA8.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        (0..PALETTE_SIZE)
+            .flat_map(|i| {
+                let [r, g, b] = self.sample(i as f32 / PALETTE_SIZE as f32);
+                [to_u8(r), to_u8(g), to_u8(b), 255]
+            })
+            .collect()
+    }
+}
+
+fn lerp(from: [f32; 3], to: [f32; 3], ratio: f32) -> [f32; 3] {
+    std::array::from_fn(|c| from[c] + (to[c] - from[c]) * ratio)
+}
+
+fn srgb_to_linear(channel: f32) -> f32 {
+    if channel <= 0.04045 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(channel: f32) -> f32 {
+    if channel <= 0.0031308 {
+        channel * 12.92
+    } else {
+        1.055 * channel.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Matrices from https://bottosson.github.io/posts/oklab/, in f64 for their precision.
+fn srgb_to_oklab(color: [f32; 3]) -> [f32; 3] {
+    let [r, g, b] = color.map(|channel| srgb_to_linear(channel) as f64);
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    ]
+    .map(|component| component as f32)
+}
+
+fn oklab_to_srgb(color: [f32; 3]) -> [f32; 3] {
+    let [lightness, a, b] = color.map(|component| component as f64);
+    let l = (lightness + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m = (lightness - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s = (lightness - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+    [
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+    ]
+    .map(|channel| linear_to_srgb((channel as f32).clamp(0.0, 1.0)))
+}
+
+fn to_u8(channel: f32) -> u8 {
+    (channel.clamp(0.0, 1.0) * 255.0).round() as u8
+}
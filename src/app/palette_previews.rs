@@ -0,0 +1,90 @@
+use eframe::egui::{load::SizedTexture, vec2, Context, ImageButton, ScrollArea, Spinner, Ui, Vec2};
+
+use super::{
+    recent::same_view,
+    thumbnails::{Thumbnails, THUMBNAIL_SIZE},
+    FractalGl, Palette, State,
+};
+
+/// Renders of the current view with each palette, to compare them before picking one.
+pub struct PalettePreviews {
+    /// View and number of palettes the previews were rendered for.
+    rendered: Option<(State, usize)>,
+    /// Thumbnail of each palette, in the order of the palettes.
+    thumbnails: Vec<u64>,
+}
+
+impl PalettePreviews {
+    pub fn new() -> Self {
+        Self {
+            rendered: None,
+            thumbnails: Vec::new(),
+        }
+    }
+
+    /// A palette changed in place, render the previews again.
+    pub fn invalidate(&mut self) {
+        self.rendered = None;
+    }
+
+    /// Render the previews again when the view or the palettes changed, but not while the
+    /// view is being dragged.
+    pub fn update(
+        &mut self,
+        ctx: &Context,
+        fractal: &mut FractalGl,
+        thumbnails: &mut Thumbnails,
+        state: &State,
+        viewport_px: Vec2,
+        palettes: &[Palette],
+    ) {
+        let up_to_date = matches!(
+            &self.rendered,
+            Some((view, count)) if *count == palettes.len() && same_view(view, state)
+        );
+        if up_to_date || ctx.input(|i| i.pointer.any_down()) {
+            return;
+        }
+        for id in self.thumbnails.drain(..) {
+            thumbnails.forget(id);
+        }
+        let mut state = *state;
+        state.use_palette = true;
+        state.custom_coloring = false;
+        self.thumbnails = palettes
+            .iter()
+            .map(|palette| thumbnails.request(ctx, fractal, state, viewport_px, Some(palette)))
+            .collect();
+        self.rendered = Some((state, palettes.len()));
+    }
+
+    /// Strip of the previews. Returns the index of the palette clicked, if any.
+    pub fn ui(
+        &self,
+        ui: &mut Ui,
+        thumbnails: &Thumbnails,
+        palettes: &[Palette],
+        selected: usize,
+    ) -> Option<usize> {
+        // Half the size of the thumbnails, to fit several of them in the panel
+        let size = vec2(THUMBNAIL_SIZE[0] as f32, THUMBNAIL_SIZE[1] as f32) / 2.0;
+        let mut clicked = None;
+        ScrollArea::horizontal().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                for (i, (id, palette)) in self.thumbnails.iter().zip(palettes).enumerate() {
+                    let response = match thumbnails.get(*id) {
+                        Some(texture) => ui.add(
+                            ImageButton::new(SizedTexture::new(texture.id(), size))
+                                .selected(i == selected),
+                        ),
+                        None => ui.add_sized(size, Spinner::new()),
+                    };
+                    if response.on_hover_text(&palette.name).clicked() {
+                        clicked = Some(i);
+                    }
+                }
+            });
+        });
+        clicked
+    }
+}
@@ -0,0 +1,53 @@
+use eframe::{
+    egui::{pos2, Color32, Rect, Response, Sense, Ui, Widget},
+    epaint::Vec2,
+};
+
+use super::Palette;
+
+/// Horizontal preview of a [Palette].
+pub struct PaletteStrip<'a> {
+    palette: &'a Palette,
+    height: f32,
+    sense: Sense,
+}
+
+impl<'a> PaletteStrip<'a> {
+    pub fn new(palette: &'a Palette) -> PaletteStrip<'a> {
+        PaletteStrip {
+            palette,
+            height: 16.0,
+            sense: Sense::hover(),
+        }
+    }
+
+    /// Interactions the strip responds to, only the hover by default.
+    pub fn sense(mut self, sense: Sense) -> Self {
+        self.sense = sense;
+        self
+    }
+}
+
+impl Widget for PaletteStrip<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let (rect, response) =
+            ui.allocate_exact_size(Vec2::new(ui.available_width(), self.height), self.sense);
+
+        let steps = (rect.width() as usize).clamp(1, 128);
+        let step_width = rect.width() / steps as f32;
+        for i in 0..steps {
+            let [r, g, b] = self.palette.sample(i as f32 / steps as f32);
+            let left = rect.left() + i as f32 * step_width;
+            ui.painter().rect_filled(
+                Rect::from_min_max(
+                    pos2(left, rect.top()),
+                    // Overlap the next step slightly to avoid gaps from rounding
+                    pos2(left + step_width + 0.5, rect.bottom()),
+                ),
+                0.0,
+                Color32::from_rgb((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8),
+            );
+        }
+        response
+    }
+}
@@ -0,0 +1,351 @@
+use std::ops::RangeInclusive;
+
+use eframe::egui::{DragValue, Modifiers, Response, Slider, SliderClamping, Ui, Vec2};
+use serde::{Deserialize, Serialize};
+
+/// Scroll distance of one mouse wheel notch, in points.
+const WHEEL_NOTCH: f32 = 50.0;
+/// Fraction of the slider range changed by one mouse wheel notch.
+const WHEEL_STEP: f32 = 0.01;
+
+use super::{i18n::tr, nudge, state, State};
+
+/// The slider-backed fields of [State], and the values derived from them, so they can be
+/// handled generically.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Parameter {
+    Zoom,
+    /// Rotation of the view, in degrees.
+    Rotation,
+    /// Iteration budget, a whole number.
+    MaxIterations,
+    JuliaX,
+    JuliaY,
+    /// Modulus of the Julia constant.
+    JuliaRadius,
+    /// Argument of the Julia constant, in degrees.
+    JuliaAngle,
+    Contrast,
+    Brightness,
+    Gamma,
+    Red,
+    Green,
+    Blue,
+    StripeDensity,
+    StripeMix,
+    TiaBlend,
+    CurvatureMix,
+    DecompositionMix,
+    TrapScale,
+    TextureBlend,
+    Exponent,
+    NovaRelaxation,
+    CyclingSpeed,
+}
+
+impl Parameter {
+    pub const ALL: [Parameter; 23] = [
+        Parameter::Zoom,
+        Parameter::Rotation,
+        Parameter::MaxIterations,
+        Parameter::JuliaX,
+        Parameter::JuliaY,
+        Parameter::JuliaRadius,
+        Parameter::JuliaAngle,
+        Parameter::Contrast,
+        Parameter::Brightness,
+        Parameter::Gamma,
+        Parameter::Red,
+        Parameter::Green,
+        Parameter::Blue,
+        Parameter::StripeDensity,
+        Parameter::StripeMix,
+        Parameter::TiaBlend,
+        Parameter::CurvatureMix,
+        Parameter::DecompositionMix,
+        Parameter::TrapScale,
+        Parameter::TextureBlend,
+        Parameter::Exponent,
+        Parameter::NovaRelaxation,
+        Parameter::CyclingSpeed,
+    ];
+
+    pub fn label(self) -> String {
+        tr(match self {
+            Parameter::Zoom => "zoom",
+            Parameter::Rotation => "rotation",
+            Parameter::MaxIterations => "max-iterations",
+            Parameter::JuliaX => "julia-1",
+            Parameter::JuliaY => "julia-2",
+            Parameter::JuliaRadius => "modulus",
+            Parameter::JuliaAngle => "argument",
+            Parameter::Contrast => "contrast",
+            Parameter::Brightness => "brightness",
+            Parameter::Gamma => "gamma",
+            Parameter::Red => "red",
+            Parameter::Green => "green",
+            Parameter::Blue => "blue",
+            Parameter::StripeDensity => "stripe-density",
+            Parameter::StripeMix => "stripe-mix",
+            Parameter::TiaBlend => "tia-blend",
+            Parameter::CurvatureMix => "curvature-mix",
+            Parameter::DecompositionMix => "decomposition-mix",
+            Parameter::TrapScale => "trap-scale",
+            Parameter::TextureBlend => "texture-blend",
+            Parameter::Exponent => "exponent",
+            Parameter::NovaRelaxation => "nova-relaxation",
+            Parameter::CyclingSpeed => "cycling-speed",
+        })
+    }
+
+    /// What the parameter does to the computation, for the tooltips.
+    pub fn help(self) -> String {
+        tr(match self {
+            Parameter::Zoom => "help-zoom",
+            Parameter::Rotation => "rotation-hint",
+            Parameter::MaxIterations => "help-max-iterations",
+            Parameter::JuliaX => "help-julia-x",
+            Parameter::JuliaY => "help-julia-y",
+            Parameter::JuliaRadius => "help-julia-radius",
+            Parameter::JuliaAngle => "help-julia-angle",
+            Parameter::Contrast => "help-contrast",
+            Parameter::Brightness => "help-brightness",
+            Parameter::Gamma => "help-gamma",
+            Parameter::Red | Parameter::Green | Parameter::Blue => "help-channel",
+            Parameter::StripeDensity => "help-stripe-density",
+            Parameter::StripeMix => "help-stripe-mix",
+            Parameter::TiaBlend => "help-tia-blend",
+            Parameter::CurvatureMix => "help-curvature-mix",
+            Parameter::DecompositionMix => "help-decomposition-mix",
+            Parameter::TrapScale => "help-trap-scale",
+            Parameter::TextureBlend => "help-texture-blend",
+            Parameter::Exponent => "help-exponent",
+            Parameter::NovaRelaxation => "help-nova-relaxation",
+            Parameter::CyclingSpeed => "help-cycling-speed",
+        })
+    }
+
+    /// Range of the slider, values outside of it can still be reached by dragging.
+    pub fn range(self) -> RangeInclusive<f32> {
+        match self {
+            Parameter::Zoom => 1.0..=5000.0,
+            Parameter::Rotation => -180.0..=180.0,
+            Parameter::MaxIterations => 16.0..=state::MAX_ITERATIONS as f32,
+            Parameter::JuliaX | Parameter::JuliaY => -1.0..=1.0,
+            Parameter::JuliaRadius => 0.0..=1.5,
+            Parameter::JuliaAngle => -180.0..=180.0,
+            Parameter::Contrast => -1.0..=1.0,
+            Parameter::Brightness => -2.0..=2.0,
+            Parameter::Gamma => 0.1..=3.0,
+            Parameter::Red | Parameter::Green | Parameter::Blue => 0.0..=1.0,
+            Parameter::StripeDensity => 1.0..=20.0,
+            Parameter::StripeMix
+            | Parameter::TiaBlend
+            | Parameter::CurvatureMix
+            | Parameter::DecompositionMix
+            | Parameter::TextureBlend => 0.0..=1.0,
+            Parameter::TrapScale => 0.05..=10.0,
+            Parameter::Exponent => 2.0..=8.0,
+            Parameter::NovaRelaxation => 0.1..=2.0,
+            Parameter::CyclingSpeed => -1.0..=1.0,
+        }
+    }
+
+    /// Unit shown after the value of the field, the zoom is shown as the magnification.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Parameter::Zoom => "×",
+            Parameter::Rotation | Parameter::JuliaAngle => "°",
+            _ => "",
+        }
+    }
+
+    pub fn logarithmic(self) -> bool {
+        matches!(
+            self,
+            Parameter::Zoom | Parameter::MaxIterations | Parameter::TrapScale
+        )
+    }
+
+    /// Move `value` by `fraction` of the slider range, the way the slider would.
+    pub fn shift(self, value: f32, fraction: f32) -> f32 {
+        let range = self.range();
+        let (start, end) = (*range.start(), *range.end());
+        if self.logarithmic() {
+            value * (end / start).powf(fraction)
+        } else {
+            value + (end - start) * fraction
+        }
+    }
+
+    pub fn value(self, state: &State) -> f32 {
+        match self {
+            Parameter::JuliaRadius => state.c_julia.x.hypot(state.c_julia.y),
+            Parameter::JuliaAngle => state.c_julia.y.atan2(state.c_julia.x).to_degrees(),
+            Parameter::Zoom => state.zoom,
+            Parameter::Rotation => state.rotation.to_degrees(),
+            Parameter::MaxIterations => state.max_iterations as f32,
+            Parameter::JuliaX => state.c_julia.x,
+            Parameter::JuliaY => state.c_julia.y,
+            Parameter::Contrast => state.contrast,
+            Parameter::Brightness => state.brightness,
+            Parameter::Gamma => state.gamma,
+            Parameter::Red => state.r,
+            Parameter::Green => state.g,
+            Parameter::Blue => state.b,
+            Parameter::StripeDensity => state.stripe_density,
+            Parameter::StripeMix => state.stripe_mix,
+            Parameter::TiaBlend => state.tia_blend,
+            Parameter::CurvatureMix => state.curvature_mix,
+            Parameter::DecompositionMix => state.decomposition_mix,
+            Parameter::TrapScale => state.trap_scale,
+            Parameter::TextureBlend => state.texture_blend,
+            Parameter::Exponent => state.exponent,
+            Parameter::NovaRelaxation => state.nova_relaxation,
+            Parameter::CyclingSpeed => state.cycling_speed,
+        }
+    }
+
+    pub fn set(self, state: &mut State, value: f32) {
+        match self {
+            Parameter::Rotation => state.rotation = value.to_radians(),
+            Parameter::MaxIterations => {
+                state.max_iterations =
+                    value.round().clamp(16.0, state::MAX_ITERATIONS as f32) as u32
+            }
+            Parameter::JuliaRadius => {
+                let angle = state.c_julia.y.atan2(state.c_julia.x);
+                state.c_julia.x = value * angle.cos();
+                state.c_julia.y = value * angle.sin();
+            }
+            Parameter::JuliaAngle => {
+                let radius = state.c_julia.x.hypot(state.c_julia.y);
+                state.c_julia.x = radius * value.to_radians().cos();
+                state.c_julia.y = radius * value.to_radians().sin();
+            }
+            _ => *self.field_mut(state) = value,
+        }
+    }
+
+    /// The field of [State] of the parameters stored as is.
+    fn field_mut(self, state: &mut State) -> &mut f32 {
+        match self {
+            Parameter::Zoom => &mut state.zoom,
+            Parameter::JuliaX => &mut state.c_julia.x,
+            Parameter::JuliaY => &mut state.c_julia.y,
+            Parameter::Contrast => &mut state.contrast,
+            Parameter::Brightness => &mut state.brightness,
+            Parameter::Gamma => &mut state.gamma,
+            Parameter::Red => &mut state.r,
+            Parameter::Green => &mut state.g,
+            Parameter::Blue => &mut state.b,
+            Parameter::StripeDensity => &mut state.stripe_density,
+            Parameter::StripeMix => &mut state.stripe_mix,
+            Parameter::TiaBlend => &mut state.tia_blend,
+            Parameter::CurvatureMix => &mut state.curvature_mix,
+            Parameter::DecompositionMix => &mut state.decomposition_mix,
+            Parameter::TrapScale => &mut state.trap_scale,
+            Parameter::TextureBlend => &mut state.texture_blend,
+            Parameter::Exponent => &mut state.exponent,
+            Parameter::NovaRelaxation => &mut state.nova_relaxation,
+            Parameter::CyclingSpeed => &mut state.cycling_speed,
+            Parameter::Rotation
+            | Parameter::MaxIterations
+            | Parameter::JuliaRadius
+            | Parameter::JuliaAngle => {
+                unreachable!("{self:?} is derived from the fields")
+            }
+        }
+    }
+
+    /// Slider for rough changes, next to a [DragValue] to read and type the exact value. The
+    /// zoom is typed as the magnification of a canvas `viewport_height_px` pixels high, see
+    /// [State::magnification].
+    ///
+    /// The mouse wheel also changes the value when hovering it. While dragging or scrolling,
+    /// Shift makes the changes 10 times finer and Ctrl 10 times coarser.
+    pub fn add_to(self, ui: &mut Ui, state: &mut State, viewport_height_px: f32) -> Response {
+        let factor = step_factor(ui.input(|i| i.modifiers));
+        ui.horizontal(|ui| {
+            let before = self.value(state);
+            let mut value = before;
+            let value = &mut value;
+            let range = self.range();
+            let (start, end) = (*range.start(), *range.end());
+            let speed = if self.logarithmic() {
+                value.abs() * 0.01
+            } else {
+                (end - start) / 300.0
+            };
+
+            let slider = ui.add(
+                Slider::new(value, range)
+                    .logarithmic(self.logarithmic())
+                    .clamping(SliderClamping::Never)
+                    .show_value(false),
+            );
+            // The slider maps the pointer to an absolute value, scaling the steps means
+            // replacing it with a relative change.
+            if slider.dragged() && factor != 1.0 {
+                let ratio = slider.drag_delta().x / slider.rect.width().max(1.0) * factor;
+                *value = self.shift(before, ratio);
+            }
+
+            // Shown value per unit of the parameter
+            let scale = match self {
+                Parameter::Zoom => 4.0 / viewport_height_px.max(1.0) as f64,
+                _ => 1.0,
+            };
+            // DragValue already slows down with Shift by itself
+            let drag_value = ui.add(
+                DragValue::from_get_set(|shown| {
+                    if let Some(shown) = shown {
+                        *value = (shown / scale) as f32;
+                    }
+                    *value as f64 * scale
+                })
+                .speed(speed as f64 * scale * factor.max(1.0) as f64)
+                // All the digits of the f32, the shortest text reading back the same value
+                .custom_formatter(|shown, _| format!("{}", shown as f32))
+                .custom_parser(|text| text.trim().trim_end_matches(self.suffix()).parse().ok())
+                .suffix(self.suffix()),
+            );
+            // Both controls are named by the label for the screen readers
+            let label = ui.label(self.label()).on_hover_text(self.help());
+            let response = slider.labelled_by(label.id) | drag_value.labelled_by(label.id);
+            if response.has_focus() || response.dragged() {
+                nudge::select(ui.ctx(), self);
+            }
+
+            if response.hovered() {
+                // Take the scroll so that the settings panel doesn't scroll as well
+                let scroll = ui.input_mut(|i| {
+                    let scroll = i.raw_scroll_delta.y;
+                    i.raw_scroll_delta = Vec2::ZERO;
+                    i.smooth_scroll_delta = Vec2::ZERO;
+                    scroll
+                });
+                if scroll != 0.0 {
+                    *value = self.shift(*value, scroll / WHEEL_NOTCH * WHEEL_STEP * factor);
+                }
+            }
+            if *value != before {
+                self.set(state, *value);
+            }
+            response
+        })
+        .inner
+    }
+}
+
+/// Multiplier applied to the steps of the parameters: Shift for fine changes, Ctrl (Cmd on
+/// macOS) for coarse ones.
+pub fn step_factor(modifiers: Modifiers) -> f32 {
+    if modifiers.shift {
+        0.1
+    } else if modifiers.command {
+        10.0
+    } else {
+        1.0
+    }
+}
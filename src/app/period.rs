@@ -0,0 +1,54 @@
+/// Longest cycle looked for, keep in sync with the fragment shader.
+pub const MAX_PERIOD: u32 = 64;
+/// Iterations done before looking for a cycle, so that the orbit reaches it.
+const SETTLE_ITERATIONS: u32 = 20_000;
+const TOLERANCE: f64 = 1e-9;
+
+/// Period of the attracting cycle of `z → z² + c`, which is the period of the hyperbolic
+/// component of the Mandelbrot set containing `c`.
+///
+/// `None` when the orbit of 0 escapes, or when it doesn't settle on a cycle of at most
+/// [MAX_PERIOD] points, which happens outside of the hyperbolic components and close to
+/// their boundaries.
+pub fn period([cx, cy]: [f64; 2]) -> Option<u32> {
+    let step = |[x, y]: [f64; 2]| [x * x - y * y + cx, 2.0 * x * y + cy];
+
+    let mut z = [0.0, 0.0];
+    for _ in 0..SETTLE_ITERATIONS {
+        z = step(z);
+        if z[0] * z[0] + z[1] * z[1] > 4.0 {
+            return None;
+        }
+    }
+
+    let start = z;
+    for period in 1..=MAX_PERIOD {
+        z = step(z);
+        if (z[0] - start[0]).hypot(z[1] - start[1]) < TOLERANCE {
+            return Some(period);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hyperbolic_components() {
+        // Main cardioid, period 2 disk, the rabbit and the airplane
+        assert_eq!(period([0.0, 0.0]), Some(1));
+        assert_eq!(period([-1.0, 0.0]), Some(2));
+        assert_eq!(period([-0.1226, 0.7449]), Some(3));
+        assert_eq!(period([-1.7549, 0.0]), Some(3));
+    }
+
+    #[test]
+    fn no_period_outside() {
+        assert_eq!(period([1.0, 0.0]), None);
+        assert_eq!(period([-2.5, 0.0]), None);
+        // Root of the period 2 disk, on the boundary where the orbit settles too slowly
+        assert_eq!(period([-0.75, 0.0]), None);
+    }
+}
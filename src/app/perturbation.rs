@@ -0,0 +1,71 @@
+use super::{state::FractalType, State};
+
+/// Zoom, in pixels per unit, above which the Mandelbrot and Julia sets are rendered by
+/// perturbation: the single precision coordinates of the shader are too coarse beyond it.
+pub const DEEP_ZOOM: f32 = 1e5;
+/// Width of the texture holding the reference orbit, the points go in rows of this size.
+pub const ORBIT_TEXTURE_WIDTH: usize = 4096;
+/// Squared modulus past which the reference orbit escaped, the cutoff of the high quality.
+const ESCAPE: f64 = 16.0;
+/// Doubles a pixel spans at the least, closer pixels would share the same coordinates.
+const PIXEL_DOUBLES: f64 = 4.0;
+
+/// Deepest zoom, in pixels per unit, of a view centered on `center`. The center is a
+/// double, the pixels cannot be closer than its precision: about 1e15 around the unit disk,
+/// far from the largest zoom of the f32 [State::zoom].
+pub fn deepest_zoom(center: [f64; 2]) -> f32 {
+    let magnitude = center[0].abs().max(center[1].abs()).max(1.0);
+    (1.0 / (magnitude * f64::EPSILON * PIXEL_DOUBLES)) as f32
+}
+
+/// Whether `state` is rendered by perturbation around [reference_orbit], only written for
+/// z² + c.
+pub fn is_deep(state: &State) -> bool {
+    state.zoom > DEEP_ZOOM
+        && state.exponent == 2.0
+        && matches!(
+            state.fractal_type,
+            FractalType::Mandelbrot | FractalType::Julia
+        )
+}
+
+/// What the reference orbit of a view depends on, to compute it again only when it changes.
+#[derive(Clone, Copy, PartialEq)]
+pub struct OrbitKey {
+    fractal_type: FractalType,
+    center: [f64; 2],
+    c_julia: [f32; 2],
+    iterations: u32,
+}
+
+impl OrbitKey {
+    pub fn new(state: &State) -> Self {
+        Self {
+            fractal_type: state.fractal_type,
+            center: [-state.center_position.x, -state.center_position.y],
+            c_julia: [state.c_julia.x, state.c_julia.y],
+            iterations: state.iterations(),
+        }
+    }
+}
+
+/// Orbit of the center of the view, in double precision, until it escapes or runs out of
+/// iterations. The shader iterates the difference of each pixel to it in single precision,
+/// small enough to keep the details.
+///
+/// The Mandelbrot orbit starts from 0 with c at the center, the Julia orbit from the center.
+pub fn reference_orbit(key: &OrbitKey) -> Vec<[f32; 2]> {
+    let (mut z, c) = match key.fractal_type {
+        FractalType::Julia => (key.center, key.c_julia.map(f64::from)),
+        _ => ([0.0, 0.0], key.center),
+    };
+    let mut orbit = Vec::with_capacity(key.iterations as usize + 1);
+    for _ in 0..=key.iterations {
+        orbit.push([z[0] as f32, z[1] as f32]);
+        if z[0] * z[0] + z[1] * z[1] > ESCAPE {
+            break;
+        }
+        z = [z[0] * z[0] - z[1] * z[1] + c[0], 2.0 * z[0] * z[1] + c[1]];
+    }
+    orbit
+}
@@ -0,0 +1,76 @@
+use eframe::egui::Ui;
+
+use super::{accessibility::icon_button, i18n::tr, parameter::Parameter, State};
+
+/// Parameters pinned to the toolbar above the canvas, in the order they were pinned.
+pub struct Pinned {
+    parameters: Vec<Parameter>,
+}
+
+impl Pinned {
+    pub fn new() -> Self {
+        Self {
+            parameters: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parameters.is_empty()
+    }
+
+    pub fn parameters(&self) -> &[Parameter] {
+        &self.parameters
+    }
+
+    pub fn set_parameters(&mut self, parameters: Vec<Parameter>) {
+        self.parameters = parameters;
+    }
+
+    /// Slider of `parameter` for the settings panel, with a button to (un)pin it. The canvas
+    /// is `viewport_height_px` pixels high.
+    pub fn slider_ui(
+        &mut self,
+        ui: &mut Ui,
+        state: &mut State,
+        viewport_height_px: f32,
+        parameter: Parameter,
+    ) {
+        ui.horizontal(|ui| {
+            parameter.add_to(ui, state, viewport_height_px);
+
+            let pinned = self.parameters.contains(&parameter);
+            let hint = if pinned {
+                tr("unpin-from-the-toolbar")
+            } else {
+                tr("pin-to-the-toolbar")
+            };
+            if icon_button(ui.selectable_label(pinned, "📌"), hint).clicked() {
+                if pinned {
+                    self.parameters.retain(|p| *p != parameter);
+                } else {
+                    self.parameters.push(parameter);
+                }
+            }
+        });
+    }
+
+    pub fn toolbar_ui(&mut self, ui: &mut Ui, state: &mut State, viewport_height_px: f32) {
+        ui.horizontal_wrapped(|ui| {
+            let mut unpinned = None;
+            for parameter in &self.parameters {
+                parameter
+                    .add_to(ui, state, viewport_height_px)
+                    .context_menu(|ui| {
+                        if ui.button(tr("unpin")).clicked() {
+                            unpinned = Some(*parameter);
+                            ui.close_menu();
+                        }
+                    });
+                ui.separator();
+            }
+            if let Some(parameter) = unpinned {
+                self.parameters.retain(|p| *p != parameter);
+            }
+        });
+    }
+}
@@ -0,0 +1,459 @@
+use std::{fmt::Write as _, fs, path::Path, sync::Arc};
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use eframe::egui::{Slider, Ui};
+use log::{error, info};
+use wasmi::{Config, Engine, Instance, Linker, Module, Store, TypedFunc};
+
+use super::{fractal_gl::FRACTAL_PARAMETERS, settings, state::PluginId, State};
+
+/// Directory of the plugins, in the configuration directory.
+const DIRECTORY: &str = "plugins";
+/// Longest text read from the memory of a plugin, in bytes.
+const MAX_TEXT: usize = 1 << 20;
+/// Instructions a call of a CPU kernel may run, so that an endless loop doesn't hang the app.
+const KERNEL_FUEL: u64 = 1_000_000;
+/// Prefix of the names of the parameters in the GLSL of the plugins, so that they cannot
+/// replace the identifiers of the shader.
+const GLSL_PREFIX: &str = "param_";
+
+/// Parameter of a plugin, shown as a slider and given to its GLSL as `param_<name>`.
+pub struct ParameterDescriptor {
+    pub name: String,
+    pub default: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+/// Fractal type defined by a WebAssembly module of the plugins directory, for the custom
+/// fractal type. The module imports nothing and exports:
+///
+/// - `memory`, and the functions `metadata() -> i32` and `glsl() -> i32` giving the address
+///   of a NUL-terminated UTF-8 text in it;
+/// - optionally, the CPU kernel `iterate(zx: f64, zy: f64, cx: f64, cy: f64) -> (f64, f64)`
+///   and `set_parameter(index: i32, value: f64)`, called with the values of the parameters
+///   before the kernel runs.
+///
+/// The metadata has a line per field, the GLSL is the `iterate(z, c)` function of the custom
+/// fractal type, both iterate from z = 0 with c the point.
+///
+/// ```text
+/// name: Multibrot
+/// description: z^power + c
+/// parameter: power 3 2 8
+/// ```
+pub struct Plugin {
+    /// Name of the file without its extension, which the views refer to.
+    pub id: PluginId,
+    pub name: String,
+    pub description: String,
+    pub parameters: Vec<ParameterDescriptor>,
+    source: String,
+    kernel: Option<Kernel>,
+}
+
+/// Whether `name` can follow [GLSL_PREFIX] as an identifier of GLSL, which reserves the
+/// names containing two underscores in a row.
+fn is_parameter_name(name: &str) -> bool {
+    name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && !name.contains("__")
+}
+
+/// Text at `address` of the memory of `instance`, up to its NUL.
+fn read_text(store: &Store<()>, instance: &Instance, address: i32) -> Result<String> {
+    let memory = instance
+        .get_memory(store, "memory")
+        .context("No exported memory")?;
+    let bytes = memory
+        .data(store)
+        .get(address as u32 as usize..)
+        .context("Text out of the memory")?;
+    let length = bytes
+        .iter()
+        .take(MAX_TEXT)
+        .position(|&byte| byte == 0)
+        .context("Text without its NUL or too long")?;
+    Ok(std::str::from_utf8(&bytes[..length])?.to_string())
+}
+
+/// Result of the function `name` of `instance`, which takes nothing.
+fn call(store: &mut Store<()>, instance: &Instance, name: &str) -> Result<i32> {
+    let function = instance
+        .get_typed_func::<(), i32>(&*store, name)
+        .with_context(|| format!("No function {name}() -> i32"))?;
+    Ok(function.call(store, ())?)
+}
+
+impl Plugin {
+    /// Fields of the text `metadata`, for the plugin of the file `id`.
+    fn parse(id: PluginId, metadata: &str, source: String) -> Result<Plugin> {
+        let mut plugin = Plugin {
+            id,
+            name: id.to_string(),
+            description: String::new(),
+            parameters: Vec::new(),
+            source,
+            kernel: None,
+        };
+        for line in metadata.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "name" => plugin.name = value.to_string(),
+                "description" => plugin.description = value.to_string(),
+                "parameter" => {
+                    let [name, default, min, max] =
+                        value.split_whitespace().collect::<Vec<_>>()[..]
+                    else {
+                        bail!(
+                            "Expected `parameter: name default min max`, got `{}`",
+                            value
+                        );
+                    };
+                    ensure!(is_parameter_name(name), "Invalid parameter name {}", name);
+                    let [default, min, max] = [default, min, max].map(str::parse::<f32>);
+                    plugin.parameters.push(ParameterDescriptor {
+                        name: name.to_string(),
+                        default: default?,
+                        min: min?,
+                        max: max?,
+                    });
+                }
+                _ => {}
+            }
+        }
+        ensure!(
+            plugin.parameters.len() <= FRACTAL_PARAMETERS,
+            "More than {} parameters",
+            FRACTAL_PARAMETERS
+        );
+        Ok(plugin)
+    }
+
+    /// Plugin of the WebAssembly module `wasm`, of the file `id`.
+    fn from_wasm(id: PluginId, wasm: &[u8]) -> Result<Plugin> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let module = Module::new(&Engine::new(&config), wasm)?;
+        let (mut store, instance) = instantiate(&module)?;
+        let address = call(&mut store, &instance, "metadata")?;
+        let metadata = read_text(&store, &instance, address).context("Invalid metadata")?;
+        let address = call(&mut store, &instance, "glsl")?;
+        let source = read_text(&store, &instance, address).context("Invalid GLSL")?;
+        let mut plugin = Plugin::parse(id, &metadata, source)?;
+        if instance.get_export(&store, "iterate").is_some() {
+            Kernel::functions(&store, &instance)?;
+            plugin.kernel = Some(Kernel {
+                module: Arc::new(module),
+                parameters: plugin.parameters.len(),
+            });
+        }
+        Ok(plugin)
+    }
+
+    /// The function for the shader, with the parameters defined as their uniform.
+    pub fn glsl(&self) -> String {
+        let mut glsl = String::new();
+        for (i, parameter) in self.parameters.iter().enumerate() {
+            let _ = writeln!(
+                glsl,
+                "#define {GLSL_PREFIX}{} u_fractalParameters[{}]",
+                parameter.name, i
+            );
+        }
+        glsl.push_str(&self.source);
+        glsl.push('\n');
+        // The names must not leak into the rest of the shader
+        for parameter in &self.parameters {
+            let _ = writeln!(glsl, "#undef {GLSL_PREFIX}{}", parameter.name);
+        }
+        glsl
+    }
+
+    /// Kernel drawing the plugin on the CPU, when the module exports `iterate`.
+    pub fn kernel(&self) -> Option<&Kernel> {
+        self.kernel.as_ref()
+    }
+
+    /// Values of the parameters when the plugin is picked.
+    pub fn defaults(&self) -> [f32; FRACTAL_PARAMETERS] {
+        let mut values = [0.0; FRACTAL_PARAMETERS];
+        for (value, parameter) in values.iter_mut().zip(&self.parameters) {
+            *value = parameter.default;
+        }
+        values
+    }
+}
+
+fn instantiate(module: &Module) -> Result<(Store<()>, Instance)> {
+    let mut store = Store::new(module.engine(), ());
+    store.set_fuel(KERNEL_FUEL).map_err(|e| anyhow!("{e}"))?;
+    let instance = Linker::new(module.engine())
+        .instantiate(&mut store, module)?
+        .start(&mut store)?;
+    Ok((store, instance))
+}
+
+/// CPU kernel of a plugin, instanced by each thread drawing with it.
+#[derive(Clone)]
+pub struct Kernel {
+    module: Arc<Module>,
+    /// Number of parameters of the plugin.
+    parameters: usize,
+}
+
+type IterateFunc = TypedFunc<(f64, f64, f64, f64), (f64, f64)>;
+type SetParameterFunc = TypedFunc<(i32, f64), ()>;
+
+impl Kernel {
+    /// The `iterate` and `set_parameter` functions of `instance`.
+    fn functions(
+        store: &Store<()>,
+        instance: &Instance,
+    ) -> Result<(IterateFunc, Option<SetParameterFunc>)> {
+        let iterate = instance
+            .get_typed_func(store, "iterate")
+            .context("The kernel is not iterate(f64, f64, f64, f64) -> (f64, f64)")?;
+        let set_parameter = match instance.get_export(store, "set_parameter") {
+            Some(_) => Some(
+                instance
+                    .get_typed_func(store, "set_parameter")
+                    .context("The parameters are not set_parameter(i32, f64)")?,
+            ),
+            None => None,
+        };
+        Ok((iterate, set_parameter))
+    }
+
+    /// Kernel ready to iterate, with the `values` of the parameters.
+    pub fn instance(&self, values: &[f32; FRACTAL_PARAMETERS]) -> Result<KernelInstance> {
+        let (mut store, instance) = instantiate(&self.module)?;
+        let (iterate, set_parameter) = Self::functions(&store, &instance)?;
+        if let Some(set_parameter) = set_parameter {
+            for (index, value) in values[..self.parameters].iter().enumerate() {
+                set_parameter.call(&mut store, (index as i32, *value as f64))?;
+            }
+        }
+        Ok(KernelInstance { store, iterate })
+    }
+}
+
+/// Instance of a [Kernel], on a single thread.
+pub struct KernelInstance {
+    store: Store<()>,
+    iterate: IterateFunc,
+}
+
+impl KernelInstance {
+    /// Next value of `z` for the point `c`.
+    pub fn iterate(&mut self, z: [f64; 2], c: [f64; 2]) -> Result<[f64; 2]> {
+        self.store
+            .set_fuel(KERNEL_FUEL)
+            .map_err(|e| anyhow!("{e}"))?;
+        let (x, y) = self
+            .iterate
+            .call(&mut self.store, (z[0], z[1], c[0], c[1]))?;
+        Ok([x, y])
+    }
+}
+
+fn load_plugin(path: &Path) -> Result<Plugin> {
+    let wasm = fs::read(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let id = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .parse()
+        .with_context(|| format!("Invalid name {}", path.display()))?;
+    Plugin::from_wasm(id, &wasm).with_context(|| format!("Invalid {}", path.display()))
+}
+
+/// Fractal types of the plugins directory.
+pub struct Plugins {
+    plugins: Vec<Plugin>,
+}
+
+impl Plugins {
+    pub fn new() -> Self {
+        Self {
+            plugins: Vec::new(),
+        }
+    }
+
+    /// Plugins of the plugins directory, in the order of their names.
+    pub fn load() -> Self {
+        let mut plugins = Self::new();
+        let Some(directory) = settings::path(DIRECTORY) else {
+            return plugins;
+        };
+        let Ok(entries) = fs::read_dir(&directory) else {
+            return plugins;
+        };
+        for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "wasm")
+            {
+                match load_plugin(&path) {
+                    Ok(plugin) => plugins.plugins.push(plugin),
+                    Err(e) => error!("Cannot load the plugin: {:?}", e),
+                }
+            }
+        }
+        plugins.plugins.sort_by(|a, b| a.name.cmp(&b.name));
+        info!(
+            "Loaded {} plugins from {}",
+            plugins.plugins.len(),
+            directory.display()
+        );
+        plugins
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Plugin> {
+        self.plugins.iter()
+    }
+
+    pub fn get(&self, id: PluginId) -> Option<&Plugin> {
+        self.plugins.iter().find(|plugin| plugin.id == id)
+    }
+
+    /// CPU kernel of the plugin of `state`, if any.
+    pub fn kernel(&self, state: &State) -> Option<Kernel> {
+        self.get(state.plugin?)?.kernel().cloned()
+    }
+
+    /// Description and parameters of the plugin of `state`.
+    pub fn ui(&self, ui: &mut Ui, state: &mut State) {
+        let Some(plugin) = state.plugin.and_then(|id| self.get(id)) else {
+            return;
+        };
+        if !plugin.description.is_empty() {
+            ui.label(&plugin.description);
+        }
+        for (value, parameter) in state.plugin_parameters.iter_mut().zip(&plugin.parameters) {
+            ui.add(Slider::new(value, parameter.min..=parameter.max).text(&parameter.name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{cpu_renderer, state::FractalType};
+
+    /// z^power + c, with the kernel when `kernel`.
+    fn multibrot(kernel: bool) -> Vec<u8> {
+        let kernel = if kernel {
+            r#"
+            (global $power (mut f64) (f64.const 2))
+            (func (export "set_parameter") (param i32 f64)
+                (global.set $power (local.get 1)))
+            (func (export "iterate") (param $zx f64) (param $zy f64) (param $cx f64) (param $cy f64)
+                (result f64 f64)
+                ;; Only the square, enough for the tests
+                (f64.add (f64.sub (f64.mul (local.get $zx) (local.get $zx))
+                                  (f64.mul (local.get $zy) (local.get $zy)))
+                         (local.get $cx))
+                (f64.add (f64.mul (f64.const 2) (f64.mul (local.get $zx) (local.get $zy)))
+                         (local.get $cy)))
+            "#
+        } else {
+            ""
+        };
+        wat::parse_str(format!(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const 0) "name: Multibrot\ndescription: z^power + c\nparameter: power 3 2 8\00")
+                (data (i32.const 100) "vec2 iterate(vec2 z, vec2 c) {{ return cpow(z, param_power) + c; }}\00")
+                (func (export "metadata") (result i32) (i32.const 0))
+                (func (export "glsl") (result i32) (i32.const 100))
+                {kernel})"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn metadata_and_glsl() {
+        let plugin = Plugin::from_wasm("multibrot".parse().unwrap(), &multibrot(false)).unwrap();
+        assert_eq!(plugin.name, "Multibrot");
+        assert_eq!(plugin.description, "z^power + c");
+        assert_eq!(plugin.parameters.len(), 1);
+        assert_eq!(plugin.defaults()[..2], [3.0, 0.0]);
+        assert!(plugin.kernel().is_none());
+        let glsl = plugin.glsl();
+        assert!(glsl.starts_with("#define param_power u_fractalParameters[0]\n"));
+        assert!(glsl.ends_with("#undef param_power\n"));
+    }
+
+    #[test]
+    fn kernel() {
+        let plugin = Plugin::from_wasm("multibrot".parse().unwrap(), &multibrot(true)).unwrap();
+        let mut kernel = plugin
+            .kernel()
+            .unwrap()
+            .instance(&plugin.defaults())
+            .unwrap();
+        assert_eq!(
+            kernel.iterate([1.0, 2.0], [0.5, 0.25]).unwrap(),
+            [-2.5, 4.25]
+        );
+    }
+
+    #[test]
+    fn cpu_render() {
+        let plugin = Plugin::from_wasm("multibrot".parse().unwrap(), &multibrot(true)).unwrap();
+        let mut state = State::new();
+        state.fractal_type = FractalType::Custom;
+        state.plugin = Some(plugin.id);
+        state.plugin_parameters = plugin.defaults();
+        assert_eq!(cpu_renderer::unsupported(&state, plugin.kernel()), None);
+        assert!(cpu_renderer::unsupported(&state, None).is_some());
+        let pixels = cpu_renderer::render(state, None, plugin.kernel(), 8, 6).unwrap();
+        // The square kernel draws the Mandelbrot set, the origin is inside
+        state.fractal_type = FractalType::Mandelbrot;
+        let mandelbrot = cpu_renderer::render(state, None, None, 8, 6).unwrap();
+        assert_eq!(
+            pixels[(3 * 8 + 4) * 4..][..4],
+            mandelbrot[(3 * 8 + 4) * 4..][..4]
+        );
+    }
+
+    #[test]
+    fn endless_kernel() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const 0) "name: Loop\00")
+                (func (export "metadata") (result i32) (i32.const 0))
+                (func (export "glsl") (result i32) (i32.const 0))
+                (func (export "iterate") (param f64 f64 f64 f64) (result f64 f64)
+                    (loop $forever (br $forever))
+                    (unreachable)))"#,
+        )
+        .unwrap();
+        let plugin = Plugin::from_wasm("loop".parse().unwrap(), &wasm).unwrap();
+        let mut kernel = plugin
+            .kernel()
+            .unwrap()
+            .instance(&plugin.defaults())
+            .unwrap();
+        assert!(kernel.iterate([0.0, 0.0], [0.0, 0.0]).is_err());
+    }
+
+    #[test]
+    fn invalid_parameter_names() {
+        let id = "test".parse().unwrap();
+        for name in ["z-value", "_c", "a__b", "2x"] {
+            let metadata = format!("parameter: {name} 1 0 2");
+            assert!(
+                Plugin::parse(id, &metadata, String::new()).is_err(),
+                "{name}"
+            );
+        }
+        // The names of the shader are fine, they are prefixed
+        assert!(Plugin::parse(id, "parameter: z 1 0 2", String::new()).is_ok());
+    }
+}
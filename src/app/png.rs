@@ -0,0 +1,433 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{self, File},
+    io::{BufWriter, Write as _},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, SyncSender, TrySendError},
+    thread::{self, JoinHandle},
+};
+
+use anyhow::{anyhow, bail, ensure, Context as _, Result};
+use eframe::egui::Vec2;
+
+use super::{
+    overlay::{self, Overlay},
+    tiled_export::{export_height, tile_state, TileRenderer},
+    State,
+};
+
+/// Side of the tiles, in pixels, well below the texture size limit of the GPUs.
+const TILE_SIZE: usize = 1024;
+/// Bands rendered while the encoder is busy with an earlier one, the memory of an export
+/// is a few bands whatever the size of the image.
+const BANDS_AHEAD: usize = 2;
+/// Most bytes of a band, wide images have bands of fewer rows.
+const BAND_BYTES: usize = 64 << 20;
+
+/// Tile still to render, at `[left, top]` in the image.
+struct Tile {
+    band: usize,
+    origin: [usize; 2],
+    size: [usize; 2],
+    state: State,
+}
+
+/// Part of an image: `state` drawn over `size` pixels from `[left, top]`, its zoom in pixels
+/// of the image.
+pub struct Panel {
+    pub origin: [usize; 2],
+    pub size: [usize; 2],
+    pub state: State,
+}
+
+/// Rows of the image, at most [TILE_SIZE], handed to the encoder once all their tiles arrived.
+#[derive(Default)]
+struct Band {
+    /// RGBA pixels, in native endian when 16 bits. Allocated with the first tile.
+    pixels: Vec<u8>,
+    /// Tiles not received yet.
+    missing: usize,
+    /// Whether the overlay is drawn over the complete band.
+    overlaid: bool,
+}
+
+/// Export of the view as a PNG image at a resolution independent of the window.
+///
+/// The image is rendered offscreen in tiles, a few per frame, band after band. A thread
+/// encodes the bands as they complete, so the image is never whole in memory.
+///
+/// Dropped before the end, the encoder stops and the partial image is removed.
+pub struct PngExport {
+    pub path: PathBuf,
+    width: usize,
+    height: usize,
+    /// 16 bits per channel instead of 8.
+    deep: bool,
+    /// Rows of the bands, but the last one.
+    band_height: usize,
+    bands: Vec<Band>,
+    /// Next band for the encoder.
+    next_band: usize,
+    tiles: VecDeque<Tile>,
+    /// Tiles requested from the renderer, by id.
+    pending: HashMap<u64, Tile>,
+    next_id: u64,
+    total: usize,
+    /// Annotations and scale bar drawn over the image.
+    overlay: Option<Overlay>,
+    /// `None` once the last band is sent.
+    to_encoder: Option<SyncSender<Vec<u8>>>,
+    encoder: Option<JoinHandle<Result<()>>>,
+}
+
+impl PngExport {
+    /// Export the view `state` gives on a canvas of `viewport_px` pixels, to an image
+    /// `width` pixels wide, with `overlay` drawn over it.
+    pub fn start(
+        path: PathBuf,
+        state: &State,
+        viewport_px: Vec2,
+        width: usize,
+        deep: bool,
+        overlay: Option<Overlay>,
+    ) -> Result<Self> {
+        let width = width.max(1);
+        let height = export_height(viewport_px, width);
+        let size = [width as f32, height as f32];
+        let whole = Panel {
+            origin: [0, 0],
+            size: [width, height],
+            state: tile_state(state, viewport_px, size, [0.0, 0.0, size[0], size[1]]),
+        };
+        let mut export = Self::from_panels(path, [width, height], &[whole], deep)?;
+        export.overlay = overlay;
+        Ok(export)
+    }
+
+    /// Export an image of `size` pixels gathering the views of `panels`. What they don't
+    /// cover is left transparent.
+    pub fn from_panels(
+        path: PathBuf,
+        size: [usize; 2],
+        panels: &[Panel],
+        deep: bool,
+    ) -> Result<Self> {
+        let [width, height] = size.map(|side| side.max(1));
+        ensure!(
+            u32::try_from(width).is_ok() && u32::try_from(height).is_ok(),
+            "The image is too large for PNG"
+        );
+        let row_bytes = width * Self::pixel_bytes(deep);
+        let band_height = (BAND_BYTES / row_bytes).clamp(1, TILE_SIZE);
+        // Refused now rather than once the rendering is under way: the bands rendered ahead,
+        // the one waiting for the encoder and the one it writes
+        Vec::<u8>::new()
+            .try_reserve_exact(band_height * row_bytes * (BANDS_AHEAD + 2))
+            .map_err(|_| anyhow!("Not enough memory for an image {width} pixels wide"))?;
+
+        let mut bands: Vec<Band> = (0..height.div_ceil(band_height))
+            .map(|_| Band::default())
+            .collect();
+        let mut tiles = VecDeque::new();
+        for (band, band_top) in (0..height).step_by(band_height).enumerate() {
+            for panel in panels {
+                // Clipped to the image and to the band, in pixels of the panel
+                let panel_width = panel.size[0].min(width.saturating_sub(panel.origin[0]));
+                let panel_height = panel.size[1].min(height.saturating_sub(panel.origin[1]));
+                let top = band_top.saturating_sub(panel.origin[1]);
+                let bottom = (band_top + band_height)
+                    .saturating_sub(panel.origin[1])
+                    .min(panel_height);
+                if top >= bottom {
+                    continue;
+                }
+                let panel_size = panel.size.map(|side| side as f32);
+                let panel_px = Vec2::from(panel_size);
+                for left in (0..panel_width).step_by(TILE_SIZE) {
+                    let right = (left + TILE_SIZE).min(panel_width);
+                    let rect = [left, top, right, bottom].map(|side| side as f32);
+                    tiles.push_back(Tile {
+                        band,
+                        origin: [panel.origin[0] + left, panel.origin[1] + top],
+                        size: [right - left, bottom - top],
+                        state: tile_state(&panel.state, panel_px, panel_size, rect),
+                    });
+                    bands[band].missing += 1;
+                }
+            }
+        }
+
+        let (to_encoder, received) = mpsc::sync_channel(1);
+        let encoder = {
+            let path = path.clone();
+            thread::spawn(move || encode(&path, [width, height], deep, received))
+        };
+        Ok(Self {
+            path,
+            width,
+            height,
+            deep,
+            band_height,
+            bands,
+            next_band: 0,
+            total: tiles.len(),
+            tiles,
+            pending: HashMap::new(),
+            next_id: 0,
+            overlay: None,
+            to_encoder: Some(to_encoder),
+            encoder: Some(encoder),
+        })
+    }
+
+    fn pixel_bytes(deep: bool) -> usize {
+        if deep {
+            8
+        } else {
+            4
+        }
+    }
+
+    /// Rows of the band `band`, the last one can be shorter.
+    fn band_rows(&self, band: usize) -> usize {
+        self.band_height.min(self.height - band * self.band_height)
+    }
+
+    /// Copy the tiles rendered since the last frame in their band, request the next ones and
+    /// hand the complete bands to the encoder. Returns whether the image is written.
+    pub fn update(&mut self, renderer: &mut dyn TileRenderer) -> Result<bool> {
+        for (id, pixels) in renderer.take_tiles() {
+            let Some(tile) = self.pending.remove(&id) else {
+                continue;
+            };
+            self.receive(&tile, &pixels?);
+        }
+
+        while self.pending.len() < renderer.capacity() {
+            // Not too far ahead of the encoder
+            let ahead = |tile: &Tile| tile.band >= self.next_band + BANDS_AHEAD;
+            if self.tiles.front().is_none_or(ahead) {
+                break;
+            }
+            let Some(tile) = self.tiles.pop_front() else {
+                break;
+            };
+            let size = tile.size.map(|side| side as i32);
+            renderer.request_tile(self.next_id, tile.state, size, self.deep);
+            self.pending.insert(self.next_id, tile);
+            self.next_id += 1;
+        }
+
+        self.send_bands()?;
+        if self.to_encoder.is_some() {
+            return Ok(false);
+        }
+        match self.encoder.take() {
+            Some(encoder) if !encoder.is_finished() => {
+                self.encoder = Some(encoder);
+                Ok(false)
+            }
+            Some(encoder) => join(encoder).map(|()| true),
+            None => Ok(true),
+        }
+    }
+
+    /// Copy the pixels of `tile` in its band.
+    fn receive(&mut self, tile: &Tile, pixels: &[u8]) {
+        let pixel_bytes = Self::pixel_bytes(self.deep);
+        let band_bytes = self.band_rows(tile.band) * self.width * pixel_bytes;
+        let band = &mut self.bands[tile.band];
+        if band.pixels.is_empty() {
+            band.pixels = vec![0; band_bytes];
+        }
+        let [left, top] = tile.origin;
+        let tile_row = tile.size[0] * pixel_bytes;
+        for (y, row) in pixels.chunks_exact(tile_row).enumerate() {
+            let start =
+                ((top - tile.band * self.band_height + y) * self.width + left) * pixel_bytes;
+            band.pixels[start..start + tile_row].copy_from_slice(row);
+        }
+        band.missing -= 1;
+    }
+
+    /// Send the complete bands to the encoder, in order, as long as it takes them.
+    fn send_bands(&mut self) -> Result<()> {
+        let Some(to_encoder) = &self.to_encoder else {
+            return Ok(());
+        };
+        while self
+            .bands
+            .get(self.next_band)
+            .is_some_and(|band| band.missing == 0)
+        {
+            let rows = self.band_rows(self.next_band);
+            let band_bytes = rows * self.width * Self::pixel_bytes(self.deep);
+            let band_top = self.next_band * self.band_height;
+            let band = &mut self.bands[self.next_band];
+            // Left empty when no panel covers the band
+            let mut pixels = std::mem::take(&mut band.pixels);
+            pixels.resize(band_bytes, 0);
+            if let Some(overlay) = self.overlay.as_ref().filter(|_| !band.overlaid) {
+                overlay.draw(&mut overlay::Tile {
+                    pixels: &mut pixels,
+                    origin: [0, band_top],
+                    size: [self.width, rows],
+                    deep: self.deep,
+                });
+                band.overlaid = true;
+            }
+            match to_encoder.try_send(pixels) {
+                Ok(()) => self.next_band += 1,
+                Err(TrySendError::Full(pixels)) => {
+                    // Sent on a later frame
+                    band.pixels = pixels;
+                    return Ok(());
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    // The encoder stopped, its error tells why
+                    self.to_encoder = None;
+                    return self.encoder.take().map_or(Ok(()), join);
+                }
+            }
+        }
+        if self.next_band == self.bands.len() {
+            // Closing the input ends the image
+            self.to_encoder = None;
+        }
+        Ok(())
+    }
+
+    /// Fraction of the tiles rendered.
+    pub fn progress(&self) -> f32 {
+        let left = self.tiles.len() + self.pending.len();
+        1.0 - left as f32 / self.total.max(1) as f32
+    }
+}
+
+fn join(encoder: JoinHandle<Result<()>>) -> Result<()> {
+    match encoder.join() {
+        Ok(written) => written,
+        Err(_) => bail!("The PNG encoder panicked"),
+    }
+}
+
+/// Write the image of `size` pixels at `path` from its bands of rows, removing it when the
+/// bands stop before its last row.
+fn encode(path: &Path, size: [usize; 2], deep: bool, bands: Receiver<Vec<u8>>) -> Result<()> {
+    let written = write_rows(path, size, deep, bands);
+    if written.is_err() {
+        let _ = fs::remove_file(path);
+    }
+    written.with_context(|| format!("Cannot write {}", path.display()))
+}
+
+fn write_rows(path: &Path, size: [usize; 2], deep: bool, bands: Receiver<Vec<u8>>) -> Result<()> {
+    let [width, height] = size;
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(if deep {
+        png::BitDepth::Sixteen
+    } else {
+        png::BitDepth::Eight
+    });
+    let mut writer = encoder.write_header()?.into_stream_writer()?;
+
+    let row_bytes = width * PngExport::pixel_bytes(deep);
+    let mut rows = 0;
+    for mut pixels in bands {
+        if deep {
+            // PNG samples are big endian
+            for sample in pixels.chunks_exact_mut(2) {
+                let value = u16::from_ne_bytes([sample[0], sample[1]]);
+                sample.copy_from_slice(&value.to_be_bytes());
+            }
+        }
+        writer.write_all(&pixels)?;
+        rows += pixels.len() / row_bytes;
+    }
+    // The export was dropped before the end
+    ensure!(rows == height, "Export cancelled");
+    writer.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Render the tiles of `export` in the color `color` gives for their left side, and
+    /// write the image.
+    fn render(mut export: PngExport, color: impl Fn(usize) -> Vec<u8>) {
+        while let Some(tile) = export.tiles.pop_front() {
+            let pixels = color(tile.origin[0]).repeat(tile.size[0] * tile.size[1]);
+            export.receive(&tile, &pixels);
+        }
+        while export.to_encoder.is_some() {
+            export.send_bands().unwrap();
+        }
+        join(export.encoder.take().unwrap()).unwrap();
+    }
+
+    fn panel(origin: [usize; 2], size: [usize; 2]) -> Panel {
+        Panel {
+            origin,
+            size,
+            state: State::new(),
+        }
+    }
+
+    #[test]
+    fn two_panels() {
+        let path = std::env::temp_dir().join("fractal_gl_two_panels.png");
+        // Apart, and taller than a band of the encoder
+        let size = [5, TILE_SIZE + 2];
+        let panels = [panel([0, 0], [2, size[1]]), panel([3, 1], [2, TILE_SIZE])];
+        let export = PngExport::from_panels(path.clone(), size, &panels, false).unwrap();
+        render(export, |left| {
+            if left == 0 {
+                vec![255, 0, 0, 255]
+            } else {
+                vec![0, 0, 255, 255]
+            }
+        });
+
+        let image = image::open(&path).unwrap().to_rgba8();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(image.dimensions(), [5, TILE_SIZE as u32 + 2].into());
+        assert_eq!(image.get_pixel(1, TILE_SIZE as u32 + 1).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(2, 10).0, [0, 0, 0, 0]);
+        assert_eq!(image.get_pixel(4, 0).0, [0, 0, 0, 0]);
+        assert_eq!(image.get_pixel(4, 1).0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(3, TILE_SIZE as u32).0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(3, TILE_SIZE as u32 + 1).0, [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn sixteen_bit_byte_order() {
+        let path = std::env::temp_dir().join("fractal_gl_sixteen_bit.png");
+        let export =
+            PngExport::from_panels(path.clone(), [3, 2], &[panel([0, 0], [3, 2])], true).unwrap();
+        let samples: [u16; 4] = [0x1234, 0xfedc, 0x00ff, 0xffff];
+        render(export, |_| {
+            samples
+                .iter()
+                .flat_map(|sample| sample.to_ne_bytes())
+                .collect()
+        });
+
+        let image = image::open(&path).unwrap().to_rgba16();
+        fs::remove_file(&path).unwrap();
+        assert!(image.pixels().all(|pixel| pixel.0 == samples));
+    }
+
+    #[test]
+    fn cancelled_export() {
+        let path = std::env::temp_dir().join("fractal_gl_cancelled_export.png");
+        let (bands, received) = mpsc::sync_channel(1);
+        bands.send(vec![0; 4 * 4]).unwrap();
+        drop(bands);
+        assert!(encode(&path, [4, 2], false, received).is_err());
+        assert!(!path.exists());
+    }
+}
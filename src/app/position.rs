@@ -1,12 +1,13 @@
-use eframe::epaint::Pos2;
-use std::ops::{Div, Sub};
+use eframe::epaint::{Pos2, Vec2};
+use serde::{Deserialize, Serialize};
+use std::ops::{Add, Div, Sub};
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 /// Location in the fractal space, by opposition to [Pos2] which is a location
 /// in the UI space. Provides ways to convert from [Pos2] to [Position].
 pub struct Position {
-    pub x: f32,
-    pub y: f32,
+    pub x: f64,
+    pub y: f64,
 }
 
 impl Position {
@@ -15,8 +16,50 @@ impl Position {
     /// *Note: The coordinate will still be relative to the upper-left corner of the visible fractal area.*
     pub fn from_screen_space(pixels_per_point: f32, screen_space: Pos2) -> Self {
         Self {
-            x: screen_space.x * pixels_per_point,
-            y: screen_space.y * pixels_per_point,
+            x: (screen_space.x * pixels_per_point) as f64,
+            y: (screen_space.y * pixels_per_point) as f64,
+        }
+    }
+
+    /// Change of `center_position` bringing the point under `pointer` to the center of the
+    /// canvas, both given in UI points.
+    pub fn recenter(pixels_per_point: f32, zoom: f32, canvas_center: Pos2, pointer: Pos2) -> Self {
+        let diff = (Self::from_screen_space(pixels_per_point, canvas_center)
+            - Self::from_screen_space(pixels_per_point, pointer))
+            / zoom as f64;
+        // The screen y axis points down, the fractal one up
+        Self {
+            x: diff.x,
+            y: -diff.y,
+        }
+    }
+
+    /// Change of `center_position` making the fractal follow a drag of `delta` UI points.
+    pub fn drag(pixels_per_point: f32, zoom: f32, delta: Vec2) -> Self {
+        Self {
+            x: (delta.x * pixels_per_point) as f64 / zoom as f64,
+            y: -(delta.y * pixels_per_point) as f64 / zoom as f64,
+        }
+    }
+
+    /// Change of `center_position` turned by the `rotation` of the view, in radians, for
+    /// the changes made on the screen.
+    pub fn rotated(self, rotation: f32) -> Self {
+        let (sin, cos) = (rotation as f64).sin_cos();
+        Self {
+            x: self.x * cos - self.y * sin,
+            y: self.x * sin + self.y * cos,
+        }
+    }
+}
+
+impl Add for Position {
+    type Output = Position;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::Output {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
         }
     }
 }
@@ -32,13 +75,127 @@ impl Sub for Position {
     }
 }
 
-impl Div<f32> for Position {
+impl Div<f64> for Position {
     type Output = Position;
 
-    fn div(self, rhs: f32) -> Self::Output {
+    fn div(self, rhs: f64) -> Self::Output {
         Self::Output {
             x: self.x / rhs,
             y: self.y / rhs,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::State;
+
+    const TOLERANCE: f64 = 1e-4;
+
+    fn assert_close(a: [f64; 2], b: [f64; 2], scale: f64) {
+        assert!(
+            (a[0] - b[0]).abs() <= TOLERANCE * scale && (a[1] - b[1]).abs() <= TOLERANCE * scale,
+            "{a:?} != {b:?}"
+        );
+    }
+
+    /// Offset of `pointer` from the center of the canvas, in physical pixels.
+    fn offset_px(pixels_per_point: f32, canvas_center: Pos2, pointer: Pos2) -> [f32; 2] {
+        let offset = (pointer - canvas_center) * pixels_per_point;
+        [offset.x, offset.y]
+    }
+
+    fn check_recenter(pixels_per_point: f32, zoom: f32, canvas_center: Pos2, pointer: Pos2) {
+        let mut state = State::new();
+        state.zoom = zoom;
+        state.center_position = Position { x: 0.3, y: -0.2 };
+        let clicked = state.point_at(offset_px(pixels_per_point, canvas_center, pointer));
+
+        state.center_position = state.center_position
+            + Position::recenter(pixels_per_point, zoom, canvas_center, pointer);
+        assert_close(state.point_at([0.0, 0.0]), clicked, 1.0);
+    }
+
+    #[test]
+    fn click_on_the_center_does_not_move() {
+        let center = Pos2::new(400.0, 300.0);
+        let change = Position::recenter(1.0, 1000.0, center, center);
+        assert_eq!((change.x, change.y), (0.0, 0.0));
+    }
+
+    #[test]
+    fn click_brings_the_point_to_the_center() {
+        let center = Pos2::new(400.0, 300.0);
+        check_recenter(1.0, 1000.0, center, Pos2::new(450.0, 280.0));
+        check_recenter(1.0, 1000.0, center, Pos2::new(10.0, 590.0));
+    }
+
+    #[test]
+    fn recenter_scales_with_the_dpi() {
+        let center = Pos2::new(400.0, 300.0);
+        let pointer = Pos2::new(450.0, 280.0);
+        let single = Position::recenter(1.0, 1000.0, center, pointer);
+        let double = Position::recenter(2.0, 1000.0, center, pointer);
+        assert_eq!((double.x, double.y), (2.0 * single.x, 2.0 * single.y));
+        check_recenter(2.0, 1000.0, center, pointer);
+        check_recenter(1.5, 1000.0, center, pointer);
+    }
+
+    #[test]
+    fn recenter_ignores_the_side_panel() {
+        // A side panel shifts the canvas, only the position relative to it matters
+        let without_panel = Position::recenter(
+            1.0,
+            1000.0,
+            Pos2::new(400.0, 300.0),
+            Pos2::new(420.0, 310.0),
+        );
+        let with_panel = Position::recenter(
+            1.0,
+            1000.0,
+            Pos2::new(650.0, 300.0),
+            Pos2::new(670.0, 310.0),
+        );
+        assert_eq!(
+            (without_panel.x, without_panel.y),
+            (with_panel.x, with_panel.y)
+        );
+        check_recenter(
+            1.0,
+            1000.0,
+            Pos2::new(650.0, 300.0),
+            Pos2::new(670.0, 310.0),
+        );
+    }
+
+    #[test]
+    fn recenter_at_zoom_extremes() {
+        let center = Pos2::new(400.0, 300.0);
+        let pointer = Pos2::new(401.0, 299.0);
+        for zoom in [1e-3, 1.0, 1e6, 1e30] {
+            let change = Position::recenter(1.0, zoom, center, pointer);
+            assert!(change.x.is_finite() && change.y.is_finite(), "zoom {zoom}");
+            assert!(change.x < 0.0 && change.y < 0.0, "zoom {zoom}");
+        }
+        check_recenter(1.0, 1e-3, center, pointer);
+    }
+
+    #[test]
+    fn drag_keeps_the_point_under_the_pointer() {
+        for pixels_per_point in [1.0, 2.0] {
+            let mut state = State::new();
+            let offset = [35.0, -12.0];
+            let grabbed = state.point_at(offset);
+
+            let delta = Vec2::new(-20.0, 15.0);
+            state.center_position =
+                state.center_position + Position::drag(pixels_per_point, state.zoom, delta);
+            let moved = [
+                offset[0] + delta.x * pixels_per_point,
+                offset[1] + delta.y * pixels_per_point,
+            ];
+            assert_close(state.point_at(moved), grabbed, 1.0);
+        }
+    }
+}
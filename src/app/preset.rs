@@ -0,0 +1,74 @@
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+use super::State;
+
+/// Version of the preset files written. Later versions are refused, the fields missing from
+/// the earlier ones take their default values.
+const PRESET_VERSION: u32 = 1;
+
+/// Top of the preset files, a reminder of the schema for the users editing them by hand.
+const HEADER: &str = "\
+# FractalGl preset, open it with Files > Load preset or drop it on the window.
+#
+# version: format of the file, only raised when the meaning of a field changes.
+# name: shown in the logs when the preset is loaded.
+# [state]: the view and its colors.
+#   fractal_type: Julia, Mandelbrot, Custom, BurningShip, Tricorn, Newton, Lyapunov or Nova.
+#   exponent: power n of z^n + c for Julia, Mandelbrot, BurningShip and Tricorn.
+#   lyapunov_sequence: growth rates of Lyapunov in turn, A for x and B for y, like \"AB\".
+#   nova_relaxation: multiplier of the Newton step of Nova.
+#   plugin, plugin_parameters: plugin of Custom, by the name of its file without the
+#     extension, and the values of its parameters.
+#   center_position: opposite of the point at the center of the view, { x, y }.
+#   zoom: pixels per unit of the complex plane.
+#   c_julia: constant of the Julia set, { x, y }.
+#   max_iterations, auto_iterations: iteration budget, raised with the zoom if automatic.
+#   contrast, brightness, gamma, r, g, b: colors of the iteration value.
+#   coloring_algorithm: Iterations, StripeAverage, TriangleInequality, CurvatureAverage,
+#     BinaryDecomposition, Period, AtomDomain, ImageTrap, TextureMap or OrbitTrap.
+#   skew: linear transform of the view around its center, column-major 2x2 matrix.
+# A missing field keeps its default value.
+
+";
+
+#[derive(Serialize, Deserialize)]
+struct Preset {
+    version: u32,
+    #[serde(default)]
+    name: String,
+    state: State,
+}
+
+/// Save `state` to the preset file `path`, named after the file.
+pub fn save(path: &Path, state: &State) -> Result<()> {
+    let preset = Preset {
+        version: PRESET_VERSION,
+        name: path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        state: state.explored(),
+    };
+    let content = format!("{HEADER}{}", toml::to_string_pretty(&preset)?);
+    fs::write(path, content).with_context(|| format!("Cannot write {}", path.display()))
+}
+
+/// State of the preset file `path`, and its name.
+pub fn load(path: &Path) -> Result<(String, State)> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let preset: Preset =
+        toml::from_str(&content).with_context(|| format!("Invalid preset {}", path.display()))?;
+    if preset.version > PRESET_VERSION {
+        bail!(
+            "{} is a preset of version {}, this version reads up to {}",
+            path.display(),
+            preset.version,
+            PRESET_VERSION
+        );
+    }
+    Ok((preset.name, preset.state.explored()))
+}
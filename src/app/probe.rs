@@ -0,0 +1,112 @@
+use eframe::egui::{vec2, Align2, Color32, FontId, Painter, Pos2, Rect, Shape, Stroke};
+
+use super::{
+    i18n::{tr, tr_args},
+    orbit::{escape, orbit},
+    period::MAX_PERIOD,
+    State,
+};
+
+/// Distance under which two values of an orbit are the same point of a cycle.
+const TOLERANCE: f64 = 1e-9;
+/// Space between the pointer and the readout, in points.
+const OFFSET: f32 = 16.0;
+const PADDING: f32 = 6.0;
+/// Points of the orbit drawn over the view.
+const ORBIT_POINTS: usize = 200;
+/// Radius of the markers of the points of the orbit, in points.
+const POINT_RADIUS: f32 = 2.0;
+
+/// Length of the cycle the orbit of `point` settles on after the iterations of `state`, if
+/// it is at most [MAX_PERIOD] points long.
+fn cycle(state: &State, point: [f64; 2]) -> Option<u32> {
+    let mut orbit = orbit(state, point)?.skip(state.iterations() as usize);
+    let start = orbit.next()?;
+    (1..=MAX_PERIOD)
+        .zip(orbit)
+        .find(|(_, z)| (z[0] - start[0]).hypot(z[1] - start[1]) < TOLERANCE)
+        .map(|(period, _)| period)
+}
+
+/// What happens to the orbit of `point` on the CPU, with the formula of the shader.
+fn readout(state: &State, point: [f64; 2]) -> Vec<String> {
+    if orbit(state, point).is_none() {
+        return vec![tr("probe-gpu-only")];
+    }
+    match escape(state, point) {
+        Some((iteration, smooth)) => vec![
+            tr_args("probe-escaped", &[("count", &iteration)]),
+            tr_args("probe-smooth", &[("value", &format!("{smooth:.3}"))]),
+            tr_args("probe-orbit", &[("length", &(iteration + 1))]),
+        ],
+        None => {
+            let mut lines = vec![tr_args("probe-bounded", &[("count", &state.iterations())])];
+            lines.push(match cycle(state, point) {
+                Some(period) => tr_args("probe-cycle", &[("period", &period)]),
+                None => tr("probe-no-cycle"),
+            });
+            lines
+        }
+    }
+}
+
+/// Positions on the canvas `rect` of the first points of the orbit of `point`, up to the
+/// first one out of the escape radius.
+fn orbit_positions(state: &State, point: [f64; 2], rect: Rect, pixels_per_point: f32) -> Vec<Pos2> {
+    let Some(orbit) = orbit(state, point) else {
+        return Vec::new();
+    };
+    let mut positions = Vec::new();
+    for z in orbit.take(ORBIT_POINTS) {
+        let [x, y] = state.offset_of(z);
+        positions.push(rect.center() + vec2(x, y) / pixels_per_point);
+        if z[0] * z[0] + z[1] * z[1] > 256.0 {
+            break;
+        }
+    }
+    positions
+}
+
+/// Draw the orbit of the point under the pointer, at `pointer_px` from the center of
+/// `rect`, and next to the pointer whether it escapes and after how many iterations.
+pub fn paint(
+    painter: &Painter,
+    rect: Rect,
+    pixels_per_point: f32,
+    state: &State,
+    pointer_px: [f32; 2],
+) {
+    let point = state.point_at(pointer_px);
+    let positions = orbit_positions(state, point, rect, pixels_per_point);
+    let orbit_painter = painter.with_clip_rect(rect);
+    // Dark outline under the line, it stays visible over light and dark areas
+    orbit_painter.add(Shape::line(
+        positions.clone(),
+        Stroke::new(3.0, Color32::BLACK),
+    ));
+    orbit_painter.add(Shape::line(
+        positions.clone(),
+        Stroke::new(1.0, Color32::YELLOW),
+    ));
+    for position in positions {
+        orbit_painter.circle_filled(position, POINT_RADIUS, Color32::YELLOW);
+    }
+
+    let lines = readout(state, point);
+    let galley = painter.layout_no_wrap(lines.join("\n"), FontId::monospace(12.0), Color32::WHITE);
+    let pointer = rect.center() + vec2(pointer_px[0], pointer_px[1]) / pixels_per_point;
+    let mut text_rect = Align2::LEFT_TOP.anchor_size(pointer + vec2(OFFSET, OFFSET), galley.size());
+    // Kept on the canvas, on the other side of the pointer near the edges
+    if text_rect.right() + PADDING > rect.right() {
+        text_rect = text_rect.translate(vec2(-text_rect.width() - 2.0 * OFFSET, 0.0));
+    }
+    if text_rect.bottom() + PADDING > rect.bottom() {
+        text_rect = text_rect.translate(vec2(0.0, -text_rect.height() - 2.0 * OFFSET));
+    }
+    painter.rect_filled(
+        text_rect.expand(PADDING),
+        4.0,
+        Color32::from_black_alpha(160),
+    );
+    painter.galley(text_rect.min, galley, Color32::WHITE);
+}
@@ -0,0 +1,83 @@
+use eframe::egui::{ComboBox, Ui};
+use log::error;
+
+use super::{i18n::tr, settings};
+
+/// Names of the settings.
+const SUPERSAMPLING_SETTING: &str = "supersampling";
+const JITTER_SETTING: &str = "jittered-samples";
+
+/// Factors of the resolution the view can be rendered at.
+const SUPERSAMPLING_FACTORS: [i32; 3] = [1, 2, 4];
+
+/// How finely the view is rendered, on top of the high quality of the state.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quality {
+    /// The view is rendered this many times larger along each axis, offscreen, and scaled
+    /// down to the canvas.
+    pub supersampling: i32,
+    /// The shader takes several samples per pixel at random positions inside it.
+    pub jitter: bool,
+}
+
+impl Default for Quality {
+    fn default() -> Self {
+        Self {
+            supersampling: 1,
+            jitter: false,
+        }
+    }
+}
+
+fn save(name: &str, value: &str) {
+    if let Err(e) = settings::save(name, value) {
+        error!("Cannot save the {} setting: {:?}", name, e);
+    }
+}
+
+impl Quality {
+    /// Settings saved from the previous runs.
+    pub fn load() -> Self {
+        Self {
+            supersampling: settings::load(SUPERSAMPLING_SETTING)
+                .and_then(|factor| factor.parse().ok())
+                .filter(|factor| SUPERSAMPLING_FACTORS.contains(factor))
+                .unwrap_or(1),
+            jitter: settings::load(JITTER_SETTING).is_some_and(|value| value == "on"),
+        }
+    }
+
+    /// Samples per pixel along each axis taken by the shader for `samples` asked by the
+    /// state: at least 2 with the jitter, a single random sample would only add noise.
+    pub fn shader_samples(&self, samples: i32) -> i32 {
+        if self.jitter {
+            samples.max(2)
+        } else {
+            samples
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ComboBox::from_label(tr("supersampling"))
+            .selected_text(format!("{}×", self.supersampling))
+            .show_ui(ui, |ui| {
+                for factor in SUPERSAMPLING_FACTORS {
+                    if ui
+                        .selectable_value(&mut self.supersampling, factor, format!("{factor}×"))
+                        .changed()
+                    {
+                        save(SUPERSAMPLING_SETTING, &factor.to_string());
+                    }
+                }
+            })
+            .response
+            .on_hover_text(tr("supersampling-hint"));
+        if ui
+            .checkbox(&mut self.jitter, tr("jittered-samples"))
+            .on_hover_text(tr("jittered-samples-hint"))
+            .changed()
+        {
+            save(JITTER_SETTING, if self.jitter { "on" } else { "off" });
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use eframe::egui::{DragValue, Slider, Ui};
+
+use super::{
+    accessibility::icon_button,
+    i18n::tr,
+    palette::{ColorStop, Palette},
+};
+
+/// Number of color stops of the generated palettes.
+const STOPS: usize = 5;
+
+/// SplitMix64, small and good enough to spread consecutive seeds apart.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    fn unit(&mut self) -> f32 {
+        (self.next() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// RGB of the hue `hue` in turns, saturation and value in `[0, 1]`.
+fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> [f32; 3] {
+    let h = hue.rem_euclid(1.0) * 6.0;
+    let f = h.fract();
+    let (p, q, t) = (
+        value * (1.0 - saturation),
+        value * (1.0 - saturation * f),
+        value * (1.0 - saturation * (1.0 - f)),
+    );
+    match h as u32 {
+        0 => [value, t, p],
+        1 => [q, value, p],
+        2 => [p, value, t],
+        3 => [p, q, value],
+        4 => [t, p, value],
+        _ => [value, p, q],
+    }
+}
+
+/// Random gradients, the same seed always gives the same palette.
+pub struct RandomPalette {
+    seed: u64,
+    /// Keep the hues within `hue_width` turns around `hue_center`.
+    lock_hue: bool,
+    hue_center: f32,
+    hue_width: f32,
+}
+
+impl RandomPalette {
+    pub fn new() -> Self {
+        Self {
+            seed: 1,
+            lock_hue: false,
+            hue_center: 0.6,
+            hue_width: 0.2,
+        }
+    }
+
+    pub fn generate(&self) -> Palette {
+        let mut random = Random(self.seed);
+        let start = random.unit();
+        let stops = (0..STOPS)
+            .map(|i| {
+                let hue = if self.lock_hue {
+                    self.hue_center + (random.unit() - 0.5) * self.hue_width
+                } else {
+                    // Small steps around the color wheel keep the gradient smooth
+                    start + i as f32 * (0.1 + 0.25 * random.unit())
+                };
+                // Alternate dark and light stops for contrast between the bands
+                let value = if i % 2 == 0 {
+                    0.1 + 0.35 * random.unit()
+                } else {
+                    0.65 + 0.35 * random.unit()
+                };
+                ColorStop {
+                    position: i as f32 / STOPS as f32,
+                    color: hsv_to_rgb(hue, 0.4 + 0.6 * random.unit(), value),
+                }
+            })
+            .collect();
+        Palette::new(format!("Random {}", self.seed), stops)
+    }
+
+    /// Seed and hue controls. Returns the new palette when it changed.
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<Palette> {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label(tr("random-palette"));
+            if icon_button(ui.button("◀"), tr("previous-seed")).clicked() {
+                self.seed = self.seed.saturating_sub(1);
+                changed = true;
+            }
+            changed |= ui
+                .add(DragValue::new(&mut self.seed).prefix(format!("{} ", tr("seed"))))
+                .changed();
+            if icon_button(ui.button("▶"), tr("next-seed")).clicked() {
+                self.seed = self.seed.saturating_add(1);
+                changed = true;
+            }
+        });
+        changed |= ui
+            .checkbox(&mut self.lock_hue, tr("lock-hue-range"))
+            .changed();
+        if self.lock_hue {
+            changed |= ui
+                .add(Slider::new(&mut self.hue_center, 0.0..=1.0).text(tr("hue")))
+                .changed();
+            changed |= ui
+                .add(Slider::new(&mut self.hue_width, 0.0..=1.0).text(tr("hue-range")))
+                .changed();
+        }
+        changed.then(|| self.generate())
+    }
+}
@@ -0,0 +1,106 @@
+use std::{collections::VecDeque, time::Duration};
+
+use eframe::egui::Context;
+use web_time::{Instant, SystemTime};
+
+use super::{i18n::tr_args, State};
+
+/// Maximum number of entries kept in the list.
+const MAX_RECENT: usize = 12;
+/// A view has been visited when it didn't change for this long.
+const SETTLE_TIME: Duration = Duration::from_millis(1500);
+
+pub struct RecentEntry {
+    pub state: State,
+    pub visited: SystemTime,
+    pub thumbnail: u64,
+}
+
+/// Views the user stopped at recently, most recent first.
+pub struct Recent {
+    entries: VecDeque<RecentEntry>,
+    /// View currently displayed, and since when.
+    current: Option<(State, Instant)>,
+}
+
+impl Recent {
+    pub fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            current: None,
+        }
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &RecentEntry> {
+        self.entries.iter()
+    }
+
+    /// Called every frame with the displayed view. Returns it once it settled and is not
+    /// already the most recent entry, the caller then records it with [Recent::push].
+    pub fn observe(&mut self, ctx: &Context, state: &State) -> Option<State> {
+        match &self.current {
+            Some((current, since)) if same_view(current, state) => {
+                if since.elapsed() < SETTLE_TIME {
+                    return None;
+                }
+            }
+            _ => {
+                self.current = Some((*state, Instant::now()));
+                // Without input there would be no frame to notice the view settled
+                ctx.request_repaint_after(SETTLE_TIME);
+                return None;
+            }
+        }
+
+        match self.entries.front() {
+            Some(latest) if same_view(&latest.state, state) => None,
+            _ => Some(*state),
+        }
+    }
+
+    /// Add `state` on top of the list. Returns the thumbnails of the entries that were
+    /// dropped: the older visit of the same view, or the oldest entry when the list is full.
+    pub fn push(&mut self, state: State, thumbnail: u64) -> Vec<u64> {
+        let mut dropped = Vec::new();
+        self.entries.retain(|entry| {
+            let keep = !same_view(&entry.state, &state);
+            if !keep {
+                dropped.push(entry.thumbnail);
+            }
+            keep
+        });
+
+        self.entries.push_front(RecentEntry {
+            state,
+            visited: SystemTime::now(),
+            thumbnail,
+        });
+        while self.entries.len() > MAX_RECENT {
+            if let Some(entry) = self.entries.pop_back() {
+                dropped.push(entry.thumbnail);
+            }
+        }
+        dropped
+    }
+}
+
+/// Whether two states show the same region, at about the same zoom level.
+pub fn same_view(a: &State, b: &State) -> bool {
+    // One screen pixel, in fractal space
+    let tolerance = 1.0 / a.zoom as f64;
+    a.fractal_type == b.fractal_type
+        && a.c_julia == b.c_julia
+        && (a.center_position.x - b.center_position.x).abs() <= tolerance
+        && (a.center_position.y - b.center_position.y).abs() <= tolerance
+        && (a.zoom / b.zoom - 1.0).abs() < 0.01
+}
+
+/// Short human-readable age of a visit.
+pub fn format_age(visited: SystemTime) -> String {
+    let seconds = visited.elapsed().unwrap_or_default().as_secs();
+    match seconds {
+        0..=59 => tr_args("seconds-ago", &[("count", &seconds)]),
+        60..=3599 => tr_args("minutes-ago", &[("count", &(seconds / 60))]),
+        _ => tr_args("hours-ago", &[("count", &(seconds / 3600))]),
+    }
+}
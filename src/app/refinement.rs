@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+use eframe::egui::{Context, Ui};
+use log::error;
+
+use super::{i18n::tr, settings, State};
+
+/// Names of the settings.
+const PROGRESSIVE_SETTING: &str = "progressive-refinement";
+const ACCUMULATE_SETTING: &str = "accumulate-samples";
+
+/// The view is refined once it did not change for this long.
+const IDLE_TIME: Duration = Duration::from_millis(150);
+/// Frames averaged into the idle view with the accumulation, each with its own jittered
+/// samples.
+const ACCUMULATED_FRAMES: u32 = 16;
+/// Pixels of the canvas along each axis for each pixel of the preview.
+pub const PREVIEW_DIVISOR: i32 = 4;
+
+/// How the view is drawn by the progressive refinement.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Refinement {
+    /// The view changes: drawn at a fraction of the resolution, to follow the input.
+    Preview,
+    /// The view is idle: drawn at the full resolution, then averaged with up to `frames`
+    /// frames of other samples.
+    Refine { frames: u32 },
+}
+
+/// Saved value of the setting `name`, `default` if it was never changed.
+fn load(name: &str, default: bool) -> bool {
+    settings::load(name).map_or(default, |value| value == "on")
+}
+
+fn save(name: &str, value: bool) {
+    if let Err(e) = settings::save(name, if value { "on" } else { "off" }) {
+        error!("Cannot save the {} setting: {:?}", name, e);
+    }
+}
+
+/// Changes of the view, to draw it coarsely while it changes and refine it once it is idle.
+pub struct Progressive {
+    enabled: bool,
+    accumulate: bool,
+    /// View last seen, without its time, and the time it changed.
+    last: Option<(State, f64)>,
+}
+
+impl Progressive {
+    /// Settings saved from the previous runs.
+    pub fn load() -> Self {
+        Self {
+            enabled: load(PROGRESSIVE_SETTING, true),
+            accumulate: load(ACCUMULATE_SETTING, false),
+            last: None,
+        }
+    }
+
+    /// Called every frame with the state drawn. Returns how to draw it, `None` to draw it
+    /// as usual: when disabled, or while `animated` since it never settles. The view counts
+    /// as changing while `interacting`, a drag held still doesn't refine it.
+    pub fn observe(
+        &mut self,
+        ctx: &Context,
+        state: &State,
+        animated: bool,
+        interacting: bool,
+    ) -> Option<Refinement> {
+        if !self.enabled || animated {
+            self.last = None;
+            return None;
+        }
+        let time = ctx.input(|i| i.time);
+        let view = State {
+            time: 0.0,
+            ..*state
+        };
+        let changed = match self.last {
+            Some((last, changed)) if last == view && !interacting => changed,
+            _ => {
+                self.last = Some((view, time));
+                time
+            }
+        };
+        let idle_in = IDLE_TIME.as_secs_f64() - (time - changed);
+        if idle_in > 0.0 {
+            // Without input there would be no frame to notice the view settled
+            ctx.request_repaint_after(Duration::from_secs_f64(idle_in));
+            return Some(Refinement::Preview);
+        }
+        Some(Refinement::Refine {
+            frames: if self.accumulate {
+                ACCUMULATED_FRAMES
+            } else {
+                1
+            },
+        })
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        if ui
+            .checkbox(&mut self.enabled, tr("progressive-refinement"))
+            .on_hover_text(tr("progressive-refinement-hint"))
+            .changed()
+        {
+            save(PROGRESSIVE_SETTING, self.enabled);
+        }
+        ui.add_enabled_ui(self.enabled, |ui| {
+            if ui
+                .checkbox(&mut self.accumulate, tr("accumulate-samples"))
+                .on_hover_text(tr("accumulate-samples-hint"))
+                .changed()
+            {
+                save(ACCUMULATE_SETTING, self.accumulate);
+            }
+        });
+    }
+}
@@ -0,0 +1,72 @@
+use eframe::egui::{vec2, Color32, Painter, Pos2, Rect, Response, Stroke};
+
+use super::{complex::Complex, State};
+
+/// Samples per pixel along each axis in the refined region.
+pub const SUPERSAMPLING: i32 = 3;
+
+/// Rectangle of the view rendered again at the highest quality, over the fast preview.
+///
+/// The corners are kept in the complex plane so that the region follows the fractal when
+/// the view moves.
+pub struct Region {
+    /// Dragging on the canvas selects the region instead of panning.
+    pub selecting: bool,
+    corners: Option<[Complex; 2]>,
+}
+
+impl Region {
+    pub fn new() -> Self {
+        Self {
+            selecting: false,
+            corners: None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.corners = None;
+    }
+
+    /// Select the region with a drag on the canvas `rect`.
+    pub fn interact(&mut self, response: &Response, rect: Rect, state: &State) {
+        let pixels_per_point = response.ctx.pixels_per_point();
+        let to_complex = |position: Pos2| {
+            let offset = (position - rect.center()) * pixels_per_point;
+            let [re, im] = state.point_at([offset.x, offset.y]);
+            Complex::new(re, im)
+        };
+        if response.drag_started() {
+            if let Some(position) = response.interact_pointer_pos() {
+                let start = to_complex(position);
+                self.corners = Some([start, start]);
+            }
+        } else if response.dragged() {
+            if let (Some(corners), Some(position)) =
+                (self.corners.as_mut(), response.interact_pointer_pos())
+            {
+                corners[1] = to_complex(position);
+            }
+        }
+        if response.drag_stopped() {
+            // Done, the next drag pans the view again
+            self.selecting = false;
+        }
+    }
+
+    /// Region on the screen, `None` when there is none or it is outside of `rect`.
+    pub fn screen_rect(&self, rect: Rect, pixels_per_point: f32, state: &State) -> Option<Rect> {
+        let to_screen = |point: Complex| {
+            let [x, y] = state.offset_of([point.re, point.im]);
+            rect.center() + vec2(x, y) / pixels_per_point
+        };
+        let [a, b] = self.corners?;
+        let region = Rect::from_two_pos(to_screen(a), to_screen(b)).intersect(rect);
+        region.is_positive().then_some(region)
+    }
+
+    /// Outline of the region, drawn over the fractal.
+    pub fn paint(&self, painter: &Painter, region: Rect) {
+        painter.rect_stroke(region, 0.0, Stroke::new(3.0, Color32::BLACK));
+        painter.rect_stroke(region, 0.0, Stroke::new(1.0, Color32::WHITE));
+    }
+}
@@ -0,0 +1,206 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, ensure, Context, Result};
+use clap::{Parser, ValueEnum};
+use image::ExtendedColorType;
+use log::info;
+
+use super::{
+    cpu_renderer,
+    plugins::Plugins,
+    position::Position,
+    state::{FractalType, State, MAX_ITERATIONS},
+    Palette,
+};
+
+/// Fractal types named on the command line, all drawn on the CPU as well when the build or
+/// the machine has no headless OpenGL.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Fractal {
+    Julia,
+    Mandelbrot,
+    BurningShip,
+    Tricorn,
+    Newton,
+    Lyapunov,
+    Nova,
+}
+
+impl From<Fractal> for FractalType {
+    fn from(fractal: Fractal) -> Self {
+        match fractal {
+            Fractal::Julia => FractalType::Julia,
+            Fractal::Mandelbrot => FractalType::Mandelbrot,
+            Fractal::BurningShip => FractalType::BurningShip,
+            Fractal::Tricorn => FractalType::Tricorn,
+            Fractal::Newton => FractalType::Newton,
+            Fractal::Lyapunov => FractalType::Lyapunov,
+            Fractal::Nova => FractalType::Nova,
+        }
+    }
+}
+
+/// Complex number of the command line, as `re,im`.
+pub fn parse_complex(value: &str) -> Result<[f64; 2]> {
+    let (re, im) = value
+        .split_once(',')
+        .context("Expected the real and imaginary parts as `re,im`")?;
+    Ok([re.trim().parse()?, im.trim().parse()?])
+}
+
+/// Render a fractal to an image file, without opening a window.
+#[derive(Parser)]
+#[command(name = "fractal_gl render")]
+struct RenderArgs {
+    /// Image file to write, its extension gives the format.
+    output: PathBuf,
+    #[arg(long, value_enum, default_value = "mandelbrot")]
+    fractal: Fractal,
+    /// Point at the center of the image, as `re,im`.
+    #[arg(
+        long,
+        value_parser = parse_complex,
+        default_value = "-0.5,0",
+        allow_hyphen_values = true
+    )]
+    center: [f64; 2],
+    /// Magnification as in Kalles Fraktaler: at 1 the image is 4 units high.
+    #[arg(long, default_value_t = 1.0)]
+    zoom: f64,
+    /// Constant of the Julia set, as `re,im`.
+    #[arg(long, value_parser = parse_complex, allow_hyphen_values = true)]
+    julia: Option<[f64; 2]>,
+    #[arg(long, default_value_t = 1920)]
+    width: u32,
+    #[arg(long, default_value_t = 1080)]
+    height: u32,
+    /// Iteration budget, raised with the zoom if not given.
+    #[arg(long)]
+    iterations: Option<u32>,
+    /// Color with a built-in palette: Grayscale, Classic, Fire or Ocean.
+    #[arg(long)]
+    palette: Option<String>,
+    /// Compute on the CPU instead of the GPU.
+    #[arg(long)]
+    cpu: bool,
+}
+
+impl RenderArgs {
+    fn state(&self) -> Result<State> {
+        ensure!(self.width > 0 && self.height > 0, "Empty image");
+        let mut state = State::new();
+        state.fractal_type = self.fractal.into();
+        state.center_position = Position {
+            x: -self.center[0],
+            y: -self.center[1],
+        };
+        state.zoom = (self.zoom * self.height as f64 / 4.0) as f32;
+        ensure!(
+            state.zoom.is_finite() && state.zoom > 0.0,
+            "Zoom {:e} is out of reach of the renderer",
+            self.zoom
+        );
+        if let Some([re, im]) = self.julia {
+            state.c_julia.x = re as f32;
+            state.c_julia.y = im as f32;
+        }
+        match self.iterations {
+            Some(iterations) => state.max_iterations = iterations.clamp(16, MAX_ITERATIONS),
+            None => state.auto_iterations = true,
+        }
+        state.use_palette = self.palette.is_some();
+        Ok(state)
+    }
+
+    fn palette(&self) -> Result<Option<Palette>> {
+        let Some(name) = &self.palette else {
+            return Ok(None);
+        };
+        match Palette::presets()
+            .into_iter()
+            .find(|palette| palette.name.eq_ignore_ascii_case(name))
+        {
+            Some(palette) => Ok(Some(palette)),
+            None => bail!("Unknown palette {}", name),
+        }
+    }
+}
+
+/// RGBA pixels of `state` on the GPU, top row first.
+#[cfg(feature = "headless")]
+fn render_gpu(state: State, palette: Option<&Palette>, width: u32, height: u32) -> Result<Vec<u8>> {
+    let gl = super::headless::context()?;
+    let mut fractal = super::FractalGl::new();
+    if let Some(palette) = palette {
+        fractal.set_palette(palette);
+    }
+    fractal.init(&gl)?;
+    let image = fractal.render_offscreen(&gl, state, width as i32, height as i32);
+    fractal.destroy(&gl);
+    Ok(image?.pixels)
+}
+
+#[cfg(not(feature = "headless"))]
+fn render_gpu(_: State, _: Option<&Palette>, _: u32, _: u32) -> Result<Vec<u8>> {
+    bail!("Built without the headless feature")
+}
+
+/// Run the `render` subcommand with its arguments, `args` starting with `render`.
+pub fn render_from_args(args: impl Iterator<Item = String>) -> Result<()> {
+    let args = RenderArgs::parse_from(args);
+    let state = args.state()?;
+    let palette = args.palette()?;
+    info!(
+        "Rendering {}x{} pixels with {} iterations",
+        args.width,
+        args.height,
+        state.iterations()
+    );
+
+    let kernel = Plugins::load().kernel(&state);
+    let pixels = if args.cpu {
+        cpu_renderer::render(
+            state,
+            palette.as_ref(),
+            kernel.as_ref(),
+            args.width,
+            args.height,
+        )?
+    } else {
+        render_gpu(state, palette.as_ref(), args.width, args.height).or_else(|e| {
+            info!("No GPU rendering ({:#}), rendering on the CPU", e);
+            cpu_renderer::render(
+                state,
+                palette.as_ref(),
+                kernel.as_ref(),
+                args.width,
+                args.height,
+            )
+        })?
+    };
+    image::save_buffer(
+        &args.output,
+        &pixels,
+        args.width,
+        args.height,
+        ExtendedColorType::Rgba8,
+    )
+    .with_context(|| format!("Cannot write {}", args.output.display()))?;
+    info!("Rendered {}", args.output.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_fractal_on_the_cpu() {
+        for fractal in Fractal::value_variants() {
+            let mut state = State::new();
+            state.fractal_type = (*fractal).into();
+            assert_eq!(cpu_renderer::unsupported(&state, None), None);
+            assert!(cpu_renderer::render(state, None, None, 4, 4).is_ok());
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::{collections::VecDeque, fmt::Write as _, fs};
+
+use eframe::egui::{vec2, Color32, Sense, Shape, Stroke, Ui};
+use log::{error, info};
+
+use super::{
+    file_dialog,
+    i18n::{tr, tr_args},
+    State,
+};
+
+/// Frames kept in the session log, the oldest are dropped past it.
+const MAX_SAMPLES: usize = 100_000;
+/// Frames shown by the trend plots.
+const PLOT_SAMPLES: usize = 300;
+
+/// Statistics of one frame.
+struct Sample {
+    /// Seconds since the start of the application.
+    time: f64,
+    /// Time spent building the frame on the CPU, in seconds.
+    frame_time: f64,
+    /// GPU time of the draw of the view, in seconds, when measured.
+    gpu_time: Option<f64>,
+    iterations: u32,
+    magnification: f64,
+    high_quality: bool,
+}
+
+/// Session log of the render statistics, with trend plots and an export as CSV.
+pub struct RenderStats {
+    samples: VecDeque<Sample>,
+}
+
+impl RenderStats {
+    pub fn new() -> Self {
+        Self {
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn record(
+        &mut self,
+        time: f64,
+        frame_time: f64,
+        gpu_time: Option<f64>,
+        state: &State,
+        viewport_height_px: f32,
+    ) {
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample {
+            time,
+            frame_time,
+            gpu_time,
+            iterations: state.iterations(),
+            magnification: state.magnification(viewport_height_px.max(1.0)),
+            high_quality: state.high_quality,
+        });
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv =
+            "time_s,frame_time_ms,gpu_time_ms,iterations,magnification,high_quality\n".to_string();
+        for sample in &self.samples {
+            let gpu_time = sample
+                .gpu_time
+                .map_or(String::new(), |time| format!("{:.3}", time * 1000.0));
+            let _ = writeln!(
+                csv,
+                "{:.3},{:.3},{},{},{:e},{}",
+                sample.time,
+                sample.frame_time * 1000.0,
+                gpu_time,
+                sample.iterations,
+                sample.magnification,
+                sample.high_quality as u8
+            );
+        }
+        csv
+    }
+
+    fn export(&self) {
+        let Some(path) =
+            file_dialog::save_file(&[(&tr("filter-csv"), &["csv"])], "render-statistics.csv")
+        else {
+            return;
+        };
+        match fs::write(&path, self.to_csv()) {
+            Ok(()) => info!("Exported the render statistics to {}", path.display()),
+            Err(e) => error!("Cannot write {}: {:?}", path.display(), e),
+        }
+    }
+
+    /// Line plot of the durations `values`, scaled to the largest one. `label` is the id
+    /// of the message taking the largest in milliseconds.
+    fn plot(ui: &mut Ui, label: &str, values: &[f64], color: Color32) {
+        let largest = values.iter().copied().fold(0.0, f64::max);
+        ui.label(tr_args(
+            label,
+            &[("max", &format!("{:.2}", largest * 1000.0))],
+        ));
+        let (rect, _) = ui.allocate_exact_size(vec2(ui.available_width(), 48.0), Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+        if values.len() < 2 || largest <= 0.0 {
+            return;
+        }
+        let points = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let x = i as f32 / (values.len() - 1) as f32;
+                let y = (value / largest) as f32;
+                rect.left_bottom() + vec2(x * rect.width(), -y * rect.height())
+            })
+            .collect();
+        painter.add(Shape::line(points, Stroke::new(1.0, color)));
+    }
+
+    /// Trend plots of the last frames, and the export of the log.
+    pub fn ui(&self, ui: &mut Ui) {
+        let recent = self
+            .samples
+            .iter()
+            .skip(self.samples.len().saturating_sub(PLOT_SAMPLES));
+        let frame_times: Vec<f64> = recent.clone().map(|s| s.frame_time).collect();
+        let gpu_times: Vec<f64> = recent.filter_map(|s| s.gpu_time).collect();
+        Self::plot(ui, "frame-time-plot", &frame_times, Color32::LIGHT_BLUE);
+        if !gpu_times.is_empty() {
+            Self::plot(ui, "gpu-time-plot", &gpu_times, Color32::LIGHT_GREEN);
+        }
+        if ui
+            .button(tr("export-render-statistics"))
+            .on_hover_text(tr("export-render-statistics-hint"))
+            .clicked()
+        {
+            self.export();
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{ensure, Context, Result};
+use eframe::egui::{Event, Key, Modifiers, RawInput, Rect};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+
+use super::State;
+
+/// Version of the replay files written, files of other versions are rejected.
+const VERSION: u32 = 1;
+
+/// First line of a replay file.
+#[derive(Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    /// State when the recording started, restored before the playback.
+    state: State,
+}
+
+/// Input of one frame, on each following line.
+#[derive(Serialize, Deserialize)]
+pub struct Frame {
+    /// Seconds since the first frame recorded.
+    time: f64,
+    screen_rect: Option<Rect>,
+    modifiers: Modifiers,
+    events: Vec<Event>,
+}
+
+/// Recording of the raw input of the application to a file, and playback of that file
+/// frame by frame. The files are JSON lines: a [Header], then one [Frame] per frame.
+pub enum Replay {
+    Idle,
+    Recording {
+        file: BufWriter<File>,
+        path: PathBuf,
+        /// Time of the first frame recorded.
+        start: Option<f64>,
+    },
+    Playing {
+        frames: VecDeque<Frame>,
+        /// Time of the first frame played.
+        start: Option<f64>,
+    },
+}
+
+impl Replay {
+    pub fn is_idle(&self) -> bool {
+        matches!(self, Replay::Idle)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        matches!(self, Replay::Recording { .. })
+    }
+
+    pub fn is_playing(&self) -> bool {
+        matches!(self, Replay::Playing { .. })
+    }
+
+    /// Record the input of the next frames to `path`, from `state`.
+    pub fn record(&mut self, path: PathBuf, state: &State) -> Result<()> {
+        let mut file = BufWriter::new(
+            File::create(&path).with_context(|| format!("Cannot create {}", path.display()))?,
+        );
+        let header = Header {
+            version: VERSION,
+            state: *state,
+        };
+        serde_json::to_writer(&mut file, &header)?;
+        writeln!(file)?;
+        info!("Recording the input to {}", path.display());
+        *self = Replay::Recording {
+            file,
+            path,
+            start: None,
+        };
+        Ok(())
+    }
+
+    /// Play the input recorded in `path` over the next frames. Returns the state to start
+    /// from.
+    pub fn play(&mut self, path: &Path) -> Result<State> {
+        let file = File::open(path).with_context(|| format!("Cannot read {}", path.display()))?;
+        let mut lines = BufReader::new(file).lines();
+        let header: Header = serde_json::from_str(
+            &lines
+                .next()
+                .with_context(|| format!("{} is empty", path.display()))??,
+        )?;
+        ensure!(
+            header.version == VERSION,
+            "Unsupported replay version {} in {}",
+            header.version,
+            path.display()
+        );
+        let frames = lines
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect::<Result<VecDeque<Frame>>>()?;
+        info!("Playing {} frames from {}", frames.len(), path.display());
+        *self = Replay::Playing {
+            frames,
+            start: None,
+        };
+        Ok(header.state)
+    }
+
+    pub fn stop(&mut self) {
+        if let Replay::Recording { file, path, .. } = self {
+            match file.flush() {
+                Ok(()) => info!("Recorded the input to {}", path.display()),
+                Err(e) => error!("Cannot write {}: {:?}", path.display(), e),
+            }
+        }
+        *self = Replay::Idle;
+    }
+
+    /// Record the input of the frame, or replace it with the one played. Escape stops the
+    /// playback, everything else from the user is ignored until the end.
+    pub fn hook(&mut self, raw_input: &mut RawInput) {
+        let now = raw_input.time.unwrap_or(0.0);
+        match self {
+            Replay::Idle => {}
+            Replay::Recording { file, path, start } => {
+                let frame = Frame {
+                    time: now - *start.get_or_insert(now),
+                    screen_rect: raw_input.screen_rect,
+                    modifiers: raw_input.modifiers,
+                    events: raw_input
+                        .events
+                        .iter()
+                        .filter(|event| !matches!(event, Event::Screenshot { .. }))
+                        .cloned()
+                        .collect(),
+                };
+                let written = serde_json::to_writer(&mut *file, &frame)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|()| Ok(writeln!(file)?));
+                if let Err(e) = written {
+                    error!("Cannot record to {}: {:?}", path.display(), e);
+                    self.stop();
+                }
+            }
+            Replay::Playing { frames, start } => {
+                let escape = raw_input.events.iter().any(|event| {
+                    matches!(
+                        event,
+                        Event::Key {
+                            key: Key::Escape,
+                            pressed: true,
+                            ..
+                        }
+                    )
+                });
+                let Some(frame) = frames.pop_front().filter(|_| !escape) else {
+                    info!("End of the replay");
+                    self.stop();
+                    return;
+                };
+                // The recorded times are shifted to follow the frames already shown, so
+                // that the time never goes back
+                raw_input.time = Some(*start.get_or_insert(now) + frame.time);
+                if frame.screen_rect.is_some() {
+                    raw_input.screen_rect = frame.screen_rect;
+                }
+                raw_input.modifiers = frame.modifiers;
+                raw_input.events = frame.events;
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use eframe::egui::{pos2, vec2, Align2, Color32, FontId, Rect, Stroke};
+
+use super::overlay::Canvas;
+
+/// Preferred length of the bar on the screen, in points.
+const TARGET_WIDTH: f32 = 120.0;
+const MARGIN: f32 = 16.0;
+
+/// Largest length of the form 1, 2 or 5 × 10ⁿ not above `length`.
+fn round_length(length: f64) -> f64 {
+    let power = 10f64.powf(length.log10().floor());
+    [5.0, 2.0, 1.0]
+        .into_iter()
+        .map(|mantissa| mantissa * power)
+        .find(|&round| round <= length)
+        .unwrap_or(power)
+}
+
+fn format_length(length: f64) -> String {
+    if (1e-3..1e4).contains(&length) {
+        // Print the digits of the round length without the noise of the float
+        format!("{}", (length * 1e3).round() / 1e3)
+    } else {
+        format!("{:.0e}", length)
+    }
+}
+
+/// Draw a map-like scale bar in the bottom left corner of `rect`, for a view of `zoom`
+/// physical pixels per unit of the complex plane.
+pub fn paint(canvas: &mut impl Canvas, rect: Rect, pixels_per_point: f32, zoom: f32) {
+    let units_per_point = pixels_per_point as f64 / zoom as f64;
+    let length = round_length(TARGET_WIDTH as f64 * units_per_point);
+    if !length.is_finite() || length <= 0.0 {
+        return;
+    }
+    let width = (length / units_per_point) as f32;
+
+    let left = rect.left_bottom() + vec2(MARGIN, -MARGIN);
+    let right = left + vec2(width, 0.0);
+    // Dark outline so the bar stays visible over light areas of the fractal
+    for stroke in [
+        Stroke::new(4.0, Color32::BLACK),
+        Stroke::new(2.0, Color32::WHITE),
+    ] {
+        canvas.line_segment([left, right], stroke);
+        canvas.line_segment([left, left - vec2(0.0, 6.0)], stroke);
+        canvas.line_segment([right, right - vec2(0.0, 6.0)], stroke);
+    }
+    let label = format_length(length);
+    let font = FontId::proportional(12.0);
+    let anchor = pos2((left.x + right.x) / 2.0, left.y - 4.0);
+    canvas.text(
+        anchor + vec2(1.0, 1.0),
+        Align2::CENTER_BOTTOM,
+        &label,
+        font.clone(),
+        Color32::BLACK,
+    );
+    canvas.text(anchor, Align2::CENTER_BOTTOM, &label, font, Color32::WHITE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_lengths() {
+        assert_eq!(round_length(1.0), 1.0);
+        assert_eq!(round_length(1.9), 1.0);
+        assert_eq!(round_length(2.0), 2.0);
+        assert_eq!(round_length(4.99), 2.0);
+        assert_eq!(round_length(7.5), 5.0);
+        assert_eq!(round_length(120.0), 100.0);
+        assert_eq!(round_length(0.03), 0.02);
+        assert_eq!(round_length(6e-20), 5e-20);
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(format_length(0.02), "0.02");
+        assert_eq!(format_length(500.0), "500");
+        assert_eq!(format_length(5e-20), "5e-20");
+        assert_eq!(format_length(1e5), "1e5");
+    }
+}
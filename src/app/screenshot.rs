@@ -0,0 +1,55 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context as _, Result};
+
+use super::State;
+
+/// Folder of the screenshots: the pictures folder of the user, or the working directory.
+fn folder() -> PathBuf {
+    env::var_os("XDG_PICTURES_DIR")
+        .map(PathBuf::from)
+        .or_else(|| {
+            let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+            Some(PathBuf::from(home).join("Pictures"))
+        })
+        .filter(|folder| folder.is_dir())
+        .unwrap_or_default()
+}
+
+/// New PNG file for a screenshot, named after the time so that they sort in order.
+pub fn new_path() -> PathBuf {
+    let seconds = web_time::SystemTime::now()
+        .duration_since(web_time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    folder().join(format!("fractal_{seconds}.png"))
+}
+
+/// JSON file next to the image `path`, with the view it shows.
+fn sidecar(path: &Path) -> PathBuf {
+    path.with_extension("json")
+}
+
+/// Save `state` next to the screenshot `path`, for [load_view].
+pub fn save_view(path: &Path, state: &State) -> Result<()> {
+    let sidecar = sidecar(path);
+    let content = serde_json::to_string_pretty(&state.explored())?;
+    fs::write(&sidecar, content).with_context(|| format!("Cannot write {}", sidecar.display()))
+}
+
+/// View of a screenshot dropped on the window: `path` is the image or its JSON sidecar.
+pub fn load_view(path: &Path) -> Result<State> {
+    let sidecar = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => path.to_path_buf(),
+        Some("png") => sidecar(path),
+        _ => bail!("{} is not a screenshot", path.display()),
+    };
+    let content = fs::read_to_string(&sidecar)
+        .with_context(|| format!("No view saved with {}", path.display()))?;
+    let state = serde_json::from_str::<State>(&content)
+        .with_context(|| format!("Invalid view in {}", sidecar.display()))?;
+    Ok(state.explored())
+}
@@ -0,0 +1,370 @@
+use std::{cell::RefCell, collections::VecDeque, mem, path::PathBuf, rc::Rc, time::Duration};
+
+use clap::ValueEnum;
+use eframe::egui::{self, Button, ProgressBar, RichText, ScrollArea, TextEdit};
+use log::error;
+use rhai::{Array, Dynamic, Engine, EvalAltResult};
+use web_time::Instant;
+
+use super::{
+    i18n::{tr, tr_args},
+    position::Position,
+    render_cli::Fractal,
+    settings,
+    state::MAX_ITERATIONS,
+    State,
+};
+
+/// Name of the setting holding the script of the console.
+const SCRIPT_SETTING: &str = "script.rhai";
+/// Operations a script may run, so that an endless loop doesn't hang the app.
+const MAX_OPERATIONS: u64 = 5_000_000;
+/// Time a script may run for, it blocks the UI meanwhile.
+const MAX_DURATION: Duration = Duration::from_millis(500);
+/// Operations between two checks of [MAX_DURATION].
+const CLOCK_OPERATIONS: u64 = 10_000;
+/// Steps a script may take.
+const MAX_STEPS: usize = 100_000;
+/// Lines of output kept in the console.
+const MAX_OUTPUT: usize = 200;
+
+const EXAMPLE: &str = "\
+// Zoom into the seahorse valley, one frame at a time
+center(-0.7436, 0.1318);
+for i in 0..120 {
+    zoom_by(1.05);
+    step();
+}
+";
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// What the app does for a script, one step per frame.
+pub enum ScriptStep {
+    /// Show the view.
+    View(State),
+    /// Export the view to a PNG file, after the previous export is done.
+    Snapshot(PathBuf, State),
+}
+
+/// View a script is working on, and the steps it took.
+struct Run {
+    state: State,
+    steps: VecDeque<ScriptStep>,
+    output: Vec<String>,
+}
+
+fn number(value: Dynamic) -> ScriptResult<f64> {
+    value
+        .as_float()
+        .or_else(|_| value.as_int().map(|value| value as f64))
+        .map_err(|kind| format!("Expected a number, got {kind}").into())
+}
+
+/// `value` as the `f32` of a field of the view, refused unless finite.
+fn finite(value: Dynamic, what: &str) -> ScriptResult<f32> {
+    let value = number(value)?;
+    if !(value as f32).is_finite() {
+        return Err(format!("Invalid {what} {value}").into());
+    }
+    Ok(value as f32)
+}
+
+impl Run {
+    /// Take a step, refused past [MAX_STEPS].
+    fn push(&mut self, step: ScriptStep) -> ScriptResult<()> {
+        if self.steps.len() >= MAX_STEPS {
+            return Err(format!("More than {MAX_STEPS} steps").into());
+        }
+        self.steps.push_back(step);
+        Ok(())
+    }
+}
+
+/// Engine with the functions of the app, working on `run`.
+fn engine(run: &Rc<RefCell<Run>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    let start = Instant::now();
+    engine.on_progress(move |operations| {
+        let late = operations % CLOCK_OPERATIONS == 0 && start.elapsed() > MAX_DURATION;
+        late.then_some(Dynamic::UNIT)
+    });
+
+    let r = run.clone();
+    engine.on_print(move |text| r.borrow_mut().output.push(text.to_string()));
+    let r = run.clone();
+    engine.register_fn("zoom", move |zoom: Dynamic| -> ScriptResult<()> {
+        let zoom = number(zoom)? as f32;
+        if !(zoom.is_finite() && zoom > 0.0) {
+            return Err(format!("Invalid zoom {zoom}").into());
+        }
+        r.borrow_mut().state.zoom = zoom;
+        Ok(())
+    });
+    let r = run.clone();
+    engine.register_fn("zoom_by", move |factor: Dynamic| -> ScriptResult<()> {
+        let zoom = r.borrow().state.zoom * number(factor)? as f32;
+        if !(zoom.is_finite() && zoom > 0.0) {
+            return Err(format!("Invalid zoom {zoom}").into());
+        }
+        r.borrow_mut().state.zoom = zoom;
+        Ok(())
+    });
+    let r = run.clone();
+    engine.register_fn("get_zoom", move || r.borrow().state.zoom as f64);
+    let r = run.clone();
+    engine.register_fn(
+        "center",
+        move |re: Dynamic, im: Dynamic| -> ScriptResult<()> {
+            let (x, y) = (-number(re)?, -number(im)?);
+            if !(x.is_finite() && y.is_finite()) {
+                return Err(format!("Invalid center {} {}", -x, -y).into());
+            }
+            r.borrow_mut().state.center_position = Position { x, y };
+            Ok(())
+        },
+    );
+    let r = run.clone();
+    engine.register_fn("get_center", move || -> Array {
+        let center = r.borrow().state.center_position;
+        vec![Dynamic::from(-center.x), Dynamic::from(-center.y)]
+    });
+    let r = run.clone();
+    engine.register_fn(
+        "julia",
+        move |re: Dynamic, im: Dynamic| -> ScriptResult<()> {
+            let (re, im) = (finite(re, "Julia constant")?, finite(im, "Julia constant")?);
+            let mut run = r.borrow_mut();
+            run.state.c_julia.x = re;
+            run.state.c_julia.y = im;
+            Ok(())
+        },
+    );
+    let r = run.clone();
+    engine.register_fn("rotation", move |degrees: Dynamic| -> ScriptResult<()> {
+        r.borrow_mut().state.rotation = finite(degrees, "rotation")?.to_radians();
+        Ok(())
+    });
+    let r = run.clone();
+    engine.register_fn("iterations", move |count: i64| -> ScriptResult<()> {
+        if count < 1 {
+            return Err(format!("Invalid iterations {count}").into());
+        }
+        let mut run = r.borrow_mut();
+        run.state.max_iterations = count.clamp(16, MAX_ITERATIONS as i64) as u32;
+        run.state.auto_iterations = false;
+        Ok(())
+    });
+    let r = run.clone();
+    engine.register_fn("fractal", move |name: &str| -> ScriptResult<()> {
+        let fractal =
+            Fractal::from_str(name, true).map_err(|_| format!("Unknown fractal {name}"))?;
+        r.borrow_mut().state.fractal_type = fractal.into();
+        Ok(())
+    });
+    let r = run.clone();
+    engine.register_fn("step", move || -> ScriptResult<()> {
+        let mut run = r.borrow_mut();
+        let state = run.state;
+        run.push(ScriptStep::View(state))
+    });
+    let r = run.clone();
+    engine.register_fn("snapshot", move |path: &str| -> ScriptResult<()> {
+        let mut run = r.borrow_mut();
+        let state = run.state;
+        run.push(ScriptStep::Snapshot(PathBuf::from(path), state))
+    });
+    engine
+}
+
+/// Console running Rhai scripts over the view, to program zooms, sweeps of the parameters
+/// and batch exports. The script runs at once on a copy of the view, then the app plays
+/// the steps it took, one per frame.
+pub struct ScriptConsole {
+    pub open: bool,
+    source: String,
+    output: VecDeque<String>,
+    steps: VecDeque<ScriptStep>,
+    /// Steps of the script being played.
+    total_steps: usize,
+}
+
+impl ScriptConsole {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            source: EXAMPLE.to_string(),
+            output: VecDeque::new(),
+            steps: VecDeque::new(),
+            total_steps: 0,
+        }
+    }
+
+    /// Console with the script of the previous run.
+    pub fn load() -> Self {
+        Self {
+            source: settings::load(SCRIPT_SETTING).unwrap_or_else(|| EXAMPLE.to_string()),
+            ..Self::new()
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        !self.steps.is_empty()
+    }
+
+    fn print(&mut self, line: String) {
+        if self.output.len() == MAX_OUTPUT {
+            self.output.pop_front();
+        }
+        self.output.push_back(line);
+    }
+
+    /// Run the script from the view `state`.
+    fn run(&mut self, state: &State) {
+        if let Err(e) = settings::save(SCRIPT_SETTING, &self.source) {
+            error!("Cannot save the script: {:?}", e);
+        }
+        let run = Rc::new(RefCell::new(Run {
+            state: *state,
+            steps: VecDeque::new(),
+            output: Vec::new(),
+        }));
+        let result = engine(&run).run(&self.source);
+        let mut run = run.borrow_mut();
+        for line in mem::take(&mut run.output) {
+            self.print(line);
+        }
+        match result {
+            Ok(()) => {
+                // Where the script left the view
+                let mut steps = mem::take(&mut run.steps);
+                steps.push_back(ScriptStep::View(run.state));
+                self.total_steps = steps.len();
+                self.steps = steps;
+            }
+            Err(e) if matches!(*e, EvalAltResult::ErrorTerminated(..)) => self.print(tr_args(
+                "script-too-long",
+                &[("seconds", &MAX_DURATION.as_secs_f32())],
+            )),
+            Err(e) => self.print(format!("{}: {}", tr("script-error"), e)),
+        }
+    }
+
+    /// Next step of the script being played, if any. A snapshot waits for the previous
+    /// export, while `exporting`.
+    pub fn next_step(&mut self, ctx: &egui::Context, exporting: bool) -> Option<ScriptStep> {
+        if self.steps.is_empty() {
+            return None;
+        }
+        ctx.request_repaint();
+        if exporting && matches!(self.steps.front(), Some(ScriptStep::Snapshot(..))) {
+            return None;
+        }
+        self.steps.pop_front()
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, state: &State) {
+        let mut open = self.open;
+        egui::Window::new(tr("script-console"))
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(tr("script-help")).small());
+                ScrollArea::vertical()
+                    .id_salt("script")
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            TextEdit::multiline(&mut self.source)
+                                .code_editor()
+                                .desired_rows(10)
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+                ui.horizontal(|ui| {
+                    if self.is_playing() {
+                        if ui.button(tr("cancel")).clicked() {
+                            self.steps.clear();
+                        }
+                        let done = self.total_steps - self.steps.len();
+                        let progress = done as f32 / self.total_steps.max(1) as f32;
+                        ui.add(ProgressBar::new(progress).show_percentage());
+                    } else {
+                        if ui.button(tr("run-script")).clicked() {
+                            self.run(state);
+                        }
+                        if ui
+                            .add_enabled(!self.output.is_empty(), Button::new(tr("clear")))
+                            .clicked()
+                        {
+                            self.output.clear();
+                        }
+                    }
+                });
+                ScrollArea::vertical()
+                    .id_salt("script output")
+                    .max_height(120.0)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for line in &self.output {
+                            ui.label(RichText::new(line).monospace());
+                        }
+                    });
+            });
+        self.open = open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::FractalType;
+
+    fn run(source: &str) -> ScriptConsole {
+        let mut console = ScriptConsole::new();
+        console.source = source.to_string();
+        console.run(&State::new());
+        console
+    }
+
+    #[test]
+    fn steps() {
+        let mut console = run("zoom(500); step(); iterations(100000000); fractal(\"julia\");");
+        assert_eq!(console.steps.len(), 2);
+        let Some(ScriptStep::View(last)) = console.steps.pop_back() else {
+            panic!("No view");
+        };
+        assert_eq!(last.zoom, 500.0);
+        assert_eq!(last.max_iterations, MAX_ITERATIONS);
+        assert_eq!(last.fractal_type, FractalType::Julia);
+    }
+
+    #[test]
+    fn errors() {
+        for source in [
+            "iterations(0);",
+            "zoom(-1);",
+            "center(1e308 * 10.0, 0);",
+            "julia(1e300, 0);",
+            "rotation(1e308 * 10.0);",
+            "for i in 0..100001 { snapshot(\"a.png\"); }",
+            "fractal(\"square\");",
+            "step(",
+        ] {
+            let console = run(source);
+            assert!(!console.is_playing(), "{source}");
+            assert_eq!(console.output.len(), 1, "{source}");
+        }
+    }
+
+    #[test]
+    fn endless_loop() {
+        let start = Instant::now();
+        let console = run("loop { }");
+        // Stopped by the operations or by the time, whichever comes first
+        assert!(start.elapsed() < 2 * MAX_DURATION);
+        assert!(!console.is_playing());
+        assert_eq!(console.output.len(), 1);
+    }
+}
@@ -0,0 +1,72 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::{env, fs, path::PathBuf};
+
+#[cfg(target_arch = "wasm32")]
+use anyhow::anyhow;
+#[cfg(not(target_arch = "wasm32"))]
+use anyhow::Context;
+use anyhow::Result;
+
+/// File holding the setting `name`, in the user configuration directory.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn path(name: &str) -> Option<PathBuf> {
+    let config = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    Some(config?.join("fractal_gl").join(name))
+}
+
+/// There is no configuration directory on the web, the settings go to the local storage.
+#[cfg(target_arch = "wasm32")]
+pub fn path(_name: &str) -> Option<std::path::PathBuf> {
+    None
+}
+
+/// Saved value of the setting `name`, if any.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(name: &str) -> Option<String> {
+    fs::read_to_string(path(name)?)
+        .ok()
+        .map(|value| value.trim().to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(name: &str, value: &str) -> Result<()> {
+    let path = path(name).context("No configuration directory")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Cannot create {}", parent.display()))?;
+    }
+    fs::write(&path, value).with_context(|| format!("Cannot write {}", path.display()))
+}
+
+/// Local storage of the browser, `None` when it is disabled.
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Key of the setting `name` in the local storage, shared with the other pages of the site.
+#[cfg(target_arch = "wasm32")]
+fn key(name: &str) -> String {
+    format!("fractal_gl/{name}")
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load(name: &str) -> Option<String> {
+    local_storage()?.get_item(&key(name)).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(name: &str, value: &str) -> Result<()> {
+    let storage = local_storage().ok_or_else(|| anyhow!("No local storage"))?;
+    storage
+        .set_item(&key(name), value)
+        .map_err(|e| anyhow!("Cannot save {}: {:?}", name, e))
+}
@@ -0,0 +1,69 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use log::{error, info};
+
+/// Delay between two checks of the shader files for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Shaders of the `assets` directory of the source tree, reloaded when they change, for the
+/// development of the shaders without restarting. The build uses the shaders embedded in the
+/// binary otherwise.
+pub struct ShaderWatch {
+    directory: PathBuf,
+    /// Modification times of the vertex and fragment shaders last loaded.
+    loaded: [Option<SystemTime>; 2],
+    last_check: f64,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl ShaderWatch {
+    /// Watch the shaders the binary was built with. They are not reloaded before they change.
+    pub fn new() -> Self {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+        info!("Watching the shaders of {}", directory.display());
+        let loaded = [
+            modified(&directory.join("vertex.shader")),
+            modified(&directory.join("fragment.shader")),
+        ];
+        Self {
+            directory,
+            loaded,
+            last_check: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Sources of the vertex and fragment shaders when one changed since the last call,
+    /// checked every [POLL_INTERVAL] at `time`.
+    pub fn poll(&mut self, time: f64) -> Option<(String, String)> {
+        if time - self.last_check < POLL_INTERVAL.as_secs_f64() {
+            return None;
+        }
+        self.last_check = time;
+        let paths = [
+            self.directory.join("vertex.shader"),
+            self.directory.join("fragment.shader"),
+        ];
+        let times = [modified(&paths[0]), modified(&paths[1])];
+        if times == self.loaded {
+            return None;
+        }
+        match (fs::read_to_string(&paths[0]), fs::read_to_string(&paths[1])) {
+            (Ok(vertex), Ok(fragment)) => {
+                info!("Reloaded the shaders of {}", self.directory.display());
+                self.loaded = times;
+                Some((vertex, fragment))
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                error!("Cannot read the shaders: {:?}", e);
+                None
+            }
+        }
+    }
+}
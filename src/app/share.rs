@@ -0,0 +1,199 @@
+use anyhow::{bail, Context as _, Result};
+use eframe::egui::{TextEdit, Ui};
+
+use super::{
+    i18n::{tr, tr_args},
+    State,
+};
+
+/// Start of the links to a view.
+const SCHEME: &str = "fractal://view/";
+/// Version of the encoding of the links. In version 1 the view is the JSON of [State], whose
+/// new fields have defaults, so that the links of older versions keep working.
+const VERSION: u32 = 1;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// URL-safe base64, without padding.
+fn encode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| {
+            bits | (*byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            text.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    text
+}
+
+fn decode(text: &str) -> Result<Vec<u8>> {
+    // A lone character of a group holds less than a byte
+    if text.len() % 4 == 1 {
+        bail!("Truncated data");
+    }
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut bits, mut count) = (0u32, 0);
+    for c in text.bytes() {
+        let value = ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .with_context(|| format!("Invalid character {:?}", c as char))?;
+        bits = bits << 6 | value as u32;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Link to the view `state`, to share it.
+pub fn to_link(state: &State) -> Result<String> {
+    let json = serde_json::to_vec(state)?;
+    Ok(format!("{SCHEME}v{VERSION}/{}", encode(&json)))
+}
+
+/// View of a link of [to_link], the scheme being optional.
+pub fn from_link(link: &str) -> Result<State> {
+    let link = link.trim();
+    let link = link.strip_prefix(SCHEME).unwrap_or(link);
+    let (version, data) = link
+        .strip_prefix('v')
+        .and_then(|link| link.split_once('/'))
+        .context("Not a link to a view")?;
+    let version: u32 = version.parse().context("Not a link to a view")?;
+    let state: State = match version {
+        1 => serde_json::from_slice(&decode(data)?).context("Invalid view")?,
+        _ => bail!(
+            "Link of version {}, from a newer version of the app",
+            version
+        ),
+    };
+    Ok(state.explored())
+}
+
+/// Copy of the view as a link, and opening of the links pasted.
+pub struct Share {
+    pasted: String,
+    error: Option<String>,
+}
+
+impl Share {
+    pub fn new() -> Self {
+        Self {
+            pasted: String::new(),
+            error: None,
+        }
+    }
+
+    /// Returns the view of the link pasted, once valid.
+    pub fn ui(&mut self, ui: &mut Ui, current: &State) -> Option<State> {
+        if ui
+            .button(tr("copy-view-link"))
+            .on_hover_text(tr("copy-view-link-hint"))
+            .clicked()
+        {
+            match to_link(current) {
+                Ok(link) => ui.ctx().copy_text(link),
+                Err(e) => self.error = Some(e.to_string()),
+            }
+        }
+        let mut state = None;
+        if ui
+            .add(TextEdit::singleline(&mut self.pasted).hint_text(tr("paste-link")))
+            .changed()
+        {
+            self.error = None;
+            if !self.pasted.trim().is_empty() {
+                match from_link(&self.pasted) {
+                    Ok(view) => {
+                        state = Some(view);
+                        self.pasted.clear();
+                    }
+                    Err(e) => {
+                        self.error = Some(tr_args("invalid-link", &[("error", &format!("{e:#}"))]))
+                    }
+                }
+            }
+        }
+        if let Some(e) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, e.as_str());
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{position::Position, FractalType};
+
+    #[test]
+    fn lengths() {
+        // Every length modulo 3, the last group having 4, 2 or 3 characters
+        for (bytes, text) in [
+            (&b""[..], ""),
+            (b"f", "Zg"),
+            (b"fo", "Zm8"),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg"),
+            (b"fooba", "Zm9vYmE"),
+            (b"foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(encode(bytes), text);
+            assert_eq!(decode(text).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn url_safe() {
+        let bytes = [0xfb, 0xff, 0xbf];
+        assert_eq!(encode(&bytes), "-_-_");
+        assert_eq!(decode("-_-_").unwrap(), bytes);
+        let all: Vec<u8> = (0..=255).collect();
+        assert_eq!(decode(&encode(&all)).unwrap(), all);
+    }
+
+    #[test]
+    fn invalid_data() {
+        for text in ["Zm9v+A", "Zm9v/A", "Zm9v=", "Zm 9v", "Zm9vé", "Zm9vY"] {
+            assert!(decode(text).is_err(), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn state_round_trip() {
+        let mut state = State::new();
+        state.fractal_type = FractalType::Julia;
+        state.center_position = Position {
+            x: -0.743643887037151,
+            y: 0.131825904205330,
+        };
+        state.zoom = 123456.75;
+        state.max_iterations = 4000;
+        let link = to_link(&state).unwrap();
+        assert!(link.starts_with("fractal://view/v1/"));
+        assert_eq!(from_link(&link).unwrap(), state.explored());
+        // Without the scheme, as some chat apps strip unknown ones
+        let pasted = format!("  {}\n", link.strip_prefix(SCHEME).unwrap());
+        assert_eq!(from_link(&pasted).unwrap(), state.explored());
+    }
+
+    #[test]
+    fn invalid_links() {
+        for link in [
+            "",
+            "https://example.com",
+            "fractal://view/1/e30",
+            "fractal://view/vx/e30",
+            "fractal://view/v2/e30",
+            "fractal://view/v1/e30!",
+            "fractal://view/v1/bm90IGpzb24",
+        ] {
+            assert!(from_link(link).is_err(), "{link:?}");
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use super::{orbit::smooth_iterations, State};
+
+/// The view is sampled on a grid of this many points per side.
+const GRID: usize = 32;
+/// Strongest correction applied, as the ratio between the stretch of the two axes.
+const MAX_ANISOTROPY: f64 = 100.0;
+
+pub const IDENTITY: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+
+/// Matrix, in column-major order, undoing the local stretch of the view.
+///
+/// The gradients of the iteration count over the view give the directions in which the
+/// structures are squeezed. The correction stretches the view in these directions, without
+/// changing its area. `radius` is half the height of the view, in the complex plane.
+pub fn estimate(state: &State, radius: f64) -> [f32; 4] {
+    let [center_x, center_y] = state.point_at([0.0, 0.0]);
+    let spacing = 2.0 * radius / (GRID - 1) as f64;
+    let values: Vec<Option<f64>> = (0..GRID * GRID)
+        .map(|i| {
+            let (column, row) = ((i % GRID) as f64, (i / GRID) as f64);
+            smooth_iterations(
+                state,
+                [
+                    center_x - radius + column * spacing,
+                    center_y - radius + row * spacing,
+                ],
+            )
+        })
+        .collect();
+
+    // Structure tensor: sum of the outer products of the gradients
+    let (mut xx, mut xy, mut yy) = (0.0, 0.0, 0.0);
+    for row in 1..GRID - 1 {
+        for column in 1..GRID - 1 {
+            let at = |column: usize, row: usize| values[row * GRID + column];
+            let (Some(left), Some(right), Some(down), Some(up)) = (
+                at(column - 1, row),
+                at(column + 1, row),
+                at(column, row - 1),
+                at(column, row + 1),
+            ) else {
+                continue;
+            };
+            let (gx, gy) = (right - left, up - down);
+            xx += gx * gx;
+            xy += gx * gy;
+            yy += gy * gy;
+        }
+    }
+
+    // Eigenvalues of the symmetric tensor
+    let half_trace = (xx + yy) / 2.0;
+    let gap = (((xx - yy) / 2.0).powi(2) + xy * xy).sqrt();
+    let (large, small) = (half_trace + gap, half_trace - gap);
+    if small.is_nan() || small <= 0.0 || !large.is_finite() {
+        return IDENTITY;
+    }
+    let small = small.max(large / (MAX_ANISOTROPY * MAX_ANISOTROPY));
+
+    // Unit eigenvector of the large eigenvalue
+    let (vx, vy) = if xy.abs() > f64::EPSILON * large {
+        (large - yy, xy)
+    } else if xx >= yy {
+        (1.0, 0.0)
+    } else {
+        (0.0, 1.0)
+    };
+    let norm = vx.hypot(vy);
+    let (vx, vy) = (vx / norm, vy / norm);
+
+    // tensor^(-1/2), scaled to keep the area
+    let scale = (large * small).powf(0.25);
+    let (a, b) = (scale / large.sqrt(), scale / small.sqrt());
+    let m00 = a * vx * vx + b * vy * vy;
+    let m01 = (a - b) * vx * vy;
+    let m11 = a * vy * vy + b * vx * vx;
+    [m00 as f32, m01 as f32, m01 as f32, m11 as f32]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{position::Position, FractalType};
+
+    fn state_at(x: f64, y: f64) -> State {
+        let mut state = State::new();
+        state.fractal_type = FractalType::Mandelbrot;
+        state.center_position = Position { x: -x, y: -y };
+        state
+    }
+
+    #[test]
+    fn no_correction_inside_the_set() {
+        assert_eq!(estimate(&state_at(-0.1, 0.0), 0.01), IDENTITY);
+    }
+
+    #[test]
+    fn correction_keeps_the_area() {
+        // Along the antenna, the structures are squeezed
+        let [m00, m01, m10, m11] = estimate(&state_at(-1.9, 0.0), 0.01);
+        assert_eq!(m01, m10);
+        assert!((m00 * m11 - m01 * m10 - 1.0).abs() < 1e-4);
+        assert_ne!([m00, m01, m10, m11], IDENTITY);
+    }
+}
@@ -0,0 +1,105 @@
+use std::path::PathBuf;
+
+use anyhow::{ensure, Result};
+use clap::Parser;
+use log::info;
+
+use super::{
+    preset,
+    render_cli::{parse_complex, Fractal},
+    GpuPreference, Position, State,
+};
+
+/// Options of the app, to open it at a given view from launchers and scripts. See
+/// `fractal_gl render --help` to render an image without a window.
+#[derive(Parser, Default)]
+#[command(name = "fractal_gl")]
+pub struct StartupArgs {
+    /// GPU to render with: default, discrete or integrated. The one of the settings if not
+    /// given.
+    #[arg(long, value_parser = GpuPreference::parse)]
+    pub gpu: Option<GpuPreference>,
+    /// Recording of a session to play from the start.
+    #[arg(long)]
+    pub replay: Option<PathBuf>,
+    /// Reload the shaders of the source tree when they change.
+    #[arg(long)]
+    pub dev: bool,
+    /// Preset file to open, the other options of the view apply over it.
+    #[arg(long)]
+    preset: Option<PathBuf>,
+    #[arg(long, value_enum)]
+    fractal: Option<Fractal>,
+    /// Point at the center of the view, as `re,im`.
+    #[arg(long, value_parser = parse_complex, allow_hyphen_values = true)]
+    center: Option<[f64; 2]>,
+    /// Magnification as in Kalles Fraktaler, like `fractal_gl render --zoom`: at 1 the view
+    /// is 4 units high.
+    #[arg(long)]
+    zoom: Option<f64>,
+    /// Constant of the Julia set, as `re,im`.
+    #[arg(long, value_parser = parse_complex, allow_hyphen_values = true)]
+    julia: Option<[f64; 2]>,
+    /// Start in fullscreen.
+    #[arg(long)]
+    pub fullscreen: bool,
+}
+
+impl StartupArgs {
+    /// Open the view asked for over `state`, the one of the last session. The zoom depends on
+    /// the size of the canvas, it is left to [StartupArgs::magnification].
+    pub fn apply(&self, state: &mut State) -> Result<()> {
+        if let Some(path) = &self.preset {
+            let (name, preset) = preset::load(path)?;
+            info!("Starting with the preset {}", name);
+            *state = preset;
+        }
+        if let Some(fractal) = self.fractal {
+            state.fractal_type = fractal.into();
+        }
+        if let Some([re, im]) = self.center {
+            state.center_position = Position { x: -re, y: -im };
+        }
+        if let Some(zoom) = self.zoom {
+            ensure!(zoom.is_finite() && zoom > 0.0, "Invalid zoom {zoom}");
+        }
+        if let Some([re, im]) = self.julia {
+            state.c_julia.x = re as f32;
+            state.c_julia.y = im as f32;
+        }
+        Ok(())
+    }
+
+    /// Magnification asked for, see [State::magnification].
+    pub fn magnification(&self) -> Option<f64> {
+        self.zoom
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> StartupArgs {
+        StartupArgs::try_parse_from(["fractal_gl"].iter().chain(args)).unwrap()
+    }
+
+    #[test]
+    fn zoom_is_a_magnification() {
+        let args = parse(&["--zoom", "1e3", "--center", "-0.75,0.1"]);
+        let mut state = State::new();
+        args.apply(&mut state).unwrap();
+        // Left to the first frame, once the height of the canvas is known
+        assert_eq!(state.zoom, State::new().zoom);
+        assert_eq!(state.point_at([0.0, 0.0]), [-0.75, 0.1]);
+        assert_eq!(args.magnification(), Some(1e3));
+    }
+
+    #[test]
+    fn invalid_zoom() {
+        for zoom in ["--zoom=0", "--zoom=-2", "--zoom=inf"] {
+            let args = parse(&[zoom]);
+            assert!(args.apply(&mut State::new()).is_err(), "{zoom}");
+        }
+    }
+}
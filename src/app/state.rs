@@ -1,15 +1,390 @@
+use std::{
+    fmt::{self, Write as _},
+    str::FromStr,
+};
+
+use anyhow::{bail, ensure};
 use emath::Vec2;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    fractal_gl::FRACTAL_PARAMETERS,
+    i18n::{tr, tr_args},
+    position::Position,
+    settings,
+};
 
-use super::position::Position;
+/// Name of the setting holding the view of the last session.
+const SESSION_SETTING: &str = "session.json";
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum FractalType {
     // Keep in sync with the fragment shader
     Julia = 0,
     Mandelbrot = 1,
+    /// Mandelbrot-like, iterating the function of the custom fractal file from z = 0.
+    Custom = 2,
+    /// Mandelbrot-like, with the absolute values of the parts of z before squaring it.
+    BurningShip = 3,
+    /// Mandelbrot-like, with the conjugate of z before squaring it. Also called Mandelbar.
+    Tricorn = 4,
+    /// Basins of attraction of the roots of a cubic polynomial under Newton's method.
+    Newton = 5,
+    /// Lyapunov exponent of the logistic map, with the growth rates of
+    /// [State::lyapunov_sequence] taken from the x and y coordinates.
+    Lyapunov = 6,
+    /// Mandelbrot-like Newton's method on z³ - 1, relaxed by [State::nova_relaxation], plus c.
+    Nova = 7,
+}
+
+impl FractalType {
+    /// Types built in the shader, the custom one aside.
+    pub const BUILTIN: [FractalType; 7] = [
+        FractalType::Julia,
+        FractalType::Mandelbrot,
+        FractalType::BurningShip,
+        FractalType::Tricorn,
+        FractalType::Newton,
+        FractalType::Lyapunov,
+        FractalType::Nova,
+    ];
+
+    pub fn label(self) -> String {
+        tr(match self {
+            FractalType::Julia => "fractal-julia",
+            FractalType::Mandelbrot => "fractal-mandelbrot",
+            FractalType::Custom => "fractal-custom",
+            FractalType::BurningShip => "fractal-burning-ship",
+            FractalType::Tricorn => "fractal-tricorn",
+            FractalType::Newton => "fractal-newton",
+            FractalType::Lyapunov => "fractal-lyapunov",
+            FractalType::Nova => "fractal-nova",
+        })
+    }
+
+    /// Iterations of z^n + c, with the power [State::exponent].
+    pub fn is_power(self) -> bool {
+        matches!(
+            self,
+            FractalType::Julia
+                | FractalType::Mandelbrot
+                | FractalType::BurningShip
+                | FractalType::Tricorn
+        )
+    }
+
+    /// The shader estimates the distance of the points to the boundary of the set, see
+    /// [BoundaryStyle].
+    pub fn has_distance_estimate(self) -> bool {
+        self.is_power()
+    }
+
+    /// The orbits are the ones of z² + c, for the analysis of the hyperbolic components.
+    pub fn is_quadratic(self) -> bool {
+        matches!(self, FractalType::Julia | FractalType::Mandelbrot)
+    }
+}
+
+/// How the orbit of a point is turned into the value that gets colored.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ColoringAlgorithm {
+    // Keep in sync with the fragment shader
+    Iterations = 0,
+    StripeAverage = 1,
+    TriangleInequality = 2,
+    CurvatureAverage = 3,
+    BinaryDecomposition = 4,
+    /// Interior points colored by the period of their attracting cycle.
+    Period = 5,
+    /// Iteration where the orbit comes the closest to 0.
+    AtomDomain = 6,
+    /// Color of an image, at the first point of the orbit landing on it.
+    ImageTrap = 7,
+    /// Texture mapped on the exterior by the iteration count and the angle of the last z.
+    TextureMap = 8,
+    /// Distance of the orbit to the closest it comes to a point, a line or a ring.
+    OrbitTrap = 9,
+}
+
+impl ColoringAlgorithm {
+    pub const ALL: [ColoringAlgorithm; 10] = [
+        ColoringAlgorithm::Iterations,
+        ColoringAlgorithm::StripeAverage,
+        ColoringAlgorithm::TriangleInequality,
+        ColoringAlgorithm::CurvatureAverage,
+        ColoringAlgorithm::BinaryDecomposition,
+        ColoringAlgorithm::Period,
+        ColoringAlgorithm::AtomDomain,
+        ColoringAlgorithm::ImageTrap,
+        ColoringAlgorithm::TextureMap,
+        ColoringAlgorithm::OrbitTrap,
+    ];
+
+    pub fn label(self) -> String {
+        tr(match self {
+            ColoringAlgorithm::Iterations => "algorithm-iteration-count",
+            ColoringAlgorithm::StripeAverage => "algorithm-stripe-average",
+            ColoringAlgorithm::TriangleInequality => "algorithm-triangle-inequality-average",
+            ColoringAlgorithm::CurvatureAverage => "algorithm-curvature-average",
+            ColoringAlgorithm::BinaryDecomposition => "algorithm-binary-decomposition",
+            ColoringAlgorithm::Period => "algorithm-interior-period",
+            ColoringAlgorithm::AtomDomain => "algorithm-atom-domains",
+            ColoringAlgorithm::ImageTrap => "algorithm-image-trap",
+            ColoringAlgorithm::TextureMap => "algorithm-texture-map",
+            ColoringAlgorithm::OrbitTrap => "algorithm-orbit-trap",
+        })
+    }
+}
+
+/// How the iteration count of the escaping points is turned into the value that gets colored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum IterationColoring {
+    // Keep in sync with the fragment shader
+    /// Continuous count, in high quality.
+    #[default]
+    Smooth = 0,
+    /// Whole count, one band of color per iteration.
+    Bands = 1,
+    /// Count remapped so that every color covers as many pixels of the view.
+    Histogram = 2,
+}
+
+impl IterationColoring {
+    pub const ALL: [IterationColoring; 3] = [
+        IterationColoring::Smooth,
+        IterationColoring::Bands,
+        IterationColoring::Histogram,
+    ];
+
+    pub fn label(self) -> String {
+        tr(match self {
+            IterationColoring::Smooth => "iteration-coloring-smooth",
+            IterationColoring::Bands => "iteration-coloring-bands",
+            IterationColoring::Histogram => "iteration-coloring-histogram",
+        })
+    }
+}
+
+/// Shape the orbit is measured against, for the orbit trap coloring.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum TrapShape {
+    // Keep in sync with the fragment shader
+    #[default]
+    Point = 0,
+    /// Line through the trap center, turned by [State::trap_rotation].
+    Line = 1,
+    /// Circle of radius [State::trap_size] around the trap center.
+    Ring = 2,
+}
+
+impl TrapShape {
+    pub const ALL: [TrapShape; 3] = [TrapShape::Point, TrapShape::Line, TrapShape::Ring];
+
+    pub fn label(self) -> String {
+        tr(match self {
+            TrapShape::Point => "trap-point",
+            TrapShape::Line => "trap-line",
+            TrapShape::Ring => "trap-ring",
+        })
+    }
 }
 
-#[derive(Clone, Copy, Debug)]
+/// How the distance estimate to the boundary of the set is drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum BoundaryStyle {
+    // Keep in sync with the fragment shader
+    #[default]
+    Off = 0,
+    /// The exterior darkens close to the boundary, crisp and anti-aliased at any zoom.
+    Edges = 1,
+    /// Only the boundary, in black on white.
+    LineArt = 2,
+}
+
+impl BoundaryStyle {
+    pub const ALL: [BoundaryStyle; 3] = [
+        BoundaryStyle::Off,
+        BoundaryStyle::Edges,
+        BoundaryStyle::LineArt,
+    ];
+
+    pub fn label(self) -> String {
+        tr(match self {
+            BoundaryStyle::Off => "boundary-off",
+            BoundaryStyle::Edges => "boundary-edges",
+            BoundaryStyle::LineArt => "boundary-line-art",
+        })
+    }
+}
+
+fn default_boundary_width() -> f32 {
+    1.0
+}
+
+fn default_exponent() -> f32 {
+    2.0
+}
+
+fn default_trap_size() -> f32 {
+    0.5
+}
+
+/// Budget of the renderer before it could be changed.
+fn default_max_iterations() -> u32 {
+    4096
+}
+
+/// Zoom of the whole fractal, where the automatic iteration budget starts increasing.
+const AUTO_ITERATIONS_ZOOM: f32 = 1000.0;
+/// Highest iteration budget.
+pub const MAX_ITERATIONS: u32 = 1 << 18;
+
+/// Cube roots of unity.
+pub fn default_newton_roots() -> [[f32; 2]; 3] {
+    let sine = 3f32.sqrt() / 2.0;
+    [[1.0, 0.0], [-0.5, sine], [-0.5, -sine]]
+}
+
+/// Longest [LyapunovSequence].
+pub const MAX_LYAPUNOV_LENGTH: usize = 32;
+
+/// Growth rates of the Lyapunov fractal in turn: A is the x coordinate of the point, B its y
+/// coordinate. Saved as a string of A and B.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct LyapunovSequence {
+    /// Bit i is set when the rate i is B, keep in sync with the fragment shader.
+    bits: u32,
+    length: u8,
+}
+
+impl LyapunovSequence {
+    pub fn bits(self) -> u32 {
+        self.bits
+    }
+
+    pub fn len(self) -> usize {
+        self.length as usize
+    }
+}
+
+impl Default for LyapunovSequence {
+    fn default() -> Self {
+        "AB".parse().unwrap()
+    }
+}
+
+impl FromStr for LyapunovSequence {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        ensure!(!text.is_empty(), tr("lyapunov-sequence-empty"));
+        ensure!(
+            text.len() <= MAX_LYAPUNOV_LENGTH,
+            tr_args(
+                "lyapunov-sequence-too-long",
+                &[("max", &MAX_LYAPUNOV_LENGTH)]
+            )
+        );
+        let mut bits = 0;
+        for (i, rate) in text.chars().enumerate() {
+            match rate.to_ascii_uppercase() {
+                'A' => {}
+                'B' => bits |= 1 << i,
+                _ => bail!(tr_args("lyapunov-sequence-invalid", &[("rate", &rate)])),
+            }
+        }
+        Ok(Self {
+            bits,
+            length: text.len() as u8,
+        })
+    }
+}
+
+impl TryFrom<String> for LyapunovSequence {
+    type Error = anyhow::Error;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl fmt::Display for LyapunovSequence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for i in 0..self.len() {
+            f.write_char(if self.bits & (1 << i) != 0 { 'B' } else { 'A' })?;
+        }
+        Ok(())
+    }
+}
+
+impl From<LyapunovSequence> for String {
+    fn from(sequence: LyapunovSequence) -> Self {
+        sequence.to_string()
+    }
+}
+
+/// Longest [PluginId], in bytes.
+pub const MAX_PLUGIN_ID: usize = 32;
+
+/// Plugin of a view, by the name of its file without the extension. Saved as a string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PluginId {
+    bytes: [u8; MAX_PLUGIN_ID],
+    length: u8,
+}
+
+impl FromStr for PluginId {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        ensure!(!text.is_empty(), "The plugin name is empty");
+        ensure!(
+            text.len() <= MAX_PLUGIN_ID,
+            "The plugin name has more than {MAX_PLUGIN_ID} bytes"
+        );
+        let mut bytes = [0; MAX_PLUGIN_ID];
+        bytes[..text.len()].copy_from_slice(text.as_bytes());
+        Ok(Self {
+            bytes,
+            length: text.len() as u8,
+        })
+    }
+}
+
+impl TryFrom<String> for PluginId {
+    type Error = anyhow::Error;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl fmt::Display for PluginId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Whole characters, copied from a string
+        f.write_str(std::str::from_utf8(&self.bytes[..self.length as usize]).unwrap_or_default())
+    }
+}
+
+impl From<PluginId> for String {
+    fn from(id: PluginId) -> Self {
+        id.to_string()
+    }
+}
+
+fn default_nova_relaxation() -> f32 {
+    1.0
+}
+
+fn default_cycling_speed() -> f32 {
+    0.1
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct State {
     pub center_position: Position,
     pub c_julia: Vec2,
@@ -20,8 +395,94 @@ pub struct State {
     pub g: f32,
     pub b: f32,
     pub gamma: f32,
+    /// Offsets of the shadows, midtones and highlights color wheels, inside the unit disk.
+    pub color_balance: [[f32; 2]; 3],
     pub high_quality: bool,
+    /// Samples per pixel along each axis, above 1 only for the refined region.
+    pub supersampling: i32,
+    /// Draw only the disk inscribed in the viewport, for the magnifier lens.
+    pub circular: bool,
+    /// Color with the palette texture instead of the R/G/B multipliers.
+    pub use_palette: bool,
+    /// Color with the function built in the coloring graph editor.
+    pub custom_coloring: bool,
+    /// Transform the iteration value with the user formula before coloring.
+    pub value_formula: bool,
+    pub coloring_algorithm: ColoringAlgorithm,
+    #[serde(default)]
+    pub iteration_coloring: IterationColoring,
+    /// Number of stripes per turn around the origin, for the stripe average.
+    pub stripe_density: f32,
+    /// Blend between the iteration count (0) and the stripe average (1).
+    pub stripe_mix: f32,
+    /// Blend between the iteration count (0) and the triangle inequality average (1).
+    pub tia_blend: f32,
+    /// Blend between the iteration count (0) and the curvature average (1).
+    pub curvature_mix: f32,
+    /// Blend between the iteration count (0) and the binary decomposition (1).
+    pub decomposition_mix: f32,
+    /// Darken along the approximate external field lines, with the binary decomposition.
+    pub field_lines: bool,
+    /// Width of the trap image in the complex plane, for the image trap.
+    pub trap_scale: f32,
+    /// Rotation of the trap image around the origin, and angle of the line trap, in radians.
+    pub trap_rotation: f32,
+    #[serde(default)]
+    pub trap_shape: TrapShape,
+    /// Center of the point and ring traps, and point of the line trap.
+    #[serde(default)]
+    pub trap_center: [f32; 2],
+    /// Radius of the ring trap.
+    #[serde(default = "default_trap_size")]
+    pub trap_size: f32,
+    #[serde(default)]
+    pub boundary_style: BoundaryStyle,
+    /// Width in pixels of the boundary drawn by [State::boundary_style].
+    #[serde(default = "default_boundary_width")]
+    pub boundary_width: f32,
+    /// Shift the colors along the palette with the time.
+    #[serde(default)]
+    pub color_cycling: bool,
+    /// Palettes gone through per second by [State::color_cycling], backwards when negative.
+    #[serde(default = "default_cycling_speed")]
+    pub cycling_speed: f32,
+    /// Blend between the colors (0) and the exterior texture (1).
+    pub texture_blend: f32,
+    /// Mirror every other repetition of the exterior texture, so that it has no seams.
+    pub texture_mirror: bool,
     pub fractal_type: FractalType,
+    /// Power n of z^n + c, 2 for the usual sets, see [FractalType::is_power].
+    #[serde(default = "default_exponent")]
+    pub exponent: f32,
+    #[serde(default)]
+    pub lyapunov_sequence: LyapunovSequence,
+    /// Plugin iterating the custom fractal type, the custom fractal file if none.
+    #[serde(default)]
+    pub plugin: Option<PluginId>,
+    /// Values of the parameters of [State::plugin], in the order of its metadata.
+    #[serde(default)]
+    pub plugin_parameters: [f32; FRACTAL_PARAMETERS],
+    /// Multiplier of the Newton step of the Nova fractal, 1 for Newton's method itself.
+    #[serde(default = "default_nova_relaxation")]
+    pub nova_relaxation: f32,
+    /// Roots of the polynomial of the Newton fractal.
+    #[serde(default = "default_newton_roots")]
+    pub newton_roots: [[f32; 2]; 3],
+    /// Seconds since the start of the application, for the animations.
+    pub time: f32,
+    /// Rotation of the view around its center, in radians, clockwise on the screen.
+    #[serde(default)]
+    pub rotation: f32,
+    /// Linear transform of the view around its center, as a column-major 2x2 matrix.
+    pub skew: [f32; 4],
+    /// Keep [State::skew] corrected for the local stretch of the view.
+    pub auto_skew: bool,
+    /// Iteration budget in high quality, a quarter of it otherwise.
+    #[serde(default = "default_max_iterations")]
+    pub max_iterations: u32,
+    /// Raise the iteration budget with the zoom, see [State::iterations].
+    #[serde(default)]
+    pub auto_iterations: bool,
 }
 
 impl State {
@@ -36,8 +497,193 @@ impl State {
             g: 0.40,
             b: 1.0,
             gamma: 1.25,
+            color_balance: [[0.0; 2]; 3],
             high_quality: true,
+            supersampling: 1,
+            circular: false,
+            use_palette: false,
+            custom_coloring: false,
+            value_formula: false,
+            coloring_algorithm: ColoringAlgorithm::Iterations,
+            iteration_coloring: IterationColoring::Smooth,
+            stripe_density: 5.0,
+            stripe_mix: 1.0,
+            tia_blend: 1.0,
+            curvature_mix: 1.0,
+            decomposition_mix: 0.5,
+            field_lines: false,
+            trap_scale: 1.0,
+            trap_rotation: 0.0,
+            trap_shape: TrapShape::Point,
+            trap_center: [0.0, 0.0],
+            trap_size: default_trap_size(),
+            boundary_style: BoundaryStyle::Off,
+            boundary_width: default_boundary_width(),
+            color_cycling: false,
+            cycling_speed: default_cycling_speed(),
+            texture_blend: 1.0,
+            texture_mirror: true,
             fractal_type: FractalType::Julia,
+            exponent: default_exponent(),
+            lyapunov_sequence: LyapunovSequence::default(),
+            plugin: None,
+            plugin_parameters: [0.0; FRACTAL_PARAMETERS],
+            nova_relaxation: default_nova_relaxation(),
+            newton_roots: default_newton_roots(),
+            time: 0.0,
+            rotation: 0.0,
+            skew: [1.0, 0.0, 0.0, 1.0],
+            auto_skew: false,
+            max_iterations: default_max_iterations(),
+            auto_iterations: false,
+        }
+    }
+
+    /// View and colors of the last session, if saved.
+    pub fn load() -> Option<State> {
+        let content = settings::load(SESSION_SETTING)?;
+        match serde_json::from_str::<State>(&content) {
+            Ok(state) => Some(state.explored()),
+            Err(e) => {
+                error!("Cannot load the last session: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Only what is explored: without the time, the supersampling of the refined region and
+    /// the circle of the lens.
+    pub fn explored(self) -> Self {
+        State {
+            time: 0.0,
+            supersampling: 1,
+            circular: false,
+            ..self
+        }
+    }
+
+    /// Back to the whole fractal of [State::new], unturned and unskewed.
+    pub fn reset_view(&mut self) {
+        let default = State::new();
+        self.center_position = default.center_position;
+        self.zoom = default.zoom;
+        self.rotation = default.rotation;
+        self.skew = default.skew;
+    }
+
+    /// Defaults of the global parameters: the view, the quality, the iterations and the
+    /// exponent, the fractal type is kept.
+    pub fn reset_global(&mut self) {
+        let default = State::new();
+        self.reset_view();
+        self.high_quality = default.high_quality;
+        self.auto_skew = default.auto_skew;
+        self.max_iterations = default.max_iterations;
+        self.auto_iterations = default.auto_iterations;
+        self.exponent = default.exponent;
+    }
+
+    /// Default Julia constant.
+    pub fn reset_julia(&mut self) {
+        self.c_julia = State::new().c_julia;
+    }
+
+    /// Default colors and coloring, the view and the fractal are kept.
+    pub fn reset_colors(&mut self) {
+        *self = State {
+            center_position: self.center_position,
+            c_julia: self.c_julia,
+            zoom: self.zoom,
+            high_quality: self.high_quality,
+            supersampling: self.supersampling,
+            circular: self.circular,
+            custom_coloring: self.custom_coloring,
+            value_formula: self.value_formula,
+            fractal_type: self.fractal_type,
+            exponent: self.exponent,
+            lyapunov_sequence: self.lyapunov_sequence,
+            plugin: self.plugin,
+            plugin_parameters: self.plugin_parameters,
+            nova_relaxation: self.nova_relaxation,
+            newton_roots: self.newton_roots,
+            time: self.time,
+            rotation: self.rotation,
+            skew: self.skew,
+            auto_skew: self.auto_skew,
+            max_iterations: self.max_iterations,
+            auto_iterations: self.auto_iterations,
+            ..State::new()
+        };
+    }
+
+    /// Save the state, for [State::load] on the next start.
+    pub fn save(&self) {
+        let saved = serde_json::to_string_pretty(self)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| settings::save(SESSION_SETTING, &content));
+        if let Err(e) = saved {
+            error!("Cannot save the session: {:?}", e);
+        }
+    }
+
+    /// Magnification of the view as in Kalles Fraktaler: at magnification 1 the view is
+    /// 4 units high.
+    pub fn magnification(&self, viewport_height_px: f32) -> f64 {
+        self.zoom as f64 * 4.0 / viewport_height_px as f64
+    }
+
+    /// Size of a physical pixel in the complex plane.
+    pub fn pixel_size(&self) -> f64 {
+        1.0 / self.zoom as f64
+    }
+
+    /// The [State::skew] after the [State::rotation], as a column-major 2x2 matrix. The
+    /// shader builds the same one.
+    pub fn transform(&self) -> [f32; 4] {
+        let (sin, cos) = self.rotation.sin_cos();
+        let s = self.skew;
+        [
+            s[0] * cos + s[2] * sin,
+            s[1] * cos + s[3] * sin,
+            -s[0] * sin + s[2] * cos,
+            -s[1] * sin + s[3] * cos,
+        ]
+    }
+
+    /// Point of the complex plane `offset_px` physical pixels away from the center of the
+    /// view, with y pointing down like on the screen.
+    pub fn point_at(&self, offset_px: [f32; 2]) -> [f64; 2] {
+        let (x, y) = (offset_px[0] / self.zoom, -offset_px[1] / self.zoom);
+        let s = self.transform();
+        [
+            -self.center_position.x + (s[0] * x + s[2] * y) as f64,
+            -self.center_position.y + (s[1] * x + s[3] * y) as f64,
+        ]
+    }
+
+    /// Inverse of [State::point_at]: offset of `point` from the center of the view, in
+    /// physical pixels.
+    pub fn offset_of(&self, point: [f64; 2]) -> [f32; 2] {
+        let x = (point[0] + self.center_position.x) as f32;
+        let y = (point[1] + self.center_position.y) as f32;
+        let s = self.transform();
+        let determinant = s[0] * s[3] - s[2] * s[1];
+        let unskewed_x = (s[3] * x - s[2] * y) / determinant;
+        let unskewed_y = (s[0] * y - s[1] * x) / determinant;
+        [unskewed_x * self.zoom, -unskewed_y * self.zoom]
+    }
+
+    /// Maximum number of iterations used by the fragment shader. In automatic mode
+    /// [State::max_iterations] doubles with every tenfold zoom past the whole fractal, the
+    /// details of deep zooms need longer orbits to escape.
+    pub fn iterations(&self) -> u32 {
+        let mut iterations = self.max_iterations as f32;
+        if self.auto_iterations {
+            iterations *= 2f32.powf((self.zoom / AUTO_ITERATIONS_ZOOM).log10().max(0.0));
+        }
+        if !self.high_quality {
+            iterations /= 4.0;
         }
+        (iterations as u32).clamp(16, MAX_ITERATIONS)
     }
 }
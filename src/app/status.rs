@@ -0,0 +1,123 @@
+use std::{collections::VecDeque, path::PathBuf, time::Duration};
+
+use eframe::egui::{RichText, Ui};
+use log::{error, info};
+use web_time::Instant;
+
+use super::{accessibility::icon_button, cannot_open, external, i18n::tr};
+
+/// How long a message stays in the status bar.
+const DISPLAY_DURATION: Duration = Duration::from_secs(8);
+/// Messages kept for the list of the last ones.
+const HISTORY_LENGTH: usize = 20;
+
+struct Message {
+    text: String,
+    /// Cause of an error, shown on hover.
+    details: Option<String>,
+    /// File written by an export, to open or reveal.
+    file: Option<PathBuf>,
+    time: Instant,
+}
+
+/// Messages of the status bar: the errors which don't stop the app, and the end of the long
+/// operations. They also go to the log.
+#[derive(Default)]
+pub struct Status {
+    messages: VecDeque<Message>,
+    /// The last message was closed, or has been shown long enough.
+    dismissed: bool,
+}
+
+impl Status {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&mut self, text: String, details: Option<String>, file: Option<PathBuf>) {
+        if self.messages.len() == HISTORY_LENGTH {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(Message {
+            text,
+            details,
+            file,
+            time: Instant::now(),
+        });
+        self.dismissed = false;
+    }
+
+    /// Progress or outcome of an operation.
+    pub fn info(&mut self, text: String) {
+        info!("{}", text);
+        self.push(text, None, None);
+    }
+
+    /// End of an export to `file`, with buttons to open it or reveal it in its folder.
+    pub fn exported(&mut self, text: String, file: PathBuf) {
+        info!("{}", text);
+        self.push(text, None, Some(file));
+    }
+
+    /// Failure of an operation, `text` tells which one.
+    pub fn error(&mut self, text: String, error: &anyhow::Error) {
+        error!("{}: {:?}", text, error);
+        self.push(text, Some(format!("{:#}", error)), None);
+    }
+
+    /// The last message, until it is closed or expires, and the list of the previous ones.
+    pub fn ui(&mut self, ui: &mut Ui) {
+        if self.messages.is_empty() {
+            return;
+        }
+        ui.separator();
+        ui.menu_button("🗒", |ui| {
+            for message in self.messages.iter().rev() {
+                let response = ui.label(message_text(ui, message));
+                if let Some(details) = &message.details {
+                    response.on_hover_text(details);
+                }
+            }
+        })
+        .response
+        .on_hover_text(tr("status-history"));
+
+        let Some(message) = self.messages.back() else {
+            return;
+        };
+        let elapsed = message.time.elapsed();
+        if self.dismissed || elapsed >= DISPLAY_DURATION {
+            self.dismissed = true;
+            return;
+        }
+        let response = ui.label(message_text(ui, message));
+        if let Some(details) = &message.details {
+            response.on_hover_text(details);
+        }
+        let mut opened = None;
+        if let Some(file) = &message.file {
+            if ui.small_button(tr("open")).clicked() {
+                opened = Some((file.clone(), external::open(file)));
+            }
+            if ui.small_button(tr("reveal-in-folder")).clicked() {
+                opened = Some((file.clone(), external::reveal(file)));
+            }
+        }
+        if icon_button(ui.small_button("✖"), tr("close-message")).clicked() {
+            self.dismissed = true;
+        }
+        ui.ctx().request_repaint_after(DISPLAY_DURATION - elapsed);
+        if let Some((file, Err(e))) = opened {
+            self.error(cannot_open(&file), &e);
+        }
+    }
+}
+
+fn message_text(ui: &Ui, message: &Message) -> RichText {
+    let text = RichText::new(&message.text);
+    if message.details.is_some() {
+        text.color(ui.visuals().error_fg_color)
+    } else {
+        text
+    }
+}
@@ -0,0 +1,107 @@
+use std::mem;
+
+use eframe::egui::Ui;
+
+use super::{
+    accessibility::icon_button,
+    i18n::{tr, tr_args},
+    History, State,
+};
+
+/// A view with its own history of changes.
+struct Tab {
+    state: State,
+    history: History,
+}
+
+/// Views open side by side in tabs, all drawn by the same renderer. The active one lives in
+/// the app, its entry only holds it while another tab is active.
+pub struct Tabs {
+    tabs: Vec<Tab>,
+    active: usize,
+}
+
+impl Tabs {
+    pub fn new() -> Self {
+        Self {
+            tabs: vec![Tab {
+                state: State::new(),
+                history: History::new(),
+            }],
+            active: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    /// Store the active view, `state` and `history`, and take the one of the tab `index`.
+    fn switch(&mut self, index: usize, state: &mut State, history: &mut History) {
+        let tab = &mut self.tabs[self.active];
+        mem::swap(&mut tab.state, state);
+        mem::swap(&mut tab.history, history);
+        self.active = index;
+        let tab = &mut self.tabs[index];
+        mem::swap(&mut tab.state, state);
+        mem::swap(&mut tab.history, history);
+    }
+
+    /// Open a copy of the active view `state` in a new tab, and switch to it.
+    pub fn open(&mut self, state: &mut State, history: &mut History) {
+        self.tabs.push(Tab {
+            state: *state,
+            history: History::new(),
+        });
+        self.switch(self.tabs.len() - 1, state, history);
+    }
+
+    fn close(&mut self, index: usize, state: &mut State, history: &mut History) {
+        if index == self.active {
+            let next = if index + 1 < self.tabs.len() {
+                index + 1
+            } else {
+                index - 1
+            };
+            self.switch(next, state, history);
+        }
+        self.tabs.remove(index);
+        if self.active > index {
+            self.active -= 1;
+        }
+    }
+
+    /// Buttons of the tabs, true when the active view changed.
+    pub fn ui(&mut self, ui: &mut Ui, state: &mut State, history: &mut History) -> bool {
+        let mut selected = None;
+        let mut closed = None;
+        let mut opened = false;
+        ui.horizontal(|ui| {
+            for index in 0..self.tabs.len() {
+                let name = tr_args("tab", &[("index", &(index + 1))]);
+                if ui.selectable_label(index == self.active, name).clicked() {
+                    selected = Some(index);
+                }
+                if self.tabs.len() > 1
+                    && icon_button(ui.small_button("✖"), tr("close-tab")).clicked()
+                {
+                    closed = Some(index);
+                }
+                ui.separator();
+            }
+            opened = icon_button(ui.button("➕"), tr("new-tab")).clicked();
+        });
+        if opened {
+            self.open(state, history);
+            true
+        } else if let Some(index) = closed {
+            self.close(index, state, history);
+            true
+        } else if let Some(index) = selected.filter(|index| *index != self.active) {
+            self.switch(index, state, history);
+            true
+        } else {
+            false
+        }
+    }
+}
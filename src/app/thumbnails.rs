@@ -0,0 +1,228 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result};
+use eframe::egui::{ColorImage, Context, TextureHandle, TextureOptions, Vec2};
+use image::ExtendedColorType;
+use log::warn;
+
+use super::{fractal_gl::Image, settings, user_image, FractalGl, Palette, State};
+
+/// Size of the thumbnails, in pixels.
+pub const THUMBNAIL_SIZE: [i32; 2] = [96, 64];
+
+/// Number of thumbnails kept on the disk, the oldest ones are removed past it.
+const MAX_CACHED: usize = 1000;
+
+/// 64 bits FNV-1a. Unlike the hashers of the standard library, its values are the same
+/// from one run and one version of Rust to the next, as the names of the cached files need.
+pub struct StableHasher {
+    hash: u64,
+}
+
+impl StableHasher {
+    pub fn new() -> Self {
+        Self {
+            hash: 0xcbf2_9ce4_8422_2325,
+        }
+    }
+
+    /// Add `bytes`, prefixed by their length so that consecutive writes cannot be confused.
+    pub fn write(&mut self, bytes: &[u8]) {
+        for byte in (bytes.len() as u64).to_le_bytes().iter().chain(bytes) {
+            self.hash = (self.hash ^ *byte as u64).wrapping_mul(0x100_0000_01b3);
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// Identifies the look of the renders of `fractal`, see [FractalGl::hash_look].
+pub fn look(fractal: &FractalGl) -> u64 {
+    let mut hasher = StableHasher::new();
+    fractal.hash_look(&mut hasher);
+    hasher.finish()
+}
+
+/// Identifies the render of the view `state` with the [look] `look`, the same from one run
+/// to the next.
+pub fn key(state: &State, look: u64) -> u64 {
+    let mut hasher = StableHasher::new();
+    hasher.write(serde_json::to_string(state).unwrap_or_default().as_bytes());
+    hasher.write(&look.to_le_bytes());
+    hasher.finish()
+}
+
+/// File of the thumbnail kept between the runs, `None` on the web.
+fn cache_path(key: u64) -> Option<PathBuf> {
+    settings::path(&format!("thumbnails/{key:016x}.png"))
+}
+
+/// Remove the oldest thumbnails of `directory` past [MAX_CACHED], those of the views and
+/// the looks no longer used.
+fn prune_cache(directory: &Path) -> Result<()> {
+    let mut files: Vec<_> = fs::read_dir(directory)
+        .with_context(|| format!("Cannot list {}", directory.display()))?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            Some((entry.metadata().ok()?.modified().ok()?, entry.path()))
+        })
+        .collect();
+    if files.len() <= MAX_CACHED {
+        return Ok(());
+    }
+    files.sort();
+    for (_, path) in &files[..files.len() - MAX_CACHED] {
+        fs::remove_file(path).with_context(|| format!("Cannot remove {}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn save_cached(path: &Path, image: &Image) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Cannot create {}", parent.display()))?;
+    }
+    image::save_buffer(
+        path,
+        &image.pixels,
+        image.width as u32,
+        image.height as u32,
+        ExtendedColorType::Rgba8,
+    )
+    .with_context(|| format!("Cannot write {}", path.display()))
+}
+
+/// Small renders of [State]s, shown next to saved locations.
+///
+/// Rendering needs the GL context, so [FractalGl] draws the requested thumbnails during its
+/// next paint and [Thumbnails::receive] turns them into textures on the following frame.
+pub struct Thumbnails {
+    next_id: u64,
+    textures: HashMap<u64, TextureHandle>,
+    /// Files the thumbnails being rendered are cached to, by id.
+    to_cache: HashMap<u64, PathBuf>,
+}
+
+impl Thumbnails {
+    pub fn new() -> Self {
+        Self {
+            next_id: 0,
+            textures: HashMap::new(),
+            to_cache: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, ctx: &Context, id: u64, image: &Image) {
+        let image = ColorImage::from_rgba_unmultiplied([image.width, image.height], &image.pixels);
+        let texture = ctx.load_texture(format!("thumbnail-{id}"), image, TextureOptions::LINEAR);
+        self.textures.insert(id, texture);
+    }
+
+    /// Like [Thumbnails::request] with the current palette, but the thumbnail rendered by a
+    /// previous run is read from the disk if there is one, and a new one is written to it.
+    /// `look` is the [look] of `fractal`.
+    pub fn request_cached(
+        &mut self,
+        ctx: &Context,
+        fractal: &mut FractalGl,
+        state: State,
+        viewport_px: Vec2,
+        look: u64,
+    ) -> u64 {
+        let path = cache_path(key(&state, look));
+        if let Some(image) = path.as_deref().and_then(|path| user_image::load(path).ok()) {
+            let id = self.next_id;
+            self.next_id += 1;
+            self.insert(ctx, id, &image);
+            return id;
+        }
+        let id = self.request(ctx, fractal, state, viewport_px, None);
+        if let Some(path) = path {
+            self.to_cache.insert(id, path);
+        }
+        id
+    }
+
+    /// Ask for a thumbnail of the view `state` gives on a canvas of `viewport_px` pixels,
+    /// colored with `palette` instead of the current palette if given.
+    /// Returns the id to [Thumbnails::get] the texture with, once ready.
+    pub fn request(
+        &mut self,
+        ctx: &Context,
+        fractal: &mut FractalGl,
+        mut state: State,
+        viewport_px: Vec2,
+        palette: Option<&Palette>,
+    ) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        // Keep the width of the view
+        state.zoom *= THUMBNAIL_SIZE[0] as f32 / viewport_px.x.max(1.0);
+        fractal.request_thumbnail(id, state, THUMBNAIL_SIZE, palette);
+        // Make sure there is a paint to render it, and a frame after that to receive it
+        ctx.request_repaint();
+        id
+    }
+
+    pub fn receive(&mut self, ctx: &Context, fractal: &mut FractalGl) {
+        for (id, image) in fractal.take_thumbnails() {
+            if let Some(path) = self.to_cache.remove(&id) {
+                let saved = save_cached(&path, &image)
+                    .and_then(|()| path.parent().map_or(Ok(()), prune_cache));
+                if let Err(e) = saved {
+                    warn!("Cannot cache the thumbnail: {:?}", e);
+                }
+            }
+            self.insert(ctx, id, &image);
+        }
+        // The paint rendering the pending thumbnails needs another frame to be received
+        if fractal.has_pending_thumbnails() {
+            ctx.request_repaint();
+        }
+    }
+
+    pub fn get(&self, id: u64) -> Option<&TextureHandle> {
+        self.textures.get(&id)
+    }
+
+    pub fn forget(&mut self, id: u64) {
+        self.textures.remove(&id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_keys() {
+        // The names of the files cached by the previous runs
+        assert_eq!(StableHasher::new().finish(), 0xcbf2_9ce4_8422_2325);
+        let state = State::new();
+        assert_eq!(key(&state, 1), key(&state, 1));
+        assert_ne!(key(&state, 1), key(&state, 2));
+        let mut zoomed = state;
+        zoomed.zoom *= 2.0;
+        assert_ne!(key(&state, 1), key(&zoomed, 1));
+    }
+
+    #[test]
+    fn writes_are_delimited() {
+        let hash = |parts: &[&[u8]]| {
+            let mut hasher = StableHasher::new();
+            for part in parts {
+                hasher.write(part);
+            }
+            hasher.finish()
+        };
+        assert_ne!(hash(&[b"ab", b"c"]), hash(&[b"a", b"bc"]));
+        assert_eq!(hash(&[b"abc"]), 0xc11a_b6d2_519b_c2b2);
+    }
+}
@@ -0,0 +1,150 @@
+use std::path::Path;
+
+use anyhow::Result;
+use eframe::egui::{Context, Vec2};
+
+use super::{
+    bigtiff::BigTiffExport,
+    dzi::{DziExport, TILES_PER_FRAME},
+    png::PngExport,
+    position::Position,
+    video::VideoExport,
+    FractalGl, State,
+};
+
+/// Renderer of the tiles of the exports: the GPU of the window, or the workers of a
+/// [super::farm::Farm].
+pub trait TileRenderer {
+    /// Render `state` on `size` pixels, see [FractalGl::request_tile].
+    fn request_tile(&mut self, id: u64, state: State, size: [i32; 2], deep: bool);
+
+    /// The tiles rendered since the last call, in any order.
+    fn take_tiles(&mut self) -> Vec<(u64, Result<Vec<u8>>)>;
+
+    /// Drop the tiles of a cancelled export, rendered or not.
+    fn forget_tiles(&mut self);
+
+    /// Tiles to keep requested at once.
+    fn capacity(&self) -> usize {
+        TILES_PER_FRAME
+    }
+}
+
+impl TileRenderer for FractalGl {
+    fn request_tile(&mut self, id: u64, state: State, size: [i32; 2], deep: bool) {
+        FractalGl::request_tile(self, id, state, size, deep);
+    }
+
+    fn take_tiles(&mut self) -> Vec<(u64, Result<Vec<u8>>)> {
+        FractalGl::take_tiles(self)
+    }
+
+    fn forget_tiles(&mut self) {
+        FractalGl::forget_tiles(self);
+    }
+}
+
+/// View of the pixels `[left, top, right, bottom]` of an image of `size` pixels showing what
+/// `state` shows on a canvas of `viewport_px` pixels.
+pub fn tile_state(state: &State, viewport_px: Vec2, size: [f32; 2], rect: [f32; 4]) -> State {
+    let [left, top, right, bottom] = rect;
+    let mut tile = *state;
+    tile.zoom *= size[0] / viewport_px.x.max(1.0);
+    tile.high_quality = true;
+    tile.circular = false;
+    let center = tile.point_at([
+        (left + right) / 2.0 - size[0] / 2.0,
+        (top + bottom) / 2.0 - size[1] / 2.0,
+    ]);
+    tile.center_position = Position {
+        x: -center[0],
+        y: -center[1],
+    };
+    tile
+}
+
+/// Height of an image `width` pixels wide, with the proportions of the canvas.
+pub fn export_height(viewport_px: Vec2, width: usize) -> usize {
+    ((width as f32 * viewport_px.y / viewport_px.x.max(1.0)).round() as usize).max(1)
+}
+
+/// Export rendered tile by tile over several frames.
+pub enum TiledExport {
+    Dzi(DziExport),
+    BigTiff(BigTiffExport),
+    Png(PngExport),
+    Video(VideoExport),
+}
+
+impl TiledExport {
+    pub fn path(&self) -> &Path {
+        match self {
+            TiledExport::Dzi(export) => &export.path,
+            TiledExport::BigTiff(export) => &export.path,
+            TiledExport::Png(export) => &export.path,
+            TiledExport::Video(export) => &export.path,
+        }
+    }
+
+    /// Write the tiles rendered since the last frame and request the next ones. Returns
+    /// whether the export is complete.
+    pub fn update(&mut self, ctx: &Context, renderer: &mut dyn TileRenderer) -> Result<bool> {
+        let done = match self {
+            TiledExport::Dzi(export) => export.update(renderer)?,
+            TiledExport::BigTiff(export) => export.update(renderer)?,
+            TiledExport::Png(export) => export.update(renderer)?,
+            TiledExport::Video(export) => export.update(renderer)?,
+        };
+        ctx.request_repaint();
+        Ok(done)
+    }
+
+    /// Fraction of the tiles written.
+    pub fn progress(&self) -> f32 {
+        match self {
+            TiledExport::Dzi(export) => export.progress(),
+            TiledExport::BigTiff(export) => export.progress(),
+            TiledExport::Png(export) => export.progress(),
+            TiledExport::Video(export) => export.progress(),
+        }
+    }
+}
+
+/// Renders every tile in a single color, for the tests of the exports.
+#[cfg(test)]
+pub struct FlatRenderer {
+    /// 16-bit channels, the 8-bit tiles get their high byte.
+    color: [u16; 4],
+    rendered: Vec<(u64, Result<Vec<u8>>)>,
+}
+
+#[cfg(test)]
+impl FlatRenderer {
+    pub fn new(color: [u16; 4]) -> Self {
+        Self {
+            color,
+            rendered: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl TileRenderer for FlatRenderer {
+    fn request_tile(&mut self, id: u64, _: State, [width, height]: [i32; 2], deep: bool) {
+        let pixel: Vec<u8> = if deep {
+            self.color.iter().flat_map(|c| c.to_ne_bytes()).collect()
+        } else {
+            self.color.iter().map(|c| (c >> 8) as u8).collect()
+        };
+        let pixels = pixel.repeat(width as usize * height as usize);
+        self.rendered.push((id, Ok(pixels)));
+    }
+
+    fn take_tiles(&mut self) -> Vec<(u64, Result<Vec<u8>>)> {
+        std::mem::take(&mut self.rendered)
+    }
+
+    fn forget_tiles(&mut self) {
+        self.rendered.clear();
+    }
+}
@@ -0,0 +1,234 @@
+use eframe::egui::{DragValue, Ui};
+
+use super::{
+    accessibility::icon_button,
+    i18n::{tr, tr_args},
+    transition::{Easing, Transition},
+    video, State,
+};
+
+/// Time between a new keyframe and the last one, in seconds.
+const KEYFRAME_SPACING: f64 = 2.0;
+/// Frame rate of the exported videos.
+pub const EXPORT_FPS: u32 = 30;
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// `from` moved to the fraction `t` of the way to `to`: the view like a [Transition], the
+/// Julia constant and the colors linearly. The rest stays the one of `from`.
+fn interpolate(from: &State, to: &State, t: f32) -> State {
+    let mut state = *from;
+    Transition::new(from, to.center_position, to.zoom, 1.0)
+        .easing(Easing::Linear)
+        .advance(t as f64, &mut state);
+    state.c_julia = from.c_julia + (to.c_julia - from.c_julia) * t;
+    for (value, (a, b)) in [
+        &mut state.contrast,
+        &mut state.brightness,
+        &mut state.gamma,
+        &mut state.r,
+        &mut state.g,
+        &mut state.b,
+    ]
+    .into_iter()
+    .zip([
+        (from.contrast, to.contrast),
+        (from.brightness, to.brightness),
+        (from.gamma, to.gamma),
+        (from.r, to.r),
+        (from.g, to.g),
+        (from.b, to.b),
+    ]) {
+        *value = lerp(a, b, t);
+    }
+    for ((offset, a), b) in state
+        .color_balance
+        .iter_mut()
+        .zip(from.color_balance)
+        .zip(to.color_balance)
+    {
+        *offset = [lerp(a[0], b[0], t), lerp(a[1], b[1], t)];
+    }
+    state
+}
+
+struct Keyframe {
+    state: State,
+    /// In seconds from the start of the timeline.
+    time: f64,
+}
+
+/// What the user asked from the timeline.
+pub enum TimelineAction {
+    /// Show the view of a keyframe.
+    Show(Box<State>),
+    Export,
+}
+
+/// Views recorded at times of the user, played back by interpolating between them.
+pub struct Timeline {
+    keyframes: Vec<Keyframe>,
+    /// Time of the playback, while playing.
+    playing: Option<f64>,
+    looping: bool,
+    /// Width of the frames of the videos, their height follows the proportions of the canvas.
+    width: usize,
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Self {
+            keyframes: Vec::new(),
+            playing: None,
+            looping: false,
+            width: video::DEFAULT_WIDTH,
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing.is_some()
+    }
+
+    /// Width of the frames of the videos.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Time of the last keyframe.
+    fn duration(&self) -> f64 {
+        self.keyframes.iter().map(|k| k.time).fold(0.0, f64::max)
+    }
+
+    /// Keyframes in the order of their times.
+    fn sorted(&self) -> Vec<&Keyframe> {
+        let mut keyframes: Vec<_> = self.keyframes.iter().collect();
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        keyframes
+    }
+
+    /// View at `time`, between the keyframes around it, `None` without two keyframes.
+    fn state_at(&self, time: f64) -> Option<State> {
+        let keyframes = self.sorted();
+        if keyframes.len() < 2 {
+            return None;
+        }
+        let next = keyframes
+            .iter()
+            .position(|k| k.time > time)
+            .unwrap_or(keyframes.len() - 1)
+            .max(1);
+        let (from, to) = (keyframes[next - 1], keyframes[next]);
+        let span = to.time - from.time;
+        let t = if span > 0.0 {
+            ((time - from.time) / span).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        Some(interpolate(&from.state, &to.state, t as f32))
+    }
+
+    /// Move the playback forward by `dt` seconds and show its view in `state`, keeping the
+    /// time of the animations.
+    pub fn advance(&mut self, dt: f64, state: &mut State) {
+        let duration = self.duration();
+        let Some(time) = self.playing.as_mut() else {
+            return;
+        };
+        *time += dt;
+        if *time > duration {
+            if self.looping && duration > 0.0 {
+                *time %= duration;
+            } else {
+                *time = duration;
+                self.playing = None;
+            }
+        }
+        let time = self.playing.unwrap_or(duration);
+        if let Some(view) = self.state_at(time) {
+            *state = State {
+                time: state.time,
+                ..view
+            };
+        }
+    }
+
+    /// Views of the frames of a video of the timeline, at [EXPORT_FPS].
+    pub fn frames(&self) -> Vec<State> {
+        let count = (self.duration() * EXPORT_FPS as f64).round() as usize + 1;
+        (0..count)
+            .filter_map(|index| {
+                let time = index as f64 / EXPORT_FPS as f64;
+                let mut state = self.state_at(time)?;
+                state.time = time as f32;
+                Some(state)
+            })
+            .collect()
+    }
+
+    /// Keyframes taken from `current`, their times, and the playback.
+    pub fn ui(&mut self, ui: &mut Ui, current: &State) -> Option<TimelineAction> {
+        let mut action = None;
+        let mut removed = None;
+        for (i, keyframe) in self.keyframes.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui
+                    .button(tr_args("keyframe", &[("index", &(i + 1))]))
+                    .on_hover_text(tr("show-keyframe-hint"))
+                    .clicked()
+                {
+                    action = Some(TimelineAction::Show(Box::new(keyframe.state)));
+                }
+                ui.add(
+                    DragValue::new(&mut keyframe.time)
+                        .range(0.0..=3600.0)
+                        .speed(0.05)
+                        .suffix(" s"),
+                );
+                if icon_button(ui.small_button("✖"), tr("remove-keyframe")).clicked() {
+                    removed = Some(i);
+                }
+            });
+        }
+        if let Some(i) = removed {
+            self.keyframes.remove(i);
+        }
+
+        if ui.button(tr("add-keyframe")).clicked() {
+            let time = if self.keyframes.is_empty() {
+                0.0
+            } else {
+                self.duration() + KEYFRAME_SPACING
+            };
+            self.keyframes.push(Keyframe {
+                state: *current,
+                time,
+            });
+        }
+
+        ui.add_enabled_ui(self.keyframes.len() >= 2, |ui| {
+            ui.horizontal(|ui| {
+                if self.playing.is_some() {
+                    if ui.button(tr("stop-timeline")).clicked() {
+                        self.playing = None;
+                    }
+                } else if ui.button(tr("play-timeline")).clicked() {
+                    self.playing = Some(0.0);
+                }
+                ui.checkbox(&mut self.looping, tr("loop-timeline"));
+            });
+            ui.horizontal(|ui| {
+                video::resolution_ui(ui, &mut self.width);
+                if ui
+                    .button(tr("export-video"))
+                    .on_hover_text(tr("export-video-hint"))
+                    .clicked()
+                {
+                    action = Some(TimelineAction::Export);
+                }
+            });
+        });
+        action
+    }
+}
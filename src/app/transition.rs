@@ -0,0 +1,77 @@
+use super::{position::Position, State};
+
+/// Pace of a [Transition] over its duration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    /// Starts and ends slowly.
+    Smooth,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 2] = [Easing::Linear, Easing::Smooth];
+
+    /// Progress at the fraction `t` of the duration.
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::Smooth => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// Smooth move of the view to a new center and zoom.
+pub struct Transition {
+    from: (Position, f32),
+    to: (Position, f32),
+    /// In seconds.
+    duration: f64,
+    elapsed: f64,
+    easing: Easing,
+}
+
+impl Transition {
+    pub fn new(state: &State, center_position: Position, zoom: f32, duration: f64) -> Self {
+        Self {
+            from: (state.center_position, state.zoom),
+            to: (center_position, zoom),
+            duration,
+            elapsed: 0.0,
+            easing: Easing::Smooth,
+        }
+    }
+
+    pub fn easing(self, easing: Easing) -> Self {
+        Self { easing, ..self }
+    }
+
+    /// Center and zoom the view ends at.
+    pub fn target(&self) -> (Position, f32) {
+        self.to
+    }
+
+    /// Move the view of `state` forward by `dt` seconds, returns false once the transition
+    /// is over.
+    pub fn advance(&mut self, dt: f64, state: &mut State) -> bool {
+        self.elapsed += dt;
+        let t = self
+            .easing
+            .apply((self.elapsed / self.duration).min(1.0) as f32);
+
+        let ((from_center, from_zoom), (to_center, to_zoom)) = (self.from, self.to);
+        // The zoom changes geometrically, and the center moves at the pace of the visible
+        // area so that the target doesn't rush out of the screen
+        state.zoom = from_zoom * (to_zoom / from_zoom).powf(t);
+        let weight = if (to_zoom - from_zoom).abs() > f32::EPSILON * from_zoom {
+            (1.0 / state.zoom - 1.0 / from_zoom) / (1.0 / to_zoom - 1.0 / from_zoom)
+        } else {
+            t
+        };
+        let weight = weight as f64;
+        state.center_position = Position {
+            x: from_center.x + (to_center.x - from_center.x) * weight,
+            y: from_center.y + (to_center.y - from_center.y) * weight,
+        };
+        self.elapsed < self.duration
+    }
+}
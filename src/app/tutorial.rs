@@ -0,0 +1,149 @@
+use eframe::egui::{self, vec2, Align2, Color32, Context, Id, LayerId, Order, Rect, Stroke, Vec2};
+use log::error;
+
+use super::{
+    i18n::{tr, tr_args},
+    settings,
+};
+
+/// Name of the setting recording that the tour was seen.
+const TUTORIAL_SETTING: &str = "tutorial";
+
+/// Parts of the UI presented by the tour, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    Canvas,
+    FractalType,
+    JuliaPanel,
+    Export,
+}
+
+impl Step {
+    const ALL: [Step; 4] = [
+        Step::Canvas,
+        Step::FractalType,
+        Step::JuliaPanel,
+        Step::Export,
+    ];
+
+    fn message(self) -> String {
+        tr(match self {
+            Step::Canvas => "tutorial-canvas",
+            Step::FractalType => "tutorial-type",
+            Step::JuliaPanel => "tutorial-julia",
+            Step::Export => "tutorial-export",
+        })
+    }
+}
+
+/// Guided tour of the UI, highlighting one control at a time.
+pub struct Tutorial {
+    /// Index in [Step::ALL] of the step shown, `None` when the tour is closed.
+    step: Option<usize>,
+    /// Where the controls of the steps were drawn this frame, if they were.
+    targets: [Option<Rect>; Step::ALL.len()],
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Self {
+            step: None,
+            targets: [None; Step::ALL.len()],
+        }
+    }
+
+    /// Tour started on the first launch only.
+    pub fn load() -> Self {
+        let mut tutorial = Self::new();
+        if settings::load(TUTORIAL_SETTING).is_none() {
+            tutorial.start();
+        }
+        tutorial
+    }
+
+    pub fn start(&mut self) {
+        self.step = Some(0);
+    }
+
+    /// Record where the control of `step` is, called while drawing it.
+    pub fn target(&mut self, step: Step, rect: Rect) {
+        if let Some(index) = Step::ALL.iter().position(|s| *s == step) {
+            self.targets[index] = Some(rect);
+        }
+    }
+
+    fn finish(&mut self) {
+        self.step = None;
+        if let Err(e) = settings::save(TUTORIAL_SETTING, "done") {
+            error!("Cannot save the tutorial setting: {:?}", e);
+        }
+    }
+
+    /// Highlight and explain the current step, after everything else was drawn.
+    pub fn show(&mut self, ctx: &Context) {
+        let targets = std::mem::replace(&mut self.targets, [None; Step::ALL.len()]);
+        let Some(index) = self.step else {
+            return;
+        };
+        let step = Step::ALL[index];
+        let target = targets[index];
+
+        let mut window = egui::Window::new(tr("tutorial"))
+            .id(Id::new("Tutorial"))
+            .collapsible(false)
+            .resizable(false);
+        match target {
+            Some(rect) => {
+                ctx.layer_painter(LayerId::new(
+                    Order::Foreground,
+                    Id::new("Tutorial highlight"),
+                ))
+                .rect_stroke(
+                    rect.expand(4.0),
+                    4.0,
+                    Stroke::new(3.0, Color32::YELLOW),
+                );
+                // Next to the controls of the side panel, over the middle of the canvas
+                window = if step == Step::Canvas {
+                    window.pivot(Align2::CENTER_CENTER).fixed_pos(rect.center())
+                } else {
+                    window
+                        .pivot(Align2::LEFT_TOP)
+                        .fixed_pos(rect.right_top() + vec2(16.0, 0.0))
+                };
+            }
+            // The control is hidden, e.g. the Julia panel of the Mandelbrot set
+            None => window = window.anchor(Align2::CENTER_CENTER, Vec2::ZERO),
+        }
+
+        let mut next = None;
+        window.show(ctx, |ui| {
+            ui.set_max_width(280.0);
+            ui.label(step.message());
+            ui.weak(tr_args(
+                "tutorial-progress",
+                &[("step", &(index + 1)), ("total", &Step::ALL.len())],
+            ));
+            ui.horizontal(|ui| {
+                if index > 0 && ui.button(tr("back")).clicked() {
+                    next = Some(Some(index - 1));
+                }
+                let last = index + 1 == Step::ALL.len();
+                if ui
+                    .button(if last { tr("finish") } else { tr("next") })
+                    .clicked()
+                {
+                    next = Some(Some(index + 1).filter(|_| !last));
+                }
+                if !last && ui.button(tr("skip-tour")).clicked() {
+                    next = Some(None);
+                }
+            });
+        });
+        match next {
+            Some(Some(index)) => self.step = Some(index),
+            Some(None) => self.finish(),
+            None => {}
+        }
+    }
+}
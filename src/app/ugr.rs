@@ -0,0 +1,189 @@
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+
+use super::palette::{ColorStop, Palette};
+
+/// UltraFractal gradients have 400 slots, `index=` values are positions in that range.
+const UGR_SLOTS: f32 = 400.0;
+
+/// Read all the gradients of an UltraFractal `.ugr` file.
+pub fn load(path: &Path) -> Result<Vec<Palette>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    parse(&content)
+}
+
+/// A `.ugr` file is a list of `name { gradient: key=value … opacity: … }` entries. In the
+/// gradient section, each `index=` is followed by the `color=` of that stop.
+pub fn parse(content: &str) -> Result<Vec<Palette>> {
+    let mut palettes = Vec::new();
+    let mut rest = content;
+
+    while let Some(open) = rest.find('{') {
+        let name = rest[..open].trim();
+        let close = rest[open..]
+            .find('}')
+            .with_context(|| format!("Unterminated gradient {name:?}"))?
+            + open;
+        let body = &rest[open + 1..close];
+        rest = &rest[close + 1..];
+
+        let mut title = name.to_string();
+        let mut index = None;
+        let mut stops = Vec::new();
+        for token in tokens(body) {
+            // The opacity section has its own index= entries, they are not colors
+            if token == "opacity:" {
+                break;
+            }
+            let Some((key, value)) = token.split_once('=') else {
+                continue;
+            };
+            match key {
+                "title" => title = value.trim_matches('"').to_string(),
+                "index" => {
+                    index = Some(
+                        value
+                            .parse::<f32>()
+                            .with_context(|| format!("Invalid index {value:?} in {name:?}"))?,
+                    )
+                }
+                "color" => {
+                    let Some(position) = index.take() else {
+                        bail!("Color without index in {name:?}");
+                    };
+                    let color = value
+                        .parse::<u32>()
+                        .with_context(|| format!("Invalid color {value:?} in {name:?}"))?;
+                    stops.push(ColorStop {
+                        position: (position / UGR_SLOTS).rem_euclid(1.0),
+                        color: from_colorref(color),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        if stops.is_empty() {
+            bail!("Gradient {name:?} has no color");
+        }
+        palettes.push(Palette::new(title, stops));
+    }
+
+    Ok(palettes)
+}
+
+/// Split on whitespace, keeping quoted strings (`title="Deep sea"`) in one piece.
+fn tokens(body: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut quoted = false;
+    for (i, c) in body.char_indices() {
+        match (c, start) {
+            ('"', _) => {
+                quoted = !quoted;
+                start.get_or_insert(i);
+            }
+            (c, Some(s)) if c.is_whitespace() && !quoted => {
+                tokens.push(&body[s..i]);
+                start = None;
+            }
+            (c, None) if !c.is_whitespace() => start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        tokens.push(&body[s..]);
+    }
+    tokens
+}
+
+/// UltraFractal stores colors as Windows `COLORREF`: `0x00BBGGRR`.
+fn from_colorref(color: u32) -> [f32; 3] {
+    [color, color >> 8, color >> 16].map(|c| (c & 0xFF) as f32 / 255.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two gradients as UltraFractal writes them, with an opacity section.
+    const SAMPLE: &str = r#"
+Sunset {
+gradient:
+  title="Warm sunset" smooth=yes rotation=0
+  index=0 color=255
+  index=200 color=65535
+  index=-50 color=16711680
+opacity:
+  smooth=no index=0 opacity=255
+}
+
+blue {
+gradient:
+  title="Deep sea" smooth=no
+  index=100 color=8388608
+}
+"#;
+
+    #[test]
+    fn gradients() {
+        let palettes = parse(SAMPLE).unwrap();
+        assert_eq!(palettes.len(), 2);
+
+        assert_eq!(palettes[0].name, "Warm sunset");
+        assert_eq!(
+            palettes[0].stops,
+            [
+                ColorStop {
+                    position: 0.0,
+                    color: [1.0, 0.0, 0.0],
+                },
+                ColorStop {
+                    position: 0.5,
+                    color: [1.0, 1.0, 0.0],
+                },
+                // Negative indices wrap around
+                ColorStop {
+                    position: 0.875,
+                    color: [0.0, 0.0, 1.0],
+                },
+            ]
+        );
+
+        assert_eq!(palettes[1].name, "Deep sea");
+        assert_eq!(
+            palettes[1].stops,
+            [ColorStop {
+                position: 0.25,
+                color: [0.0, 0.0, 128.0 / 255.0],
+            }]
+        );
+    }
+
+    #[test]
+    fn name_without_title() {
+        let palettes = parse("plain { gradient: index=0 color=0 }").unwrap();
+        assert_eq!(palettes[0].name, "plain");
+    }
+
+    #[test]
+    fn empty() {
+        assert!(parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn malformed() {
+        for content in [
+            "open { gradient: index=0 color=255",
+            "none { gradient: title=\"None\" }",
+            "lost { gradient: color=255 }",
+            "index { gradient: index=first color=255 }",
+            "color { gradient: index=0 color=red }",
+            "negative { gradient: index=0 color=-1 }",
+        ] {
+            assert!(parse(content).is_err(), "{content:?}");
+        }
+    }
+}
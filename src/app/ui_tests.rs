@@ -0,0 +1,78 @@
+use egui_kittest::{
+    kittest::{Key, Queryable},
+    Harness,
+};
+
+use super::{FractalApp, FractalGl, FractalType};
+
+/// The app without GL context: the paint callbacks are never run.
+fn harness() -> Harness<'static, FractalApp> {
+    let fractal = FractalGl::new();
+    let mut harness = Harness::new_state(
+        |ctx, app: &mut FractalApp| app.show(ctx),
+        FractalApp::with_renderer(fractal),
+    );
+    harness.run();
+    harness
+}
+
+#[test]
+fn high_quality_checkbox() {
+    let mut harness = harness();
+    let high_quality = harness.state().state.high_quality;
+
+    harness.get_by_label("High Quality").click();
+    harness.run();
+
+    assert_eq!(harness.state().state.high_quality, !high_quality);
+}
+
+#[test]
+fn change_fractal_type() {
+    let mut harness = harness();
+    assert_eq!(harness.state().state.fractal_type, FractalType::Julia);
+
+    // The label next to the combo box has the same name
+    harness
+        .get_by_role_and_label(egui::accesskit::Role::ComboBox, "Type")
+        .click();
+    harness.run();
+    harness.get_by_label("Mandelbrot").click();
+    harness.run();
+
+    assert_eq!(harness.state().state.fractal_type, FractalType::Mandelbrot);
+    // The Julia section is hidden for the Mandelbrot set
+    assert!(harness.query_by_label("Julia parameters").is_none());
+}
+
+#[test]
+fn zoom_slider() {
+    let mut harness = harness();
+    let zoom = harness.state().state.zoom;
+
+    // The zoom is the first slider of the settings
+    let slider = harness
+        .get_all_by_role(egui::accesskit::Role::Slider)
+        .next();
+    let slider = slider.expect("No slider in the settings");
+    slider.focus();
+    slider.key_press(Key::ArrowRight);
+    harness.run();
+
+    assert!(harness.state().state.zoom > zoom);
+}
+
+#[test]
+fn exit_closes_the_viewport() {
+    let mut harness = harness();
+
+    harness.get_by_label("Exit").click();
+    harness.step();
+
+    let closed = harness
+        .output()
+        .viewport_output
+        .values()
+        .any(|viewport| viewport.commands.contains(&egui::ViewportCommand::Close));
+    assert!(closed);
+}
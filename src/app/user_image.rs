@@ -0,0 +1,24 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+pub use super::fractal_gl::Image;
+
+/// Largest side of the textures, larger images are downscaled.
+const MAX_SIZE: u32 = 1024;
+
+/// Image for the textures of the shader: the image trap and the exterior texture.
+pub fn load(path: &Path) -> Result<Image> {
+    let image = image::open(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let image = if image.width() > MAX_SIZE || image.height() > MAX_SIZE {
+        image.thumbnail(MAX_SIZE, MAX_SIZE)
+    } else {
+        image
+    }
+    .to_rgba8();
+    Ok(Image {
+        width: image.width() as usize,
+        height: image.height() as usize,
+        pixels: image.into_raw(),
+    })
+}
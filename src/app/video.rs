@@ -0,0 +1,435 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, SyncSender},
+    thread::{self, JoinHandle},
+};
+
+use anyhow::{bail, ensure, Context as _, Result};
+use eframe::egui::{ComboBox, DragValue, Ui, Vec2};
+use image::ExtendedColorType;
+
+use super::{
+    i18n::{tr, tr_args},
+    tiled_export::{export_height, tile_state, TileRenderer},
+    transition::{Easing, Transition},
+    State,
+};
+
+/// Frames rendered at the same time, each one is a whole image.
+const FRAMES_IN_FLIGHT: usize = 2;
+/// Widths of the frames offered for the videos.
+const RESOLUTIONS: [(&str, usize); 3] = [("720p", 1280), ("1080p", 1920), ("4K", 3840)];
+/// Width of the frames of a new video.
+pub const DEFAULT_WIDTH: usize = 1280;
+
+/// Buttons choosing the width of the frames of a video among [RESOLUTIONS].
+pub fn resolution_ui(ui: &mut Ui, width: &mut usize) {
+    for (label, resolution) in RESOLUTIONS {
+        if ui.selectable_label(*width == resolution, label).clicked() {
+            *width = resolution;
+        }
+    }
+}
+
+/// Zoom animation between two views of the user, the keyframes.
+pub struct ZoomSequence {
+    start: Option<State>,
+    end: Option<State>,
+    /// In seconds.
+    duration: f64,
+    fps: u32,
+    easing: Easing,
+    /// Width of the frames, their height follows the proportions of the canvas.
+    width: usize,
+}
+
+/// What the user asked from the keyframe editor.
+pub enum SequenceAction {
+    /// Play the animation in the view.
+    Preview(Box<(State, Transition)>),
+    Export,
+}
+
+fn easing_label(easing: Easing) -> String {
+    tr(match easing {
+        Easing::Linear => "easing-linear",
+        Easing::Smooth => "easing-smooth",
+    })
+}
+
+impl ZoomSequence {
+    pub fn new() -> Self {
+        Self {
+            start: None,
+            end: None,
+            duration: 10.0,
+            fps: 30,
+            easing: Easing::Smooth,
+            width: DEFAULT_WIDTH,
+        }
+    }
+
+    fn transition(&self) -> Option<(State, Transition)> {
+        let (start, end) = (self.start?, self.end?);
+        let transition = Transition::new(&start, end.center_position, end.zoom, self.duration)
+            .easing(self.easing);
+        Some((start, transition))
+    }
+
+    fn frame_count(&self) -> usize {
+        (self.duration * self.fps as f64).round() as usize + 1
+    }
+
+    /// View of the frame `index`.
+    fn frame(&self, index: usize) -> Option<State> {
+        let (mut state, mut transition) = self.transition()?;
+        let time = index as f64 / self.fps as f64;
+        transition.advance(time, &mut state);
+        state.time = time as f32;
+        Some(state)
+    }
+
+    /// Keyframes taken from `current`, and the settings of the video.
+    pub fn ui(&mut self, ui: &mut Ui, current: &State) -> Option<SequenceAction> {
+        for (label, keyframe) in [
+            ("set-start-keyframe", &mut self.start),
+            ("set-end-keyframe", &mut self.end),
+        ] {
+            ui.horizontal(|ui| {
+                if ui.button(tr(label)).clicked() {
+                    *keyframe = Some(*current);
+                }
+                if let Some(state) = keyframe {
+                    ui.label(format!("×{:.3e}", state.zoom));
+                }
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.label(tr("duration"));
+            ui.add(
+                DragValue::new(&mut self.duration)
+                    .range(0.5..=600.0)
+                    .suffix(" s"),
+            );
+            ComboBox::from_id_salt("Video frame rate")
+                .selected_text(format!("{} fps", self.fps))
+                .show_ui(ui, |ui| {
+                    for fps in [24, 30, 60] {
+                        ui.selectable_value(&mut self.fps, fps, format!("{fps} fps"));
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ComboBox::from_label(tr("easing"))
+                .selected_text(easing_label(self.easing))
+                .show_ui(ui, |ui| {
+                    for easing in Easing::ALL {
+                        ui.selectable_value(&mut self.easing, easing, easing_label(easing));
+                    }
+                });
+            resolution_ui(ui, &mut self.width);
+        });
+
+        let mut action = None;
+        ui.add_enabled_ui(self.transition().is_some(), |ui| {
+            ui.horizontal(|ui| {
+                if ui.button(tr("preview")).clicked() {
+                    action = self
+                        .transition()
+                        .map(|preview| SequenceAction::Preview(Box::new(preview)));
+                }
+                if ui
+                    .button(tr("export-video"))
+                    .on_hover_text(tr("export-video-hint"))
+                    .clicked()
+                {
+                    action = Some(SequenceAction::Export);
+                }
+            });
+            ui.label(tr_args(
+                "video-frames",
+                &[("count", &self.frame_count().to_string())],
+            ));
+        });
+        action
+    }
+}
+
+/// ffmpeg encoding the frames into a video. The frames are written to its input on a
+/// thread, the UI only waits when ffmpeg is [FRAMES_IN_FLIGHT] frames behind.
+///
+/// Dropped before [Encoder::finish], after an error or a cancel, ffmpeg is stopped and the
+/// partial video removed.
+struct Ffmpeg {
+    child: Child,
+    path: PathBuf,
+    /// `None` once the input is closed.
+    frames: Option<SyncSender<Vec<u8>>>,
+    writer: Option<JoinHandle<io::Result<()>>>,
+    finished: bool,
+}
+
+impl Ffmpeg {
+    /// Run `command`, writing to `path`, and the thread feeding it the frames.
+    fn spawn(mut command: Command, path: &Path) -> Result<Self> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .context("Cannot run ffmpeg, export the frames as PNG images instead")?;
+        let mut stdin = child.stdin.take().context("No input to ffmpeg")?;
+        let (frames, received) = mpsc::sync_channel::<Vec<u8>>(FRAMES_IN_FLIGHT);
+        let writer = thread::spawn(move || {
+            for pixels in received {
+                stdin.write_all(&pixels)?;
+            }
+            // Closing the input ends the video
+            Ok(())
+        });
+        Ok(Self {
+            child,
+            path: path.to_path_buf(),
+            frames: Some(frames),
+            writer: Some(writer),
+            finished: false,
+        })
+    }
+
+    /// Close the input and wait for the writer, returning its error if any.
+    fn close_input(&mut self) -> Result<()> {
+        self.frames = None;
+        match self.writer.take().map(JoinHandle::join) {
+            Some(Ok(written)) => written.context("Cannot send the frames to ffmpeg"),
+            Some(Err(_)) => bail!("The writer of the frames panicked"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Ffmpeg {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        // Killed first, the writer could be blocked on a full pipe
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = self.close_input();
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Destination of the frames: numbered PNG images, or an ffmpeg process encoding them.
+enum Encoder {
+    Frames(PathBuf),
+    Ffmpeg(Ffmpeg),
+}
+
+impl Encoder {
+    fn start(path: &Path, width: usize, height: usize, fps: u32) -> Result<Self> {
+        let extension = path.extension().unwrap_or_default().to_string_lossy();
+        let codec = match extension.to_lowercase().as_str() {
+            "png" => return Ok(Encoder::Frames(path.to_path_buf())),
+            "mp4" => "libx264",
+            "webm" => "libvpx-vp9",
+            _ => bail!("Unknown video format {:?}", extension),
+        };
+        let mut command = Command::new("ffmpeg");
+        command
+            .args([
+                "-y",
+                "-loglevel",
+                "error",
+                "-f",
+                "rawvideo",
+                "-pix_fmt",
+                "rgba",
+            ])
+            .args(["-s", &format!("{width}x{height}"), "-r", &fps.to_string()])
+            .args(["-i", "-", "-c:v", codec, "-pix_fmt", "yuv420p"])
+            .arg(path);
+        Ok(Encoder::Ffmpeg(Ffmpeg::spawn(command, path)?))
+    }
+
+    fn write(&mut self, index: usize, pixels: Vec<u8>, width: usize, height: usize) -> Result<()> {
+        match self {
+            Encoder::Frames(path) => {
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                let frame = path.with_file_name(format!("{stem}_{index:05}.png"));
+                image::save_buffer(
+                    &frame,
+                    &pixels,
+                    width as u32,
+                    height as u32,
+                    ExtendedColorType::Rgba8,
+                )
+                .with_context(|| format!("Cannot write {}", frame.display()))
+            }
+            Encoder::Ffmpeg(ffmpeg) => {
+                let sent = ffmpeg.frames.as_ref().map(|frames| frames.send(pixels));
+                if let Some(Err(_)) = sent {
+                    // The writer stopped, its error tells why
+                    ffmpeg.close_input()?;
+                    bail!("ffmpeg stopped reading the frames");
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        if let Encoder::Ffmpeg(mut ffmpeg) = self {
+            ffmpeg.close_input()?;
+            let status = ffmpeg.child.wait()?;
+            ensure!(status.success(), "ffmpeg failed with {}", status);
+            ffmpeg.finished = true;
+        }
+        Ok(())
+    }
+}
+
+/// Export of a [ZoomSequence], its frames rendered offscreen one after the other.
+pub struct VideoExport {
+    pub path: PathBuf,
+    frames: Vec<State>,
+    width: usize,
+    height: usize,
+    /// Frames requested from the renderer, and written to the encoder.
+    requested: usize,
+    written: usize,
+    /// Frames rendered before the ones preceding them, by index.
+    rendered: BTreeMap<usize, Vec<u8>>,
+    encoder: Option<Encoder>,
+}
+
+impl VideoExport {
+    /// Export `sequence`, as shown on a canvas of `viewport_px` pixels, to a video or to
+    /// numbered PNG images after the extension of `path`.
+    pub fn start(path: PathBuf, sequence: &ZoomSequence, viewport_px: Vec2) -> Result<Self> {
+        let frames = (0..sequence.frame_count())
+            .map(|index| sequence.frame(index).context("Missing keyframe"))
+            .collect::<Result<Vec<_>>>()?;
+        Self::from_frames(path, frames, sequence.width, sequence.fps, viewport_px)
+    }
+
+    /// Export the views `frames`, played at `fps` frames per second, to a video `width`
+    /// pixels wide, like [VideoExport::start].
+    pub fn from_frames(
+        path: PathBuf,
+        frames: Vec<State>,
+        width: usize,
+        fps: u32,
+        viewport_px: Vec2,
+    ) -> Result<Self> {
+        ensure!(!frames.is_empty(), "No frame to export");
+        // Even sizes, for the chroma subsampling of the encoders
+        let width = (width / 2 * 2).max(2);
+        let height = (export_height(viewport_px, width) / 2 * 2).max(2);
+        let size = [width as f32, height as f32];
+        let frames = frames
+            .iter()
+            .map(|frame| tile_state(frame, viewport_px, size, [0.0, 0.0, size[0], size[1]]))
+            .collect();
+        let encoder = Encoder::start(&path, width, height, fps)?;
+        Ok(Self {
+            path,
+            frames,
+            width,
+            height,
+            requested: 0,
+            written: 0,
+            rendered: BTreeMap::new(),
+            encoder: Some(encoder),
+        })
+    }
+
+    /// Send the frames rendered since the last frame to the encoder, in order, and request
+    /// the next ones. Returns whether the export is complete.
+    pub fn update(&mut self, renderer: &mut dyn TileRenderer) -> Result<bool> {
+        for (id, pixels) in renderer.take_tiles() {
+            self.rendered.insert(id as usize, pixels?);
+        }
+        // The workers of a render farm finish the frames in any order
+        while let Some(pixels) = self.rendered.remove(&self.written) {
+            if let Some(encoder) = &mut self.encoder {
+                encoder.write(self.written, pixels, self.width, self.height)?;
+            }
+            self.written += 1;
+        }
+
+        while self.requested < self.frames.len() && self.requested - self.written < FRAMES_IN_FLIGHT
+        {
+            let size = [self.width as i32, self.height as i32];
+            renderer.request_tile(
+                self.requested as u64,
+                self.frames[self.requested],
+                size,
+                false,
+            );
+            self.requested += 1;
+        }
+
+        let done = self.written == self.frames.len();
+        if done {
+            if let Some(encoder) = self.encoder.take() {
+                encoder.finish()?;
+            }
+        }
+        Ok(done)
+    }
+
+    /// Fraction of the frames written.
+    pub fn progress(&self) -> f32 {
+        self.written as f32 / self.frames.len().max(1) as f32
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// Encoder copying its input to `path`, standing in for ffmpeg.
+    fn copy_to(path: &Path) -> Encoder {
+        let mut command = Command::new("sh");
+        command.args(["-c", "cat > \"$0\""]).arg(path);
+        Encoder::Ffmpeg(Ffmpeg::spawn(command, path).unwrap())
+    }
+
+    #[test]
+    fn finished_video() {
+        let path = std::env::temp_dir().join("fractal_gl_finished_video.raw");
+        let mut encoder = copy_to(&path);
+        for index in 0..5 {
+            encoder.write(index, vec![index as u8; 4], 1, 1).unwrap();
+        }
+        encoder.finish().unwrap();
+        let content = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(content.len(), 20);
+        assert_eq!(content[16..], [4; 4]);
+    }
+
+    #[test]
+    fn cancelled_video() {
+        let path = std::env::temp_dir().join("fractal_gl_cancelled_video.raw");
+        let mut encoder = copy_to(&path);
+        encoder.write(0, vec![0; 4], 1, 1).unwrap();
+        drop(encoder);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn failed_encoder() {
+        let path = std::env::temp_dir().join("fractal_gl_failed_video.raw");
+        let mut command = Command::new("sh");
+        command.args(["-c", "exit 1"]);
+        let mut encoder = Encoder::Ffmpeg(Ffmpeg::spawn(command, &path).unwrap());
+        // Large frames, the pipe is full once the encoder is gone
+        let written = (0..8).try_for_each(|index| encoder.write(index, vec![0; 1 << 20], 1, 1));
+        assert!(written.and_then(|()| encoder.finish()).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+use std::{
+    io::Write as _,
+    net::TcpStream,
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use log::{debug, info, warn};
+
+use super::{
+    cpu_renderer,
+    farm::{self, Job, Message},
+    Palette,
+};
+
+/// Delay between two attempts to reach the coordinator.
+const RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Render the tiles of the exports of a render farm, without a window.
+#[derive(Parser)]
+#[command(name = "fractal_gl worker")]
+struct WorkerArgs {
+    /// Machine exporting with its render farm enabled, as `host:port`.
+    coordinator: String,
+    /// Compute on the CPU instead of the GPU.
+    #[arg(long)]
+    cpu: bool,
+}
+
+/// Headless OpenGL context and its renderer, set up with the palette of the tiles.
+#[cfg(feature = "headless")]
+struct Gpu {
+    gl: eframe::glow::Context,
+    fractal: super::FractalGl,
+    palette: Option<Palette>,
+}
+
+/// Draws the tiles, on the GPU unless told otherwise or unavailable.
+enum Renderer {
+    #[cfg(feature = "headless")]
+    Gpu(Box<Gpu>),
+    Cpu,
+}
+
+impl Renderer {
+    fn new(cpu: bool) -> Self {
+        if cpu {
+            return Renderer::Cpu;
+        }
+        match Self::gpu(None) {
+            Ok(renderer) => renderer,
+            Err(e) => {
+                info!("No GPU rendering ({:#}), rendering on the CPU", e);
+                Renderer::Cpu
+            }
+        }
+    }
+
+    #[cfg(feature = "headless")]
+    fn gpu(palette: Option<Palette>) -> Result<Self> {
+        let gl = super::headless::context()?;
+        let mut fractal = super::FractalGl::new();
+        if let Some(palette) = &palette {
+            fractal.set_palette(palette);
+        }
+        fractal.init(&gl)?;
+        Ok(Renderer::Gpu(Box::new(Gpu {
+            gl,
+            fractal,
+            palette,
+        })))
+    }
+
+    #[cfg(not(feature = "headless"))]
+    fn gpu(_: Option<Palette>) -> Result<Self> {
+        bail!("Built without the headless feature")
+    }
+
+    /// RGBA pixels of `job`, top row first, with 16 bits per channel in the native byte order
+    /// if it is deep.
+    fn render(&mut self, job: &Job) -> Result<Vec<u8>> {
+        #[cfg(feature = "headless")]
+        if let Renderer::Gpu(gpu) = self {
+            if gpu.palette != job.palette {
+                // Uploaded when the renderer is set up, offscreen renders don't update it
+                gpu.fractal.destroy(&gpu.gl);
+                *self = Self::gpu(job.palette.clone())?;
+            }
+        }
+        let [width, height] = job.size;
+        match self {
+            #[cfg(feature = "headless")]
+            Renderer::Gpu(gpu) => {
+                let Gpu { gl, fractal, .. } = gpu.as_mut();
+                fractal.render_offscreen_pixels(gl, job.state, width, height, job.deep)
+            }
+            Renderer::Cpu => {
+                let pixels = cpu_renderer::render(
+                    job.state,
+                    job.palette.as_ref(),
+                    None,
+                    width as u32,
+                    height as u32,
+                )?;
+                if !job.deep {
+                    return Ok(pixels);
+                }
+                // 8 bits spread over 16
+                Ok(pixels
+                    .into_iter()
+                    .flat_map(|channel| (u16::from(channel) * 257).to_ne_bytes())
+                    .collect())
+            }
+        }
+    }
+}
+
+/// Connection to the coordinator, made once it listens.
+fn connect(coordinator: &str) -> Result<TcpStream> {
+    let mut waiting = false;
+    loop {
+        match TcpStream::connect(coordinator) {
+            Ok(stream) => return Ok(stream),
+            Err(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => {
+                if !waiting {
+                    info!("Waiting for the render farm of {}", coordinator);
+                    waiting = true;
+                }
+                thread::sleep(RETRY_INTERVAL);
+            }
+            Err(e) => return Err(e).with_context(|| format!("Cannot reach {coordinator}")),
+        }
+    }
+}
+
+/// Run the `worker` subcommand with its arguments, `args` starting with `worker`: render the
+/// tiles the coordinator sends until it asks to stop.
+pub fn worker_from_args(args: impl Iterator<Item = String>) -> Result<()> {
+    let args = WorkerArgs::parse_from(args);
+    let mut renderer = Renderer::new(args.cpu);
+    let mut stream = connect(&args.coordinator)?;
+    stream.set_nodelay(true)?;
+    info!("Connected to {}", args.coordinator);
+
+    let mut rendered = 0;
+    loop {
+        let message = farm::read_message(&mut stream)?;
+        let job = match message {
+            Some(Message::Render(job)) => job,
+            Some(Message::Shutdown) => break,
+            None => {
+                warn!("The coordinator closed the connection");
+                break;
+            }
+            Some(_) => bail!("Unexpected message"),
+        };
+
+        let start = Instant::now();
+        let compressed = renderer
+            .render(&job)
+            .and_then(|pixels| farm::compress_tile(&pixels, job.size, job.deep));
+        match compressed {
+            Ok(compressed) => {
+                let bytes = compressed.len() as u64;
+                farm::write_message(&mut stream, &Message::Rendered { id: job.id, bytes })?;
+                stream.write_all(&compressed)?;
+                rendered += 1;
+            }
+            Err(e) => {
+                warn!("Cannot render tile {}: {:#}", job.id, e);
+                let error = format!("{e:#}");
+                farm::write_message(&mut stream, &Message::Failed { id: job.id, error })?;
+            }
+        }
+        debug!("Tile {} in {:?}", job.id, start.elapsed());
+    }
+    info!("Rendered {} tiles, exiting", rendered);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{farm::Farm, tiled_export::TileRenderer as _, State};
+
+    #[test]
+    fn renders_for_the_farm() {
+        let mut farm = Farm::start(0).unwrap();
+        let args = [
+            "worker".to_owned(),
+            format!("127.0.0.1:{}", farm.port),
+            "--cpu".to_owned(),
+        ];
+        let worker = thread::spawn(move || worker_from_args(args.into_iter()));
+        farm.request_tile(0, State::new(), [4, 2], false);
+        farm.request_tile(1, State::new(), [4, 2], true);
+
+        let mut tiles = Vec::new();
+        for _ in 0..200 {
+            tiles.extend(farm.take_tiles());
+            if tiles.len() == 2 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        tiles.sort_by_key(|(id, _)| *id);
+        let [(_, Ok(pixels)), (_, Ok(deep))] = &tiles[..] else {
+            panic!("Tiles not rendered");
+        };
+        assert_eq!(pixels.len(), 4 * 2 * 4);
+        // The same colors, spread over 16 bits
+        let widened = deep
+            .chunks_exact(2)
+            .map(|sample| (u16::from_ne_bytes([sample[0], sample[1]]) / 257) as u8);
+        assert!(widened.eq(pixels.iter().copied()));
+
+        drop(farm);
+        worker.join().unwrap().unwrap();
+    }
+}
@@ -0,0 +1,109 @@
+use eframe::egui::{Color32, Painter, PointerButton, Pos2, Rect, Response, Stroke};
+
+use super::{position::Position, State};
+
+/// Smallest side of a box zoomed in on, in points, smaller drags are dropped.
+const MIN_SIZE: f32 = 4.0;
+
+/// Center and zoom of the view of `state` showing the whole `selection`, drawn over the
+/// canvas `rect`, both in UI points.
+pub fn target(
+    state: &State,
+    selection: Rect,
+    rect: Rect,
+    pixels_per_point: f32,
+) -> (Position, f32) {
+    let offset = (selection.center() - rect.center()) * pixels_per_point;
+    let [re, im] = state.point_at([offset.x, offset.y]);
+    let factor = (rect.width() / selection.width()).min(rect.height() / selection.height());
+    (Position { x: -re, y: -im }, state.zoom * factor)
+}
+
+/// Rectangle drawn by a drag over the canvas, the view then zooms in on it.
+pub struct ZoomBox {
+    /// The primary button draws the box instead of panning, for the next drag.
+    pub active: bool,
+    /// Corners of the box being drawn, in UI points.
+    corners: Option<[Pos2; 2]>,
+}
+
+impl ZoomBox {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            corners: None,
+        }
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.corners.is_some()
+    }
+
+    /// Follow the drags by `button` over the canvas. The box, in UI points, once it is
+    /// released.
+    pub fn interact(&mut self, response: &Response, button: Option<PointerButton>) -> Option<Rect> {
+        let button = button?;
+        let pointer = response.interact_pointer_pos();
+        if response.drag_started_by(button) {
+            self.corners = pointer.map(|pointer| [pointer, pointer]);
+        } else if response.dragged_by(button) {
+            if let (Some(corners), Some(pointer)) = (self.corners.as_mut(), pointer) {
+                corners[1] = pointer;
+            }
+        }
+        if !response.drag_stopped_by(button) {
+            return None;
+        }
+        self.active = false;
+        let [a, b] = self.corners.take()?;
+        let selection = Rect::from_two_pos(a, b);
+        (selection.width() >= MIN_SIZE && selection.height() >= MIN_SIZE).then_some(selection)
+    }
+
+    /// Outline of the box being drawn, if any.
+    pub fn paint(&self, painter: &Painter) {
+        if let Some([a, b]) = self.corners {
+            let selection = Rect::from_two_pos(a, b);
+            painter.rect_filled(selection, 0.0, Color32::from_white_alpha(24));
+            painter.rect_stroke(selection, 0.0, Stroke::new(3.0, Color32::BLACK));
+            painter.rect_stroke(selection, 0.0, Stroke::new(1.0, Color32::WHITE));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eframe::egui::Vec2;
+
+    use super::*;
+
+    const CANVAS: Rect = Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(800.0, 600.0));
+
+    #[test]
+    fn box_in_the_middle() {
+        let state = State::new();
+        let selection = Rect::from_center_size(CANVAS.center(), Vec2::new(200.0, 150.0));
+        let (center, zoom) = target(&state, selection, CANVAS, 1.0);
+        assert_eq!((center.x, center.y), (0.0, 0.0));
+        assert_eq!(zoom, 4.0 * state.zoom);
+    }
+
+    #[test]
+    fn box_off_center() {
+        for pixels_per_point in [1.0, 2.0] {
+            let state = State::new();
+            let selection = Rect::from_min_max(Pos2::new(500.0, 100.0), Pos2::new(700.0, 200.0));
+            let (center, zoom) = target(&state, selection, CANVAS, pixels_per_point);
+            // The point under the center of the box becomes the center of the view
+            let offset = (selection.center() - CANVAS.center()) * pixels_per_point;
+            let mut zoomed = state;
+            zoomed.center_position = center;
+            assert_eq!(
+                zoomed.point_at([0.0, 0.0]),
+                state.point_at([offset.x, offset.y])
+            );
+            // The box, wider than the canvas, fits in it
+            assert_eq!(zoom, 4.0 * state.zoom);
+        }
+    }
+}
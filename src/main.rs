@@ -1,21 +1,74 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 #![allow(unsafe_code)]
 
+#[cfg(not(target_arch = "wasm32"))]
 use anyhow::{anyhow, Context, Result};
+#[cfg(not(target_arch = "wasm32"))]
+use clap::Parser;
 
 mod app;
 
+#[cfg(not(target_arch = "wasm32"))]
 fn main() -> Result<()> {
     simple_logger::init_with_level(log::Level::Info).context("Error Initialising the logger")?;
+    if std::env::args().nth(1).as_deref() == Some("render") {
+        return app::render_from_args(std::env::args().skip(1));
+    }
+    if std::env::args().nth(1).as_deref() == Some("worker") {
+        return app::worker_from_args(std::env::args().skip(1));
+    }
+    let args = app::StartupArgs::parse();
+    let gpu = args.gpu.unwrap_or_else(app::GpuPreference::load);
+    gpu.apply();
+    let vsync = app::FrameRate::load_vsync();
+
     let options = eframe::NativeOptions {
         multisampling: 8,
+        vsync,
         renderer: eframe::Renderer::Glow,
+        // Reopen where the window was left: size, position, maximized and fullscreen state
+        persist_window: true,
+        viewport: egui::ViewportBuilder::default().with_app_id("fractal_gl"),
         ..Default::default()
     };
     eframe::run_native(
         "Custom 3D painting in eframe using glow",
         options,
-        Box::new(|cc| Ok(Box::new(app::FractalApp::new(cc)?))),
+        Box::new(move |cc| Ok(Box::new(app::FractalApp::new(cc, gpu, vsync, args)?))),
     )
     .map_err(|e| anyhow!("Error when starting the eframe Framework: {:?}", e))
 }
+
+/// Web entry point: the app is drawn on the canvas `fractal_gl` of the page, see index.html.
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    use wasm_bindgen::JsCast as _;
+
+    eframe::WebLogger::init(log::LevelFilter::Info).ok();
+    let gpu = app::GpuPreference::load();
+    let vsync = app::FrameRate::load_vsync();
+    wasm_bindgen_futures::spawn_local(async move {
+        let canvas = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.get_element_by_id("fractal_gl"))
+            .and_then(|element| element.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+            .expect("No canvas fractal_gl in the page");
+        let result = eframe::WebRunner::new()
+            .start(
+                canvas,
+                eframe::WebOptions::default(),
+                Box::new(move |cc| {
+                    Ok(Box::new(app::FractalApp::new(
+                        cc,
+                        gpu,
+                        vsync,
+                        app::StartupArgs::default(),
+                    )?))
+                }),
+            )
+            .await;
+        if let Err(e) = result {
+            log::error!("Error when starting the eframe Framework: {:?}", e);
+        }
+    });
+}